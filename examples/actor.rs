@@ -61,6 +61,8 @@ async fn main() {
         created_at: None,
         skip_persistence: false,
         signer_type: None,
+        bech32_hrp: None,
+        coin_type: None,
     };
 
     send_message(&tx, MessageType::SetStrongholdPassword("password".to_string())).await;
@@ -3,34 +3,60 @@
 
 use crate::{
     account_manager::{AccountOptions, AccountStore},
-    address::{Address, AddressBuilder, AddressWrapper},
+    address::{Address, AddressBuilder, AddressOutput, AddressWrapper, IOTA_COIN_TYPE},
     client::{ClientOptions, Node},
     event::TransferProgressType,
-    message::{Message, MessagePayload, MessageType, TransactionEssence, Transfer},
+    message::{
+        DepositAddressInputPolicy, Message, MessagePayload, MessageType, TransactionEssence, TransactionInput, Transfer,
+    },
     signing::{GenerateAddressMetadata, SignerType},
 };
 
-use chrono::prelude::{DateTime, Local};
+use chrono::prelude::{DateTime, Local, Utc};
 use getset::{Getters, Setters};
-use iota::message::prelude::MessageId;
+use iota::message::{
+    constants::INPUT_OUTPUT_COUNT_MAX,
+    prelude::{MessageId, OutputId, UTXOInput},
+};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, RwLock, RwLockWriteGuard};
 
 use std::{
+    collections::HashSet,
     hash::{Hash, Hasher},
+    num::NonZeroU64,
     ops::Deref,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 mod sync;
-pub(crate) use sync::{repost_message, AccountSynchronizeStep, RepostAction, SyncedAccountData};
-pub use sync::{AccountSynchronizer, SyncedAccount};
+pub(crate) use sync::{
+    find_dust_warnings, missing_dust_allowance, reconcile_pending_transfers, repost_message, AccountSynchronizeStep,
+    EmittedBalanceStates, RepostAction, SyncCoalescer, SyncedAccountData, DUST_ALLOWANCE_VALUE,
+};
+pub use sync::{
+    AccountSynchronizer, AddressSyncFailure, ConfirmationTimeEstimate, ConsolidationPlan, DustWarning, EssenceDump,
+    EstimateConfidence, PreparedTransaction, SyncParameters, SyncedAccount,
+};
 
 const ACCOUNT_ID_PREFIX: &str = "wallet-account://";
+/// The number of addresses derived with the new signer to validate a signer type migration.
+const SIGNER_MIGRATION_CHECKED_ADDRESSES: usize = 3;
+/// The time window in which a transfer with the same idempotency key returns the previous transfer message.
+const IDEMPOTENCY_KEY_WINDOW_HOURS: i64 = 24;
+
+// Serializes the account renames and the account insertions (alias check and store insertion),
+// so concurrent renames or account creations can't end up with a duplicated alias.
+pub(crate) static ALIAS_UPDATE_LOCK: Lazy<Mutex<()>> = Lazy::new(Default::default);
+/// The default ratio of the maximum output count used by
+/// [addresses_near_output_limit](struct.Account.html#method.addresses_near_output_limit).
+pub const DEFAULT_OUTPUT_LIMIT_WARN_RATIO: f64 = 0.8;
 
 /// The account identifier.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq)]
@@ -117,6 +143,9 @@ pub struct AccountInitialiser {
     signer_type: Option<SignerType>,
     skip_persistence: bool,
     index: Option<usize>,
+    bech32_hrp: Option<String>,
+    account_storage_path: Option<PathBuf>,
+    coin_type: u32,
 }
 
 impl AccountInitialiser {
@@ -144,6 +173,9 @@ impl AccountInitialiser {
             signer_type: None,
             skip_persistence: false,
             index: None,
+            bech32_hrp: None,
+            account_storage_path: None,
+            coin_type: IOTA_COIN_TYPE,
         }
     }
 
@@ -185,31 +217,67 @@ impl AccountInitialiser {
         self
     }
 
-    /// Sets the account index. Useful for account discovery.
-    pub(crate) fn index(mut self, index: usize) -> Self {
+    /// Sets the bech32 human readable part used to generate the account addresses.
+    /// If not defined, we'll get it from the network or the node.
+    /// It's validated against the node's HRP on the first account sync.
+    pub fn bech32_hrp(mut self, bech32_hrp: String) -> Self {
+        self.bech32_hrp.replace(bech32_hrp);
+        self
+    }
+
+    /// Stores the account on a separate SQLite storage file instead of the manager storage.
+    /// The file is encrypted with the manager's storage password if there's one,
+    /// and the manager loads the accounts from all storage files on startup.
+    /// Note that the Stronghold signer keeps reading the seed from the manager's snapshot.
+    pub fn storage_path(mut self, storage_path: impl AsRef<Path>) -> Self {
+        self.account_storage_path.replace(storage_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the account index, used to derive the account addresses.
+    /// Useful to recreate accounts at their original indexes when restoring a wallet.
+    /// If not defined, the first index not used by an account of the signer type is used.
+    /// The initialisation fails with `AccountIndexAlreadyExists` if the index is already used by an account.
+    pub fn index(mut self, index: usize) -> Self {
         self.index.replace(index);
         self
     }
 
+    /// Sets the BIP44 coin type used to derive the account addresses, e.g. for a private Tangle.
+    /// Defaults to the IOTA coin type (4218).
+    pub fn coin_type(mut self, coin_type: u32) -> Self {
+        self.coin_type = coin_type;
+        self
+    }
+
     /// Initialises the account.
     pub async fn initialise(mut self) -> crate::Result<AccountHandle> {
+        // held until the account is inserted on the store, so the alias check can't be raced
+        let _alias_update_guard = if self.skip_persistence {
+            None
+        } else {
+            Some(ALIAS_UPDATE_LOCK.lock().await)
+        };
         let accounts = self.accounts.read().await;
 
         let signer_type = self.signer_type.ok_or(crate::Error::AccountInitialiseRequiredField(
             crate::error::AccountInitialiseRequiredField::SignerType,
         ))?;
 
-        let index = if let Some(index) = self.index {
-            index
-        } else {
-            let mut account_index = 0;
-            for account in accounts.values() {
-                if account.read().await.signer_type() == &signer_type {
-                    account_index += 1;
-                }
+        let mut used_indexes = HashSet::new();
+        for account in accounts.values() {
+            let account = account.read().await;
+            if account.signer_type() == &signer_type && account.coin_type() == &self.coin_type {
+                used_indexes.insert(*account.index());
             }
-            account_index
-        };
+        }
+        // without an explicit index, fill the first gap left by the accounts created with an explicit index
+        let index = self
+            .index
+            .unwrap_or_else(|| (0..).find(|index| !used_indexes.contains(index)).unwrap_or_default());
+        if used_indexes.contains(&index) {
+            return Err(crate::Error::AccountIndexAlreadyExists);
+        }
 
         let alias = self.alias.unwrap_or_else(|| format!("Account {}", index + 1));
         let created_at = self.created_at.unwrap_or_else(Local::now);
@@ -233,12 +301,20 @@ impl AccountInitialiser {
             }
         }
 
+        crate::signing::get_signer(&signer_type)
+            .await
+            .lock()
+            .await
+            .check_mnemonic(&self.storage_path, index)
+            .await?;
+
         self.addresses.sort();
 
         let mut account = Account {
             id: index.to_string(),
             signer_type: signer_type.clone(),
             index,
+            coin_type: self.coin_type,
             alias,
             created_at,
             last_synced_at: None,
@@ -247,47 +323,56 @@ impl AccountInitialiser {
             client_options: self.client_options,
             storage_path: self.storage_path,
             skip_persistence: self.skip_persistence,
+            dirty: false,
+            max_stored_messages: self.account_options.max_stored_messages,
+            pruned_message_ids: HashSet::new(),
+            pending_transfers: Vec::new(),
+            consolidation_schedule: ConsolidationSchedule::default(),
+            last_consolidated_at: None,
         };
 
-        let bech32_hrp = match account.client_options.network().as_deref() {
-            Some("testnet") => "atoi".to_string(),
-            Some("mainnet") => "iota".to_string(),
-            _ => {
-                let client_options = account.client_options.clone();
-                let is_monitoring = self.is_monitoring.clone();
-                let get_from_client_task = async {
-                    let hrp = crate::client::get_client(&client_options, Some(is_monitoring))
-                        .await?
-                        .read()
-                        .await
-                        .get_network_info()
-                        .await
-                        .map_err(|e| match e {
-                            iota::client::Error::SyncedNodePoolEmpty => crate::Error::NodesNotSynced(
-                                client_options
-                                    .nodes()
-                                    .iter()
-                                    .map(|node| node.url.as_str())
-                                    .collect::<Vec<&str>>()
-                                    .join(", "),
-                            ),
-                            _ => e.into(),
-                        })?
-                        .bech32_hrp;
-                    crate::Result::Ok(hrp)
-                };
-                match latest_account_handle {
-                    Some(handle) => {
-                        let latest_account = handle.read().await;
-                        if latest_account.client_options == account.client_options {
-                            latest_account.bech32_hrp()
-                        } else {
-                            get_from_client_task.await?
+        let bech32_hrp = match self.bech32_hrp {
+            Some(bech32_hrp) => bech32_hrp,
+            None => match account.client_options.network().as_deref() {
+                Some("testnet") => "atoi".to_string(),
+                Some("mainnet") => "iota".to_string(),
+                _ => {
+                    let client_options = account.client_options.clone();
+                    let is_monitoring = self.is_monitoring.clone();
+                    let get_from_client_task = async {
+                        let hrp = crate::client::get_client(&client_options, Some(is_monitoring))
+                            .await?
+                            .read()
+                            .await
+                            .get_network_info()
+                            .await
+                            .map_err(|e| match e {
+                                iota::client::Error::SyncedNodePoolEmpty => crate::Error::NodesNotSynced(
+                                    client_options
+                                        .nodes()
+                                        .iter()
+                                        .map(|node| node.url.as_str())
+                                        .collect::<Vec<&str>>()
+                                        .join(", "),
+                                ),
+                                _ => e.into(),
+                            })?
+                            .bech32_hrp;
+                        crate::Result::Ok(hrp)
+                    };
+                    match latest_account_handle {
+                        Some(handle) => {
+                            let latest_account = handle.read().await;
+                            if latest_account.client_options == account.client_options {
+                                latest_account.bech32_hrp()
+                            } else {
+                                get_from_client_task.await?
+                            }
                         }
+                        None => get_from_client_task.await?,
                     }
-                    None => get_from_client_task.await?,
                 }
-            }
+            },
         };
 
         for address in account.addresses.iter_mut() {
@@ -330,6 +415,27 @@ impl AccountInitialiser {
         };
         crypto::hashes::sha::SHA256(&raw, &mut digest);
         account.set_id(format!("{}{}", ACCOUNT_ID_PREFIX, hex::encode(digest)));
+        // the id is derived from the first address, so an account with the same id is derived from the same seed
+        // (e.g. the same mnemonic on another signer type) and would replace the existing account on the store
+        if !self.skip_persistence && accounts.contains_key(account.id()) {
+            return Err(crate::Error::AccountConflict(account.id().clone()));
+        }
+
+        if let Some(account_storage_path) = self.account_storage_path {
+            if !self.skip_persistence && account_storage_path != self.storage_path {
+                let manager_storage = crate::storage::get(&self.storage_path).await?;
+                let mut manager_storage = manager_storage.lock().await;
+                crate::storage::init_account_storage(&account_storage_path, manager_storage.encryption_key()).await?;
+                manager_storage.add_account_storage_path(&account_storage_path).await?;
+                // the account events are persisted along with the manager events
+                if let Some(event_storage_path) = crate::storage::get_event_storage_path(&self.storage_path).await {
+                    crate::storage::link_event_storage(&account_storage_path, &event_storage_path).await;
+                }
+                #[cfg(feature = "stronghold")]
+                crate::signing::stronghold::link_snapshot_path(&account_storage_path, &self.storage_path).await;
+            }
+            account.set_storage_path(account_storage_path);
+        }
 
         let guard = if self.skip_persistence {
             AccountHandle::new(
@@ -369,6 +475,9 @@ pub struct Account {
     signer_type: SignerType,
     /// The account index
     index: usize,
+    /// The BIP44 coin type used to derive the account addresses.
+    #[serde(rename = "coinType", default = "default_coin_type")]
+    coin_type: u32,
     /// The account alias.
     alias: String,
     /// Time of account creation.
@@ -392,6 +501,71 @@ pub struct Account {
     #[getset(set = "pub(crate)", get = "pub(crate)")]
     #[serde(skip)]
     skip_persistence: bool,
+    /// Whether the account changed since it was last saved.
+    #[getset(get = "pub(crate)")]
+    #[serde(skip)]
+    dirty: bool,
+    /// The maximum number of messages kept on the account.
+    #[getset(set = "pub(crate)", get = "pub(crate)")]
+    #[serde(skip)]
+    max_stored_messages: Option<usize>,
+    /// The ids of the messages pruned because of the `max_stored_messages` option, so the sync doesn't fetch them again.
+    #[serde(rename = "prunedMessageIds", default)]
+    pruned_message_ids: HashSet<MessageId>,
+    /// The journal of the transfers in flight, used to reconcile the transfers interrupted by a crash.
+    #[serde(rename = "pendingTransfers", default)]
+    pending_transfers: Vec<PendingTransfer>,
+    /// When the polling system consolidates the account outputs.
+    #[serde(rename = "consolidationSchedule", default)]
+    consolidation_schedule: ConsolidationSchedule,
+    /// Time of the last output consolidation.
+    #[serde(rename = "lastConsolidatedAt", default)]
+    #[getset(set = "pub(crate)")]
+    last_consolidated_at: Option<DateTime<Local>>,
+}
+
+fn default_coin_type() -> u32 {
+    IOTA_COIN_TYPE
+}
+
+/// When the polling system consolidates the account outputs (if the automatic output consolidation is enabled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "schedule", content = "value")]
+pub enum ConsolidationSchedule {
+    /// On every polling cycle.
+    EveryPoll,
+    /// At most once per the given number of seconds.
+    Interval(u64),
+    /// Only when the account doesn't have unconfirmed messages.
+    WhenIdle,
+}
+
+impl Default for ConsolidationSchedule {
+    fn default() -> Self {
+        Self::EveryPoll
+    }
+}
+
+/// A transfer journaled on the account storage while it's in flight,
+/// so it can be reconciled with the node if the wallet stops before the transfer finishes.
+#[derive(Debug, Getters, Serialize, Deserialize, Clone, PartialEq)]
+#[getset(get = "pub")]
+pub struct PendingTransfer {
+    /// The journal entry identifier.
+    id: String,
+    /// The outputs consumed by the transfer.
+    inputs: Vec<OutputId>,
+    /// The transfer target address.
+    #[serde(with = "crate::serde::iota_address_serde")]
+    address: AddressWrapper,
+    /// The transfer amount.
+    amount: u64,
+    /// Time the transfer was started.
+    #[serde(rename = "createdAt")]
+    created_at: DateTime<Local>,
+    /// The id of the transfer message, set once the message is built and about to be broadcasted.
+    #[serde(rename = "messageId")]
+    message_id: Option<MessageId>,
 }
 
 /// A thread guard over an account.
@@ -403,15 +577,21 @@ pub struct AccountHandle {
     pub(crate) account_options: AccountOptions,
     pub(crate) is_monitoring: Arc<AtomicBool>,
     is_mqtt_enabled: Arc<AtomicBool>,
+    pub(crate) sync_coalescer: Arc<SyncCoalescer>,
+    pub(crate) emitted_balance_states: Arc<EmittedBalanceStates>,
+    transfer_idempotency_lock: Arc<Mutex<()>>,
+    // the journal ids of the transfers running on this process, which must not be reconciled
+    pub(crate) in_flight_transfers: Arc<Mutex<HashSet<String>>>,
 }
 
 impl AccountHandle {
     pub(crate) fn new(
-        account: Account,
+        mut account: Account,
         accounts: AccountStore,
         account_options: AccountOptions,
         is_monitoring: Arc<AtomicBool>,
     ) -> Self {
+        account.set_max_stored_messages(account_options.max_stored_messages);
         Self {
             inner: Arc::new(RwLock::new(account)),
             accounts,
@@ -419,6 +599,10 @@ impl AccountHandle {
             account_options,
             is_monitoring,
             is_mqtt_enabled: Arc::new(AtomicBool::new(true)),
+            sync_coalescer: Default::default(),
+            emitted_balance_states: Default::default(),
+            transfer_idempotency_lock: Default::default(),
+            in_flight_transfers: Default::default(),
         }
     }
 
@@ -427,7 +611,7 @@ impl AccountHandle {
     pub(crate) async fn output_consolidation_addresses(&self) -> Vec<AddressWrapper> {
         let mut addresses = Vec::new();
         let account = self.inner.read().await;
-        for address in account.addresses() {
+        for address in account.addresses().iter().filter(|a| !a.imported()) {
             if address.available_outputs(&account).len() >= self.account_options.output_consolidation_threshold {
                 addresses.push(address.address().clone());
             }
@@ -490,13 +674,49 @@ impl AccountHandle {
         AccountSynchronizer::new(self.clone()).await
     }
 
+    /// Waits until the account has been synced at least once, e.g. right after creating or importing it.
+    /// Triggers a sync if the account was never synced; an in-flight (e.g. background) sync is awaited
+    /// and its result reused instead of querying the node again.
+    /// Returns [Error::SyncTimeout](../enum.Error.html#variant.SyncTimeout) if the sync doesn't finish in time.
+    pub async fn await_initial_sync(&self, timeout: Duration) -> crate::Result<()> {
+        let sync = async {
+            if self.read().await.last_synced_at().is_none() {
+                self.sync().await.execute().await?;
+            }
+            crate::Result::Ok(())
+        };
+        tokio::time::timeout(timeout, sync)
+            .await
+            .unwrap_or_else(|_| Err(crate::Error::SyncTimeout))
+    }
+
     /// Consolidate account outputs.
     pub async fn consolidate_outputs(&self) -> crate::Result<Vec<Message>> {
         self.sync().await.execute().await?.consolidate_outputs().await
     }
 
+    /// Consolidate account outputs, sweeping the consolidated value to the given address (e.g. a cold storage
+    /// address) instead of sending it back to each source address.
+    /// The target address must belong to the account.
+    pub async fn consolidate_outputs_to(&self, target: &AddressWrapper) -> crate::Result<Vec<Message>> {
+        self.sync().await.execute().await?.consolidate_outputs_to(target).await
+    }
+
     /// Send messages.
     pub async fn transfer(&self, transfer_obj: Transfer) -> crate::Result<Message> {
+        // transfers with an idempotency key run one at a time,
+        // so a concurrent retry waits for the previous transfer and returns its message
+        let _idempotency_guard = match &transfer_obj.idempotency_key {
+            Some(key) => {
+                let guard = self.transfer_idempotency_lock.lock().await;
+                if let Some(message) = self.inner.read().await.get_idempotent_transfer(key, Utc::now()) {
+                    log::debug!("[TRANSFER] found a previous transfer with idempotency key {}", key);
+                    return Ok(message.clone());
+                }
+                Some(guard)
+            }
+            None => None,
+        };
         let account_id = self.id().await;
         transfer_obj
             .emit_event_if_needed(account_id.clone(), TransferProgressType::SyncingAccount)
@@ -505,6 +725,62 @@ impl AccountHandle {
         synced.transfer(transfer_obj).await
     }
 
+    /// Ensures the given account address can receive `expected_small_payments` more dust outputs,
+    /// e.g. before publishing it as a receive address for micro payments.
+    /// If its dust allowance doesn't cover them, a dust allowance output providing the missing capacity
+    /// is sent to the address (funded from the other account addresses, so its existing dust allowance output
+    /// isn't consumed) and its message is returned.
+    /// Returns `None` if the address can already receive the payments.
+    pub async fn ensure_dust_capacity(
+        &self,
+        address: &AddressWrapper,
+        expected_small_payments: usize,
+    ) -> crate::Result<Option<Message>> {
+        match self.dust_capacity_transfer(address, expected_small_payments).await? {
+            Some(transfer) => self.transfer(transfer).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // The transfer sending the dust allowance output missing on the address, if any.
+    async fn dust_capacity_transfer(
+        &self,
+        address: &AddressWrapper,
+        expected_small_payments: usize,
+    ) -> crate::Result<Option<Transfer>> {
+        let missing_allowance = {
+            let account = self.inner.read().await;
+            let account_address = account
+                .addresses()
+                .iter()
+                .find(|a| a.address() == address)
+                .ok_or(crate::Error::InvalidAddress)?;
+            missing_dust_allowance(account_address, expected_small_payments as u64)?
+        };
+
+        Ok(missing_allowance.map(|amount| {
+            log::debug!(
+                "[DUST] sending a dust allowance output of {}i to {}",
+                amount,
+                address.to_bech32()
+            );
+            // safe to unwrap since the missing allowance is at least 1Mi
+            Transfer::builder(address.clone(), NonZeroU64::new(amount).unwrap())
+                .with_dust_allowance_output(true)
+                // the address isn't used as input, so its dust allowance output isn't consumed
+                .with_deposit_address_input_policy(DepositAddressInputPolicy::Strict)
+                .finish()
+        }))
+    }
+
+    /// Reconciles the transfers interrupted before finishing (e.g. by a crash) with the node.
+    /// A transfer whose message reached the node is resumed (stored on the account and watched until confirmed),
+    /// otherwise its inputs are released. Returns the reconciled transfers.
+    /// The polling system runs the reconciliation automatically.
+    pub async fn reconcile_interrupted_transfers(&self) -> crate::Result<Vec<PendingTransfer>> {
+        reconcile_pending_transfers(self).await
+    }
+
     /// Retry message.
     pub async fn retry(&self, message_id: &MessageId) -> crate::Result<Message> {
         self.sync().await.execute().await?.retry(message_id).await
@@ -546,6 +822,281 @@ impl AccountHandle {
         Ok(address)
     }
 
+    /// Gets a new unused address that is expected to receive funds until `expires_at`, e.g. for a temporary invoice.
+    /// If the address doesn't receive any funds until then, it's flagged as expired on the next polling
+    /// and an [address expired](../event/fn.on_address_expired.html) event is emitted.
+    pub async fn generate_address_with_expiration(&self, expires_at: DateTime<Local>) -> crate::Result<Address> {
+        let mut account = self.inner.write().await;
+        let mut address = crate::address::get_new_address(&account, GenerateAddressMetadata { syncing: false }).await?;
+        address.set_expires_at(Some(expires_at));
+
+        account
+            .do_mut(|account| {
+                account.addresses.push(address.clone());
+                Ok(())
+            })
+            .await?;
+
+        self.monitor_address(address.address().clone());
+
+        Ok(address)
+    }
+
+    /// Generates one address per label and reserves it, e.g. to allocate a pool of invoice addresses up front.
+    /// Reserved addresses are labeled and are never used as deposit address (see
+    /// [latest_address](struct.Account.html#method.latest_address)) or as remainder target,
+    /// unless the transfer explicitly reuses the input address as remainder.
+    pub async fn reserve_addresses(&self, labels: Vec<String>) -> crate::Result<Vec<Address>> {
+        let mut account = self.inner.write().await;
+        let mut addresses = Vec::new();
+        for label in labels {
+            let mut address =
+                crate::address::get_new_address(&account, GenerateAddressMetadata { syncing: false }).await?;
+            address.set_label(Some(label));
+            address.set_reserved(true);
+            // push it right away so the next address is derived with the following key index
+            account.addresses.push(address.clone());
+            addresses.push(address);
+        }
+        account.save().await?;
+
+        for address in &addresses {
+            self.monitor_address(address.address().clone());
+        }
+
+        Ok(addresses)
+    }
+
+    /// Migrates the account to another signer type that controls the same seed (e.g. from Stronghold to Ledger).
+    /// The new signer must derive the same addresses as the account's existing ones,
+    /// otherwise the migration is refused with `Error::SignerMismatch`.
+    pub async fn migrate_signer_type(&self, new_type: SignerType) -> crate::Result<()> {
+        let (account_id, account_index, coin_type) = {
+            let account = self.inner.read().await;
+            (account.id().clone(), *account.index(), *account.coin_type())
+        };
+        for (id, account_handle) in self.accounts.read().await.iter() {
+            if id != &account_id {
+                let account = account_handle.read().await;
+                if account.signer_type() == &new_type
+                    && account.index() == &account_index
+                    && account.coin_type() == &coin_type
+                {
+                    return Err(crate::Error::AccountIndexAlreadyExists);
+                }
+            }
+        }
+
+        let mut account = self.inner.write().await;
+        let signer = crate::signing::get_signer(&new_type).await;
+        let mut signer = signer.lock().await;
+        // imported addresses aren't derived from the account seed
+        let derived_addresses: Vec<Address> = account
+            .addresses()
+            .iter()
+            .filter(|a| !a.imported())
+            .take(SIGNER_MIGRATION_CHECKED_ADDRESSES)
+            .cloned()
+            .collect();
+        for address in derived_addresses {
+            let derived_address = signer
+                .generate_address(
+                    &account,
+                    *address.key_index(),
+                    *address.internal(),
+                    GenerateAddressMetadata { syncing: false },
+                )
+                .await?;
+            if &derived_address != address.address().as_ref() {
+                return Err(crate::Error::SignerMismatch);
+            }
+        }
+
+        account
+            .do_mut(|account| {
+                account.signer_type = new_type;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retires the current deposit address and generates a new one, even if the current one is unused
+    /// (e.g. after it was shared publicly).
+    /// The retired address is still synced, but it's no longer returned as the latest or unused address.
+    pub async fn rotate_deposit_address(&self) -> crate::Result<Address> {
+        let mut account = self.inner.write().await;
+        let address = crate::address::get_new_address(&account, GenerateAddressMetadata { syncing: false }).await?;
+
+        account
+            .do_mut(|account| {
+                account.latest_address_mut().set_retired(true);
+                account.addresses.push(address.clone());
+                Ok(())
+            })
+            .await?;
+
+        self.monitor_address(address.address().clone());
+
+        Ok(address)
+    }
+
+    /// Imports an externally derived address into the account so it's included on the account syncs.
+    /// Imported addresses are flagged as such and are ignored by the address discovery and gap limit logic.
+    /// They're watch-only: their outputs are never used as transfer inputs and they never receive change.
+    pub async fn import_address(
+        &self,
+        address: AddressWrapper,
+        key_index: usize,
+        internal: bool,
+    ) -> crate::Result<Address> {
+        let mut account = self.inner.write().await;
+        if account.addresses.iter().any(|a| a.address() == &address) {
+            return Err(crate::Error::AddressAlreadyExists);
+        }
+        if account
+            .addresses
+            .iter()
+            .any(|a| *a.key_index() == key_index && *a.internal() == internal)
+        {
+            return Err(crate::Error::AddressIndexAlreadyExists);
+        }
+
+        let address = AddressBuilder::new()
+            .address(address)
+            .key_index(key_index)
+            .balance(0)
+            .outputs(Vec::new())
+            .internal(internal)
+            .imported(true)
+            .build()?;
+
+        account
+            .do_mut(|account| {
+                account.addresses.push(address.clone());
+                Ok(())
+            })
+            .await?;
+
+        self.monitor_address(address.address().clone());
+
+        Ok(address)
+    }
+
+    /// Unsubscribes and subscribes again to the MQTT topics of the account addresses.
+    /// Useful when the monitoring is enabled but the account events stopped arriving,
+    /// e.g. after a [MQTT connection state change](../event/fn.on_mqtt_connection_state_change.html) event.
+    pub async fn resubscribe_mqtt(&self) -> crate::Result<()> {
+        // the subscription might be already gone, so we ignore unsubscribe errors
+        if let Err(e) = crate::monitor::unsubscribe(self.clone()).await {
+            log::debug!("[MQTT] failed to unsubscribe before resubscribing: {:?}", e);
+        }
+        crate::monitor::monitor_account_addresses_balance(self.clone()).await;
+        Ok(())
+    }
+
+    /// Re-queries the metadata of the account's unconfirmed messages and updates their confirmation state,
+    /// emitting the [confirmation state change](../event/fn.on_confirmation_state_change.html) events.
+    /// Unlike a full sync, the addresses, outputs and balances are left untouched,
+    /// so it's cheap enough to be called frequently between syncs.
+    /// Returns the messages whose confirmation state changed.
+    pub async fn refresh_confirmations(&self) -> crate::Result<Vec<Message>> {
+        let (client_options, unconfirmed_message_ids) = {
+            let account = self.inner.read().await;
+            let message_ids: Vec<MessageId> = account
+                .list_messages(0, 0, Some(MessageType::Unconfirmed))
+                .iter()
+                .map(|message| *message.id())
+                .collect();
+            (account.client_options().clone(), message_ids)
+        };
+        if unconfirmed_message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let client = crate::client::get_node_client(&client_options, Some(self.is_monitoring.clone())).await?;
+        let mut confirmation_states = Vec::new();
+        for message_id in unconfirmed_message_ids {
+            if let Some(confirmed) = client.get_message_confirmation(&message_id).await? {
+                let (confirmed, confirming) = crate::client::apply_confirmation_threshold(
+                    &*client,
+                    &message_id,
+                    Some(confirmed),
+                    self.account_options.confirmation_threshold,
+                )
+                .await?;
+                confirmation_states.push((message_id, confirmed, confirming));
+            }
+        }
+
+        let mut account = self.inner.write().await;
+        let mut updated_messages = Vec::new();
+        let mut confirming_changed = false;
+        for (message_id, confirmed, confirming) in confirmation_states {
+            if let Some(message) = account.get_message_mut(&message_id) {
+                if message.confirming() != &confirming {
+                    message.set_confirming(confirming);
+                    confirming_changed = true;
+                }
+                if message.confirmed() != &confirmed {
+                    message.set_confirmed(confirmed);
+                    updated_messages.push(message.clone());
+                }
+            }
+        }
+        if !updated_messages.is_empty() || confirming_changed {
+            account.save().await?;
+            for message in &updated_messages {
+                crate::event::emit_confirmation_state_change(
+                    &account,
+                    message.clone(),
+                    *message.confirmed() == Some(true),
+                    self.account_options.persist_events,
+                )
+                .await?;
+            }
+        }
+
+        Ok(updated_messages)
+    }
+
+    /// Fetches the output with the given identifier from the node and updates the local copy,
+    /// recomputing the balance of the address owning it.
+    /// Useful to reconcile the spent status of a single output without a full sync.
+    pub async fn refresh_output(&self, output_id: &OutputId) -> crate::Result<AddressOutput> {
+        let (client_options, bech32_hrp) = {
+            let account = self.inner.read().await;
+            (account.client_options().clone(), account.bech32_hrp())
+        };
+        let client = crate::client::get_node_client(&client_options, Some(self.is_monitoring.clone())).await?;
+        let output_response = client
+            .get_output(&UTXOInput::new(*output_id.transaction_id(), output_id.index())?)
+            .await?;
+        let output = AddressOutput::from_output_response(output_response, bech32_hrp)?;
+
+        let mut account = self.inner.write().await;
+        let address = account
+            .addresses
+            .iter_mut()
+            .find(|a| a.address() == output.address())
+            .ok_or_else(|| crate::Error::OutputNotOwned(output_id.to_string()))?;
+        address.outputs.insert(*output_id, output.clone());
+        let balance = address
+            .outputs
+            .values()
+            .filter(|o| !o.is_spent)
+            .fold(0, |acc, o| acc + *o.amount());
+        address.set_balance(balance);
+        account.save().await?;
+
+        Ok(output)
+    }
+
+    /// Gets a snapshot of the addresses currently locked by in-progress transfers.
+    /// These addresses are excluded from the input selection until the transfer finishes.
+    pub async fn locked_addresses(&self) -> Vec<AddressWrapper> {
+        self.locked_addresses.lock().await.clone()
+    }
+
     pub(crate) fn monitor_address(&self, address: AddressWrapper) {
         let handle = self.clone();
         crate::spawn(async move {
@@ -583,6 +1134,7 @@ impl AccountHandle {
             latest_address.outputs_mut(),
             address_wrapper,
             bech32_hrp,
+            None,
             self.account_options,
             self.is_monitoring.clone(),
         )
@@ -602,8 +1154,51 @@ impl AccountHandle {
         self.inner.read().await.balance()
     }
 
-    /// Bridge to [Account#set_alias](struct.Account.html#method.set_alias).
+    /// Bridge to [Account#projected_balance](struct.Account.html#method.projected_balance).
+    pub async fn projected_balance(&self) -> i64 {
+        self.inner.read().await.projected_balance()
+    }
+
+    /// Bridge to [Account#list_pending_transactions](struct.Account.html#method.list_pending_transactions).
+    pub async fn list_pending_transactions(&self) -> Vec<Message> {
+        self.inner
+            .read()
+            .await
+            .list_pending_transactions()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Bridge to [Account#set_consolidation_schedule](struct.Account.html#method.set_consolidation_schedule).
+    pub async fn set_consolidation_schedule(&self, schedule: ConsolidationSchedule) -> crate::Result<()> {
+        self.inner.write().await.set_consolidation_schedule(schedule).await
+    }
+
+    /// Updates the account alias.
+    /// Returns `AccountAliasAlreadyExists` if the alias is already used by another account.
+    /// The renames are serialized, so concurrent renames can't end up with a duplicated alias.
     pub async fn set_alias(&self, alias: impl AsRef<str>) -> crate::Result<()> {
+        let alias = alias.as_ref();
+        let _alias_update_guard = ALIAS_UPDATE_LOCK.lock().await;
+
+        // the aliases are collected first, so no other lock is held while the account is updated
+        let account_handles: Vec<AccountHandle> = self
+            .accounts
+            .read()
+            .await
+            .values()
+            .filter(|account_handle| !Arc::ptr_eq(&account_handle.inner, &self.inner))
+            .cloned()
+            .collect();
+        let mut aliases = Vec::new();
+        for account_handle in account_handles {
+            aliases.push(account_handle.read().await.alias().clone());
+        }
+        if aliases.iter().any(|a| a == alias) {
+            return Err(crate::Error::AccountAliasAlreadyExists);
+        }
+
         self.inner.write().await.set_alias(alias).await
     }
 
@@ -625,6 +1220,11 @@ impl AccountHandle {
             .collect()
     }
 
+    /// Bridge to [Account#export_history_csv](struct.Account.html#method.export_history_csv).
+    pub async fn export_history_csv(&self) -> String {
+        self.inner.read().await.export_history_csv()
+    }
+
     /// Bridge to [Account#list_spent_addresses](struct.Account.html#method.list_spent_addresses).
     /// This method clones the account's addresses so when querying a large list of addresses
     /// prefer using the `read` method to access the account instance.
@@ -651,6 +1251,51 @@ impl AccountHandle {
             .collect()
     }
 
+    /// Bridge to [Account#addresses_near_output_limit](struct.Account.html#method.addresses_near_output_limit).
+    pub async fn addresses_near_output_limit(&self, warn_ratio: f64) -> Vec<(AddressWrapper, usize)> {
+        self.inner.read().await.addresses_near_output_limit(warn_ratio)
+    }
+
+    /// Bridge to [Account#expired_addresses](struct.Account.html#method.expired_addresses).
+    pub async fn expired_addresses(&self) -> Vec<Address> {
+        self.inner
+            .read()
+            .await
+            .expired_addresses()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Bridge to [Account#addresses_since](struct.Account.html#method.addresses_since).
+    pub async fn addresses_since(&self, since: DateTime<Local>) -> Vec<Address> {
+        self.inner
+            .read()
+            .await
+            .addresses_since(since)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Bridge to [Account#addresses_by_balance](struct.Account.html#method.addresses_by_balance).
+    /// This method clones the account's addresses so when querying a large list of addresses
+    /// prefer using the `read` method to access the account instance.
+    pub async fn addresses_by_balance(&self) -> Vec<Address> {
+        self.inner
+            .read()
+            .await
+            .addresses_by_balance()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the number of messages stored on the account.
+    pub async fn message_count(&self) -> usize {
+        self.inner.read().await.messages().len()
+    }
+
     /// Bridge to [Account#get_message](struct.Account.html#method.get_message).
     pub async fn get_message(&self, message_id: &MessageId) -> Option<Message> {
         self.inner.read().await.get_message(message_id).cloned()
@@ -658,7 +1303,7 @@ impl AccountHandle {
 }
 
 /// Account balance information.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AccountBalance {
     /// Account's total balance.
     pub total: u64,
@@ -674,8 +1319,25 @@ pub struct AccountBalance {
     pub outgoing: u64,
 }
 
+/// Account address usage information, see [Account#address_usage_stats](struct.Account.html#method.address_usage_stats).
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressUsageStats {
+    /// The number of addresses generated on the account.
+    pub total: usize,
+    /// The number of addresses that received at least one output.
+    pub used: usize,
+    /// The number of addresses that never received an output.
+    pub unused: usize,
+    /// The largest number of consecutive unused addresses on the public or internal address chain.
+    #[serde(rename = "largestGap")]
+    pub largest_gap: usize,
+    /// The number of addresses that received more than one output.
+    pub reused: usize,
+}
+
 impl Account {
     pub(crate) async fn save(&mut self) -> crate::Result<()> {
+        self.prune_messages();
         if !self.skip_persistence {
             let storage_path = self.storage_path.clone();
             crate::storage::get(&storage_path)
@@ -685,6 +1347,21 @@ impl Account {
                 .save_account(&self.id, self)
                 .await?;
         }
+        self.dirty = false;
+        Ok(())
+    }
+
+    // Flags the account as changed, so the next `save_if_dirty` call writes it.
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    // Saves the account only if it changed since it was last saved,
+    // so the sync and polling steps that didn't change anything don't rewrite the account.
+    pub(crate) async fn save_if_dirty(&mut self) -> crate::Result<()> {
+        if self.dirty {
+            self.save().await?;
+        }
         Ok(())
     }
 
@@ -700,25 +1377,29 @@ impl Account {
     }
 
     /// Returns the most recent address of the account.
+    /// [Reserved](../address/struct.Address.html#method.reserved) addresses are skipped.
     pub fn latest_address(&self) -> &Address {
-        // the addresses list is never empty because we generate an address on the account creation
+        // the addresses list is never empty because we generate an address on the account creation,
+        // and the addresses are only reserved after it
         self.addresses
             .iter()
-            .filter(|a| !a.internal())
+            .filter(|a| !a.internal() && !a.imported() && !a.reserved())
             .max_by_key(|a| a.key_index())
             .unwrap()
     }
 
     fn latest_address_mut(&mut self) -> &mut Address {
-        // the addresses list is never empty because we generate an address on the account creation
+        // the addresses list is never empty because we generate an address on the account creation,
+        // and the addresses are only reserved after it
         self.addresses
             .iter_mut()
-            .filter(|a| !a.internal())
+            .filter(|a| !a.internal() && !a.imported() && !a.reserved())
             .max_by_key(|a| *a.key_index())
             .unwrap()
     }
 
     /// Gets the account balance information.
+    /// The balance of the imported (watch-only) addresses isn't included.
     pub fn balance(&self) -> AccountBalance {
         let (incoming, outgoing) = self.list_messages(0, 0, Some(MessageType::Confirmed)).iter().fold(
             (0, 0),
@@ -736,19 +1417,79 @@ impl Account {
                 (incoming, outgoing)
             },
         );
+        // imported addresses are watch-only, so their funds aren't the account funds
+        let addresses = self.addresses.iter().filter(|address| !address.imported());
         AccountBalance {
-            total: self.addresses.iter().fold(0, |acc, address| acc + address.balance()),
-            available: self
-                .addresses()
-                .iter()
-                .fold(0, |acc, addr| acc + addr.available_balance(&self)),
+            total: addresses.clone().fold(0, |acc, address| acc + address.balance()),
+            available: addresses.fold(0, |acc, addr| acc + addr.available_balance(&self)),
             incoming,
             outgoing,
         }
     }
 
-    /// Updates the account alias.
-    pub async fn set_alias(&mut self, alias: impl AsRef<str>) -> crate::Result<()> {
+    /// Gets the balance the account will have once all its unconfirmed messages are confirmed:
+    /// the total balance plus the [pending balance effect](../message/struct.Message.html#method.pending_balance_effect)
+    /// of each unconfirmed message.
+    pub fn projected_balance(&self) -> i64 {
+        self.list_messages(0, 0, Some(MessageType::Unconfirmed))
+            .iter()
+            .fold(self.balance().total as i64, |acc, message| {
+                acc + message.pending_balance_effect(self)
+            })
+    }
+
+    /// Whether the message is a transaction sent by the account, i.e. at least one of its inputs
+    /// spends an output of an account address (known by the account or by the input metadata).
+    pub fn is_outgoing(&self, message: &Message) -> bool {
+        match message.payload() {
+            Some(MessagePayload::Transaction(tx)) => {
+                let TransactionEssence::Regular(essence) = tx.essence();
+                essence.inputs().iter().any(|input| match input {
+                    TransactionInput::UTXO(input) => self.addresses.iter().any(|a| {
+                        a.outputs().contains_key(input.input.output_id())
+                            || input
+                                .metadata
+                                .as_ref()
+                                .map(|metadata| a.address() == metadata.address())
+                                == Some(true)
+                    }),
+                    _ => false,
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Lists the transactions sent by the account that aren't confirmed yet (pending or conflicting),
+    /// e.g. to show a pending badge. Reattachments are merged as on [list_messages](#method.list_messages).
+    pub fn list_pending_transactions(&self) -> Vec<&Message> {
+        self.list_messages(0, 0, None)
+            .into_iter()
+            .filter(|message| !message.confirmed().unwrap_or(false) && self.is_outgoing(message))
+            .collect()
+    }
+
+    /// Updates when the polling system consolidates the account outputs, e.g. to avoid constant address churn.
+    pub async fn set_consolidation_schedule(&mut self, schedule: ConsolidationSchedule) -> crate::Result<()> {
+        self.consolidation_schedule = schedule;
+        self.save().await
+    }
+
+    /// Whether the consolidation schedule allows consolidating the account outputs at the given time.
+    pub(crate) fn is_consolidation_due(&self, now: DateTime<Local>) -> bool {
+        match self.consolidation_schedule {
+            ConsolidationSchedule::EveryPoll => true,
+            ConsolidationSchedule::Interval(seconds) => match self.last_consolidated_at {
+                Some(last_consolidated_at) => now - last_consolidated_at >= chrono::Duration::seconds(seconds as i64),
+                None => true,
+            },
+            ConsolidationSchedule::WhenIdle => !self.messages.iter().any(|m| m.confirmed().is_none()),
+        }
+    }
+
+    // Updates the account alias without checking the other accounts;
+    // callers must hold `ALIAS_UPDATE_LOCK` and check the alias uniqueness (see `AccountHandle::set_alias`).
+    pub(crate) async fn set_alias(&mut self, alias: impl AsRef<str>) -> crate::Result<()> {
         let alias = alias.as_ref().to_string();
 
         self.alias = alias;
@@ -882,6 +1623,67 @@ impl Account {
         }
     }
 
+    /// Exports the account's value transactions as CSV, one row per transaction (reattachments are merged).
+    ///
+    /// The first line is the header `date,direction,amount,counterparty,message_id,confirmation_status` where:
+    /// - `date`: the message timestamp in RFC 3339 format.
+    /// - `direction`: `incoming`, `outgoing` or `internal` (a transfer between the accounts of the same mnemonic).
+    /// - `amount`: the transaction value in IOTA, excluding the remainder value.
+    /// - `counterparty`: the bech32 addresses of the other side of the transaction separated by `;`; the recipient
+    ///   addresses for outgoing and internal transactions, the input addresses (when known) for incoming transactions.
+    /// - `message_id`: the message identifier in hex format.
+    /// - `confirmation_status`: `confirmed`, `pending` or `conflicting`.
+    pub fn export_history_csv(&self) -> String {
+        let mut csv = String::from("date,direction,amount,counterparty,message_id,confirmation_status\n");
+        for message in self.list_messages(0, 0, Some(MessageType::Value)) {
+            let essence = match message.payload() {
+                Some(MessagePayload::Transaction(tx)) => match tx.essence() {
+                    TransactionEssence::Regular(essence) => essence,
+                },
+                _ => continue,
+            };
+            let direction = if essence.internal() {
+                "internal"
+            } else if essence.incoming() {
+                "incoming"
+            } else {
+                "outgoing"
+            };
+            let counterparty: Vec<String> = if essence.incoming() && !essence.internal() {
+                essence
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| match input {
+                        TransactionInput::UTXO(input) => input.metadata.as_ref().map(|m| m.address().to_bech32()),
+                        _ => None,
+                    })
+                    .collect()
+            } else {
+                essence
+                    .outputs()
+                    .iter()
+                    .filter(|output| !output.is_remainder())
+                    .filter_map(|output| output.address().map(|address| address.to_bech32()))
+                    .collect()
+            };
+            let confirmation_status = match message.confirmed() {
+                Some(true) => "confirmed",
+                Some(false) => "conflicting",
+                None => "pending",
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                message.timestamp().to_rfc3339(),
+                direction,
+                essence.value(),
+                counterparty.join(";"),
+                message.id(),
+                confirmation_status
+            ));
+        }
+        csv
+    }
+
     /// Gets the spent addresses.
     pub fn list_spent_addresses(&self) -> Vec<&Address> {
         self.addresses
@@ -898,10 +1700,150 @@ impl Account {
             .collect()
     }
 
+    /// Gets the ids of every output (spent and unspent) known on the account addresses.
+    pub fn all_output_ids(&self) -> Vec<OutputId> {
+        self.addresses
+            .iter()
+            .flat_map(|address| address.outputs().keys().cloned())
+            .collect()
+    }
+
+    /// Gets the address usage summary, useful to inspect the address gap and the address reuse.
+    pub fn address_usage_stats(&self) -> AddressUsageStats {
+        let used = self.addresses.iter().filter(|a| !a.outputs().is_empty()).count();
+        let reused = self.addresses.iter().filter(|a| a.outputs().len() > 1).count();
+
+        let mut largest_gap = 0;
+        for internal in &[false, true] {
+            let mut chain: Vec<&Address> = self.addresses.iter().filter(|a| a.internal() == internal).collect();
+            chain.sort_by_key(|a| *a.key_index());
+            let mut gap = 0;
+            for address in chain {
+                if address.outputs().is_empty() {
+                    gap += 1;
+                    largest_gap = largest_gap.max(gap);
+                } else {
+                    gap = 0;
+                }
+            }
+        }
+
+        AddressUsageStats {
+            total: self.addresses.len(),
+            used,
+            unused: self.addresses.len() - used,
+            largest_gap,
+            reused,
+        }
+    }
+
+    /// Gets the addresses holding funds, sorted descending by available balance.
+    /// Addresses without available balance are excluded.
+    pub fn addresses_by_balance(&self) -> Vec<&Address> {
+        let mut addresses: Vec<(u64, &Address)> = self
+            .addresses
+            .iter()
+            .map(|address| (address.available_balance(&self), address))
+            .filter(|(balance, _)| *balance > 0)
+            .collect();
+        addresses.sort_by(|(a, _), (b, _)| b.cmp(a));
+        addresses.into_iter().map(|(_, address)| address).collect()
+    }
+
+    /// Gets the addresses whose available output count exceeds `warn_ratio` times the maximum number of inputs
+    /// of a transaction, along with their output count.
+    /// These addresses should be consolidated before they can't be spent in a single transaction.
+    /// Use [DEFAULT_OUTPUT_LIMIT_WARN_RATIO](constant.DEFAULT_OUTPUT_LIMIT_WARN_RATIO.html) as the default ratio.
+    pub fn addresses_near_output_limit(&self, warn_ratio: f64) -> Vec<(AddressWrapper, usize)> {
+        let limit = warn_ratio * INPUT_OUTPUT_COUNT_MAX as f64;
+        self.addresses
+            .iter()
+            .map(|address| (address.address().clone(), address.available_outputs(&self).len()))
+            .filter(|(_, output_count)| *output_count as f64 > limit)
+            .collect()
+    }
+
+    /// Gets the addresses that passed their expiration time without receiving any funds.
+    /// Expired addresses are kept on the account for history purposes, but shouldn't be used as active invoices.
+    pub fn expired_addresses(&self) -> Vec<&Address> {
+        self.addresses.iter().filter(|address| *address.expired()).collect()
+    }
+
+    /// Gets the addresses generated after `since` (e.g. the change addresses created during a transfer),
+    /// so clients can update their address list incrementally.
+    /// Addresses stored before the generation time was tracked are never included.
+    pub fn addresses_since(&self, since: DateTime<Local>) -> Vec<&Address> {
+        self.addresses
+            .iter()
+            .filter(|address| matches!(address.created_at(), Some(created_at) if *created_at > since))
+            .collect()
+    }
+
+    // Flags the addresses that passed their expiration time without receiving any funds and returns them.
+    pub(crate) fn flag_expired_addresses(&mut self, now: DateTime<Local>) -> Vec<AddressWrapper> {
+        let mut expired = Vec::new();
+        for address in self.addresses.iter_mut() {
+            if let Some(expires_at) = *address.expires_at() {
+                if !address.expired() && expires_at <= now && address.outputs().is_empty() {
+                    address.set_expired(true);
+                    expired.push(address.address().clone());
+                    self.dirty = true;
+                }
+            }
+        }
+        expired
+    }
+
+    // Gets the message sent by a transfer with the given idempotency key in the last 24 hours.
+    pub(crate) fn get_idempotent_transfer(&self, key: &str, now: DateTime<Utc>) -> Option<&Message> {
+        self.messages.iter().find(|message| {
+            message.idempotency_key().as_deref() == Some(key)
+                && now.signed_duration_since(*message.timestamp())
+                    <= chrono::Duration::hours(IDEMPOTENCY_KEY_WINDOW_HOURS)
+        })
+    }
+
+    pub(crate) fn journal_transfer(&mut self, inputs: Vec<OutputId>, address: AddressWrapper, amount: u64) -> String {
+        let mut id = [0; 16];
+        crypto::utils::rand::fill(&mut id).unwrap();
+        let id = hex::encode(id);
+        self.pending_transfers.push(PendingTransfer {
+            id: id.clone(),
+            inputs,
+            address,
+            amount,
+            created_at: Local::now(),
+            message_id: None,
+        });
+        self.dirty = true;
+        id
+    }
+
+    pub(crate) fn set_pending_transfer_message_id(&mut self, id: &str, message_id: MessageId) {
+        if let Some(pending_transfer) = self.pending_transfers.iter_mut().find(|t| t.id == id) {
+            pending_transfer.message_id = Some(message_id);
+            self.dirty = true;
+        }
+    }
+
+    pub(crate) fn remove_pending_transfer(&mut self, id: &str) {
+        let pending_transfers_count = self.pending_transfers.len();
+        self.pending_transfers.retain(|t| t.id != id);
+        self.dirty |= self.pending_transfers.len() != pending_transfers_count;
+    }
+
     pub(crate) fn append_messages(&mut self, messages: Vec<Message>) {
+        self.dirty |= !messages.is_empty();
         messages.into_iter().for_each(
-            |message| match self.messages.iter().position(|m| m.id() == message.id()) {
+            |mut message| match self.messages.iter().position(|m| m.id() == message.id()) {
                 Some(index) => {
+                    // the memo is only stored locally, so it's kept when the message is updated
+                    if message.memo().is_none() {
+                        message.set_memo(self.messages[index].memo().clone());
+                    }
+                    if message.idempotency_key().is_none() {
+                        message.set_idempotency_key(self.messages[index].idempotency_key().clone());
+                    }
                     self.messages[index] = message;
                 }
                 None => {
@@ -909,6 +1851,40 @@ impl Account {
                 }
             },
         );
+        self.prune_messages();
+    }
+
+    // The stored messages ids and their confirmation state.
+    // The pruned messages were confirmed, so they're listed as such and the sync doesn't fetch them again.
+    pub(crate) fn known_message_states(&self) -> Vec<(MessageId, Option<bool>)> {
+        self.messages
+            .iter()
+            .map(|m| (*m.id(), *m.confirmed()))
+            .chain(self.pruned_message_ids.iter().map(|id| (*id, Some(true))))
+            .collect()
+    }
+
+    // Removes the oldest confirmed messages exceeding the `max_stored_messages` option.
+    // Unconfirmed messages are always kept.
+    fn prune_messages(&mut self) {
+        if let Some(max_stored_messages) = self.max_stored_messages {
+            if self.messages.len() > max_stored_messages {
+                let mut confirmed_messages: Vec<&Message> = self
+                    .messages
+                    .iter()
+                    .filter(|m| m.confirmed().unwrap_or(false))
+                    .collect();
+                confirmed_messages.sort_by_key(|m| *m.timestamp());
+                let pruned_message_ids: Vec<MessageId> = confirmed_messages
+                    .into_iter()
+                    .take(self.messages.len() - max_stored_messages)
+                    .map(|m| *m.id())
+                    .collect();
+                log::debug!("[ACCOUNT] pruning {} messages", pruned_message_ids.len());
+                self.messages.retain(|m| !pruned_message_ids.contains(m.id()));
+                self.pruned_message_ids.extend(pruned_message_ids);
+            }
+        }
     }
 
     pub(crate) fn append_addresses(&mut self, addresses: Vec<Address>) {
@@ -916,10 +1892,26 @@ impl Account {
             .into_iter()
             .for_each(|address| match self.addresses.iter().position(|a| a == &address) {
                 Some(index) => {
+                    let mut address = address;
+                    // the address expiration isn't known by the sync, so we keep the stored one
+                    if address.expires_at().is_none() {
+                        address.set_expires_at(*self.addresses[index].expires_at());
+                        address.set_expired(*self.addresses[index].expired());
+                    }
+                    if !address.retired() {
+                        address.set_retired(*self.addresses[index].retired());
+                    }
+                    // the address is rebuilt by the sync, so we keep its original generation time
+                    address.set_created_at(*self.addresses[index].created_at());
+                    address.set_label(self.addresses[index].label().clone());
+                    address.set_reserved(*self.addresses[index].reserved());
+                    self.dirty |= address.balance() != self.addresses[index].balance()
+                        || address.outputs() != self.addresses[index].outputs();
                     self.addresses[index] = address;
                 }
                 None => {
                     self.addresses.push(address);
+                    self.dirty = true;
                 }
             });
     }
@@ -1004,6 +1996,64 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn bech32_hrp_override() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+        let account_handle = manager
+            .create_account(client_options)
+            .unwrap()
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .bech32_hrp("iota".to_string())
+            .initialise()
+            .await
+            .expect("failed to add account");
+
+        let account = account_handle.read().await;
+        assert_eq!(account.bech32_hrp(), "iota".to_string());
+        assert!(account.latest_address().address().to_bech32().starts_with("iota1"));
+    }
+
+    #[tokio::test]
+    async fn coin_type() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+        let account_handle = manager
+            .create_account(client_options)
+            .unwrap()
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .coin_type(1)
+            .initialise()
+            .await
+            .expect("failed to add account");
+
+        let account = account_handle.read().await;
+        assert_eq!(*account.coin_type(), 1);
+        let address = account.latest_address();
+        assert_eq!(
+            address.derivation_path_with_coin_type(*account.coin_type(), *account.index()),
+            format!("m/44H/1H/{}H/0H/{}H", account.index(), address.key_index())
+                .parse::<slip10::BIP32Path>()
+                .unwrap()
+        );
+
+        // accounts stored without a coin type use the IOTA coin type
+        let mut json = serde_json::to_value(&*account).unwrap();
+        json.as_object_mut().unwrap().remove("coinType");
+        let stored_account: super::Account = serde_json::from_value(json).unwrap();
+        assert_eq!(*stored_account.coin_type(), crate::address::IOTA_COIN_TYPE);
+    }
+
     #[tokio::test]
     async fn account_handle_bridge_getters() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -1097,6 +2147,88 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn import_address() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+        let latest_address = account_handle.latest_address().await;
+
+        let iota_address = crate::test_utils::generate_random_iota_address();
+        let imported_address = account_handle
+            .import_address(iota_address.clone(), *latest_address.key_index() + 10, false)
+            .await
+            .unwrap();
+
+        assert!(imported_address.imported());
+        assert!(account_handle.read().await.addresses().contains(&imported_address));
+        assert_eq!(account_handle.latest_address().await, latest_address);
+        assert!(account_handle.import_address(iota_address, 0, false).await.is_err());
+        // the key index is already used by a derived address
+        let res = account_handle
+            .import_address(
+                crate::test_utils::generate_random_iota_address(),
+                *latest_address.key_index(),
+                false,
+            )
+            .await;
+        assert!(matches!(res, Err(crate::Error::AddressIndexAlreadyExists)));
+
+        // the imported address doesn't take a key index of the derived addresses
+        let generated_address = account_handle.generate_address().await.unwrap();
+        assert_eq!(*generated_address.key_index(), *latest_address.key_index() + 1);
+    }
+
+    #[tokio::test]
+    async fn export_history_csv() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let received_message = crate::test_utils::GenerateMessageBuilder::default()
+            .address(address.clone())
+            .value(100)
+            .incoming(true)
+            .confirmed(Some(true))
+            .build()
+            .await;
+        let recipient = crate::test_utils::generate_random_address();
+        let sent_message = crate::test_utils::GenerateMessageBuilder::default()
+            .address(recipient.clone())
+            .value(50)
+            .incoming(false)
+            .confirmed(None)
+            .build()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address])
+            .messages(vec![received_message.clone(), sent_message.clone()])
+            .create()
+            .await;
+
+        let csv = account_handle.export_history_csv().await;
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("date,direction,amount,counterparty,message_id,confirmation_status")
+        );
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 2);
+
+        let received_row = rows
+            .iter()
+            .find(|row| row[4] == received_message.id().to_string())
+            .unwrap();
+        assert_eq!(received_row[1], "incoming");
+        assert_eq!(received_row[5], "confirmed");
+
+        let sent_row = rows.iter().find(|row| row[4] == sent_message.id().to_string()).unwrap();
+        assert_eq!(sent_row[1], "outgoing");
+        assert_eq!(sent_row[2], "50");
+        assert_eq!(sent_row[3], recipient.address().to_bech32());
+        assert_eq!(sent_row[5], "pending");
+    }
+
     #[tokio::test]
     async fn latest_address() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -1170,6 +2302,72 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn addresses_by_balance() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut addresses = Vec::new();
+        for (index, balance) in [0, 20, 50, 10].iter().enumerate() {
+            let outputs = if *balance > 0 {
+                vec![_generate_address_output(*balance)]
+            } else {
+                vec![]
+            };
+            addresses.push(
+                AddressBuilder::new()
+                    .address(crate::test_utils::generate_random_iota_address())
+                    .key_index(index)
+                    .balance(*balance)
+                    .outputs(outputs)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(addresses)
+            .create()
+            .await;
+
+        let balances: Vec<u64> = account_handle
+            .addresses_by_balance()
+            .await
+            .iter()
+            .map(|address| *address.balance())
+            .collect();
+        assert_eq!(balances, vec![50, 20, 10]);
+    }
+
+    #[tokio::test]
+    async fn max_stored_messages() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+        let latest_address = account_handle.read().await.latest_address().clone();
+        account_handle.write().await.set_max_stored_messages(Some(2));
+
+        let mut messages = Vec::new();
+        for _ in 0..3 {
+            messages.push(
+                crate::test_utils::GenerateMessageBuilder::default()
+                    .address(latest_address.clone())
+                    .confirmed(Some(true))
+                    .build()
+                    .await,
+            );
+        }
+        let unconfirmed_message = crate::test_utils::GenerateMessageBuilder::default()
+            .address(latest_address.clone())
+            .confirmed(None)
+            .build()
+            .await;
+        messages.push(unconfirmed_message.clone());
+        account_handle.write().await.append_messages(messages);
+
+        assert_eq!(account_handle.message_count().await, 2);
+        assert!(account_handle.get_message(unconfirmed_message.id()).await.is_some());
+    }
+
     #[tokio::test]
     async fn list_all_messages() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -1334,4 +2532,826 @@ mod tests {
         )
         .await;
     }
+
+    #[tokio::test]
+    async fn create_account_without_mnemonic() {
+        struct NoMnemonicSigner;
+
+        #[async_trait::async_trait]
+        impl crate::signing::Signer for NoMnemonicSigner {
+            async fn store_mnemonic(&mut self, _: &std::path::PathBuf, _: String) -> crate::Result<()> {
+                Ok(())
+            }
+
+            async fn generate_address(
+                &mut self,
+                _: &super::Account,
+                _: usize,
+                _: bool,
+                _: crate::signing::GenerateAddressMetadata,
+            ) -> crate::Result<iota::Address> {
+                unreachable!()
+            }
+
+            async fn sign_message<'a>(
+                &mut self,
+                _: &super::Account,
+                _: &iota::Essence,
+                _: &mut Vec<crate::signing::TransactionInput>,
+                _: crate::signing::SignMessageMetadata<'a>,
+            ) -> crate::Result<Vec<iota::UnlockBlock>> {
+                unreachable!()
+            }
+
+            async fn check_mnemonic(&mut self, _: &std::path::PathBuf, _: usize) -> crate::Result<()> {
+                Err(crate::Error::MnemonicNotSet)
+            }
+        }
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let signer_type = crate::signing::SignerType::Custom("no-mnemonic".to_string());
+        crate::signing::set_signer(signer_type.clone(), NoMnemonicSigner).await;
+
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .unwrap()
+            .build()
+            .unwrap();
+        let res = manager
+            .create_account(client_options)
+            .unwrap()
+            .signer_type(signer_type)
+            .initialise()
+            .await;
+        assert!(matches!(res, Err(crate::Error::MnemonicNotSet)));
+        assert!(manager.get_accounts().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refresh_confirmations() {
+        use crate::client::NodeClient;
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let node_message = crate::test_utils::MockNodeClient::default()
+            .finish_pow(None)
+            .await
+            .unwrap();
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_message(node_message.clone(), Some(true))
+            .register()
+            .await;
+
+        let mut confirmed_message = crate::test_utils::GenerateMessageBuilder::default()
+            .confirmed(None)
+            .build()
+            .await;
+        confirmed_message.id = node_message.id().0;
+        let pending_message = crate::test_utils::GenerateMessageBuilder::default()
+            .confirmed(None)
+            .build()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .messages(vec![confirmed_message.clone(), pending_message.clone()])
+            .create()
+            .await;
+
+        let updated_messages = account_handle.refresh_confirmations().await.unwrap();
+        assert_eq!(updated_messages.len(), 1);
+        assert_eq!(updated_messages[0].id(), confirmed_message.id());
+
+        let account = account_handle.read().await;
+        assert_eq!(
+            account.get_message(confirmed_message.id()).unwrap().confirmed(),
+            &Some(true)
+        );
+        assert_eq!(account.get_message(pending_message.id()).unwrap().confirmed(), &None);
+    }
+
+    #[tokio::test]
+    async fn refresh_confirmations_with_threshold() {
+        use crate::client::NodeClient;
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let node_message = crate::test_utils::MockNodeClient::default()
+            .finish_pow(None)
+            .await
+            .unwrap();
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_message(node_message.clone(), Some(true))
+            .with_confirmation_depth(node_message.id().0, 2)
+            .register()
+            .await;
+
+        let mut message = crate::test_utils::GenerateMessageBuilder::default()
+            .confirmed(None)
+            .build()
+            .await;
+        message.id = node_message.id().0;
+
+        let mut account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .messages(vec![message.clone()])
+            .create()
+            .await;
+
+        // included, but confirmed by less milestones than the threshold
+        account_handle.account_options.confirmation_threshold = 3;
+        let updated_messages = account_handle.refresh_confirmations().await.unwrap();
+        assert!(updated_messages.is_empty());
+        {
+            let account = account_handle.read().await;
+            let stored_message = account.get_message(message.id()).unwrap();
+            assert_eq!(stored_message.confirmed(), &None);
+            assert!(*stored_message.confirming());
+        }
+
+        account_handle.account_options.confirmation_threshold = 2;
+        let updated_messages = account_handle.refresh_confirmations().await.unwrap();
+        assert_eq!(updated_messages.len(), 1);
+        let account = account_handle.read().await;
+        let stored_message = account.get_message(message.id()).unwrap();
+        assert_eq!(stored_message.confirmed(), &Some(true));
+        assert!(!*stored_message.confirming());
+    }
+
+    #[tokio::test]
+    async fn refresh_output() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedSingle);
+        let output_id = output.id().unwrap();
+
+        let mut spent_output = output;
+        spent_output.is_spent = true;
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(spent_output)
+            .register()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        let refreshed_output = account_handle.refresh_output(&output_id).await.unwrap();
+        assert!(refreshed_output.is_spent);
+
+        let account = account_handle.read().await;
+        let refreshed_address = account.addresses().iter().find(|a| a == &&address).unwrap();
+        assert!(refreshed_address.outputs()[&output_id].is_spent);
+        assert_eq!(*refreshed_address.balance(), 0);
+    }
+
+    #[tokio::test]
+    async fn rotate_deposit_address() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let previous_address = account_handle.latest_address().await;
+
+        let address = account_handle.rotate_deposit_address().await.unwrap();
+        assert_eq!(account_handle.latest_address().await, address);
+
+        let account = account_handle.read().await;
+        let retired_address = account.addresses().iter().find(|a| a == &&previous_address).unwrap();
+        assert!(*retired_address.retired());
+        assert!(!account.latest_address().retired());
+    }
+
+    #[tokio::test]
+    async fn reserve_addresses() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let deposit_address = account_handle.latest_address().await;
+
+        let reserved = account_handle
+            .reserve_addresses(vec!["invoice-1".to_string(), "invoice-2".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(reserved.len(), 2);
+        assert_eq!(reserved[0].label(), &Some("invoice-1".to_string()));
+        assert_eq!(reserved[1].label(), &Some("invoice-2".to_string()));
+        assert!(reserved.iter().all(|a| *a.reserved()));
+        assert_ne!(reserved[0].key_index(), reserved[1].key_index());
+
+        // the reserved addresses aren't used as deposit address
+        assert_eq!(account_handle.latest_address().await, deposit_address);
+        let address = account_handle.generate_address().await.unwrap();
+        assert_eq!(*address.key_index(), *reserved[1].key_index() + 1);
+        assert_eq!(account_handle.latest_address().await, address);
+
+        let account = account_handle.read().await;
+        let stored = account.addresses().iter().find(|a| a == &&reserved[0]).unwrap();
+        assert!(*stored.reserved());
+    }
+
+    #[tokio::test]
+    async fn locked_addresses() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        assert!(account_handle.locked_addresses().await.is_empty());
+
+        let address = account_handle.latest_address().await.address().clone();
+        account_handle.locked_addresses.lock().await.push(address.clone());
+        assert_eq!(account_handle.locked_addresses().await, vec![address]);
+    }
+
+    #[tokio::test]
+    async fn message_memo() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+
+        let mut message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+        message.set_memo(Some("rent payment".to_string()));
+        account_handle.write().await.append_messages(vec![message.clone()]);
+
+        // the updated message (e.g. after a sync) keeps the memo
+        let mut updated_message = message.clone();
+        updated_message.set_memo(None);
+        updated_message.set_confirmed(Some(true));
+        account_handle.write().await.append_messages(vec![updated_message]);
+
+        let account = account_handle.read().await;
+        let stored_message = account.get_message(message.id()).unwrap();
+        assert_eq!(stored_message.memo(), &Some("rent payment".to_string()));
+        assert_eq!(stored_message.confirmed(), &Some(true));
+
+        let serialized = serde_json::to_string(&stored_message).unwrap();
+        let deserialized: Message = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.memo(), &Some("rent payment".to_string()));
+    }
+
+    #[tokio::test]
+    async fn addresses_near_output_limit() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut busy_address = crate::test_utils::generate_random_address();
+        for _ in 0..2 {
+            crate::test_utils::add_address_output(&mut busy_address, 1000000, OutputKind::SignatureLockedSingle);
+        }
+        let mut address = crate::test_utils::generate_random_address();
+        address.set_key_index(1);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![busy_address.clone(), address])
+            .create()
+            .await;
+
+        assert!(account_handle
+            .addresses_near_output_limit(super::DEFAULT_OUTPUT_LIMIT_WARN_RATIO)
+            .await
+            .is_empty());
+        // 1% of the limit is exceeded by 2 outputs
+        assert_eq!(
+            account_handle.addresses_near_output_limit(0.01).await,
+            vec![(busy_address.address().clone(), 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn transfer_idempotency_key() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut sent_message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+        sent_message.set_idempotency_key(Some("payment-1".to_string()));
+        let mut expired_message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+        expired_message.set_idempotency_key(Some("payment-0".to_string()));
+        expired_message.set_timestamp(chrono::Utc::now() - chrono::Duration::hours(25));
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .messages(vec![sent_message.clone(), expired_message])
+            .create()
+            .await;
+
+        // the retried transfer isn't executed, the previous message is returned instead
+        let transfer = crate::message::Transfer::builder(
+            crate::test_utils::generate_random_iota_address(),
+            std::num::NonZeroU64::new(1000000).unwrap(),
+        )
+        .with_idempotency_key("payment-1")
+        .finish();
+        let message = account_handle.transfer(transfer).await.unwrap();
+        assert_eq!(message.id(), sent_message.id());
+
+        let account = account_handle.read().await;
+        assert!(account
+            .get_idempotent_transfer("payment-0", chrono::Utc::now())
+            .is_none());
+        assert!(account
+            .get_idempotent_transfer("payment-2", chrono::Utc::now())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn addresses_since() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let since = chrono::Local::now() - chrono::Duration::minutes(1);
+
+        let mut old_address = crate::test_utils::generate_random_address();
+        old_address.set_created_at(Some(chrono::Local::now() - chrono::Duration::hours(1)));
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![old_address.clone()])
+            .create()
+            .await;
+
+        let new_address = account_handle.generate_address().await.unwrap();
+        let mut account = account_handle.write().await;
+        assert_eq!(
+            account
+                .addresses_since(since)
+                .into_iter()
+                .map(|a| a.address().clone())
+                .collect::<Vec<_>>(),
+            vec![new_address.address().clone()]
+        );
+
+        // the sync rebuilds the address, but its generation time must be kept
+        let mut synced_address = old_address.clone();
+        synced_address.set_created_at(Some(chrono::Local::now()));
+        account.append_addresses(vec![synced_address]);
+        assert_eq!(account.addresses_since(since).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_addresses() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let expires_at = chrono::Local::now() - chrono::Duration::minutes(1);
+
+        let mut paid_address = crate::test_utils::generate_random_address();
+        paid_address.set_expires_at(Some(expires_at));
+        crate::test_utils::add_address_output(&mut paid_address, 1000000, OutputKind::SignatureLockedSingle);
+        let mut unpaid_address = crate::test_utils::generate_random_address();
+        unpaid_address.set_key_index(1);
+        unpaid_address.set_expires_at(Some(expires_at));
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![paid_address, unpaid_address.clone()])
+            .create()
+            .await;
+
+        let active_address = account_handle
+            .generate_address_with_expiration(chrono::Local::now() + chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert!(active_address.expires_at().is_some());
+
+        let mut account = account_handle.write().await;
+        assert!(account.expired_addresses().is_empty());
+        assert_eq!(
+            account.flag_expired_addresses(chrono::Local::now()),
+            vec![unpaid_address.address().clone()]
+        );
+        // addresses are flagged only once
+        assert!(account.flag_expired_addresses(chrono::Local::now()).is_empty());
+
+        // the sync doesn't know the expiration, so it must be kept when the address is updated
+        let mut synced_address = unpaid_address.clone();
+        synced_address.set_expires_at(None);
+        account.append_addresses(vec![synced_address]);
+        assert_eq!(
+            account
+                .expired_addresses()
+                .into_iter()
+                .map(|a| a.address().clone())
+                .collect::<Vec<_>>(),
+            vec![unpaid_address.address().clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn consumed_outputs() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedSingle);
+        let output_id = output.id().unwrap();
+
+        let spending_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .build()
+            .await;
+        let foreign_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(TransactionId::new([1; 32]))
+            .build()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address])
+            .messages(vec![spending_message.clone(), foreign_message.clone()])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+        assert_eq!(spending_message.consumed_outputs(&account), vec![output_id]);
+        assert!(foreign_message.consumed_outputs(&account).is_empty());
+    }
+
+    #[tokio::test]
+    async fn pending_balance_effect() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedSingle);
+        let mut remainder_address = crate::test_utils::generate_random_address();
+        remainder_address.set_key_index(1);
+
+        let external_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .build()
+            .await;
+        let remainder_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .address(remainder_address.clone())
+            .value(400000)
+            .build()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address, remainder_address])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+        assert_eq!(external_message.pending_balance_effect(&account), -1000000);
+        assert_eq!(remainder_message.pending_balance_effect(&account), -600000);
+    }
+
+    #[tokio::test]
+    async fn projected_balance() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedSingle);
+        let mut remainder_address = crate::test_utils::generate_random_address();
+        remainder_address.set_key_index(1);
+
+        // spends the 1Mi output, sending 400000i back to the account
+        let pending_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .address(remainder_address.clone())
+            .value(400000)
+            .confirmed(None)
+            .build()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address, remainder_address])
+            .messages(vec![pending_message])
+            .create()
+            .await;
+        assert_eq!(account_handle.balance().await.total, 1000000);
+        assert_eq!(account_handle.projected_balance().await, 400000);
+    }
+
+    #[tokio::test]
+    async fn list_pending_transactions() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedSingle);
+
+        let pending_outgoing_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .confirmed(None)
+            .build()
+            .await;
+        let confirmed_outgoing_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(output.transaction_id)
+            .confirmed(Some(true))
+            .build()
+            .await;
+        let pending_incoming_message = crate::test_utils::GenerateMessageBuilder::default()
+            .input_transaction_id(TransactionId::new([9; 32]))
+            .confirmed(None)
+            .incoming(true)
+            .build()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address])
+            .messages(vec![
+                pending_outgoing_message.clone(),
+                confirmed_outgoing_message,
+                pending_incoming_message.clone(),
+            ])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+        assert!(account.is_outgoing(&pending_outgoing_message));
+        assert!(!account.is_outgoing(&pending_incoming_message));
+        assert_eq!(account.list_pending_transactions(), vec![&pending_outgoing_message]);
+    }
+
+    #[tokio::test]
+    async fn migrate_signer_type() {
+        struct FixedAddressSigner(iota::Address);
+
+        #[async_trait::async_trait]
+        impl crate::signing::Signer for FixedAddressSigner {
+            async fn store_mnemonic(&mut self, _: &std::path::PathBuf, _: String) -> crate::Result<()> {
+                Ok(())
+            }
+
+            async fn generate_address(
+                &mut self,
+                _: &super::Account,
+                _: usize,
+                _: bool,
+                _: crate::signing::GenerateAddressMetadata,
+            ) -> crate::Result<iota::Address> {
+                Ok(self.0)
+            }
+
+            async fn sign_message<'a>(
+                &mut self,
+                _: &super::Account,
+                _: &iota::Essence,
+                _: &mut Vec<crate::signing::TransactionInput>,
+                _: crate::signing::SignMessageMetadata<'a>,
+            ) -> crate::Result<Vec<iota::UnlockBlock>> {
+                unreachable!()
+            }
+        }
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        let other_seed_signer_type = crate::signing::SignerType::Custom("other-seed".to_string());
+        crate::signing::set_signer(
+            other_seed_signer_type.clone(),
+            FixedAddressSigner(*crate::test_utils::generate_random_iota_address().as_ref()),
+        )
+        .await;
+        let res = account_handle.migrate_signer_type(other_seed_signer_type).await;
+        assert!(matches!(res, Err(crate::Error::SignerMismatch)));
+
+        let same_seed_signer_type = crate::signing::SignerType::Custom("same-seed".to_string());
+        crate::signing::set_signer(
+            same_seed_signer_type.clone(),
+            FixedAddressSigner(*address.address().as_ref()),
+        )
+        .await;
+        account_handle
+            .migrate_signer_type(same_seed_signer_type.clone())
+            .await
+            .unwrap();
+        assert_eq!(account_handle.signer_type().await, same_seed_signer_type);
+    }
+
+    #[tokio::test]
+    async fn indexation_json() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Invoice {
+            id: u32,
+            description: String,
+        }
+
+        let invoice = Invoice {
+            id: 1,
+            description: "coffee".to_string(),
+        };
+        let transfer = crate::message::Transfer::builder(
+            crate::test_utils::generate_random_iota_address(),
+            std::num::NonZeroU64::new(1000000).unwrap(),
+        )
+        .with_index_and_json("invoices".to_string(), &invoice)
+        .unwrap()
+        .finish();
+        let indexation = transfer.indexation.clone().unwrap();
+        assert_eq!(indexation.index(), b"invoices");
+
+        let mut message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+        assert!(message.indexation_json::<Invoice>().unwrap().is_none());
+        message.payload = Some(MessagePayload::Indexation(Box::new(indexation)));
+        assert_eq!(message.indexation_json::<Invoice>().unwrap(), Some(invoice));
+    }
+
+    #[tokio::test]
+    async fn all_output_ids() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut first_address = crate::test_utils::generate_random_address();
+        let unspent_output =
+            crate::test_utils::add_address_output(&mut first_address, 1000000, OutputKind::SignatureLockedSingle);
+        let mut second_address = crate::test_utils::generate_random_address();
+        second_address.set_key_index(1);
+        let mut spent_output = crate::test_utils::generate_address_output(
+            second_address.address(),
+            2000000,
+            OutputKind::SignatureLockedSingle,
+        );
+        spent_output.is_spent = true;
+        second_address
+            .outputs
+            .insert(spent_output.id().unwrap(), spent_output.clone());
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![first_address, second_address])
+            .create()
+            .await;
+        let mut output_ids = account_handle.read().await.all_output_ids();
+        output_ids.sort_by_key(|id| id.to_string());
+        let mut expected = vec![unspent_output.id().unwrap(), spent_output.id().unwrap()];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(output_ids, expected);
+    }
+
+    #[tokio::test]
+    async fn address_usage_stats() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut addresses = Vec::new();
+        for key_index in 0..5 {
+            let mut address = crate::test_utils::generate_random_address();
+            address.set_key_index(key_index);
+            addresses.push(address);
+        }
+        // index 0 is reused, indexes 1 and 2 are unused, index 3 is used and index 4 is unused
+        for amount in &[1000000, 2000000] {
+            crate::test_utils::add_address_output(&mut addresses[0], *amount, OutputKind::SignatureLockedSingle);
+        }
+        crate::test_utils::add_address_output(&mut addresses[3], 3000000, OutputKind::SignatureLockedSingle);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(addresses)
+            .create()
+            .await;
+        let stats = account_handle.read().await.address_usage_stats();
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.used, 2);
+        assert_eq!(stats.unused, 3);
+        assert_eq!(stats.largest_gap, 2);
+        assert_eq!(stats.reused, 1);
+    }
+
+    #[tokio::test]
+    async fn await_initial_sync() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+        assert!(account_handle.last_synced_at().await.is_none());
+
+        account_handle
+            .await_initial_sync(std::time::Duration::from_secs(30))
+            .await
+            .unwrap();
+        let last_synced_at = account_handle.last_synced_at().await;
+        assert!(last_synced_at.is_some());
+
+        // the account was already synced, so nothing runs
+        account_handle
+            .await_initial_sync(std::time::Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(account_handle.last_synced_at().await, last_synced_at);
+    }
+
+    #[tokio::test]
+    async fn ensure_dust_capacity() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let allowance_output =
+            crate::test_utils::add_address_output(&mut address, 1000000, OutputKind::SignatureLockedDustAllowance);
+        for _ in 0..3 {
+            crate::test_utils::add_address_output(&mut address, 1, OutputKind::SignatureLockedSingle);
+        }
+
+        let mut funded_address = crate::test_utils::generate_random_address();
+        funded_address.set_key_index(1);
+        crate::test_utils::add_address_output(&mut funded_address, 5000000, OutputKind::SignatureLockedSingle);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone(), funded_address])
+            .create()
+            .await;
+
+        // the 1Mi dust allowance covers 10 dust outputs
+        assert!(account_handle
+            .ensure_dust_capacity(address.address(), 7)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(super::missing_dust_allowance(&address, 8).unwrap(), Some(1000000));
+        assert_eq!(super::missing_dust_allowance(&address, 27).unwrap(), Some(2000000));
+        // an address holds 100 dust outputs at most
+        assert!(matches!(
+            account_handle.ensure_dust_capacity(address.address(), 98).await,
+            Err(crate::Error::DustError(_))
+        ));
+
+        // the missing dust allowance is sent from another address, keeping the existing dust allowance output
+        let transfer = account_handle
+            .dust_capacity_transfer(address.address(), 8)
+            .await
+            .unwrap()
+            .unwrap();
+        let prepared_transaction = crate::account::SyncedAccount::from(account_handle.clone())
+            .await
+            .prepare_transfer(transfer)
+            .await
+            .unwrap();
+        assert!(!prepared_transaction.inputs().contains(&allowance_output));
+        assert!(prepared_transaction
+            .outputs()
+            .iter()
+            .any(|output| output.kind() == OutputKind::SignatureLockedDustAllowance
+                && output.address() == Some(address.address())
+                && output.amount() == 1000000));
+        assert!(prepared_transaction.dust_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reconcile_interrupted_transfers() {
+        use crate::client::NodeClient;
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let node_message = crate::test_utils::MockNodeClient::default()
+            .finish_pow(None)
+            .await
+            .unwrap();
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_message(node_message.clone(), None)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+
+        let (broadcasted_id, unsent_id, in_flight_id) = {
+            let mut account = account_handle.write().await;
+            let address = crate::test_utils::generate_random_iota_address();
+            let broadcasted_id = account.journal_transfer(Vec::new(), address.clone(), 1000000);
+            account.set_pending_transfer_message_id(&broadcasted_id, node_message.id().0);
+            let unsent_id = account.journal_transfer(Vec::new(), address.clone(), 2000000);
+            let in_flight_id = account.journal_transfer(Vec::new(), address, 3000000);
+            (broadcasted_id, unsent_id, in_flight_id)
+        };
+        account_handle
+            .in_flight_transfers
+            .lock()
+            .await
+            .insert(in_flight_id.clone());
+
+        let mut reconciled: Vec<String> = account_handle
+            .reconcile_interrupted_transfers()
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.id().clone())
+            .collect();
+        reconciled.sort();
+        let mut expected = vec![broadcasted_id, unsent_id];
+        expected.sort();
+        assert_eq!(reconciled, expected);
+
+        let account = account_handle.read().await;
+        // the broadcasted transfer is watched, the transfer running on this process is kept
+        assert!(account.get_message(&node_message.id().0).is_some());
+        assert_eq!(account.pending_transfers().len(), 1);
+        assert_eq!(account.pending_transfers()[0].id(), &in_flight_id);
+    }
+
+    #[tokio::test]
+    async fn consolidation_schedule() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let now = chrono::Local::now();
+        assert!(account_handle.read().await.is_consolidation_due(now));
+
+        account_handle
+            .set_consolidation_schedule(super::ConsolidationSchedule::Interval(3600))
+            .await
+            .unwrap();
+        let mut account = account_handle.write().await;
+        // never consolidated
+        assert!(account.is_consolidation_due(now));
+        account.set_last_consolidated_at(Some(now - chrono::Duration::minutes(30)));
+        assert!(!account.is_consolidation_due(now));
+        account.set_last_consolidated_at(Some(now - chrono::Duration::hours(2)));
+        assert!(account.is_consolidation_due(now));
+
+        account
+            .set_consolidation_schedule(super::ConsolidationSchedule::WhenIdle)
+            .await
+            .unwrap();
+        assert!(account.is_consolidation_due(now));
+        let unconfirmed_message = crate::test_utils::GenerateMessageBuilder::default()
+            .confirmed(None)
+            .build()
+            .await;
+        account.append_messages(vec![unconfirmed_message]);
+        assert!(!account.is_consolidation_due(now));
+    }
 }
@@ -1,7 +1,8 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::address::AddressWrapper;
+use crate::{address::AddressWrapper, message::InputSelectionStrategy};
+use chrono::prelude::{DateTime, Local};
 use rand::{prelude::SliceRandom, thread_rng};
 use std::{
     cmp::Ordering,
@@ -16,6 +17,137 @@ pub struct Input {
     pub address: AddressWrapper,
     pub internal: bool,
     pub balance: u64,
+    pub key_index: usize,
+    pub created_at: Option<DateTime<Local>>,
+    pub output_count: usize,
+}
+
+/// Selects the inputs with the given strategy, consuming at most `max_inputs` outputs.
+/// The strategies other than the default branch and bound are deterministic.
+pub fn select_input_with_strategy(
+    target: u64,
+    mut available_utxos: Vec<Input>,
+    strategy: InputSelectionStrategy,
+    max_inputs: usize,
+) -> crate::Result<Vec<Input>> {
+    let total_available_balance: u64 = available_utxos.iter().map(|utxo| utxo.balance).sum();
+    if target > total_available_balance {
+        return Err(crate::Error::InsufficientFunds);
+    }
+
+    match strategy {
+        InputSelectionStrategy::BranchAndBound => {
+            let selected_utxos = select_input(target, available_utxos.clone())?;
+            if output_count(&selected_utxos) <= max_inputs {
+                return Ok(selected_utxos);
+            }
+            // the selection consumes too many outputs, so fewer, larger inputs are picked instead
+            sort_by_value_per_output(&mut available_utxos);
+            return select_in_order(target, available_utxos.clone(), max_inputs).ok_or_else(|| {
+                let needed = select_in_order(target, available_utxos, usize::MAX)
+                    .map(|selected_utxos| output_count(&selected_utxos))
+                    .unwrap_or_else(|| output_count(&selected_utxos));
+                crate::Error::TooManyInputsRequired {
+                    needed,
+                    max: max_inputs,
+                }
+            });
+        }
+        InputSelectionStrategy::MinimizeInputs => {
+            // a single address covering the target is the smallest selection
+            let single_input = available_utxos
+                .iter()
+                .filter(|utxo| utxo.output_count <= max_inputs && is_valid_selection(target, utxo.balance))
+                .min_by(|a, b| {
+                    a.output_count
+                        .cmp(&b.output_count)
+                        .then(a.balance.cmp(&b.balance))
+                        .then_with(|| address_order(a, b))
+                });
+            if let Some(input) = single_input {
+                return Ok(vec![input.clone()]);
+            }
+            // otherwise the addresses holding the most value per output come first
+            sort_by_value_per_output(&mut available_utxos);
+        }
+        InputSelectionStrategy::MinimizeDust => available_utxos.sort_by(|a, b| {
+            a.balance
+                .cmp(&b.balance)
+                .then(b.output_count.cmp(&a.output_count))
+                .then_with(|| address_order(a, b))
+        }),
+        InputSelectionStrategy::OldestFirst => available_utxos.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then(a.internal.cmp(&b.internal))
+                .then(a.key_index.cmp(&b.key_index))
+                .then_with(|| address_order(a, b))
+        }),
+        InputSelectionStrategy::LargestFirst => available_utxos.sort_by(|a, b| {
+            b.balance
+                .cmp(&a.balance)
+                .then(b.internal.cmp(&a.internal))
+                .then_with(|| address_order(a, b))
+        }),
+    }
+    select_in_order(target, available_utxos.clone(), max_inputs).ok_or_else(|| {
+        match select_in_order(target, available_utxos, usize::MAX) {
+            // the strategy order reaches the target without the cap, so only the cap is hit
+            Some(selected_utxos) => crate::Error::TooManyInputsRequired {
+                needed: output_count(&selected_utxos),
+                max: max_inputs,
+            },
+            None => crate::Error::InputSelectionFailed(strategy),
+        }
+    })
+}
+
+// The bech32 address breaks the ties so the selection doesn't depend on the addresses order.
+fn address_order(a: &Input, b: &Input) -> Ordering {
+    a.address.to_bech32().cmp(&b.address.to_bech32())
+}
+
+// Sorts the inputs so the addresses holding the most value per output come first.
+fn sort_by_value_per_output(utxos: &mut [Input]) {
+    utxos.sort_by(|a, b| {
+        (b.balance as u128 * a.output_count as u128)
+            .cmp(&(a.balance as u128 * b.output_count as u128))
+            .then_with(|| address_order(a, b))
+    });
+}
+
+// The number of outputs consumed by the inputs.
+fn output_count(utxos: &[Input]) -> usize {
+    utxos.iter().map(|utxo| utxo.output_count).sum()
+}
+
+// Whether the selected balance reaches the target without leaving a dust remainder.
+fn is_valid_selection(target: u64, selected_balance: u64) -> bool {
+    selected_balance == target || (selected_balance > target && selected_balance - target >= DUST_ALLOWANCE_VALUE)
+}
+
+// Selects the inputs in the given order until the target is reached,
+// skipping the ones that would exceed the maximum number of outputs consumed.
+fn select_in_order(target: u64, available_utxos: Vec<Input>, max_inputs: usize) -> Option<Vec<Input>> {
+    let mut selected_coins = Vec::new();
+    let mut selected_balance = 0;
+    let mut selected_outputs = 0;
+    for utxo in available_utxos {
+        if is_valid_selection(target, selected_balance) {
+            break;
+        }
+        if selected_outputs + utxo.output_count > max_inputs {
+            continue;
+        }
+        selected_balance += utxo.balance;
+        selected_outputs += utxo.output_count;
+        selected_coins.push(utxo);
+    }
+    if is_valid_selection(target, selected_balance) {
+        Some(selected_coins)
+    } else {
+        None
+    }
 }
 
 pub fn select_input(target: u64, mut available_utxos: Vec<Input>) -> crate::Result<Vec<Input>> {
@@ -152,6 +284,9 @@ mod tests {
                 address: address.address().clone(),
                 balance: *address.balance(),
                 internal: false,
+                key_index: i,
+                created_at: None,
+                output_count: 1,
             });
         }
         available_utxos
@@ -246,4 +381,110 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn strategies() {
+        let input = |balance: u64, output_count: usize, key_index: usize| super::Input {
+            address: AddressWrapper::new(
+                IotaAddress::Ed25519(Ed25519Address::new([key_index as u8; 32])),
+                "iota".to_string(),
+            ),
+            internal: false,
+            balance,
+            key_index,
+            created_at: None,
+            output_count,
+        };
+        let available_utxos = vec![
+            input(5_000_000, 5, 0),
+            input(3_000_000, 1, 1),
+            input(2_000_000, 1, 2),
+            input(1_000_000, 2, 3),
+        ];
+        let selected_key_indexes = |strategy: InputSelectionStrategy, max_inputs: usize| {
+            select_input_with_strategy(3_000_000, available_utxos.clone(), strategy, max_inputs)
+                .unwrap()
+                .iter()
+                .map(|input| input.key_index)
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(selected_key_indexes(InputSelectionStrategy::LargestFirst, 127), vec![0]);
+        assert_eq!(
+            selected_key_indexes(InputSelectionStrategy::MinimizeInputs, 127),
+            vec![1]
+        );
+        assert_eq!(
+            selected_key_indexes(InputSelectionStrategy::MinimizeDust, 127),
+            vec![3, 2]
+        );
+        assert_eq!(selected_key_indexes(InputSelectionStrategy::OldestFirst, 127), vec![0]);
+        // the largest address has too many outputs
+        assert_eq!(selected_key_indexes(InputSelectionStrategy::LargestFirst, 4), vec![1]);
+
+        match select_input_with_strategy(
+            12_000_000,
+            available_utxos.clone(),
+            InputSelectionStrategy::LargestFirst,
+            127,
+        ) {
+            Err(crate::Error::InsufficientFunds) => {}
+            _ => panic!("unexpected response"),
+        }
+        // every address is needed, but they have more outputs than allowed
+        match select_input_with_strategy(
+            11_000_000,
+            available_utxos.clone(),
+            InputSelectionStrategy::LargestFirst,
+            8,
+        ) {
+            Err(crate::Error::TooManyInputsRequired { needed: 9, max: 8 }) => {}
+            _ => panic!("unexpected response"),
+        }
+        // the selection would leave a dust remainder
+        match select_input_with_strategy(10_500_000, available_utxos, InputSelectionStrategy::LargestFirst, 127) {
+            Err(crate::Error::InputSelectionFailed(InputSelectionStrategy::LargestFirst)) => {}
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[test]
+    fn branch_and_bound_max_inputs() {
+        let input = |balance: u64, output_count: usize, key_index: usize| super::Input {
+            address: AddressWrapper::new(
+                IotaAddress::Ed25519(Ed25519Address::new([key_index as u8; 32])),
+                "iota".to_string(),
+            ),
+            internal: false,
+            balance,
+            key_index,
+            created_at: None,
+            output_count,
+        };
+        let available_utxos = vec![input(4_000_000, 4, 0), input(2_000_000, 1, 1), input(2_000_000, 1, 2)];
+        let selected_key_indexes = |max_inputs: usize| {
+            let mut key_indexes: Vec<usize> = select_input_with_strategy(
+                4_000_000,
+                available_utxos.clone(),
+                InputSelectionStrategy::BranchAndBound,
+                max_inputs,
+            )
+            .unwrap()
+            .iter()
+            .map(|input| input.key_index)
+            .collect();
+            key_indexes.sort_unstable();
+            key_indexes
+        };
+
+        // the largest address matches the target exactly
+        assert_eq!(selected_key_indexes(127), vec![0]);
+        // but it has too many outputs, so the larger inputs are picked instead
+        assert_eq!(selected_key_indexes(2), vec![1, 2]);
+
+        match select_input_with_strategy(4_000_000, available_utxos, InputSelectionStrategy::BranchAndBound, 1) {
+            Err(crate::Error::TooManyInputsRequired { needed: 2, max: 1 }) => {}
+            _ => panic!("unexpected response"),
+        }
+    }
 }
@@ -2,114 +2,173 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account::{Account, AccountHandle},
-    account_manager::{AccountOptions, AccountStore},
-    address::{Address, AddressBuilder, AddressOutput, AddressWrapper, OutputKind},
-    client::ClientOptions,
+    account::{Account, AccountHandle, PendingTransfer},
+    account_manager::{AccountOptions, AccountStore, AddressSyncOrder, SyncRetryPolicy},
+    address::{parse_output, Address, AddressBuilder, AddressOutput, AddressWrapper, IotaAddress, OutputKind},
+    client::{ClientOptions, NodeClient, NodeMetrics},
     event::{
-        emit_balance_change, emit_confirmation_state_change, emit_transaction_event, BalanceChange,
-        TransactionEventType, TransferProgressType,
+        emit_balance_change, emit_confirmation_state_change, emit_consolidation_completed, emit_consolidation_started,
+        emit_gap_limit_reached, emit_sync_completed, emit_transaction_event, BalanceChange, TransactionEventType,
+        TransferProgressType,
+    },
+    message::{
+        DepositAddressInputPolicy, Message, MessagePayload, RemainderValueStrategy, TransactionEssence,
+        TransactionInput, TransactionOutput, Transfer,
     },
-    message::{Message, RemainderValueStrategy, Transfer},
     signing::{GenerateAddressMetadata, SignMessageMetadata},
 };
 
 use bee_common::packable::Packable;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use getset::Getters;
 use iota::{
-    bee_rest_api::types::dtos::LedgerInclusionStateDto,
-    client::{api::finish_pow, AddressOutputsOptions, Client},
     message::{
         constants::INPUT_OUTPUT_COUNT_MAX,
         prelude::{
             Essence, Input, Message as IotaMessage, MessageId, Output, Payload, RegularEssence,
-            SignatureLockedSingleOutput, TransactionPayload, UTXOInput, UnlockBlocks,
+            SignatureLockedDustAllowanceOutput, SignatureLockedSingleOutput, TransactionPayload, UTXOInput,
+            UnlockBlocks,
         },
     },
     Bech32Address, OutputId,
 };
+use rand::Rng;
 use serde::Serialize;
-use slip10::BIP32Path;
-use tokio::sync::MutexGuard;
+use tokio::sync::{Mutex, MutexGuard, RwLockWriteGuard};
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
     num::NonZeroU64,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 mod input_selection;
 
-const DUST_ALLOWANCE_VALUE: u64 = 1_000_000;
+pub(crate) const DUST_ALLOWANCE_VALUE: u64 = 1_000_000;
+const DUST_ALLOWANCE_PER_OUTPUT: u64 = 100_000;
+const MAX_DUST_OUTPUTS_PER_ADDRESS: u64 = 100;
+const ADDRESS_BALANCE_BATCH_SIZE: usize = 50;
+/// The maximum number of addresses synced at the same time, so the address sync order decides which ones sync first.
+const ADDRESS_SYNC_CONCURRENCY: usize = 10;
+/// The referenced rate (percentage) below which the confirmation time estimate stops growing.
+const MIN_ESTIMATE_REFERENCED_RATE: f64 = 10.0;
+/// The maximum delay before retrying a failed node request, excluding the jitter.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+// The number of local PoW threads that exceeded their time budget and are still running in the background.
+static ABANDONED_LOCAL_POW_COUNT: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug, Clone)]
 pub(crate) struct SyncedMessage {
     pub(crate) id: MessageId,
     pub(crate) confirmed: Option<bool>,
+    pub(crate) confirming: bool,
     pub(crate) inner: IotaMessage,
 }
 
+// Whether the node request failed with a server or network error, so it can be retried.
+// Malformed responses (e.g. decode or body errors) fail the same way again, so they aren't retried.
+fn is_retryable_error(error: &crate::Error) -> bool {
+    match error {
+        crate::Error::NodeUnreachable(_) => true,
+        crate::Error::ClientError(error) => match error.as_ref() {
+            iota::client::Error::ResponseError(status, _) => *status >= 500,
+            iota::client::Error::ReqwestError(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// The delay before the given retry (starting at 0): `base_delay * 2^retry`, capped at `MAX_RETRY_DELAY`.
+fn retry_delay(policy: SyncRetryPolicy, retry: u32) -> Duration {
+    2u32.checked_pow(retry)
+        .and_then(|factor| policy.base_delay.checked_mul(factor))
+        .map_or(MAX_RETRY_DELAY, |delay| delay.min(MAX_RETRY_DELAY))
+}
+
+// Runs the node request, retrying it with an exponential backoff according to the retry policy.
+// If the request keeps failing, the first error is returned since the later ones may only be a consequence of it.
+async fn with_retries<T, F, Fut>(policy: SyncRetryPolicy, mut request: F) -> crate::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = crate::Result<T>>,
+{
+    let mut retry = 0;
+    let mut first_error = None;
+    loop {
+        match request().await {
+            Err(e) if retry < policy.max_retries && is_retryable_error(&e) => {
+                let mut delay = retry_delay(policy, retry);
+                let jitter = policy.jitter.as_millis() as u64;
+                if jitter > 0 {
+                    let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=jitter));
+                    delay = delay.checked_add(jitter).unwrap_or(delay);
+                }
+                retry += 1;
+                log::debug!(
+                    "[SYNC] node request failed ({}), retrying ({}/{}) in {:?}",
+                    e,
+                    retry,
+                    policy.max_retries,
+                    delay
+                );
+                first_error.get_or_insert(e);
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(first_error.unwrap_or(e)),
+            res => return res,
+        }
+    }
+}
+
 async fn get_address_outputs(
     address: &Bech32Address,
-    client: &Client,
+    client: &dyn NodeClient,
     fetch_spent_outputs: bool,
+    retry_policy: SyncRetryPolicy,
 ) -> crate::Result<Vec<UTXOInput>> {
-    let mut address_outputs = client
-        .get_address()
-        .outputs(
-            &address,
-            AddressOutputsOptions {
-                include_spent: fetch_spent_outputs,
-                ..Default::default()
-            },
-        )
-        .await?
-        .to_vec();
+    let mut address_outputs = with_retries(retry_policy, || {
+        client.get_address_outputs(&address, fetch_spent_outputs)
+    })
+    .await?;
     // if we hit the max output length, we need to fetch again without including spent outputs
     if fetch_spent_outputs && address_outputs.len() == 1000 {
-        let unspent_address_outputs = client
-            .get_address()
-            .outputs(
-                &address,
-                AddressOutputsOptions {
-                    include_spent: false,
-                    ..Default::default()
-                },
-            )
-            .await?
-            .to_vec();
+        let unspent_address_outputs =
+            with_retries(retry_policy, || client.get_address_outputs(&address, false)).await?;
         address_outputs.extend(unspent_address_outputs);
-        address_outputs.dedup();
+        // the unspent outputs were already fetched if they're among the first 1000 outputs
+        let mut output_ids = HashSet::new();
+        address_outputs.retain(|output| output_ids.insert(*output.output_id()));
     }
     Ok(address_outputs)
 }
 
-#[derive(Default)]
-struct MessageMetadata {
-    confirmed: Option<bool>,
-}
-
-async fn get_message_and_metadata(
-    client: &Client,
-    message_id: &MessageId,
-) -> crate::Result<Option<(IotaMessage, MessageMetadata)>> {
-    match client.get_message().data(message_id).await {
-        Ok(message) => {
-            let metadata = client
-                .get_message()
-                .metadata(message_id)
-                .await
-                .map(|metadata| MessageMetadata {
-                    confirmed: metadata
-                        .ledger_inclusion_state
-                        .map(|l| l == LedgerInclusionStateDto::Included),
-                })
-                .unwrap_or_default();
-            Ok(Some((message, metadata)))
+// Gets the balance of the given addresses, querying the node concurrently in chunks of
+// `ADDRESS_BALANCE_BATCH_SIZE` addresses, so the address syncs don't query the balance one by one.
+// The addresses whose balance couldn't be fetched are not included in the returned map,
+// so the caller should fall back to querying their balance individually.
+async fn get_address_balances(client: &dyn NodeClient, addresses: &[AddressWrapper]) -> HashMap<String, u64> {
+    let mut balances = HashMap::new();
+    for chunk in addresses.chunks(ADDRESS_BALANCE_BATCH_SIZE) {
+        let bech32_addresses: Vec<Bech32Address> = chunk.iter().map(|a| a.to_bech32().into()).collect();
+        match client.balances(&bech32_addresses).await {
+            Ok(responses) if responses.len() == chunk.len() => {
+                for (address, balance) in chunk.iter().zip(responses) {
+                    balances.insert(address.to_bech32(), balance);
+                }
+            }
+            Ok(_) => log::debug!("[SYNC] batch balance response doesn't match the requested addresses"),
+            Err(e) => log::debug!("[SYNC] failed to get the balance of the addresses batch: {:?}", e),
         }
-        Err(iota::client::Error::ResponseError(status_code, _)) if status_code == 404 => Ok(None),
-        Err(e) => Err(e.into()),
     }
+    balances
 }
 
 pub(crate) async fn sync_address(
@@ -118,16 +177,25 @@ pub(crate) async fn sync_address(
     outputs: &mut HashMap<OutputId, AddressOutput>,
     iota_address: AddressWrapper,
     bech32_hrp: String,
+    balance: Option<u64>,
     options: AccountOptions,
     is_monitoring: Arc<AtomicBool>,
 ) -> crate::Result<(u64, Vec<SyncedMessage>)> {
-    let client_guard = crate::client::get_client(client_options, Some(is_monitoring)).await?;
-    let client = client_guard.read().await;
+    let client = crate::client::get_node_client(client_options, Some(is_monitoring)).await?;
 
     let bech32_address = iota_address.to_bech32().into();
 
-    let address_outputs = get_address_outputs(&bech32_address, &client, options.sync_spent_outputs).await?;
-    let balance = client.get_address().balance(&bech32_address).await?.balance;
+    let address_outputs = get_address_outputs(
+        &bech32_address,
+        &*client,
+        options.sync_spent_outputs,
+        options.sync_retry_policy,
+    )
+    .await?;
+    let balance = match balance {
+        Some(balance) => balance,
+        None => with_retries(options.sync_retry_policy, || client.balance(&bech32_address)).await?,
+    };
     let mut found_messages = vec![];
 
     log::debug!(
@@ -137,25 +205,41 @@ pub(crate) async fn sync_address(
         balance,
     );
 
+    // the outputs the node reports as unspent, to restore the ones that are locally spent
+    let unspent_address_outputs = if options.reconcile_spent_outputs && options.sync_spent_outputs {
+        with_retries(options.sync_retry_policy, || {
+            client.get_address_outputs(&bech32_address, false)
+        })
+        .await?
+    } else {
+        address_outputs.clone()
+    };
+
     let mut tasks = Vec::new();
     for utxo_input in address_outputs.iter() {
         let utxo_input = utxo_input.clone();
         // if we already have the output and it is spent, we don't need to get the info from the node
         let existing_output = outputs.get(utxo_input.output_id()).cloned();
         if let Some(existing_output) = &existing_output {
-            if existing_output.is_spent {
+            if existing_output.is_spent
+                && !(options.reconcile_spent_outputs && unspent_address_outputs.contains(&utxo_input))
+            {
                 continue;
             }
         }
 
-        let client_guard = client_guard.clone();
+        let client = client.clone();
         let bech32_hrp = bech32_hrp.clone();
         let account_messages = account_messages.clone();
         tasks.push(async move {
             tokio::spawn(async move {
-                let client = client_guard.read().await;
-                let output = client.get_output(&utxo_input).await?;
+                let output = with_retries(options.sync_retry_policy, || client.get_output(&utxo_input)).await?;
                 let found_output = AddressOutput::from_output_response(output, bech32_hrp.to_string())?;
+                // treasury outputs can't be spent by the account, so we skip them unless requested
+                if found_output.kind == OutputKind::Treasury && !options.include_treasury_outputs {
+                    log::debug!("[SYNC] skipping treasury output {:?}", utxo_input.output_id());
+                    return crate::Result::Ok(None);
+                }
                 let message_id = *found_output.message_id();
 
                 // if we already have the message stored
@@ -165,27 +249,37 @@ pub(crate) async fn sync_address(
                     .iter()
                     .any(|(id, confirmed)| id == &message_id && confirmed.is_some())
                 {
-                    return crate::Result::Ok((found_output, None));
+                    return Ok(Some((found_output, None)));
                 }
 
-                if let Some((message, metadata)) = get_message_and_metadata(&client, &message_id).await? {
+                if let Some((message, confirmed)) =
+                    with_retries(options.sync_retry_policy, || client.get_message(&message_id)).await?
+                {
                     // if the output is spent, the message is confirmed
-                    let confirmed = if found_output.is_spent {
+                    let confirmed = if found_output.is_spent && !options.require_spent_output_confirmation {
                         Some(true)
                     } else {
-                        metadata.confirmed
+                        confirmed
                     };
-                    return Ok((
+                    let (confirmed, confirming) = crate::client::apply_confirmation_threshold(
+                        &*client,
+                        &message_id,
+                        confirmed,
+                        options.confirmation_threshold,
+                    )
+                    .await?;
+                    return Ok(Some((
                         found_output,
                         Some(SyncedMessage {
                             id: message_id,
                             confirmed,
+                            confirming,
                             inner: message,
                         }),
-                    ));
+                    )));
                 }
 
-                Ok((found_output, None))
+                Ok(Some((found_output, None)))
             })
             .await
         });
@@ -193,10 +287,20 @@ pub(crate) async fn sync_address(
 
     for res in futures::future::try_join_all(tasks)
         .await
-        .expect("failed to sync address")
+        .map_err(|e| crate::Error::Panic(e.to_string()))?
     {
-        let (found_output, found_message) = res?;
-        outputs.insert(found_output.id()?, found_output);
+        let (found_output, found_message) = match res? {
+            Some(found) => found,
+            None => continue,
+        };
+        let output_id = found_output.id()?;
+        if !found_output.is_spent && outputs.get(&output_id).map(|o| o.is_spent).unwrap_or(false) {
+            log::warn!(
+                "[SYNC] output {} is unspent on the node but was spent locally, restoring it",
+                output_id
+            );
+        }
+        outputs.insert(output_id, found_output);
         if let Some(m) = found_message {
             found_messages.push(m);
         }
@@ -217,7 +321,7 @@ async fn get_address_for_sync(
     if let Some(address) = account
         .addresses()
         .iter()
-        .find(|a| *a.key_index() == index && *a.internal() == internal)
+        .find(|a| *a.key_index() == index && *a.internal() == internal && !a.imported())
     {
         Ok(Some(address.address().clone()))
     } else {
@@ -247,27 +351,38 @@ async fn get_address_for_sync(
     }
 }
 
+// Syncs the given addresses concurrently.
+// An address that fails to sync is reported as a failure instead of aborting the other addresses sync;
+// the first error is returned if none of the addresses could be synced.
 async fn sync_address_list(
     addresses: Vec<Address>,
     account_messages: Vec<(MessageId, Option<bool>)>,
     is_monitoring: Arc<AtomicBool>,
     options: AccountOptions,
     client_options: ClientOptions,
-) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>)> {
+) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>, Vec<AddressSyncFailure>)> {
+    let balances = {
+        let client = crate::client::get_node_client(&client_options, Some(is_monitoring.clone())).await?;
+        let address_wrappers: Vec<AddressWrapper> = addresses.iter().map(|a| a.address().clone()).collect();
+        get_address_balances(&*client, &address_wrappers).await
+    };
     let mut tasks = Vec::new();
     for mut address in addresses {
         let account_messages = account_messages.clone();
         let mut outputs = address.outputs().clone();
         let is_monitoring = is_monitoring.clone();
         let client_options = client_options.clone();
+        let balance = balances.get(&address.address().to_bech32()).copied();
+        let bech32_address = address.address().to_bech32();
         tasks.push(async move {
-            tokio::spawn(async move {
+            let res = tokio::spawn(async move {
                 let (balance, messages) = sync_address(
                     account_messages,
                     &client_options,
                     &mut outputs,
                     address.address().clone(),
                     address.address().bech32_hrp.clone(),
+                    balance,
                     options,
                     is_monitoring,
                 )
@@ -276,23 +391,55 @@ async fn sync_address_list(
                 address.set_outputs(outputs);
                 crate::Result::Ok((messages, address))
             })
-            .await
+            .await;
+            (bech32_address, res)
         });
     }
     let mut found_addresses = Vec::new();
     let mut found_messages = Vec::new();
-    let results = futures::future::try_join_all(tasks)
-        .await
-        .expect("failed to sync addresses");
-    for res in results {
-        let (messages, address) = res?;
-        // if the address is a change address and has no outputs, we ignore it
-        if !(*address.internal() && address.outputs().is_empty()) {
-            found_addresses.push(address);
+    let mut synced_any = false;
+    let mut failures = Vec::new();
+    for (bech32_address, res) in futures::future::join_all(tasks).await {
+        match res.map_err(|e| crate::Error::Panic(e.to_string())).and_then(|res| res) {
+            Ok((messages, address)) => {
+                synced_any = true;
+                // if the address is a change address and has no outputs, we ignore it
+                if !(*address.internal() && address.outputs().is_empty()) {
+                    found_addresses.push(address);
+                }
+                found_messages.extend(messages);
+            }
+            Err(e) => failures.push((bech32_address, e)),
+        }
+    }
+    let failed_addresses = collect_sync_failures(failures, synced_any)?;
+    // keep a stable address order regardless of the tasks scheduling
+    found_addresses.sort_by_key(|address| (*address.key_index(), *address.internal()));
+    Ok((found_addresses, found_messages, failed_addresses))
+}
+
+// Converts the address sync errors to failure reports.
+// If no address could be synced, the first error is returned instead so the sync fails as a whole.
+fn collect_sync_failures(
+    failures: Vec<(String, crate::Error)>,
+    synced_any: bool,
+) -> crate::Result<Vec<AddressSyncFailure>> {
+    if !synced_any {
+        if let Some((_, error)) = failures.into_iter().next() {
+            return Err(error);
         }
-        found_messages.extend(messages);
+        return Ok(Vec::new());
     }
-    Ok((found_addresses, found_messages))
+    Ok(failures
+        .into_iter()
+        .map(|(address, error)| {
+            log::warn!("[SYNC] failed to sync address {}: {}", address, error);
+            AddressSyncFailure {
+                address,
+                error: error.to_string(),
+            }
+        })
+        .collect())
 }
 
 /// Syncs addresses with the tangle.
@@ -309,24 +456,29 @@ async fn sync_address_list(
 ///
 /// # Return value
 ///
-/// Returns a (addresses, messages) tuples representing the address history up to latest unused address,
-/// and the messages associated with the addresses.
+/// Returns a (addresses, messages, suggested gap limit, failed addresses) tuple representing the address history up to
+/// latest unused address, the messages associated with the addresses, if funds were found up to the gap limit boundary
+/// (so funds beyond it may have been missed), a larger gap limit to sync with and the addresses that couldn't be
+/// synced.
 async fn sync_addresses(
     account: &Account,
     address_index: usize,
     gap_limit: usize,
     options: AccountOptions,
     is_monitoring: Arc<AtomicBool>,
-) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>)> {
+) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>, Option<usize>, Vec<AddressSyncFailure>)> {
     let mut address_index = address_index;
 
     let mut generated_addresses = vec![];
     let mut found_messages = vec![];
+    let mut failed_addresses = vec![];
+    let mut last_used_index: Option<usize> = None;
+    let mut address_generation_locked;
 
     let bech32_hrp = account.bech32_hrp().clone();
 
     loop {
-        let mut address_generation_locked = false;
+        address_generation_locked = false;
         let mut generated_iota_addresses = vec![]; // collection of (address_index, internal, address) pairs
         for i in address_index..(address_index + gap_limit) {
             // generate both `public` and `internal (change)` addresses
@@ -350,8 +502,7 @@ async fn sync_addresses(
             .iter()
             .map(|a| (a.address().clone(), a.outputs().clone()))
             .collect();
-        let account_messages: Vec<(MessageId, Option<bool>)> =
-            account.messages().iter().map(|m| (*m.id(), *m.confirmed())).collect();
+        let account_messages = account.known_message_states();
         let client_options = account.client_options().clone();
 
         let mut addresses_to_sync = Vec::new();
@@ -371,20 +522,45 @@ async fn sync_addresses(
             addresses_to_sync.push(address);
         }
 
-        let (found_addresses_, found_messages_) = sync_address_list(
-            addresses_to_sync,
+        let (found_addresses_, found_messages_, failed_addresses_) = sync_address_list(
+            addresses_to_sync.clone(),
             account_messages,
             is_monitoring.clone(),
             options,
             client_options.clone(),
         )
         .await?;
+        // an address that couldn't be synced might have activity, so the window isn't considered empty
+        let has_failures = !failed_addresses_.is_empty();
         curr_generated_addresses.extend(found_addresses_);
+        // a new public address that couldn't be synced is kept unsynced so the public key indexes don't have a hole
+        // (which would make the next generated address reuse a key index); it's synced again on the next sync
+        for failure in &failed_addresses_ {
+            if let Some(address) = addresses_to_sync.iter().find(|address| {
+                address.address().to_bech32() == failure.address
+                    && !address.internal()
+                    && !account.addresses().contains(address)
+            }) {
+                curr_generated_addresses.push(address.clone());
+            }
+        }
+        curr_generated_addresses.sort_by_key(|address| (*address.key_index(), *address.internal()));
         curr_found_messages.extend(found_messages_);
+        failed_addresses.extend(failed_addresses_);
 
         address_index += gap_limit;
 
-        let is_empty = curr_found_messages.is_empty()
+        if let Some(index) = curr_generated_addresses
+            .iter()
+            .filter(|address| !address.outputs().is_empty())
+            .map(|address| *address.key_index())
+            .max()
+        {
+            last_used_index = Some(last_used_index.map_or(index, |last| last.max(index)));
+        }
+
+        let is_empty = !has_failures
+            && curr_found_messages.is_empty()
             && curr_generated_addresses
                 .iter()
                 .all(|address| address.outputs().is_empty());
@@ -405,36 +581,68 @@ async fn sync_addresses(
         }
     }
 
-    Ok((generated_addresses, found_messages))
+    // funds were found up to the gap limit boundary (the unused addresses after the last used one barely cover the gap)
+    // or the scan stopped before the gap was checked, so there might be more of them beyond it;
+    // a gap limit of 1 only syncs the next address, so the boundary check doesn't apply
+    let suggested_gap_limit = last_used_index.and_then(|last_used_index| {
+        let unused_addresses = address_index - last_used_index - 1;
+        if address_generation_locked || (gap_limit > 1 && unused_addresses <= gap_limit) {
+            Some(gap_limit * 2)
+        } else {
+            None
+        }
+    });
+
+    Ok((
+        generated_addresses,
+        found_messages,
+        suggested_gap_limit,
+        failed_addresses,
+    ))
 }
 
 /// Syncs messages with the tangle.
 /// The method should ensures that the wallet local state has messages associated with the address history.
+/// The addresses that couldn't be synced are returned along with the synced addresses and messages.
 async fn sync_messages(
     account: &Account,
     skip_addresses: &[Address],
     options: AccountOptions,
-) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>)> {
+) -> crate::Result<(Vec<Address>, Vec<SyncedMessage>, Vec<AddressSyncFailure>)> {
     let mut messages = vec![];
     let client_options = account.client_options().clone();
 
     let messages_with_known_confirmation: Vec<MessageId> = account
-        .messages()
-        .iter()
-        .filter(|m| m.confirmed().is_some())
-        .map(|m| *m.id())
+        .known_message_states()
+        .into_iter()
+        .filter(|(_, confirmed)| confirmed.is_some())
+        .map(|(id, _)| id)
         .collect();
 
     let mut addresses = Vec::new();
 
-    let client = crate::client::get_client(&client_options, None).await?;
+    let client = crate::client::get_node_client(&client_options, None).await?;
+
+    let mut addresses_to_sync: Vec<Address> = account
+        .addresses()
+        .iter()
+        .filter(|address| !skip_addresses.contains(address))
+        .cloned()
+        .collect();
+    sort_addresses_for_sync(account, &mut addresses_to_sync, options.address_sync_order);
+    let balances = get_address_balances(
+        &*client,
+        &addresses_to_sync
+            .iter()
+            .map(|a| a.address().clone())
+            .collect::<Vec<AddressWrapper>>(),
+    )
+    .await;
 
     let mut tasks = Vec::new();
-    for mut address in account.addresses().to_vec() {
-        if skip_addresses.contains(&address) {
-            continue;
-        }
+    for mut address in addresses_to_sync {
         let client = client.clone();
+        let balance = balances.get(&address.address().to_bech32()).copied();
         let messages_with_known_confirmation = messages_with_known_confirmation.clone();
         let mut outputs = account
             .addresses()
@@ -442,21 +650,21 @@ async fn sync_messages(
             .find(|a| a == &&address)
             .map(|a| a.outputs().clone())
             .unwrap_or_default();
+        let bech32_address = address.address().to_bech32();
         tasks.push(async move {
-            tokio::spawn(async move {
-                let client = client.read().await;
-
+            let res = tokio::spawn(async move {
+                let node_address: Bech32Address = address.address().to_bech32().into();
                 let address_outputs = get_address_outputs(
-                    &address.address().to_bech32().into(),
-                    &client,
+                    &node_address,
+                    &*client,
                     options.sync_spent_outputs,
+                    options.sync_retry_policy,
                 )
                 .await?;
-                let balance = client
-                    .get_address()
-                    .balance(&address.address().to_bech32().into())
-                    .await?
-                    .balance;
+                let balance = match balance {
+                    Some(balance) => balance,
+                    None => with_retries(options.sync_retry_policy, || client.balance(&node_address)).await?,
+                };
 
                 log::debug!(
                     "[SYNC] syncing messages and outputs for address {}, got {} outputs and balance {}",
@@ -471,7 +679,8 @@ async fn sync_messages(
                         // if we already have the output and it is spent, we don't need to get the info from the node
                         Some(output) if output.is_spent => output.clone(),
                         _ => {
-                            let output = client.get_output(utxo_input).await?;
+                            let output =
+                                with_retries(options.sync_retry_policy, || client.get_output(utxo_input)).await?;
                             AddressOutput::from_output_response(output, address.address().bech32_hrp().to_string())?
                         }
                     };
@@ -488,12 +697,26 @@ async fn sync_messages(
                         continue;
                     }
 
-                    if let Some((message, metadata)) = get_message_and_metadata(&client, &output_message_id).await? {
+                    if let Some((message, confirmed)) =
+                        with_retries(options.sync_retry_policy, || client.get_message(&output_message_id)).await?
+                    {
                         // if the output is spent, the message is confirmed
-                        let confirmed = if is_spent { Some(true) } else { metadata.confirmed };
+                        let confirmed = if is_spent && !options.require_spent_output_confirmation {
+                            Some(true)
+                        } else {
+                            confirmed
+                        };
+                        let (confirmed, confirming) = crate::client::apply_confirmation_threshold(
+                            &*client,
+                            &output_message_id,
+                            confirmed,
+                            options.confirmation_threshold,
+                        )
+                        .await?;
                         messages.push(SyncedMessage {
                             id: output_message_id,
                             confirmed,
+                            confirming,
                             inner: message,
                         });
                     }
@@ -504,20 +727,179 @@ async fn sync_messages(
 
                 crate::Result::Ok((address, messages))
             })
-            .await
+            .await;
+            (bech32_address, res)
         });
     }
 
-    for res in futures::future::try_join_all(tasks)
-        .await
-        .expect("failed to sync messages")
-    {
-        let (address, found_messages) = res?;
+    // the tasks are started in the address sync order, with at most `ADDRESS_SYNC_CONCURRENCY` running at once
+    let results: Vec<_> = futures::stream::iter(tasks)
+        .buffered(ADDRESS_SYNC_CONCURRENCY)
+        .collect()
+        .await;
+    let mut synced_any = false;
+    let mut failures = Vec::new();
+    for (bech32_address, res) in results {
+        match res.map_err(|e| crate::Error::Panic(e.to_string())).and_then(|res| res) {
+            Ok((address, found_messages)) => {
+                synced_any = true;
+                addresses.push(address);
+                messages.extend(found_messages);
+            }
+            Err(e) => failures.push((bech32_address, e)),
+        }
+    }
+    let failed_addresses = collect_sync_failures(failures, synced_any)?;
+
+    Ok((addresses, messages, failed_addresses))
+}
+
+// Sorts the addresses so the sync tasks are started in the given order.
+fn sort_addresses_for_sync(account: &Account, addresses: &mut [Address], order: AddressSyncOrder) {
+    match order {
+        AddressSyncOrder::Storage => {}
+        AddressSyncOrder::RecentlyActive => {
+            let mut latest_activity: HashMap<String, DateTime<Utc>> = HashMap::new();
+            for message in account.messages() {
+                for address in message.addresses() {
+                    let timestamp = latest_activity
+                        .entry(address.to_bech32())
+                        .or_insert(*message.timestamp());
+                    if message.timestamp() > timestamp {
+                        *timestamp = *message.timestamp();
+                    }
+                }
+            }
+            // addresses without messages go last, keeping their storage order
+            addresses.sort_by_cached_key(|a| std::cmp::Reverse(latest_activity.get(&a.address().to_bech32()).copied()));
+        }
+        AddressSyncOrder::Balance => addresses.sort_by_key(|a| std::cmp::Reverse(*a.balance())),
+    }
+}
+
+// Marks the outputs whose spending message isn't known to be confirmed as unspent.
+// Used when the node's spent flag can't be trusted, e.g. when the node isn't fully synced.
+// If the spending message isn't known or the node doesn't return it anymore (e.g. it was pruned), its confirmation
+// can't be checked, so the spent flag is only trusted if the node is healthy.
+async fn unmark_unconfirmed_spent_outputs(
+    account: &Account,
+    client: &dyn NodeClient,
+    synced_messages: &[SyncedMessage],
+    addresses: &mut [Address],
+) -> crate::Result<()> {
+    let mut spent_output_confirmations: HashMap<OutputId, (MessageId, Option<bool>)> = HashMap::new();
+    for message in account.messages() {
+        if let Some(MessagePayload::Transaction(tx)) = message.payload() {
+            let TransactionEssence::Regular(essence) = tx.essence();
+            for input in essence.inputs() {
+                if let TransactionInput::UTXO(input) = input {
+                    spent_output_confirmations.insert(*input.input.output_id(), (*message.id(), *message.confirmed()));
+                }
+            }
+        }
+    }
+    for message in synced_messages {
+        if let Some(Payload::Transaction(tx)) = message.inner.payload() {
+            if let Essence::Regular(essence) = tx.essence() {
+                for input in essence.inputs() {
+                    if let Input::UTXO(input) = input {
+                        spent_output_confirmations.insert(*input.output_id(), (message.id, message.confirmed));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut node_is_healthy = None;
+    for address in addresses {
+        for (output_id, output) in address.outputs_mut().iter_mut() {
+            if !output.is_spent {
+                continue;
+            }
+            let spending_message_confirmation = match spent_output_confirmations.get(output_id) {
+                Some((_, Some(confirmed))) => Some(*confirmed),
+                // the spending message is pending, so we check if the node still returns it
+                Some((message_id, None)) => client
+                    .get_message(message_id)
+                    .await?
+                    .map(|(_, confirmed)| confirmed.unwrap_or(false)),
+                None => None,
+            };
+            let confirmed = match spending_message_confirmation {
+                Some(confirmed) => confirmed,
+                None => {
+                    if node_is_healthy.is_none() {
+                        node_is_healthy = Some(
+                            client
+                                .get_node_metrics()
+                                .await?
+                                .map(|metrics| metrics.is_healthy)
+                                .unwrap_or(false),
+                        );
+                    }
+                    node_is_healthy == Some(true)
+                }
+            };
+            if !confirmed {
+                log::debug!(
+                    "[SYNC] output {:?} marked as unspent because the spending message isn't confirmed",
+                    output_id
+                );
+                output.is_spent = false;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Checks if the account's bech32 HRP matches the node's HRP, logging a warning otherwise.
+async fn check_bech32_hrp(account: &Account, is_monitoring: Arc<AtomicBool>) -> crate::Result<()> {
+    let client = crate::client::get_node_client(account.client_options(), Some(is_monitoring)).await?;
+    let node_bech32_hrp = client.get_node_info().await?.bech32_hrp;
+    let account_bech32_hrp = account.bech32_hrp();
+    if node_bech32_hrp != account_bech32_hrp {
+        log::warn!(
+            "[SYNC] account {} bech32 HRP `{}` doesn't match the node's HRP `{}`",
+            account.id(),
+            account_bech32_hrp,
+            node_bech32_hrp
+        );
+    }
+    Ok(())
+}
+
+// Refreshes the balance of the account addresses without fetching their outputs or messages.
+async fn sync_balances(
+    account: &Account,
+    sync_parameters: SyncParameters,
+    options: AccountOptions,
+    is_monitoring: Arc<AtomicBool>,
+) -> crate::Result<SyncedAccountData> {
+    let client = crate::client::get_node_client(account.client_options(), Some(is_monitoring)).await?;
+    let account_addresses: Vec<AddressWrapper> = account.addresses().iter().map(|a| a.address().clone()).collect();
+    let balances = get_address_balances(&*client, &account_addresses).await;
+
+    let mut addresses = Vec::new();
+    for address in account.addresses() {
+        let balance = match balances.get(&address.address().to_bech32()) {
+            Some(balance) => *balance,
+            None => {
+                let bech32_address: Bech32Address = address.address().to_bech32().into();
+                with_retries(options.sync_retry_policy, || client.balance(&bech32_address)).await?
+            }
+        };
+        let mut address = address.clone();
+        address.set_balance(balance);
         addresses.push(address);
-        messages.extend(found_messages);
     }
 
-    Ok((addresses, messages))
+    Ok(SyncedAccountData {
+        messages: Vec::new(),
+        addresses,
+        suggested_gap_limit: None,
+        failed_addresses: Vec::new(),
+        sync_parameters,
+    })
 }
 
 async fn perform_sync(
@@ -533,7 +915,23 @@ async fn perform_sync(
         address_index,
         gap_limit
     );
-    let (mut found_addresses, found_messages) = if let Some(index) = steps
+
+    // on the first sync, we validate the bech32 HRP the addresses were generated with
+    if account.last_synced_at().is_none() {
+        if let Err(e) = check_bech32_hrp(&account, is_monitoring.clone()).await {
+            log::debug!("[SYNC] failed to validate the bech32 HRP: {:?}", e);
+        }
+    }
+
+    let sync_parameters = SyncParameters {
+        address_index,
+        gap_limit,
+        balance_only: steps.contains(&AccountSynchronizeStep::BalanceOnly),
+    };
+    if sync_parameters.balance_only {
+        return sync_balances(account, sync_parameters, options, is_monitoring).await;
+    }
+    let (mut found_addresses, found_messages, suggested_gap_limit, mut failed_addresses) = if let Some(index) = steps
         .iter()
         .position(|s| matches!(s, AccountSynchronizeStep::SyncAddresses(_)))
     {
@@ -543,8 +941,7 @@ async fn perform_sync(
                     "[SYNC] syncing specific addresses: {:?}",
                     addresses.iter().map(|a| a.to_bech32()).collect::<Vec<String>>()
                 );
-                let account_messages: Vec<(MessageId, Option<bool>)> =
-                    account.messages().iter().map(|m| (*m.id(), *m.confirmed())).collect();
+                let account_messages = account.known_message_states();
                 let mut addresses_to_sync = Vec::new();
                 for address in account.addresses() {
                     if !addresses.contains(address.address()) {
@@ -556,17 +953,19 @@ async fn perform_sync(
                         .balance(0)
                         .outputs(Vec::new())
                         .internal(*address.internal())
+                        .imported(*address.imported())
                         .build()?;
                     addresses_to_sync.push(address);
                 }
-                sync_address_list(
+                let (addresses, messages, failed_addresses) = sync_address_list(
                     addresses_to_sync,
                     account_messages,
                     is_monitoring,
                     options,
                     account.client_options().clone(),
                 )
-                .await?
+                .await?;
+                (addresses, messages, None, failed_addresses)
             } else {
                 sync_addresses(&account, address_index, gap_limit, options, is_monitoring).await?
             }
@@ -574,24 +973,33 @@ async fn perform_sync(
             unreachable!()
         }
     } else {
-        (Vec::new(), Vec::new())
+        (Vec::new(), Vec::new(), None, Vec::new())
     };
 
     let mut new_messages = vec![];
     for found_message in found_messages {
-        if !account
-            .messages()
-            .iter()
-            .any(|message| message.id() == &found_message.id && message.confirmed() == &found_message.confirmed)
-        {
+        if !account.messages().iter().any(|message| {
+            message.id() == &found_message.id
+                && message.confirmed() == &found_message.confirmed
+                && message.confirming() == &found_message.confirming
+        }) {
             new_messages.push(found_message);
         }
     }
 
     if steps.contains(&AccountSynchronizeStep::SyncMessages) {
-        let (synced_addresses, synced_messages) = sync_messages(&account, &found_addresses, options).await?;
+        let (synced_addresses, synced_messages, failed_message_addresses) =
+            sync_messages(&account, &found_addresses, options).await?;
         found_addresses.extend(synced_addresses);
+        failed_addresses.extend(failed_message_addresses);
         new_messages.extend(synced_messages.into_iter());
+        // the addresses are stored in the (key index, internal) order
+        found_addresses.sort_by_key(|address| (*address.key_index(), *address.internal()));
+    }
+
+    if options.require_spent_output_confirmation {
+        let client = crate::client::get_node_client(account.client_options(), None).await?;
+        unmark_unconfirmed_spent_outputs(&account, &*client, &new_messages, &mut found_addresses).await?;
     }
 
     let mut addresses_to_save = vec![];
@@ -625,13 +1033,17 @@ async fn perform_sync(
     Ok(SyncedAccountData {
         messages: new_messages,
         addresses: addresses_to_save,
+        suggested_gap_limit,
+        failed_addresses,
+        sync_parameters,
     })
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) enum AccountSynchronizeStep {
     SyncAddresses(Option<Vec<AddressWrapper>>),
     SyncMessages,
+    BalanceOnly,
 }
 
 #[derive(Debug, Clone)]
@@ -647,6 +1059,15 @@ pub(crate) struct ConfirmationChangeEventData {
     pub(crate) confirmed: bool,
 }
 
+// Checks if the address is locked by a running transfer.
+// The addresses are compared regardless of their bech32 HRP, so a lock is still honored
+// if the account holds the same address with a different HRP (e.g. after a partial migration).
+fn is_address_locked(locked_addresses: &[AddressWrapper], address: &AddressWrapper) -> bool {
+    locked_addresses
+        .iter()
+        .any(|locked_address| locked_address.eq_ignore_hrp(address))
+}
+
 /// Account sync helper.
 pub struct AccountSynchronizer {
     account_handle: AccountHandle,
@@ -654,12 +1075,83 @@ pub struct AccountSynchronizer {
     gap_limit: usize,
     skip_persistence: bool,
     steps: Vec<AccountSynchronizeStep>,
+    suppress_new_transaction_events: bool,
+    suppress_confirmation_change_events: bool,
+    suppress_balance_change_events: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct SyncedAccountData {
     pub(crate) messages: Vec<SyncedMessage>,
     pub(crate) addresses: Vec<Address>,
+    pub(crate) suggested_gap_limit: Option<usize>,
+    pub(crate) failed_addresses: Vec<AddressSyncFailure>,
+    pub(crate) sync_parameters: SyncParameters,
+}
+
+/// The parameters an account sync was performed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct SyncParameters {
+    /// The address index the address scan started from.
+    #[serde(rename = "addressIndex")]
+    address_index: usize,
+    /// The number of address indexes scanned on each address window.
+    #[serde(rename = "gapLimit")]
+    gap_limit: usize,
+    /// Whether only the address balances were refreshed.
+    #[serde(rename = "balanceOnly")]
+    balance_only: bool,
+}
+
+/// An address that couldn't be synced.
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct AddressSyncFailure {
+    /// The address (bech32 encoded).
+    address: String,
+    /// The sync error.
+    error: String,
+}
+
+#[derive(Debug)]
+struct FetchedHistory {
+    sequence: u64,
+    address_index: usize,
+    gap_limit: usize,
+    steps: Vec<AccountSynchronizeStep>,
+    data: SyncedAccountData,
+}
+
+/// Coalesces concurrent history fetches of an account.
+/// A sync request waiting for an in-flight fetch with the same parameters reuses its result
+/// instead of querying the node again.
+#[derive(Debug, Default)]
+pub(crate) struct SyncCoalescer {
+    // number of history fetches finished (successfully or not)
+    completed: AtomicU64,
+    // number of sync requests fetching or waiting for a fetch
+    waiting: AtomicU64,
+    // the latest fetched history, kept until the requests waiting for it got it; the lock is held while fetching
+    latest: Mutex<Option<FetchedHistory>>,
+}
+
+/// The address states (balance and output set hash) of the latest balance change event emissions,
+/// so an unchanged state isn't notified again when the balance change inference runs on the next sync.
+#[derive(Debug, Default)]
+pub(crate) struct EmittedBalanceStates(Mutex<HashMap<String, (u64, u64)>>);
+
+// hashes the address output set (output ids and their spent flag), independently of the map iteration order
+fn output_set_hash(address: &Address) -> u64 {
+    let mut outputs: Vec<(String, bool)> = address
+        .outputs()
+        .iter()
+        .map(|(output_id, output)| (output_id.to_string(), output.is_spent))
+        .collect();
+    outputs.sort();
+    let mut hasher = DefaultHasher::new();
+    outputs.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl SyncedAccountData {
@@ -685,6 +1177,7 @@ impl SyncedAccountData {
                         &client_options,
                     )
                     .with_confirmed(new_message.confirmed)
+                    .with_confirming(new_message.confirming)
                     .finish()
                     .await
                 })
@@ -716,6 +1209,19 @@ impl AccountSynchronizer {
                 AccountSynchronizeStep::SyncAddresses(None),
                 AccountSynchronizeStep::SyncMessages,
             ],
+            suppress_new_transaction_events: false,
+            suppress_confirmation_change_events: false,
+            suppress_balance_change_events: false,
+        }
+    }
+
+    /// The parameters the sync will be performed with.
+    /// The address index and gap limit default to the latest address index and, if it's 0, a gap limit of 10.
+    pub fn parameters(&self) -> SyncParameters {
+        SyncParameters {
+            address_index: self.address_index,
+            gap_limit: self.gap_limit,
+            balance_only: self.steps.contains(&AccountSynchronizeStep::BalanceOnly),
         }
     }
 
@@ -732,6 +1238,36 @@ impl AccountSynchronizer {
         self
     }
 
+    /// Doesn't emit the new transaction events of the messages found on this sync.
+    /// Useful on the initial sync, so the account history is populated without a notification per message.
+    pub fn suppress_new_transaction_events(mut self) -> Self {
+        self.suppress_new_transaction_events = true;
+        self
+    }
+
+    /// Doesn't emit the confirmation state change events of the messages updated on this sync.
+    pub fn suppress_confirmation_change_events(mut self) -> Self {
+        self.suppress_confirmation_change_events = true;
+        self
+    }
+
+    /// Doesn't emit the balance change events of the addresses updated on this sync.
+    /// The balance states are still recorded, so the next sync only notifies new changes.
+    pub fn suppress_balance_change_events(mut self) -> Self {
+        self.suppress_balance_change_events = true;
+        self
+    }
+
+    /// Only refreshes the balance of the account addresses, with a single balance request per address,
+    /// e.g. for dashboards polling frequently.
+    /// The address outputs and messages aren't fetched, so balance change events are emitted but
+    /// new transaction events aren't; the outputs (and the available balance) are updated on the next full sync.
+    /// The account `last_synced_at` timestamp is only set by a full sync.
+    pub fn balance_only(mut self) -> Self {
+        self.steps = vec![AccountSynchronizeStep::BalanceOnly];
+        self
+    }
+
     /// Initial address index to start syncing.
     pub fn address_index(mut self, address_index: usize) -> Self {
         self.address_index = address_index;
@@ -747,19 +1283,62 @@ impl AccountSynchronizer {
     }
 
     pub(crate) async fn get_new_history(&self) -> crate::Result<SyncedAccountData> {
-        perform_sync(
-            &*self.account_handle.read().await,
-            self.address_index,
-            self.gap_limit,
-            &self.steps,
-            self.account_handle.account_options,
-            self.account_handle.is_monitoring.clone(),
-        )
-        .await
+        let coalescer = &self.account_handle.sync_coalescer;
+        coalescer.waiting.fetch_add(1, Ordering::SeqCst);
+        let completed_before_request = coalescer.completed.load(Ordering::SeqCst);
+        let mut latest = coalescer.latest.lock().await;
+
+        // a fetch with the same parameters finished while this request was waiting, so we share its result
+        let reused_history = match &*latest {
+            Some(history)
+                if history.sequence >= completed_before_request
+                    && history.address_index == self.address_index
+                    && history.gap_limit == self.gap_limit
+                    && history.steps == self.steps =>
+            {
+                log::debug!("[SYNC] reusing the history fetched by a concurrent sync");
+                Some(history.data.clone())
+            }
+            _ => None,
+        };
+        let res = match reused_history {
+            Some(data) => Ok(data),
+            None => {
+                let sequence = coalescer.completed.load(Ordering::SeqCst);
+                let res = perform_sync(
+                    &*self.account_handle.read().await,
+                    self.address_index,
+                    self.gap_limit,
+                    &self.steps,
+                    self.account_handle.account_options,
+                    self.account_handle.is_monitoring.clone(),
+                )
+                .await;
+                *latest = match &res {
+                    Ok(data) => Some(FetchedHistory {
+                        sequence,
+                        address_index: self.address_index,
+                        gap_limit: self.gap_limit,
+                        steps: self.steps.clone(),
+                        data: data.clone(),
+                    }),
+                    Err(_) => None,
+                };
+                coalescer.completed.fetch_add(1, Ordering::SeqCst);
+                res
+            }
+        };
+
+        // the requests made after the fetch can't reuse it, so the history is dropped once no request is waiting
+        if coalescer.waiting.fetch_sub(1, Ordering::SeqCst) == 1 {
+            *latest = None;
+        }
+        res
     }
 
     pub(crate) async fn get_events(
         account_options: AccountOptions,
+        emitted_balance_states: &EmittedBalanceStates,
         addresses_before_sync: &[(String, u64, HashMap<OutputId, AddressOutput>)],
         addresses: &[Address],
         new_messages: &[Message],
@@ -767,14 +1346,39 @@ impl AccountSynchronizer {
     ) -> crate::Result<SyncedAccountEvents> {
         // balance event
         let mut balance_change_events = Vec::new();
+        let mut emitted_states = emitted_balance_states.0.lock().await;
         for address_after_sync in addresses.iter() {
             let address_bech32 = address_after_sync.address().to_bech32();
             let (address_before_sync, before_sync_balance, before_sync_outputs) = addresses_before_sync
                 .iter()
                 .find(|(address, _, _)| &address_bech32 == address)
                 .cloned()
-                .unwrap_or_else(|| (address_bech32, 0, HashMap::new()));
-            if *address_after_sync.balance() != before_sync_balance {
+                .unwrap_or_else(|| (address_bech32.clone(), 0, HashMap::new()));
+            // outputs spent locally that were restored by the spent output reconciliation
+            let restored_outputs: Vec<&AddressOutput> = address_after_sync
+                .outputs()
+                .iter()
+                .filter(|(output_id, output)| {
+                    !output.is_spent
+                        && before_sync_outputs
+                            .get(output_id)
+                            .map(|before_sync_output| before_sync_output.is_spent)
+                            .unwrap_or(false)
+                })
+                .map(|(_, output)| output)
+                .collect();
+            let balance_changed = *address_after_sync.balance() != before_sync_balance;
+            if balance_changed || !restored_outputs.is_empty() {
+                let state = (*address_after_sync.balance(), output_set_hash(address_after_sync));
+                if emitted_states.get(&address_bech32) == Some(&state) {
+                    log::debug!(
+                        "[SYNC] balance change on {} already notified for its current state",
+                        address_bech32
+                    );
+                    continue;
+                }
+                emitted_states.insert(address_bech32, state);
+
                 log::debug!(
                     "[SYNC] address {} balance changed from {} to {}",
                     address_before_sync,
@@ -785,6 +1389,20 @@ impl AccountSynchronizer {
                 let mut output_change_balance = 0i64;
                 // we use this flag in case the new balance is 0
                 let mut emitted_event = false;
+                // the node balance already included the restored outputs, so they don't count as output changes
+                for output in restored_outputs {
+                    let balance_change = BalanceChange::received(output.amount);
+                    log::info!(
+                        "[SYNC] balance change on {} {:?} (restored output)",
+                        address_after_sync.address().to_bech32(),
+                        balance_change
+                    );
+                    balance_change_events.push(BalanceChangeEventData {
+                        address: address_after_sync.address().clone(),
+                        balance_change,
+                        message_id: Some(output.message_id),
+                    });
+                }
                 // check new and updated outputs to find message ids
                 // note that this is unreliable if we're not syncing spent outputs,
                 // since not all information are collected.
@@ -821,7 +1439,7 @@ impl AccountSynchronizer {
                 // been emitted, we emit the remainder value with `None` as
                 // message_id
                 let balance_change = *address_after_sync.balance() as i64 - before_sync_balance as i64;
-                if !emitted_event || output_change_balance != balance_change {
+                if balance_changed && (!emitted_event || output_change_balance != balance_change) {
                     let balance_change = if balance_change > 0 {
                         // balance_change is positive; subtract the already emitted balance.
                         BalanceChange::received((balance_change - output_change_balance) as u64)
@@ -897,8 +1515,13 @@ impl AccountSynchronizer {
                 if !self.skip_persistence {
                     account.append_addresses(new_addresses.to_vec());
                     account.append_messages(parsed_messages.to_vec());
-                    account.set_last_synced_at(Some(chrono::Local::now()));
-                    account.save().await?;
+                    // a balance only sync doesn't fetch the outputs and messages, so the account isn't fully synced yet
+                    if !data.sync_parameters.balance_only {
+                        account.set_last_synced_at(Some(chrono::Local::now()));
+                        account.mark_dirty();
+                    }
+                    // the sync changes are written at once
+                    account.save_if_dirty().await?;
                 }
 
                 let mut new_messages = Vec::new();
@@ -915,37 +1538,31 @@ impl AccountSynchronizer {
                     }
                 }
 
-                let persist_events = self.account_handle.account_options.persist_events;
-                let events = Self::get_events(
+                let mut events = Self::get_events(
                     self.account_handle.account_options,
+                    &self.account_handle.emitted_balance_states,
                     &addresses_before_sync,
                     account.addresses(),
                     &new_messages,
                     &confirmation_changed_messages,
                 )
                 .await?;
-                for message in events.new_transaction_events {
-                    emit_transaction_event(TransactionEventType::NewTransaction, &account, message, persist_events)
-                        .await?;
+                if self.suppress_new_transaction_events {
+                    events.new_transaction_events.clear();
                 }
-                for confirmation_change_event in events.confirmation_change_events {
-                    emit_confirmation_state_change(
-                        &account,
-                        confirmation_change_event.message,
-                        confirmation_change_event.confirmed,
-                        persist_events,
-                    )
-                    .await?;
+                if self.suppress_confirmation_change_events {
+                    events.confirmation_change_events.clear();
                 }
-                for balance_change_event in events.balance_change_events {
-                    emit_balance_change(
-                        &account,
-                        &balance_change_event.address,
-                        balance_change_event.message_id,
-                        balance_change_event.balance_change,
-                        persist_events,
-                    )
-                    .await?;
+                if self.suppress_balance_change_events {
+                    events.balance_change_events.clear();
+                }
+                events.emit(&account, self.account_handle.account_options).await?;
+                if let Some(suggested_gap) = data.suggested_gap_limit {
+                    log::warn!(
+                        "[SYNC] funds found up to the gap limit boundary; syncing with a gap limit of {} is suggested",
+                        suggested_gap
+                    );
+                    emit_gap_limit_reached(&account, suggested_gap).await;
                 }
 
                 let mut updated_messages = new_messages;
@@ -971,6 +1588,9 @@ impl AccountSynchronizer {
                         .cloned()
                         .collect(),
                     messages: updated_messages,
+                    suggested_gap_limit: data.suggested_gap_limit,
+                    failed_addresses: data.failed_addresses,
+                    sync_parameters: Some(data.sync_parameters),
                 };
                 Ok(synced_account)
             }
@@ -1008,15 +1628,228 @@ pub struct SyncedAccount {
     /// The newly generated and updated account addresses.
     #[getset(get = "pub")]
     pub(crate) addresses: Vec<Address>,
+    /// The gap limit suggested for the next sync if funds were found up to the gap limit boundary.
+    #[serde(rename = "suggestedGapLimit")]
+    #[getset(get = "pub")]
+    pub(crate) suggested_gap_limit: Option<usize>,
+    /// The addresses that couldn't be synced; their previous state is kept.
+    #[serde(rename = "failedAddresses")]
+    #[getset(get = "pub")]
+    pub(crate) failed_addresses: Vec<AddressSyncFailure>,
+    /// The parameters the sync was performed with, e.g. the gap limit that was applied.
+    /// `None` if the synced account wasn't created by a sync (e.g. when the account is kept up to date by MQTT).
+    #[serde(rename = "syncParameters")]
+    #[getset(get = "pub")]
+    pub(crate) sync_parameters: Option<SyncParameters>,
 }
 
-#[derive(Debug, Clone, Getters)]
-pub(crate) struct SyncedAccountEvents {
-    pub(crate) balance_change_events: Vec<BalanceChangeEventData>,
-    pub(crate) new_transaction_events: Vec<Message>,
+/// The consolidation transactions that would be sent by
+/// [SyncedAccount#consolidate_outputs](struct.SyncedAccount.html#method.consolidate_outputs).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct ConsolidationPlan {
+    /// The number of consolidation transactions.
+    #[serde(rename = "transactionCount")]
+    transaction_count: usize,
+    /// The number of inputs of each consolidation transaction.
+    #[serde(rename = "inputsPerTransaction")]
+    inputs_per_transaction: Vec<usize>,
+    /// The number of new addresses that would be generated (e.g. confirmed on a Ledger device).
+    #[serde(rename = "newAddresses")]
+    new_addresses: usize,
+}
+
+/// The confidence of a [ConfirmationTimeEstimate](struct.ConfirmationTimeEstimate.html).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum EstimateConfidence {
+    /// The node isn't healthy or references few of the messages it receives.
+    Low,
+    /// The node references most of the messages it receives.
+    Medium,
+    /// The node is healthy and references nearly all of the messages it receives.
+    High,
+}
+
+/// The estimated time until a message is confirmed, computed by
+/// [SyncedAccount#estimate_confirmation_time](struct.SyncedAccount.html#method.estimate_confirmation_time).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct ConfirmationTimeEstimate {
+    /// The estimated time until a message sent now is confirmed.
+    time: Duration,
+    /// How reliable the estimate is.
+    confidence: EstimateConfidence,
+}
+
+impl ConfirmationTimeEstimate {
+    // A message is usually referenced by the next milestone,
+    // so the estimate is a milestone interval scaled by the share of messages the milestones reference.
+    fn from_node_metrics(metrics: &NodeMetrics) -> Option<Self> {
+        let milestone_interval = metrics.milestone_interval?;
+        if metrics.messages_per_second <= 0.0 || metrics.referenced_rate <= 0.0 {
+            return Some(Self {
+                time: milestone_interval,
+                confidence: EstimateConfidence::Low,
+            });
+        }
+        let referenced_rate = metrics.referenced_rate.clamp(MIN_ESTIMATE_REFERENCED_RATE, 100.0);
+        let confidence = if !metrics.is_healthy || referenced_rate < 50.0 {
+            EstimateConfidence::Low
+        } else if referenced_rate < 90.0 {
+            EstimateConfidence::Medium
+        } else {
+            EstimateConfidence::High
+        };
+        Some(Self {
+            time: milestone_interval.mul_f64(100.0 / referenced_rate),
+            confidence,
+        })
+    }
+}
+
+/// The transaction essence a transfer would sign, built by
+/// [SyncedAccount#essence_dump](struct.SyncedAccount.html#method.essence_dump).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct EssenceDump {
+    /// The packed essence bytes; their BLAKE2b-256 hash is what gets signed.
+    bytes: Vec<u8>,
+    /// The consumed outputs, in the essence input order.
+    inputs: Vec<AddressOutput>,
+    /// The essence outputs, in the essence output order.
+    outputs: Vec<TransactionOutput>,
+}
+
+impl EssenceDump {
+    fn new(account: &Account, transfer_essence: TransferEssence) -> Self {
+        let bech32_hrp = account.bech32_hrp();
+        let remainder_deposit_address: Option<&IotaAddress> =
+            transfer_essence.remainder_deposit_address.as_ref().map(|a| a.as_ref());
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        if let Essence::Regular(essence) = &transfer_essence.essence {
+            for input in essence.inputs() {
+                if let Input::UTXO(input) = input {
+                    if let Some(output) = transfer_essence
+                        .consumed_outputs
+                        .iter()
+                        .find(|output| output.id().ok().as_ref() == Some(input.output_id()))
+                    {
+                        inputs.push(output.clone());
+                    }
+                }
+            }
+            for output in essence.outputs() {
+                let (kind, address, amount) = parse_output(output);
+                let remainder = kind == OutputKind::SignatureLockedSingle
+                    && address == remainder_deposit_address
+                    && amount == transfer_essence.remainder_value;
+                outputs.push(TransactionOutput::new(output, bech32_hrp.clone(), remainder));
+            }
+        }
+        Self {
+            bytes: transfer_essence.essence.pack_new(),
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// A dust rule the transaction would break, so the network would reject it.
+#[derive(Debug, Clone, PartialEq, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct DustWarning {
+    /// The bech32 address whose dust outputs wouldn't be covered by its dust allowance.
+    address: String,
+    /// The amount of the dust output created on the address,
+    /// or `None` if consuming its dust allowance output leaves the existing dust outputs uncovered.
+    amount: Option<u64>,
+}
+
+/// The preview of a transfer, built by
+/// [SyncedAccount#prepare_transfer](struct.SyncedAccount.html#method.prepare_transfer).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct PreparedTransaction {
+    /// The selected inputs.
+    inputs: Vec<AddressOutput>,
+    /// The transaction outputs, including the remainder output.
+    outputs: Vec<TransactionOutput>,
+    /// The remainder value.
+    #[serde(rename = "remainderValue")]
+    remainder_value: u64,
+    /// The bech32 address receiving the remainder value.
+    #[serde(rename = "remainderDepositAddress")]
+    remainder_deposit_address: Option<String>,
+    /// The dust rules the transaction breaks; the transfer fails if it isn't empty.
+    #[serde(rename = "dustWarnings")]
+    dust_warnings: Vec<DustWarning>,
+    /// Whether the remainder value goes back to an input address whose outputs are all consumed by the transaction.
+    #[serde(rename = "remainderToConsumedInput")]
+    remainder_to_consumed_input: bool,
+}
+
+#[derive(Debug, Clone, Getters)]
+pub(crate) struct SyncedAccountEvents {
+    pub(crate) balance_change_events: Vec<BalanceChangeEventData>,
+    pub(crate) new_transaction_events: Vec<Message>,
     pub(crate) confirmation_change_events: Vec<ConfirmationChangeEventData>,
 }
 
+impl SyncedAccountEvents {
+    /// Emits the sync events, either individually or as a single `SyncCompleted` event
+    /// if the `sync_events_summary` option is set.
+    pub(crate) async fn emit(self, account: &Account, account_options: AccountOptions) -> crate::Result<()> {
+        let persist_events = account_options.persist_events;
+        if account_options.sync_events_summary {
+            if !self.new_transaction_events.is_empty()
+                || !self.balance_change_events.is_empty()
+                || !self.confirmation_change_events.is_empty()
+            {
+                emit_sync_completed(
+                    account,
+                    self.new_transaction_events,
+                    self.balance_change_events
+                        .into_iter()
+                        .map(|e| (e.address, e.message_id, e.balance_change))
+                        .collect(),
+                    self.confirmation_change_events
+                        .into_iter()
+                        .map(|e| (e.message, e.confirmed))
+                        .collect(),
+                    persist_events,
+                )
+                .await?;
+            }
+            return Ok(());
+        }
+
+        for message in self.new_transaction_events {
+            emit_transaction_event(TransactionEventType::NewTransaction, account, message, persist_events).await?;
+        }
+        for confirmation_change_event in self.confirmation_change_events {
+            emit_confirmation_state_change(
+                account,
+                confirmation_change_event.message,
+                confirmation_change_event.confirmed,
+                persist_events,
+            )
+            .await?;
+        }
+        for balance_change_event in self.balance_change_events {
+            emit_balance_change(
+                account,
+                &balance_change_event.address,
+                balance_change_event.message_id,
+                balance_change_event.balance_change,
+                persist_events,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
 impl SyncedAccount {
     /// Emulates a synced account from an account handle.
     /// Should only be used if sync is guaranteed (e.g. when using MQTT)
@@ -1032,9 +1865,19 @@ impl SyncedAccount {
             is_empty: false,
             messages: Default::default(),
             addresses: Default::default(),
+            suggested_gap_limit: None,
+            failed_addresses: Default::default(),
+            sync_parameters: None,
         }
     }
 
+    /// Whether the sync found funds up to the gap limit boundary without being able to look beyond it,
+    /// meaning that there might be funds on addresses that weren't synced.
+    /// Syncing again with the [suggested gap limit](#method.suggested_gap_limit) is recommended in that case.
+    pub fn possibly_missed_funds(&self) -> bool {
+        self.suggested_gap_limit.is_some()
+    }
+
     /// Selects input addresses for a value transaction.
     /// The method ensures that the recipient address doesn’t match any of the selected inputs or the remainder address.
     ///
@@ -1054,55 +1897,78 @@ impl SyncedAccount {
         account: &'a Account,
         addresses: &'a [Address],
         address: &'a AddressWrapper,
+    ) -> crate::Result<(Vec<input_selection::Input>, Option<input_selection::Input>)> {
+        let (selected_addresses, remainder) =
+            self.find_inputs(locked_addresses.as_slice(), transfer_obj, account, addresses, address)?;
+
+        locked_addresses.extend(
+            selected_addresses
+                .iter()
+                .map(|a| a.address.clone())
+                .collect::<Vec<AddressWrapper>>(),
+        );
+
+        Ok((selected_addresses, remainder))
+    }
+
+    /// Runs the input selection without locking the selected addresses.
+    fn find_inputs(
+        &self,
+        locked_addresses: &[AddressWrapper],
+        transfer_obj: &Transfer,
+        account: &Account,
+        addresses: &[Address],
+        address: &AddressWrapper,
     ) -> crate::Result<(Vec<input_selection::Input>, Option<input_selection::Input>)> {
         let available_addresses: Vec<input_selection::Input> = addresses
             .iter()
             .filter(|a| {
-                // we allow an input equal to the deposit address only if it has more than one output
-                (a.address() != address || a.available_outputs(&account).len() > 1)
+                // on the lenient policy we allow an input equal to the deposit address only if it has more than one
+                // output
+                // imported addresses are watch-only
+                !a.imported()
+                    && (a.address() != address
+                        || (transfer_obj.deposit_address_input == DepositAddressInputPolicy::Lenient
+                            && a.available_outputs(&account).len() > 1))
                     && a.available_balance(&account) > 0
-                    && !locked_addresses.contains(a.address())
-            })
-            .map(|a| input_selection::Input {
-                address: a.address().clone(),
-                internal: *a.internal(),
-                balance: a.available_balance(&account),
+                    && !is_address_locked(locked_addresses, a.address())
             })
+            .map(|a| input_selection_input(a, account))
             .collect();
-        let mut selected_addresses = input_selection::select_input(transfer_obj.amount.get(), available_addresses)?;
-        let has_remainder = selected_addresses.iter().fold(0, |acc, a| acc + a.balance) > transfer_obj.amount.get();
+        let mut selected_addresses = input_selection::select_input_with_strategy(
+            transfer_value(transfer_obj)?,
+            available_addresses,
+            transfer_obj.input_selection_strategy,
+            max_input_count(transfer_obj),
+        )?;
+        let has_remainder = selected_addresses.iter().fold(0, |acc, a| acc + a.balance) > transfer_value(transfer_obj)?;
 
         // if we're reusing the input address for remainder output
         // and we have remainder value, we should run the input selection again
-        // without the output address.
+        // without the output addresses.
         if has_remainder
             && transfer_obj.remainder_value_strategy == RemainderValueStrategy::ReuseAddress
-            && addresses.iter().any(|input| input.address() == &transfer_obj.address)
+            && addresses.iter().any(|input| transfer_obj.is_recipient(input.address()))
         {
             let available_addresses: Vec<input_selection::Input> = addresses
                 .iter()
                 .filter(|a| {
-                    // we do not allow the deposit address as input address
-                    a.address() != address
+                    // we do not allow the deposit addresses as input address
+                    !a.imported()
+                        && !transfer_obj.is_recipient(a.address())
                         && a.available_balance(&account) > 0
-                        && !locked_addresses.contains(a.address())
-                })
-                .map(|a| input_selection::Input {
-                    address: a.address().clone(),
-                    internal: *a.internal(),
-                    balance: a.available_balance(&account),
+                        && !is_address_locked(locked_addresses, a.address())
                 })
+                .map(|a| input_selection_input(a, account))
                 .collect();
-            selected_addresses = input_selection::select_input(transfer_obj.amount.get(), available_addresses)?;
+            selected_addresses = input_selection::select_input_with_strategy(
+                transfer_value(transfer_obj)?,
+                available_addresses,
+                transfer_obj.input_selection_strategy,
+                max_input_count(transfer_obj),
+            )?;
         }
 
-        locked_addresses.extend(
-            selected_addresses
-                .iter()
-                .map(|a| a.address.clone())
-                .collect::<Vec<AddressWrapper>>(),
-        );
-
         let remainder = if has_remainder {
             selected_addresses.last().cloned()
         } else {
@@ -1112,27 +1978,53 @@ impl SyncedAccount {
         Ok((selected_addresses, remainder))
     }
 
-    async fn get_output_consolidation_transfers(&self) -> crate::Result<Vec<Transfer>> {
+    // Gets the transfers consolidating the outputs of each address exceeding the consolidation threshold.
+    // The value is sent back to the source address, or to the `target` address if provided.
+    async fn get_output_consolidation_transfers(
+        &self,
+        target: Option<&AddressWrapper>,
+    ) -> crate::Result<Vec<Transfer>> {
         let mut transfers: Vec<Transfer> = Vec::new();
         // collect the transactions we need to make
         {
             let account = self.account_handle.read().await;
-            for address in account.addresses() {
+            if let Some(target) = target {
+                if !account.addresses().iter().any(|a| a.address() == target) {
+                    return Err(crate::Error::InvalidConsolidationTargetAddress);
+                }
+            }
+            for address in account.addresses().iter().filter(|a| !a.imported()) {
                 let address_outputs = address.available_outputs(&account);
                 // the address outputs exceed the threshold, so we push a transfer to our vector
                 if address_outputs.len() >= self.account_handle.account_options.output_consolidation_threshold {
+                    let deposit_address = target.unwrap_or_else(|| address.address());
+                    // the reserve is only meaningful when the value leaves the source address;
+                    // it's kept on the first chunk as the remainder output sent back to the source address
+                    let mut reserve = match self.account_handle.account_options.consolidation_reserve_per_address {
+                        Some(reserve) if reserve > 0 && deposit_address != address.address() => {
+                            reserve.max(DUST_ALLOWANCE_VALUE)
+                        }
+                        _ => 0,
+                    };
                     for outputs in address_outputs.chunks(INPUT_OUTPUT_COUNT_MAX) {
+                        let chunk_amount = outputs.iter().fold(0, |acc, output| acc + output.amount);
+                        let amount = chunk_amount.saturating_sub(reserve);
+                        if reserve > 0 {
+                            reserve = 0;
+                            // the remaining value would be a dust output, so the whole chunk stays as reserve
+                            if amount < DUST_ALLOWANCE_VALUE {
+                                continue;
+                            }
+                        }
                         transfers.push(
-                            Transfer::builder(
-                                address.address().clone(),
-                                NonZeroU64::new(address.available_balance(&account)).unwrap(),
-                            )
-                            .with_input(
-                                address.address().clone(),
-                                outputs.iter().map(|o| (*o).clone()).collect(),
-                            )
-                            .with_events(false)
-                            .finish(),
+                            Transfer::builder(deposit_address.clone(), NonZeroU64::new(amount).unwrap())
+                                .with_input(
+                                    address.address().clone(),
+                                    outputs.iter().map(|o| (*o).clone()).collect(),
+                                )
+                                .with_remainder_value_strategy(RemainderValueStrategy::ReuseAddress)
+                                .with_events(false)
+                                .finish(),
                         );
                     }
                 }
@@ -1141,11 +2033,48 @@ impl SyncedAccount {
         Ok(transfers)
     }
 
+    /// Gets the transactions and new addresses the output consolidation would need, without executing it.
+    /// Useful for Ledger users, who must confirm each generated address on the device.
+    pub async fn consolidation_plan(&self) -> ConsolidationPlan {
+        // without a target the consolidation transfers can't fail
+        let transfers = self.get_output_consolidation_transfers(None).await.unwrap_or_default();
+        let account = self.account_handle.read().await;
+        ConsolidationPlan {
+            transaction_count: transfers.len(),
+            inputs_per_transaction: transfers
+                .iter()
+                .map(|transfer| transfer.input.as_ref().map(|(_, outputs)| outputs.len()).unwrap_or(0))
+                .collect(),
+            new_addresses: consolidation_new_addresses(&account, &transfers),
+        }
+    }
+
     /// Consolidate account outputs.
     pub(crate) async fn consolidate_outputs(&self) -> crate::Result<Vec<Message>> {
+        self.consolidate_outputs_internal(None).await
+    }
+
+    /// Consolidate account outputs, sending the consolidated value to the `target` address.
+    pub(crate) async fn consolidate_outputs_to(&self, target: &AddressWrapper) -> crate::Result<Vec<Message>> {
+        self.consolidate_outputs_internal(Some(target)).await
+    }
+
+    async fn consolidate_outputs_internal(&self, target: Option<&AddressWrapper>) -> crate::Result<Vec<Message>> {
+        let transfers = self.get_output_consolidation_transfers(target).await?;
+        if transfers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut consolidated_addresses: Vec<&AddressWrapper> = transfers
+            .iter()
+            .filter_map(|t| t.input.as_ref().map(|(address, _)| address))
+            .collect();
+        consolidated_addresses.dedup();
+        emit_consolidation_started(self.id.clone(), consolidated_addresses.len()).await;
+
         let mut tasks = Vec::new();
         // run the transfers in parallel
-        for transfer in self.get_output_consolidation_transfers().await? {
+        for transfer in transfers {
             let task = self.transfer(transfer);
             tasks.push(task);
         }
@@ -1155,11 +2084,226 @@ impl SyncedAccount {
             messages.push(message);
         }
 
+        {
+            let mut account = self.account_handle.write().await;
+            account.set_last_consolidated_at(Some(chrono::Local::now()));
+            account.save().await?;
+        }
+
+        emit_consolidation_completed(self.id.clone(), messages.iter().map(|m| *m.id()).collect()).await;
+
         Ok(messages)
     }
 
+    /// Lists the transactions sent by the account that aren't confirmed yet.
+    /// See [Account#list_pending_transactions](../account/struct.Account.html#method.list_pending_transactions).
+    pub async fn list_pending_transactions(&self) -> Vec<Message> {
+        self.account_handle.list_pending_transactions().await
+    }
+
+    /// Estimates the time until a message sent now is confirmed, from the node's milestone interval and referenced rate.
+    /// Returns `None` if the node doesn't expose enough metrics to compute it.
+    pub async fn estimate_confirmation_time(&self) -> crate::Result<Option<ConfirmationTimeEstimate>> {
+        let client_options = self.account_handle.read().await.client_options().clone();
+        let client =
+            crate::client::get_node_client(&client_options, Some(self.account_handle.is_monitoring.clone())).await?;
+        Ok(client
+            .get_node_metrics()
+            .await?
+            .and_then(|metrics| ConfirmationTimeEstimate::from_node_metrics(&metrics)))
+    }
+
+    /// Checks whether a transfer of `amount` to the `to` address can be built right now.
+    /// Runs the same input selection as the transfer (considering the addresses locked by pending transfers)
+    /// without locking any address, so it can be used to enable or disable a send action.
+    pub async fn can_spend(&self, amount: NonZeroU64, to: &AddressWrapper) -> bool {
+        let account = self.account_handle.read().await;
+        let mut transfer_obj = Transfer::builder(to.clone(), amount).finish();
+        if account.addresses().iter().any(|a| a.address() == to) {
+            transfer_obj.remainder_value_strategy = RemainderValueStrategy::ReuseAddress;
+        }
+        let locked_addresses = self.account_handle.locked_addresses.lock().await.clone();
+        self.find_inputs(&locked_addresses, &transfer_obj, &account, account.addresses(), to)
+            .is_ok()
+    }
+
     /// Send messages.
-    pub(super) async fn transfer(&self, mut transfer_obj: Transfer) -> crate::Result<Message> {
+    pub(super) async fn transfer(&self, transfer_obj: Transfer) -> crate::Result<Message> {
+        let res = self.transfer_internal(transfer_obj.clone()).await;
+        if let Err(crate::Error::DustOutputNotAllowed { address, amount }) = &res {
+            // the dust output isn't allowed, so we try again adding a dust allowance output to its address
+            if transfer_obj.auto_dust_allowance && transfer_obj.dust_allowance_address.is_none() {
+                log::debug!(
+                    "[TRANSFER] dust output of {}i on {} isn't allowed, creating a dust allowance output",
+                    amount,
+                    address
+                );
+                let address = address.clone();
+                let dust_address = crate::address::parse(&address)?;
+                // the dust allowance output value stays with its address, so we never fund one outside the account
+                if !self
+                    .account_handle
+                    .read()
+                    .await
+                    .addresses()
+                    .iter()
+                    .any(|a| a.address() == &dust_address)
+                {
+                    return Err(crate::Error::DustAllowanceNotFunded(address));
+                }
+                let mut transfer_obj = transfer_obj;
+                // the dust output is the remainder, so it's kept on the address receiving the dust allowance output
+                if !transfer_obj.is_recipient(&dust_address) {
+                    transfer_obj.remainder_value_strategy =
+                        RemainderValueStrategy::AccountAddress(dust_address.clone());
+                }
+                transfer_obj.dust_allowance_address = Some(dust_address);
+                return match self.transfer_internal(transfer_obj).await {
+                    // the balance isn't enough to fund the dust allowance output without leaving dust behind
+                    Err(crate::Error::InsufficientFunds)
+                    | Err(crate::Error::DustError(_))
+                    | Err(crate::Error::DustOutputNotAllowed { .. })
+                    | Err(crate::Error::InputSelectionFailed(_)) => Err(crate::Error::DustAllowanceNotFunded(address)),
+                    res => res,
+                };
+            }
+        }
+        res
+    }
+
+    async fn transfer_internal(&self, transfer_obj: Transfer) -> crate::Result<Message> {
+        let (transfer_obj, input_addresses, remainder_address) = self.lock_transfer_inputs(transfer_obj).await?;
+
+        // journal the transfer so it can be reconciled if the wallet stops before it finishes
+        let journal_id = {
+            let mut account = self.account_handle.write().await;
+            let inputs = input_addresses
+                .iter()
+                .flat_map(|(_, outputs)| outputs.iter().filter_map(|output| output.id().ok()))
+                .collect();
+            let journal_id =
+                account.journal_transfer(inputs, transfer_obj.address.clone(), transfer_obj.total_amount()?);
+            self.account_handle
+                .in_flight_transfers
+                .lock()
+                .await
+                .insert(journal_id.clone());
+            if let Err(e) = account.save().await {
+                account.remove_pending_transfer(&journal_id);
+                drop(account);
+                self.account_handle.in_flight_transfers.lock().await.remove(&journal_id);
+                self.unlock_transfer_inputs(&input_addresses).await;
+                return Err(e);
+            }
+            journal_id
+        };
+
+        let res = perform_transfer(
+            transfer_obj,
+            &input_addresses,
+            self.account_handle.clone(),
+            remainder_address,
+            &journal_id,
+        )
+        .await;
+
+        // the journal entry is removed with the message storage on success;
+        // once its message id is journaled the message may have reached the node,
+        // so the entry is kept and the transfer is resolved by the reconciliation
+        if res.is_err() {
+            let mut account = self.account_handle.write().await;
+            let maybe_broadcasted = account
+                .pending_transfers()
+                .iter()
+                .any(|t| t.id() == &journal_id && t.message_id().is_some());
+            if !maybe_broadcasted {
+                account.remove_pending_transfer(&journal_id);
+                if let Err(e) = account.save().await {
+                    log::error!("[TRANSFER] failed to remove the transfer journal entry: {:?}", e);
+                }
+            }
+        }
+        self.account_handle.in_flight_transfers.lock().await.remove(&journal_id);
+
+        self.unlock_transfer_inputs(&input_addresses).await;
+
+        res
+    }
+
+    /// Builds the transaction essence the transfer would sign, without signing or sending it.
+    /// The returned dump holds the packed essence bytes and its inputs and outputs, in the signed order,
+    /// e.g. to compare them with an independent implementation or the essence displayed by a Ledger device.
+    /// The input addresses are only locked while the essence is built.
+    pub async fn essence_dump(&self, transfer_obj: Transfer) -> crate::Result<EssenceDump> {
+        self.build_essence_preview(transfer_obj, |account, transfer_essence| {
+            check_dust_warnings(&transfer_essence.dust_warnings)?;
+            Ok(EssenceDump::new(account, transfer_essence))
+        })
+        .await
+    }
+
+    /// Previews the transfer without signing or sending it: runs the input selection and builds the transaction
+    /// outputs, reporting the remainder and the dust rules the transaction would break instead of failing on them.
+    /// The input addresses are only locked while the preview is built.
+    pub async fn prepare_transfer(&self, transfer_obj: Transfer) -> crate::Result<PreparedTransaction> {
+        self.build_essence_preview(transfer_obj, |account, transfer_essence| {
+            let remainder_value = transfer_essence.remainder_value;
+            let remainder_deposit_address = transfer_essence
+                .remainder_deposit_address
+                .as_ref()
+                .map(|a| a.to_bech32());
+            let dust_warnings = transfer_essence.dust_warnings.clone();
+            let remainder_to_consumed_input = transfer_essence.remainder_to_consumed_input;
+            let EssenceDump { inputs, outputs, .. } = EssenceDump::new(account, transfer_essence);
+            Ok(PreparedTransaction {
+                inputs,
+                outputs,
+                remainder_value,
+                remainder_deposit_address,
+                dust_warnings,
+                remainder_to_consumed_input,
+            })
+        })
+        .await
+    }
+
+    // Builds the transfer essence without changing the account (dry run) and maps it with `f`.
+    // The input addresses are locked while the essence is built and unlocked afterwards, even if it fails.
+    async fn build_essence_preview<T, F>(&self, mut transfer_obj: Transfer, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&Account, TransferEssence) -> crate::Result<T>,
+    {
+        transfer_obj.with_events = false;
+        let (transfer_obj, input_addresses, remainder_address) = self.lock_transfer_inputs(transfer_obj).await?;
+
+        let mut account = self.account_handle.write().await;
+        let res = build_transfer_essence(
+            &transfer_obj,
+            &input_addresses,
+            &self.account_handle,
+            &mut account,
+            remainder_address.as_ref(),
+            true,
+        )
+        .await
+        .and_then(|transfer_essence| f(&account, transfer_essence));
+        drop(account);
+
+        self.unlock_transfer_inputs(&input_addresses).await;
+
+        res
+    }
+
+    // Selects the transfer inputs and locks their addresses, so concurrent transfers don't consume the same outputs.
+    // The remainder value strategy and the remainder address index are resolved on the returned transfer.
+    async fn lock_transfer_inputs(
+        &self,
+        mut transfer_obj: Transfer,
+    ) -> crate::Result<(
+        Transfer,
+        Vec<(input_selection::Input, Vec<AddressOutput>)>,
+        Option<input_selection::Input>,
+    )> {
         let account_ = self.account_handle.read().await;
 
         // if the deposit address belongs to the account, we'll reuse the input address
@@ -1180,7 +2324,7 @@ impl SyncedAccount {
         let mut locked_addresses = account_address_locker.lock().await;
 
         // prepare the transfer getting some needed objects and values
-        let value = transfer_obj.amount.get();
+        let value = transfer_value(&transfer_obj)?;
 
         let balance = account_.balance();
 
@@ -1188,13 +2332,31 @@ impl SyncedAccount {
             return Err(crate::Error::InsufficientFunds);
         }
 
+        // resolve the remainder address index to the account address
+        if let RemainderValueStrategy::AccountAddressIndex { key_index, internal } =
+            transfer_obj.remainder_value_strategy
+        {
+            let remainder_deposit_address = account_
+                .addresses()
+                .iter()
+                .find(|addr| {
+                    *addr.key_index() == key_index
+                        && *addr.internal() == internal
+                        && !addr.imported()
+                        && !addr.reserved()
+                })
+                .ok_or(crate::Error::InvalidRemainderValueAddress)?;
+            transfer_obj.remainder_value_strategy =
+                RemainderValueStrategy::AccountAddress(remainder_deposit_address.address().clone());
+        }
+
         if let RemainderValueStrategy::AccountAddress(ref remainder_deposit_address) =
             transfer_obj.remainder_value_strategy
         {
             if !account_
                 .addresses()
                 .iter()
-                .any(|addr| addr.address() == remainder_deposit_address)
+                .any(|addr| addr.address() == remainder_deposit_address && !addr.reserved())
             {
                 return Err(crate::Error::InvalidRemainderValueAddress);
             }
@@ -1205,19 +2367,31 @@ impl SyncedAccount {
             Option<input_selection::Input>,
         ) = match transfer_obj.input.take() {
             Some((address, address_inputs)) => {
-                if let Some(address) = account_.addresses().iter().find(|a| a.address() == &address) {
+                // imported addresses are watch-only
+                if let Some(address) = account_
+                    .addresses()
+                    .iter()
+                    .find(|a| a.address() == &address && !a.imported())
+                {
+                    let input = input_selection::Input {
+                        internal: *address.internal(),
+                        balance: address_inputs.iter().fold(0, |acc, input| acc + input.amount),
+                        address: address.address().clone(),
+                        key_index: *address.key_index(),
+                        created_at: *address.created_at(),
+                        output_count: address_inputs.len(),
+                    };
+                    if input.balance < value {
+                        return Err(crate::Error::InsufficientFunds);
+                    }
+                    check_input_count(&transfer_obj, address_inputs.len())?;
                     locked_addresses.push(address.address().clone());
-                    (
-                        vec![(
-                            input_selection::Input {
-                                internal: *address.internal(),
-                                balance: address_inputs.iter().fold(0, |acc, input| acc + input.amount),
-                                address: address.address().clone(),
-                            },
-                            address_inputs,
-                        )],
-                        None,
-                    )
+                    let remainder_address = if input.balance > value {
+                        Some(input.clone())
+                    } else {
+                        None
+                    };
+                    (vec![(input, address_inputs)], remainder_address)
                 } else {
                     // TODO
                     return Err(crate::Error::InsufficientFunds);
@@ -1266,24 +2440,19 @@ impl SyncedAccount {
             remainder_address
         );
 
-        let res = perform_transfer(
-            transfer_obj,
-            &input_addresses,
-            self.account_handle.clone(),
-            remainder_address,
-        )
-        .await;
+        Ok((transfer_obj, input_addresses, remainder_address))
+    }
 
-        let mut locked_addresses = account_address_locker.lock().await;
-        for (input_address, _) in &input_addresses {
+    // Unlocks the input addresses locked by `lock_transfer_inputs`.
+    async fn unlock_transfer_inputs(&self, input_addresses: &[(input_selection::Input, Vec<AddressOutput>)]) {
+        let mut locked_addresses = self.account_handle.locked_addresses.lock().await;
+        for (input_address, _) in input_addresses {
             let index = locked_addresses
                 .iter()
-                .position(|a| &input_address.address == a)
+                .position(|a| a.eq_ignore_hrp(&input_address.address))
                 .unwrap();
             locked_addresses.remove(index);
         }
-
-        res
     }
 
     /// Retry message.
@@ -1302,69 +2471,222 @@ impl SyncedAccount {
     }
 }
 
-async fn perform_transfer(
-    transfer_obj: Transfer,
-    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
-    account_handle: AccountHandle,
-    remainder_address: Option<input_selection::Input>,
-) -> crate::Result<Message> {
-    let mut utxos = vec![];
-    let mut transaction_inputs = vec![];
-    // store (amount, address, new_created) to check later if dust is allowed
-    let mut dust_and_allowance_recorders = Vec::new();
+// The transaction essence built for a transfer, along with the data needed to sign it.
+struct TransferEssence {
+    essence: Essence,
+    transaction_inputs: Vec<crate::signing::TransactionInput>,
+    consumed_outputs: Vec<AddressOutput>,
+    remainder_value: u64,
+    remainder_deposit_address: Option<AddressWrapper>,
+    addresses_to_watch: Vec<AddressWrapper>,
+    // the dust rules the transaction breaks
+    dust_warnings: Vec<DustWarning>,
+    // whether the remainder value goes back to an input address fully consumed by the transaction
+    remainder_to_consumed_input: bool,
+}
 
-    if transfer_obj.amount.get() < DUST_ALLOWANCE_VALUE {
-        dust_and_allowance_recorders.push((transfer_obj.amount.get(), transfer_obj.address.to_bech32(), true));
+// Finds an existing internal address to receive the remainder of a transfer from the public `remainder_address`.
+// The internal address with the same key index is preferred; otherwise the change address pool is reused
+// deterministically (unfunded addresses first, then the lowest key index), so repeated transfers converge on
+// stable change addresses instead of generating a new one each time.
+// The transfer recipients and the transfer inputs are never picked from the pool.
+fn find_change_address<'a>(
+    account: &'a Account,
+    remainder_address: &Address,
+    transfer_obj: &Transfer,
+    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
+) -> Option<&'a Address> {
+    if let Some(address) = account
+        .addresses()
+        .iter()
+        .find(|a| *a.internal() && a.key_index() == remainder_address.key_index() && !a.imported())
+    {
+        return Some(address);
     }
+    account
+        .addresses()
+        .iter()
+        .filter(|a| {
+            *a.internal()
+                && !a.retired()
+                && !a.imported()
+                && !transfer_obj.is_recipient(a.address())
+                && !input_addresses.iter().any(|(input, _)| &input.address == a.address())
+        })
+        .min_by_key(|a| (*a.balance() > 0, *a.key_index()))
+}
 
-    let account_ = account_handle.read().await;
-
-    for (input_address, address_outputs) in input_addresses {
-        let account_address = account_
-            .addresses()
-            .iter()
-            .find(|a| a.address() == &input_address.address)
-            .unwrap();
-
-        let mut outputs = vec![];
-        let address_path = BIP32Path::from_str(&format!(
-            "m/44H/4218H/{}H/{}H/{}H",
-            *account_.index(),
-            *account_address.internal() as u32,
-            *account_address.key_index()
-        ))
-        .unwrap();
+// Where the remainder of a transfer is deposited.
+enum RemainderTarget {
+    // an account address, given by the transfer or the remainder address itself
+    Address(AddressWrapper),
+    // the latest public address, or a new public address if the latest one receives a transfer output
+    LatestAddress,
+    // an existing change address
+    ChangeAddress(AddressWrapper),
+    // a new change address with the key index of the remainder address
+    NewChangeAddress,
+}
 
-        for address_output in address_outputs {
-            outputs.push((
-                (*address_output).clone(),
-                *account_address.key_index(),
-                *account_address.internal(),
-                address_path.clone(),
-            ));
-        }
-        utxos.extend(outputs.into_iter());
+// Resolves where the remainder of a transfer from `remainder_address` goes, following the transfer remainder value
+// strategy, without generating any address. Also returns whether the strategy targets an input address fully consumed
+// by the transfer; such remainder is sent to a change address instead if `reroute_consumed_input_remainder` is set.
+fn resolve_remainder_target(
+    account: &Account,
+    remainder_address: &Address,
+    transfer_obj: &Transfer,
+    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
+) -> (RemainderTarget, bool) {
+    let mut remainder_value_strategy = transfer_obj.remainder_value_strategy.clone();
+    let strategy_target = match &remainder_value_strategy {
+        RemainderValueStrategy::ReuseAddress => Some(remainder_address.address()),
+        RemainderValueStrategy::AccountAddress(target_address) => Some(target_address),
+        _ => None,
+    };
+    let targets_consumed_input = strategy_target
+        .map(|target| is_consumed_input_address(account, target, input_addresses))
+        .unwrap_or(false);
+    if targets_consumed_input && transfer_obj.reroute_consumed_input_remainder {
+        remainder_value_strategy = RemainderValueStrategy::ChangeAddress;
     }
 
-    let mut inputs_for_essence: Vec<Input> = Vec::new();
-    let mut outputs_for_essence: Vec<Output> = Vec::new();
-    outputs_for_essence
-        .push(SignatureLockedSingleOutput::new(*transfer_obj.address.as_ref(), transfer_obj.amount.get())?.into());
-    let mut current_output_sum = 0;
-    let mut remainder_value = 0;
-
-    for (utxo, address_index, address_internal, address_path) in utxos {
-        match utxo.kind {
-            OutputKind::SignatureLockedSingle => {
-                if utxo.amount < DUST_ALLOWANCE_VALUE {
-                    dust_and_allowance_recorders.push((utxo.amount, utxo.address.to_bech32(), false));
-                }
+    let target = match remainder_value_strategy {
+        RemainderValueStrategy::ReuseAddress => RemainderTarget::Address(remainder_address.address().clone()),
+        RemainderValueStrategy::AccountAddress(target_address) => RemainderTarget::Address(target_address),
+        RemainderValueStrategy::AccountAddressIndex { .. } => {
+            unreachable!("the remainder address index is resolved before the remainder target")
+        }
+        // the remainder of an internal address goes to the latest address
+        RemainderValueStrategy::ChangeAddress if *remainder_address.internal() => RemainderTarget::LatestAddress,
+        RemainderValueStrategy::ChangeAddress => {
+            match find_change_address(account, remainder_address, transfer_obj, input_addresses) {
+                Some(address) => RemainderTarget::ChangeAddress(address.address().clone()),
+                None => RemainderTarget::NewChangeAddress,
             }
-            OutputKind::SignatureLockedDustAllowance => {
-                dust_and_allowance_recorders.push((utxo.amount, utxo.address.to_bech32(), false));
+        }
+    };
+    (target, targets_consumed_input)
+}
+
+// The number of addresses the consolidation transfers generate: a change address for each remainder sent to a new
+// change address, and a new public address once if the latest address receives value (the transfer value or a
+// remainder).
+fn consolidation_new_addresses(account: &Account, transfers: &[Transfer]) -> usize {
+    let latest_address = account.latest_address().address();
+    let mut new_change_addresses = 0;
+    let mut uses_latest_address = false;
+    for transfer in transfers {
+        if transfer.is_recipient(latest_address) {
+            uses_latest_address = true;
+        }
+        let (input_address, outputs) = match &transfer.input {
+            Some(input) => input,
+            None => continue,
+        };
+        let input_value = outputs.iter().fold(0, |acc, output| acc + output.amount);
+        if input_value <= transfer.amount.get() {
+            continue;
+        }
+        let input_address = match account.addresses().iter().find(|a| a.address() == input_address) {
+            Some(address) => address,
+            None => continue,
+        };
+        let input_addresses = vec![(input_selection_input(input_address, account), outputs.clone())];
+        match resolve_remainder_target(account, input_address, transfer, &input_addresses).0 {
+            RemainderTarget::Address(address) => {
+                if &address == latest_address {
+                    uses_latest_address = true;
+                }
             }
-            OutputKind::Treasury => {}
+            RemainderTarget::LatestAddress => uses_latest_address = true,
+            RemainderTarget::ChangeAddress(_) => {}
+            RemainderTarget::NewChangeAddress => new_change_addresses += 1,
+        }
+    }
+    new_change_addresses + if uses_latest_address { 1 } else { 0 }
+}
+
+// Whether the transfer consumes all the unspent outputs of the input address.
+fn is_consumed_input_address(
+    account: &Account,
+    address: &AddressWrapper,
+    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
+) -> bool {
+    let consumed_outputs = match input_addresses.iter().find(|(input, _)| &input.address == address) {
+        Some((_, outputs)) => outputs,
+        None => return false,
+    };
+    account
+        .addresses()
+        .iter()
+        .find(|a| a.address() == address)
+        .map(|a| {
+            a.outputs().values().filter(|o| !o.is_spent).all(|output| {
+                consumed_outputs
+                    .iter()
+                    .any(|o| o.transaction_id() == output.transaction_id() && o.index() == output.index())
+            })
+        })
+        .unwrap_or(false)
+}
+
+// Builds the transaction essence of the transfer.
+// On a dry run, the addresses generated as remainder value target aren't stored on the account.
+async fn build_transfer_essence(
+    transfer_obj: &Transfer,
+    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
+    account_handle: &AccountHandle,
+    account_: &mut RwLockWriteGuard<'_, Account>,
+    remainder_address: Option<&input_selection::Input>,
+    dry_run: bool,
+) -> crate::Result<TransferEssence> {
+    let mut utxos = vec![];
+    let mut transaction_inputs = vec![];
+    // store the consumed outputs to check later if the created dust outputs are allowed
+    let mut consumed_outputs = Vec::new();
+
+    for (input_address, address_outputs) in input_addresses {
+        let account_address = account_
+            .addresses()
+            .iter()
+            .find(|a| a.address() == &input_address.address)
+            .unwrap();
+
+        let mut outputs = vec![];
+        let address_path = account_address.derivation_path_with_coin_type(*account_.coin_type(), *account_.index());
+
+        for address_output in address_outputs {
+            outputs.push((
+                (*address_output).clone(),
+                *account_address.key_index(),
+                *account_address.internal(),
+                address_path.clone(),
+            ));
         }
+        utxos.extend(outputs.into_iter());
+    }
+
+    let mut inputs_for_essence: Vec<Input> = Vec::new();
+    let mut outputs_for_essence: Vec<Output> = Vec::new();
+    outputs_for_essence.push(if transfer_obj.dust_allowance_output {
+        SignatureLockedDustAllowanceOutput::new(*transfer_obj.address.as_ref(), transfer_obj.amount.get())?.into()
+    } else {
+        SignatureLockedSingleOutput::new(*transfer_obj.address.as_ref(), transfer_obj.amount.get())?.into()
+    });
+    for (address, amount) in &transfer_obj.additional_outputs {
+        outputs_for_essence.push(SignatureLockedSingleOutput::new(*address.as_ref(), amount.get())?.into());
+    }
+    if let Some(dust_allowance_address) = &transfer_obj.dust_allowance_address {
+        outputs_for_essence.push(
+            SignatureLockedDustAllowanceOutput::new(*dust_allowance_address.as_ref(), DUST_ALLOWANCE_VALUE)?.into(),
+        );
+    }
+    let transfer_value = transfer_value(&transfer_obj)?;
+    let mut current_output_sum = 0;
+    let mut remainder_value = 0;
+
+    for (utxo, address_index, address_internal, address_path) in utxos {
+        consumed_outputs.push(utxo.clone());
 
         let input: Input = UTXOInput::new(*utxo.transaction_id(), *utxo.index())?.into();
         inputs_for_essence.push(input.clone());
@@ -1374,7 +2696,7 @@ async fn perform_transfer(
             address_path,
             address_internal,
         });
-        if current_output_sum == transfer_obj.amount.get() {
+        if current_output_sum == transfer_value {
             log::debug!(
                     "[TRANSFER] current output sum matches the transfer value, adding {} to the remainder value (currently at {})",
                     utxo.amount(),
@@ -1382,7 +2704,7 @@ async fn perform_transfer(
                 );
             // already filled the transfer value; just collect the output value as remainder
             remainder_value += *utxo.amount();
-        } else if current_output_sum + *utxo.amount() > transfer_obj.amount.get() {
+        } else if current_output_sum + *utxo.amount() > transfer_value {
             log::debug!(
                 "[TRANSFER] current output sum ({}) would exceed the transfer value if added to the output amount ({})",
                 current_output_sum,
@@ -1390,7 +2712,7 @@ async fn perform_transfer(
             );
             // if the used UTXO amount is greater than the transfer value,
             // this is the last iteration and we'll have remainder value
-            let missing_value = transfer_obj.amount.get() - current_output_sum;
+            let missing_value = transfer_value - current_output_sum;
             remainder_value += *utxo.amount() - missing_value;
             current_output_sum += missing_value;
             log::debug!(
@@ -1398,11 +2720,6 @@ async fn perform_transfer(
                 missing_value,
                 remainder_value
             );
-
-            let remaining_balance_on_source = current_output_sum - transfer_obj.amount.get();
-            if remaining_balance_on_source < DUST_ALLOWANCE_VALUE && remaining_balance_on_source != 0 {
-                dust_and_allowance_recorders.push((remaining_balance_on_source, utxo.address().to_bech32(), true));
-            }
         } else {
             log::debug!(
                 "[TRANSFER] adding output amount {}, current sum {}",
@@ -1410,99 +2727,117 @@ async fn perform_transfer(
                 current_output_sum
             );
             current_output_sum += *utxo.amount();
-
-            if current_output_sum > transfer_obj.amount.get() {
-                let remaining_balance_on_source = current_output_sum - transfer_obj.amount.get();
-                if remaining_balance_on_source < DUST_ALLOWANCE_VALUE && remaining_balance_on_source != 0 {
-                    dust_and_allowance_recorders.push((remaining_balance_on_source, utxo.address().to_bech32(), true));
-                }
-            }
         }
     }
 
-    drop(account_);
-    let mut account_ = account_handle.write().await;
-
     let mut addresses_to_watch = vec![];
+    let mut remainder_to_consumed_input = false;
 
     // if there's remainder value, we check the strategy defined in the transfer
-    let mut remainder_value_deposit_address = None;
     let remainder_deposit_address = if remainder_value > 0 {
-        let remainder_address = remainder_address.as_ref().expect("remainder address not defined");
+        let remainder_address = remainder_address.expect("remainder address not defined");
         let remainder_address = account_
             .addresses()
             .iter()
             .find(|a| a.address() == &remainder_address.address)
-            .unwrap();
+            .unwrap()
+            .clone();
 
         log::debug!("[TRANSFER] remainder value is {}", remainder_value);
 
-        let remainder_deposit_address = match transfer_obj.remainder_value_strategy.clone() {
-            // use one of the account's addresses to send the remainder value
-            RemainderValueStrategy::AccountAddress(target_address) => {
+        let (remainder_target, targets_consumed_input) =
+            resolve_remainder_target(&account_, &remainder_address, transfer_obj, input_addresses);
+        if targets_consumed_input {
+            log::warn!("[TRANSFER] the remainder target is an input address fully consumed by the transfer");
+            transfer_obj
+                .emit_event_if_needed(
+                    account_.id().to_string(),
+                    TransferProgressType::RemainderToConsumedInputAddress,
+                )
+                .await;
+            if transfer_obj.reroute_consumed_input_remainder {
+                log::debug!("[TRANSFER] rerouting the remainder value to a change address");
+            } else {
+                remainder_to_consumed_input = true;
+            }
+        }
+
+        let remainder_deposit_address = match remainder_target {
+            // keep the remainder value on the address or use one of the account's addresses
+            RemainderTarget::Address(target_address) => {
                 log::debug!(
-                    "[TARGET] using user defined account address as remainder target: {}",
+                    "[TRANSFER] using account address as remainder target: {}",
                     target_address.to_bech32()
                 );
                 target_address
             }
-            // generate a new change address to send the remainder value
-            RemainderValueStrategy::ChangeAddress => {
-                if *remainder_address.internal() {
-                    let mut deposit_address = account_.latest_address().address().clone();
-                    // if the latest address is the transfer's address, we'll generate a new one as remainder deposit
-                    if deposit_address == transfer_obj.address {
-                        transfer_obj
-                            .emit_event_if_needed(
-                                account_.id().to_string(),
-                                TransferProgressType::GeneratingRemainderDepositAddress,
-                            )
-                            .await;
-                        account_handle.generate_address_internal(&mut account_).await?;
-                        deposit_address = account_.latest_address().address().clone();
-                    }
-                    log::debug!(
-                        "[TRANSFER] the remainder address is internal, so using latest address as remainder target: {}",
-                        deposit_address.to_bech32()
-                    );
-                    deposit_address
-                } else if let Some(address) = account_
-                    .addresses()
-                    .iter()
-                    .find(|a| *a.internal() && a.key_index() == remainder_address.key_index())
-                {
-                    address.address().clone()
-                } else {
+            RemainderTarget::LatestAddress => {
+                let mut deposit_address = account_.latest_address().address().clone();
+                // if the latest address receives a transfer output, we'll generate a new one as remainder deposit
+                if transfer_obj.is_recipient(&deposit_address) {
                     transfer_obj
                         .emit_event_if_needed(
                             account_.id().to_string(),
                             TransferProgressType::GeneratingRemainderDepositAddress,
                         )
                         .await;
-                    let change_address = crate::address::get_new_change_address(
-                        &account_,
-                        &remainder_address,
-                        GenerateAddressMetadata { syncing: false },
+                    deposit_address = if dry_run {
+                        crate::address::get_new_address(&account_, GenerateAddressMetadata { syncing: false })
+                            .await?
+                            .address()
+                            .clone()
+                    } else {
+                        account_handle.generate_address_internal(account_).await?;
+                        account_.latest_address().address().clone()
+                    };
+                }
+                log::debug!(
+                    "[TRANSFER] the remainder address is internal, so using latest address as remainder target: {}",
+                    deposit_address.to_bech32()
+                );
+                deposit_address
+            }
+            RemainderTarget::ChangeAddress(address) => {
+                log::debug!(
+                    "[TRANSFER] reusing change address as remainder target: {}",
+                    address.to_bech32()
+                );
+                address
+            }
+            // generate a new change address to send the remainder value
+            RemainderTarget::NewChangeAddress => {
+                transfer_obj
+                    .emit_event_if_needed(
+                        account_.id().to_string(),
+                        TransferProgressType::GeneratingRemainderDepositAddress,
                     )
-                    .await?;
-                    let addr = change_address.address().clone();
-                    log::debug!(
-                        "[TRANSFER] generated new change address as remainder target: {}",
-                        addr.to_bech32()
-                    );
+                    .await;
+                let change_address = crate::address::get_new_change_address(
+                    &account_,
+                    &remainder_address,
+                    GenerateAddressMetadata { syncing: false },
+                )
+                .await?;
+                let addr = change_address.address().clone();
+                log::debug!(
+                    "[TRANSFER] generated new change address as remainder target: {}",
+                    addr.to_bech32()
+                );
+                if !dry_run {
                     account_.append_addresses(vec![change_address]);
                     addresses_to_watch.push(addr.clone());
-                    addr
                 }
-            }
-            // keep the remainder value on the address
-            RemainderValueStrategy::ReuseAddress => {
-                let address = remainder_address.address().clone();
-                log::debug!("[TRANSFER] reusing address as remainder target {}", address.to_bech32());
-                address
+                addr
             }
         };
-        remainder_value_deposit_address = Some(remainder_deposit_address.clone());
+        // a transaction can't have two outputs to the same address
+        if transfer_obj.is_recipient(&remainder_deposit_address) {
+            return Err(crate::Error::InvalidTransfer(vec![
+                crate::message::TransferValidationError::DuplicateRecipient {
+                    address: remainder_deposit_address.to_bech32(),
+                },
+            ]));
+        }
         outputs_for_essence
             .push(SignatureLockedSingleOutput::new(*remainder_deposit_address.as_ref(), remainder_value)?.into());
         Some(remainder_deposit_address)
@@ -1510,29 +2845,12 @@ async fn perform_transfer(
         None
     };
 
-    if let Some(remainder_deposit_address) = &remainder_deposit_address {
-        if remainder_value < DUST_ALLOWANCE_VALUE {
-            dust_and_allowance_recorders.push((remainder_value, remainder_deposit_address.to_bech32(), true));
-        }
-    }
-
     let client =
-        crate::client::get_client(account_.client_options(), Some(account_handle.is_monitoring.clone())).await?;
-    let client = client.read().await;
+        crate::client::get_node_client(account_.client_options(), Some(account_handle.is_monitoring.clone())).await?;
 
-    // Check if we would let dust on an address behind or send new dust, which would make the tx unconfirmable
-    let mut single_addresses = HashSet::new();
-    for dust_or_allowance in &dust_and_allowance_recorders {
-        single_addresses.insert(dust_or_allowance.1.to_string());
-    }
-    for address in single_addresses {
-        let created_or_consumed_outputs: Vec<(u64, bool)> = dust_and_allowance_recorders
-            .iter()
-            .filter(|d| d.1 == address)
-            .map(|(amount, _, flag)| (*amount, *flag))
-            .collect();
-        is_dust_allowed(&account_, &client, address, created_or_consumed_outputs).await?;
-    }
+    // Check if we would let dust on an address behind or send new dust, which would make the tx unconfirmable;
+    // the transfer fails on any warning, but a preview reports them
+    let dust_warnings = find_dust_warnings(&account_, &*client, &consumed_outputs, &outputs_for_essence).await?;
 
     // Build transaction essence
     let mut essence_builder = RegularEssence::builder();
@@ -1552,6 +2870,50 @@ async fn perform_transfer(
     let essence = essence_builder.finish()?;
     let essence = Essence::Regular(essence);
 
+    Ok(TransferEssence {
+        essence,
+        transaction_inputs,
+        consumed_outputs,
+        remainder_value,
+        remainder_deposit_address,
+        addresses_to_watch,
+        dust_warnings,
+        remainder_to_consumed_input,
+    })
+}
+
+async fn perform_transfer(
+    transfer_obj: Transfer,
+    input_addresses: &[(input_selection::Input, Vec<AddressOutput>)],
+    account_handle: AccountHandle,
+    remainder_address: Option<input_selection::Input>,
+    journal_id: &str,
+) -> crate::Result<Message> {
+    let mut account_ = account_handle.write().await;
+
+    let TransferEssence {
+        essence,
+        mut transaction_inputs,
+        remainder_value,
+        remainder_deposit_address,
+        mut addresses_to_watch,
+        dust_warnings,
+        ..
+    } = build_transfer_essence(
+        &transfer_obj,
+        input_addresses,
+        &account_handle,
+        &mut account_,
+        remainder_address.as_ref(),
+        false,
+    )
+    .await?;
+    check_dust_warnings(&dust_warnings)?;
+    let remainder_value_deposit_address = remainder_deposit_address.clone();
+
+    let client =
+        crate::client::get_node_client(account_.client_options(), Some(account_handle.is_monitoring.clone())).await?;
+
     transfer_obj
         .emit_event_if_needed(account_.id().to_string(), TransferProgressType::SigningTransaction)
         .await;
@@ -1586,10 +2948,22 @@ async fn perform_transfer(
     transfer_obj
         .emit_event_if_needed(account_.id().to_string(), TransferProgressType::PerformingPoW)
         .await;
-    let message = finish_pow(&client, Some(Payload::Transaction(Box::new(transaction)))).await?;
+    let message = finish_transfer_pow(
+        client.clone(),
+        account_.client_options(),
+        account_handle.is_monitoring.clone(),
+        &transfer_obj,
+        account_.id(),
+        Payload::Transaction(Box::new(transaction)),
+    )
+    .await?;
 
     log::debug!("[TRANSFER] submitting message {:#?}", message);
 
+    // journal the message id before broadcasting it, so an interrupted transfer can be looked up on the node
+    account_.set_pending_transfer_message_id(journal_id, message.id().0);
+    account_.save().await?;
+
     transfer_obj
         .emit_event_if_needed(account_.id().to_string(), TransferProgressType::Broadcasting)
         .await;
@@ -1599,12 +2973,12 @@ async fn perform_transfer(
     // if this is a transfer to the account's latest address or we used the latest as deposit of the remainder
     // value, we generate a new one to keep the latest address unused
     let latest_address = account_.latest_address().address();
-    if latest_address == &transfer_obj.address
+    if transfer_obj.is_recipient(latest_address)
         || (remainder_value_deposit_address.is_some() && &remainder_value_deposit_address.unwrap() == latest_address)
     {
         log::debug!(
             "[TRANSFER] generating new address since {}",
-            if latest_address == &transfer_obj.address {
+            if transfer_obj.is_recipient(latest_address) {
                 "latest address equals the transfer address"
             } else {
                 "latest address equals the remainder value deposit address"
@@ -1618,7 +2992,7 @@ async fn perform_transfer(
     // drop the  client ref so it doesn't lock the Message parsing
     drop(client);
 
-    let message = Message::from_iota_message(
+    let mut message = Message::from_iota_message(
         message_id,
         message,
         account_handle.accounts.clone(),
@@ -1628,7 +3002,10 @@ async fn perform_transfer(
     )
     .finish()
     .await?;
+    message.set_memo(transfer_obj.memo.clone());
+    message.set_idempotency_key(transfer_obj.idempotency_key.clone());
     account_.append_messages(vec![message.clone()]);
+    account_.remove_pending_transfer(journal_id);
 
     account_.save().await?;
 
@@ -1639,83 +3016,338 @@ async fn perform_transfer(
     Ok(message)
 }
 
-// Calculate the outputs on this address after the transaction gets confirmed so we know if we can send dust or
-// dust allowance outputs (as input). the bool in the outputs defines if we consume this output (false) or create a new
-// one (true)
-async fn is_dust_allowed(
-    account: &Account,
-    client: &iota::Client,
-    address: String,
-    outputs: Vec<(u64, bool)>,
-) -> crate::Result<()> {
-    // balance of all dust allowance outputs
-    let mut dust_allowance_balance: i64 = 0;
-    // Amount of dust outputs
-    let mut dust_outputs_amount: i64 = 0;
-
-    // Add outputs from this transaction
-    for output in outputs {
-        match output.1 {
-            // add newly created outputs
-            true => {
-                if output.0 >= DUST_ALLOWANCE_VALUE {
-                    dust_allowance_balance += output.0 as i64;
-                } else {
-                    dust_outputs_amount += 1
-                }
+// Builds the transfer message, doing the PoW on the node if the local PoW exceeds its time budget.
+// The local PoW is CPU-bound and never yields, so it runs on a blocking thread raced against the deadline.
+// That thread can't be cancelled: on timeout it keeps running until the PoW finishes and its result is dropped.
+// To bound the wasted CPU, the local PoW is skipped while a previously abandoned one is still running.
+async fn finish_transfer_pow(
+    client: Arc<dyn NodeClient>,
+    client_options: &ClientOptions,
+    is_monitoring: Arc<AtomicBool>,
+    transfer_obj: &Transfer,
+    account_id: &str,
+    payload: Payload,
+) -> crate::Result<IotaMessage> {
+    let timeout = match client_options.local_pow_timeout() {
+        Some(timeout) if *client_options.local_pow() => *timeout,
+        _ => return client.finish_pow(Some(payload)).await,
+    };
+    let timed_out = if ABANDONED_LOCAL_POW_COUNT.load(Ordering::SeqCst) > 0 {
+        log::debug!("[TRANSFER] a previous local PoW is still running, falling back to remote PoW");
+        true
+    } else {
+        let local_payload = payload.clone();
+        let runtime = tokio::runtime::Handle::current();
+        let mut local_pow =
+            tokio::task::spawn_blocking(move || runtime.block_on(client.finish_pow(Some(local_payload))));
+        match tokio::time::timeout(timeout, &mut local_pow).await {
+            Ok(Ok(message)) => return message,
+            Ok(Err(e)) => {
+                log::error!("[TRANSFER] local PoW task failed: {:?}, falling back to remote PoW", e);
+                false
             }
-            // remove consumed outputs
-            false => {
-                if output.0 >= DUST_ALLOWANCE_VALUE {
-                    dust_allowance_balance -= output.0 as i64;
-                } else {
-                    dust_outputs_amount -= 1;
-                }
+            Err(_) => {
+                log::debug!(
+                    "[TRANSFER] local PoW exceeded {:?}, falling back to remote PoW",
+                    timeout
+                );
+                ABANDONED_LOCAL_POW_COUNT.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let _ = local_pow.await;
+                    ABANDONED_LOCAL_POW_COUNT.fetch_sub(1, Ordering::SeqCst);
+                });
+                true
             }
         }
+    };
+    transfer_obj
+        .emit_event_if_needed(account_id.to_string(), TransferProgressType::FallingBackToRemotePoW)
+        .await;
+    let remote_pow = async {
+        let remote_client =
+            crate::client::get_node_client(&client_options.remote_pow_options(), Some(is_monitoring)).await?;
+        remote_client.finish_pow(Some(payload)).await
+    };
+    remote_pow.await.map_err(|e| {
+        log::error!("[TRANSFER] remote PoW failed: {:?}", e);
+        // the error is only reported as a PoW timeout if the local PoW really exceeded its time budget
+        if timed_out {
+            crate::Error::PowTimeout
+        } else {
+            e
+        }
+    })
+}
+
+// The maximum number of inputs the transfer can consume.
+fn max_input_count(transfer_obj: &Transfer) -> usize {
+    transfer_obj
+        .max_inputs
+        .unwrap_or(INPUT_OUTPUT_COUNT_MAX)
+        .min(INPUT_OUTPUT_COUNT_MAX)
+}
+
+// The input selection candidate for the available balance of the account address.
+fn input_selection_input(address: &Address, account: &Account) -> input_selection::Input {
+    input_selection::Input {
+        address: address.address().clone(),
+        internal: *address.internal(),
+        balance: address.available_balance(account),
+        key_index: *address.key_index(),
+        created_at: *address.created_at(),
+        output_count: address.available_outputs(account).len(),
     }
+}
 
-    // Get outputs from address and apply values
-    let address_outputs = if let Some(address) = account.addresses().iter().find(|a| a.address().to_bech32() == address)
-    {
-        address
-            .outputs()
-            .values()
-            .map(|output| (output.amount, output.kind.clone()))
-            .collect()
+// Checks that the transaction inputs don't exceed the transfer `max_inputs` option
+// or the maximum number of inputs of a transaction.
+fn check_input_count(transfer_obj: &Transfer, input_count: usize) -> crate::Result<()> {
+    let max = max_input_count(transfer_obj);
+    if input_count > max {
+        Err(crate::Error::TooManyInputsRequired {
+            needed: input_count,
+            max,
+        })
     } else {
-        let outputs = client.find_outputs(&[], &[address.to_string().into()]).await?;
-        let mut address_outputs = Vec::new();
-        for output in outputs {
-            let output = AddressOutput::from_output_response(output, "".to_string())?;
-            address_outputs.push((output.amount, output.kind));
+        Ok(())
+    }
+}
+
+// The number of dust outputs an address can hold with the given dust allowance balance:
+// each 100_000i of dust allowance covers one dust output, up to 100 dust outputs.
+fn allowed_dust_outputs(dust_allowance_balance: u64) -> u64 {
+    std::cmp::min(
+        dust_allowance_balance / DUST_ALLOWANCE_PER_OUTPUT,
+        MAX_DUST_OUTPUTS_PER_ADDRESS,
+    )
+}
+
+// The value of the dust allowance output the account address needs to receive `additional_dust_outputs` dust outputs,
+// or `None` if its dust allowance balance already covers them.
+pub(crate) fn missing_dust_allowance(address: &Address, additional_dust_outputs: u64) -> crate::Result<Option<u64>> {
+    let mut dust_allowance_balance = 0;
+    let mut dust_outputs = additional_dust_outputs;
+    for output in address.outputs().values().filter(|o| !o.is_spent) {
+        match output.kind {
+            OutputKind::SignatureLockedDustAllowance => dust_allowance_balance += output.amount,
+            OutputKind::SignatureLockedSingle if output.amount < DUST_ALLOWANCE_VALUE => dust_outputs += 1,
+            _ => {}
         }
-        address_outputs
-    };
-    for (amount, kind) in address_outputs {
-        match kind {
-            OutputKind::SignatureLockedDustAllowance => {
-                dust_allowance_balance += amount as i64;
-            }
-            OutputKind::SignatureLockedSingle => {
-                if amount < DUST_ALLOWANCE_VALUE {
-                    dust_outputs_amount += 1;
+    }
+
+    if dust_outputs > MAX_DUST_OUTPUTS_PER_ADDRESS {
+        return Err(crate::Error::DustError(format!(
+            "address {} can't hold more than {} dust outputs",
+            address.address().to_bech32(),
+            MAX_DUST_OUTPUTS_PER_ADDRESS
+        )));
+    }
+    if allowed_dust_outputs(dust_allowance_balance) >= dust_outputs {
+        return Ok(None);
+    }
+    let missing = dust_outputs * DUST_ALLOWANCE_PER_OUTPUT - dust_allowance_balance;
+    // a dust allowance output must be worth at least 1Mi
+    Ok(Some(missing.max(DUST_ALLOWANCE_VALUE)))
+}
+
+// The value the transfer consumes: the transfer amount plus the dust allowance output value, if any.
+fn transfer_value(transfer_obj: &Transfer) -> crate::Result<u64> {
+    let total_amount = transfer_obj.total_amount()?;
+    match transfer_obj.dust_allowance_address {
+        Some(_) => total_amount.checked_add(DUST_ALLOWANCE_VALUE).ok_or_else(|| {
+            crate::Error::InvalidTransfer(vec![crate::message::TransferValidationError::AmountOverflow])
+        }),
+        None => Ok(total_amount),
+    }
+}
+
+// Fails with the error of the first dust warning, if any.
+fn check_dust_warnings(dust_warnings: &[DustWarning]) -> crate::Result<()> {
+    match dust_warnings.first() {
+        Some(DustWarning {
+            address,
+            amount: Some(amount),
+        }) => Err(crate::Error::DustOutputNotAllowed {
+            address: address.clone(),
+            amount: *amount,
+        }),
+        Some(DustWarning { address, amount: None }) => Err(crate::Error::DustError(format!(
+            "consuming the dust allowance output would leave the dust outputs on address {} uncovered",
+            address
+        ))),
+        None => Ok(()),
+    }
+}
+
+// Checks that every dust output created by the transaction (including the remainder output) is covered by the dust
+// allowance of its address after the transaction gets confirmed, so the network doesn't reject it.
+// Consuming a dust allowance output can also leave the existing dust outputs of an address uncovered.
+pub(crate) async fn find_dust_warnings(
+    account: &Account,
+    client: &dyn NodeClient,
+    consumed_outputs: &[AddressOutput],
+    created_outputs: &[Output],
+) -> crate::Result<Vec<DustWarning>> {
+    let mut dust_warnings = Vec::new();
+    // (address, amount, is dust allowance) of the created outputs
+    let created_outputs: Vec<(IotaAddress, u64, bool)> = created_outputs
+        .iter()
+        .filter_map(|output| {
+            let (kind, address, amount) = parse_output(output);
+            address.map(|address| (*address, amount, kind == OutputKind::SignatureLockedDustAllowance))
+        })
+        .collect();
+
+    // the addresses that receive dust or have a dust allowance output consumed
+    let mut addresses_to_check: Vec<IotaAddress> = Vec::new();
+    let dust_addresses = created_outputs
+        .iter()
+        .filter(|(_, amount, is_dust_allowance)| !is_dust_allowance && *amount < DUST_ALLOWANCE_VALUE)
+        .map(|(address, _, _)| *address);
+    let consumed_allowance_addresses = consumed_outputs
+        .iter()
+        .filter(|o| o.kind == OutputKind::SignatureLockedDustAllowance)
+        .map(|o| *o.address.as_ref());
+    for address in dust_addresses.chain(consumed_allowance_addresses) {
+        if !addresses_to_check.contains(&address) {
+            addresses_to_check.push(address);
+        }
+    }
+
+    let consumed_output_ids: Vec<OutputId> = consumed_outputs.iter().filter_map(|o| o.id().ok()).collect();
+
+    for address in addresses_to_check {
+        let address = AddressWrapper::new(address, account.bech32_hrp());
+
+        // the address unspent outputs that aren't consumed by this transaction
+        let existing_outputs: Vec<(u64, OutputKind)> =
+            if let Some(account_address) = account.addresses().iter().find(|a| a.address() == &address) {
+                account_address
+                    .outputs()
+                    .values()
+                    .filter(|o| !o.is_spent && !consumed_output_ids.contains(&o.id().unwrap()))
+                    .map(|o| (o.amount, o.kind.clone()))
+                    .collect()
+            } else {
+                let mut outputs = Vec::new();
+                for utxo_input in client.get_address_outputs(&address.to_bech32().into(), false).await? {
+                    let output = AddressOutput::from_output_response(
+                        client.get_output(&utxo_input).await?,
+                        address.bech32_hrp().to_string(),
+                    )?;
+                    if !output.is_spent && !consumed_output_ids.contains(&output.id()?) {
+                        outputs.push((output.amount, output.kind));
+                    }
                 }
+                outputs
+            };
+
+        // the address state as if this transaction gets confirmed
+        let mut dust_allowance_balance = 0;
+        let mut dust_outputs_amount = 0;
+        for (amount, kind) in existing_outputs {
+            match kind {
+                OutputKind::SignatureLockedDustAllowance => dust_allowance_balance += amount,
+                OutputKind::SignatureLockedSingle if amount < DUST_ALLOWANCE_VALUE => dust_outputs_amount += 1,
+                _ => {}
+            }
+        }
+        let mut created_dust_outputs = Vec::new();
+        for (_, amount, is_dust_allowance) in created_outputs.iter().filter(|(a, _, _)| a == address.as_ref()) {
+            if *is_dust_allowance {
+                dust_allowance_balance += amount;
+            } else if *amount < DUST_ALLOWANCE_VALUE {
+                dust_outputs_amount += 1;
+                created_dust_outputs.push(*amount);
             }
-            OutputKind::Treasury => {}
+        }
+
+        if dust_outputs_amount > allowed_dust_outputs(dust_allowance_balance) {
+            dust_warnings.push(DustWarning {
+                address: address.to_bech32(),
+                amount: created_dust_outputs.first().copied(),
+            });
         }
     }
 
-    // Here dust_allowance_balance and dust_outputs_amount should be as if this transaction gets confirmed
-    // Max allowed dust outputs is 100
-    let allowed_dust_amount = std::cmp::min(dust_allowance_balance / 100_000, 100);
-    if dust_outputs_amount > allowed_dust_amount {
-        return Err(crate::Error::DustError(format!(
-            "No dust output allowed on address {}",
-            address
-        )));
+    Ok(dust_warnings)
+}
+
+/// Reconciles the journaled transfers that were interrupted (e.g. by a crash) with the node.
+/// A transfer whose message reached the node is stored on the account, so it's watched (and retried) like any other
+/// pending transfer; otherwise the transfer wasn't broadcasted and its inputs are released.
+/// Returns the reconciled journal entries.
+pub(crate) async fn reconcile_pending_transfers(account_handle: &AccountHandle) -> crate::Result<Vec<PendingTransfer>> {
+    let (interrupted_transfers, client_options) = {
+        let account = account_handle.read().await;
+        // the in flight transfers are registered while holding the account lock, so the set is up to date here
+        let in_flight_transfers = account_handle.in_flight_transfers.lock().await.clone();
+        let interrupted_transfers: Vec<PendingTransfer> = account
+            .pending_transfers()
+            .iter()
+            .filter(|t| !in_flight_transfers.contains(t.id()))
+            .cloned()
+            .collect();
+        (interrupted_transfers, account.client_options().clone())
+    };
+    if interrupted_transfers.is_empty() {
+        return Ok(Vec::new());
     }
-    Ok(())
+
+    // the node is queried without holding the account lock
+    let client = crate::client::get_node_client(&client_options, Some(account_handle.is_monitoring.clone())).await?;
+    let mut node_messages = Vec::new();
+    for pending_transfer in &interrupted_transfers {
+        let node_message = match pending_transfer.message_id() {
+            Some(message_id) => client.get_message(message_id).await?,
+            None => None,
+        };
+        node_messages.push(node_message);
+    }
+    drop(client);
+
+    let mut account = account_handle.write().await;
+    let mut reconciled_transfers = Vec::new();
+    for (pending_transfer, node_message) in interrupted_transfers.into_iter().zip(node_messages) {
+        // the entry was reconciled by a concurrent call while the lock was released
+        if !account.pending_transfers().contains(&pending_transfer) {
+            continue;
+        }
+        match (pending_transfer.message_id(), node_message) {
+            (Some(message_id), Some((node_message, confirmed))) => {
+                log::info!(
+                    "[TRANSFER] interrupted transfer {} was broadcasted as message {}, resuming it",
+                    pending_transfer.id(),
+                    message_id
+                );
+                if account.get_message(message_id).is_none() {
+                    let message = Message::from_iota_message(
+                        *message_id,
+                        node_message,
+                        account_handle.accounts.clone(),
+                        account.id(),
+                        account.addresses(),
+                        account.client_options(),
+                    )
+                    .with_confirmed(confirmed)
+                    .finish()
+                    .await?;
+                    account.append_messages(vec![message]);
+                }
+            }
+            _ => {
+                log::info!(
+                    "[TRANSFER] interrupted transfer {} wasn't broadcasted, releasing its inputs",
+                    pending_transfer.id()
+                );
+            }
+        }
+        account.remove_pending_transfer(pending_transfer.id());
+        reconciled_transfers.push(pending_transfer);
+    }
+    account.save().await?;
+
+    Ok(reconciled_transfers)
 }
 
 pub(crate) enum RepostAction {
@@ -1740,21 +3372,23 @@ pub(crate) async fn repost_message(
                 .find(|m| m.payload() == message_to_repost.payload())
                 .unwrap();
             if message_to_repost.confirmed().unwrap_or(false) {
-                return Err(crate::Error::ClientError(Box::new(
-                    iota::client::Error::NoNeedPromoteOrReattach(message_id.to_string()),
-                )));
+                return Err(crate::Error::MessageAlreadyConfirmed(message_id.to_string()));
             }
 
             let client =
                 crate::client::get_client(account.client_options(), Some(account_handle.is_monitoring.clone())).await?;
             let client = client.read().await;
 
-            let (id, message) = match action {
-                RepostAction::Promote => client.promote(message_id).await?,
-                RepostAction::Reattach => client.reattach(message_id).await?,
-                RepostAction::Retry => client.retry(message_id).await?,
+            let res = match action {
+                RepostAction::Promote => client.promote(message_id).await,
+                RepostAction::Reattach => client.reattach(message_id).await,
+                RepostAction::Retry => client.retry(message_id).await,
             };
-            let message = Message::from_iota_message(
+            let (id, message) = res.map_err(|e| match e {
+                iota::client::Error::ResponseError(404, _) => crate::Error::MessageNotFound,
+                _ => e.into(),
+            })?;
+            let mut message = Message::from_iota_message(
                 id,
                 message,
                 account_handle.accounts.clone(),
@@ -1764,6 +3398,10 @@ pub(crate) async fn repost_message(
             )
             .finish()
             .await?;
+            // reattachments keep the memo of the original transfer
+            if message.payload() == message_to_repost.payload() {
+                message.set_memo(message_to_repost.memo().clone());
+            }
 
             account.append_messages(vec![message.clone()]);
 
@@ -1800,55 +3438,514 @@ mod tests {
         // TODO improve test when the node API is ready to use
     }
 
-    // this needs a proper client mock to run on CI
-    // #[tokio::test]
-    #[allow(dead_code)]
-    async fn dust_transfer() {
+    #[tokio::test]
+    async fn coalesce_concurrent_syncs() {
         let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let synchronizer = account_handle.sync().await;
+
+        // simulate a fetch that was in flight when the sync was requested
+        let fetched_address = crate::test_utils::generate_random_address();
+        *account_handle.sync_coalescer.latest.lock().await = Some(super::FetchedHistory {
+            sequence: 0,
+            address_index: synchronizer.address_index,
+            gap_limit: synchronizer.gap_limit,
+            steps: synchronizer.steps.clone(),
+            data: super::SyncedAccountData {
+                messages: Vec::new(),
+                addresses: vec![fetched_address.clone()],
+                suggested_gap_limit: None,
+                failed_addresses: Vec::new(),
+                sync_parameters: super::SyncParameters {
+                    address_index: 0,
+                    gap_limit: 10,
+                    balance_only: false,
+                },
+            },
+        });
 
-        // first we create an address with balance - the source address
-        let mut address1 = crate::test_utils::generate_random_address();
-        let output = crate::address::AddressOutput {
-            transaction_id: iota::TransactionId::from([0; 32]),
-            message_id: iota::MessageId::from([0; 32]),
-            index: 0,
-            amount: 10000000,
-            is_spent: false,
-            address: address1.address().clone(),
-            kind: crate::address::OutputKind::SignatureLockedSingle,
-        };
-        address1.outputs.insert(output.id().unwrap(), output);
-        address1.set_balance(10000000);
-
-        // then we create an address without balance - the deposit address
-        let address2 = crate::test_utils::generate_random_address();
-
-        let mut address3 = crate::test_utils::generate_random_address();
-        address3.set_key_index(0);
-        address3.set_internal(true);
-        let output = crate::address::AddressOutput {
-            transaction_id: iota::TransactionId::from([0; 32]),
-            message_id: iota::MessageId::from([0; 32]),
-            index: 0,
-            amount: 10000000,
-            is_spent: false,
-            address: address3.address().clone(),
-            kind: crate::address::OutputKind::SignatureLockedDustAllowance,
-        };
-        address3.outputs.insert(output.id().unwrap(), output);
+        let data = synchronizer.get_new_history().await.unwrap();
+        assert_eq!(data.addresses, vec![fetched_address]);
+        // no other request is waiting for the fetched history, so it isn't kept
+        assert!(account_handle.sync_coalescer.latest.lock().await.is_none());
+    }
 
-        println!(
-            "{}\n{}\n{}",
-            address1.address().to_bech32(),
-            address2.address().to_bech32(),
-            address3.address().to_bech32()
+    #[tokio::test]
+    async fn sync_with_mock_client() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1500000,
+            crate::address::OutputKind::SignatureLockedSingle,
         );
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output.clone())
+            .register()
+            .await;
 
         let account_handle = crate::test_utils::AccountCreator::new(&manager)
-            .addresses(vec![address1, address2.clone(), address3])
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
             .create()
             .await;
-        let id = account_handle.id().await;
+        account_handle.sync().await.execute().await.unwrap();
+
+        let account = account_handle.read().await;
+        let synced_address = account.addresses().iter().find(|a| a == &&address).unwrap();
+        assert_eq!(*synced_address.balance(), 1500000);
+        assert!(synced_address.outputs().contains_key(&output.id().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn pruned_messages_arent_synced_again() {
+        use crate::client::NodeClient;
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let node_message = crate::test_utils::MockNodeClient::default()
+            .finish_pow(None)
+            .await
+            .unwrap();
+        let address = crate::test_utils::generate_random_address();
+        let mut output = crate::test_utils::generate_address_output(
+            address.address(),
+            1500000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        output.message_id = node_message.id().0;
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .with_message(node_message.clone(), Some(true))
+            .register()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+        account_handle.write().await.set_max_stored_messages(Some(0));
+
+        let synced = account_handle.sync().await.execute().await.unwrap();
+        assert_eq!(synced.messages().len(), 1);
+        assert_eq!(account_handle.message_count().await, 0);
+
+        // the pruned message is known, so it isn't fetched and reported again
+        let synced = account_handle.sync().await.execute().await.unwrap();
+        assert!(synced.messages().is_empty());
+        assert_eq!(account_handle.message_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn sync_parameters() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+
+        // the default gap limit is used since the latest address index is 0
+        let synchronizer = account_handle.sync().await;
+        assert_eq!(*synchronizer.parameters().gap_limit(), 10);
+        let synced = synchronizer.execute().await.unwrap();
+        let sync_parameters = synced.sync_parameters().unwrap();
+        assert_eq!(*sync_parameters.address_index(), 0);
+        assert_eq!(*sync_parameters.gap_limit(), 10);
+        assert!(!sync_parameters.balance_only());
+
+        let synced = account_handle.sync().await.gap_limit(20).execute().await.unwrap();
+        assert_eq!(*synced.sync_parameters().unwrap().gap_limit(), 20);
+    }
+
+    #[tokio::test]
+    async fn balance_only_sync() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1500000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .register()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = account_handle.sync().await.balance_only().execute().await.unwrap();
+
+        // the balance is refreshed but the outputs aren't fetched
+        assert!(synced.messages().is_empty());
+        let account = account_handle.read().await;
+        assert_eq!(account.addresses().len(), 1);
+        let synced_address = &account.addresses()[0];
+        assert_eq!(*synced_address.balance(), 1500000);
+        assert!(synced_address.outputs().is_empty());
+        assert!(account.last_synced_at().is_none());
+    }
+
+    #[tokio::test]
+    async fn sync_saves_the_account_once() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        struct CountingAdapter {
+            account_id: String,
+            writes: Arc<AtomicUsize>,
+        }
+        #[async_trait::async_trait]
+        impl crate::storage::StorageAdapter for CountingAdapter {
+            async fn get(&self, _key: &str) -> crate::Result<String> {
+                Err(crate::Error::RecordNotFound)
+            }
+            async fn set(&mut self, key: &str, _record: String) -> crate::Result<()> {
+                if key == self.account_id {
+                    self.writes.fetch_add(1, Ordering::SeqCst);
+                }
+                Ok(())
+            }
+            async fn remove(&mut self, _key: &str) -> crate::Result<()> {
+                Ok(())
+            }
+        }
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(crate::test_utils::generate_address_output(
+                address.address(),
+                1500000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            ))
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+
+        let writes = Arc::new(AtomicUsize::new(0));
+        crate::storage::set(
+            manager.storage_path(),
+            None,
+            Box::new(CountingAdapter {
+                account_id: account_handle.id().await,
+                writes: writes.clone(),
+            }),
+        )
+        .await;
+
+        // the new addresses, outputs and messages found by the sync are written at once
+        account_handle.sync().await.execute().await.unwrap();
+        assert_eq!(writes.load(Ordering::SeqCst), 1);
+        assert!(!account_handle.read().await.dirty());
+
+        // nothing changed, so the balance only sync doesn't write the account
+        account_handle.sync().await.balance_only().execute().await.unwrap();
+        assert_eq!(writes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sync_retry_policy() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let policy = crate::account_manager::SyncRetryPolicy {
+            max_retries: 2,
+            base_delay: std::time::Duration::from_millis(1),
+            jitter: std::time::Duration::from_millis(1),
+        };
+        let attempts = AtomicU32::new(0);
+        let attempts_ = &attempts;
+        let request = |status: u16, failures: u32| {
+            move || async move {
+                let attempt = attempts_.fetch_add(1, Ordering::SeqCst);
+                if attempt < failures {
+                    Err(crate::Error::from(iota::client::Error::ResponseError(
+                        status,
+                        format!("error {}", attempt),
+                    )))
+                } else {
+                    Ok(())
+                }
+            }
+        };
+
+        // transient server errors are retried
+        assert!(super::with_retries(policy, request(502, 2)).await.is_ok());
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+        // the first error is returned once the retries are exhausted
+        match &super::with_retries(policy, request(503, 5)).await {
+            Err(crate::Error::ClientError(error)) => match error.as_ref() {
+                iota::client::Error::ResponseError(503, message) => assert_eq!(message, "error 0"),
+                _ => panic!("unexpected error"),
+            },
+            _ => panic!("unexpected response"),
+        }
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+        // client errors aren't retried
+        assert!(super::with_retries(policy, request(404, 1)).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // the delay doubles on each retry, up to the maximum delay
+        assert_eq!(super::retry_delay(policy, 3), std::time::Duration::from_millis(8));
+        let policy = crate::account_manager::SyncRetryPolicy {
+            max_retries: 40,
+            base_delay: std::time::Duration::from_secs(u64::MAX),
+            jitter: std::time::Duration::from_millis(0),
+        };
+        assert_eq!(super::retry_delay(policy, 0), super::MAX_RETRY_DELAY);
+        assert_eq!(super::retry_delay(policy, 39), super::MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn address_outputs_over_the_page_limit() {
+        let address = crate::test_utils::generate_random_iota_address();
+        let mut mock = crate::test_utils::MockNodeClient::default();
+        for index in 0..1000 {
+            let mut output = crate::test_utils::generate_address_output(
+                &address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+            // the unspent outputs aren't the last ones, so the duplicates aren't consecutive
+            output.is_spent = index % 2 == 0;
+            mock = mock.with_output(output);
+        }
+        let client_options = mock.register().await;
+        let client = crate::client::get_node_client(&client_options, None).await.unwrap();
+
+        let bech32_address: iota::Bech32Address = address.to_bech32().into();
+        let address_outputs = super::get_address_outputs(
+            &bech32_address,
+            &*client,
+            true,
+            crate::account_manager::SyncRetryPolicy::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address_outputs.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn failed_address_sync() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let failing_address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1500000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .with_unavailable_address(failing_address.address().to_bech32())
+            .register()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone(), failing_address.clone()])
+            .create()
+            .await;
+        let synced = account_handle
+            .sync()
+            .await
+            .steps(vec![super::AccountSynchronizeStep::SyncAddresses(Some(vec![
+                address.address().clone(),
+                failing_address.address().clone(),
+            ]))])
+            .execute()
+            .await
+            .unwrap();
+
+        // the failing address is reported and the other address is still synced
+        assert_eq!(synced.failed_addresses().len(), 1);
+        assert_eq!(
+            synced.failed_addresses()[0].address(),
+            &failing_address.address().to_bech32()
+        );
+        let account = account_handle.read().await;
+        let synced_address = account.addresses().iter().find(|a| a == &&address).unwrap();
+        assert_eq!(*synced_address.balance(), 1500000);
+    }
+
+    #[tokio::test]
+    async fn failed_address_sync_window_isnt_empty() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let failing_address = crate::test_utils::generate_random_address();
+        let mut next_address = crate::test_utils::generate_random_address();
+        next_address.set_key_index(1);
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_unavailable_address(failing_address.address().to_bech32())
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![failing_address.clone(), next_address.clone()])
+            .create()
+            .await;
+
+        let account = account_handle.read().await;
+        let (addresses, _, _, failed_addresses) =
+            super::sync_addresses(&account, 0, 1, account_handle.account_options, Default::default())
+                .await
+                .unwrap();
+        assert_eq!(failed_addresses.len(), 1);
+        // the failed address might have funds, so the scan goes on to the next window
+        assert!(addresses.iter().any(|a| a.address() == next_address.address()));
+    }
+
+    #[tokio::test]
+    async fn failed_new_address_keeps_the_key_index() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 1000000, crate::address::OutputKind::SignatureLockedSingle);
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        // the address at key index 1 fails to sync and the one at key index 2 has funds
+        let mut new_addresses = Vec::new();
+        {
+            let account = account_handle.read().await;
+            for key_index in 1..3 {
+                new_addresses.push(
+                    crate::address::get_iota_address(
+                        &account,
+                        key_index,
+                        false,
+                        address.address().bech32_hrp().to_string(),
+                        crate::signing::GenerateAddressMetadata { syncing: true },
+                    )
+                    .await
+                    .unwrap(),
+                );
+            }
+        }
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(crate::test_utils::generate_address_output(
+                address.address(),
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            ))
+            .with_output(crate::test_utils::generate_address_output(
+                &new_addresses[1],
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            ))
+            .with_unavailable_address(new_addresses[0].to_bech32())
+            .register()
+            .await;
+        account_handle.write().await.client_options = client_options;
+
+        let synced = account_handle.sync().await.execute().await.unwrap();
+        assert_eq!(synced.failed_addresses().len(), 1);
+        assert!(account_handle
+            .read()
+            .await
+            .addresses()
+            .iter()
+            .any(|a| a.address() == &new_addresses[0]));
+
+        // the failed address keeps its key index, so the generated address doesn't reuse one
+        let generated_address = account_handle.generate_address().await.unwrap();
+        let account = account_handle.read().await;
+        assert_eq!(
+            account
+                .addresses()
+                .iter()
+                .filter(|a| !a.internal() && a.key_index() == generated_address.key_index())
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn suppress_sync_events() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1500000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output.clone())
+            .register()
+            .await;
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let account_id = account_handle.id().await;
+
+        let balance_events = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let balance_events_ = balance_events.clone();
+        let listener_id = crate::event::on_balance_change(move |event| {
+            if event.account_id == account_id {
+                *balance_events_.lock().unwrap() += 1;
+            }
+        })
+        .await;
+
+        let synced = account_handle
+            .sync()
+            .await
+            .suppress_balance_change_events()
+            .execute()
+            .await
+            .unwrap();
+        crate::event::remove_balance_change_listener(&listener_id).await;
+
+        // the balance is synced but no event is emitted
+        assert_eq!(*balance_events.lock().unwrap(), 0);
+        assert!(synced.addresses().iter().any(|a| a == &address));
+        let account = account_handle.read().await;
+        let synced_address = account.addresses().iter().find(|a| a == &&address).unwrap();
+        assert_eq!(*synced_address.balance(), 1500000);
+    }
+
+    #[tokio::test]
+    async fn dust_transfer() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        // first we create an address with balance - the source address
+        let mut address1 = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(
+            &mut address1,
+            10000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+
+        // then we create an address without balance - the deposit address
+        let address2 = crate::test_utils::generate_random_address();
+
+        // and the change address receiving the remainder, without a dust allowance output
+        let mut address3 = crate::test_utils::generate_random_address();
+        address3.set_key_index(0);
+        address3.set_internal(true);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address1, address2.clone(), address3.clone()])
+            .create()
+            .await;
+        let id = account_handle.id().await;
         let index = account_handle.index().await;
         let synced = super::SyncedAccount {
             id,
@@ -1858,6 +3955,9 @@ mod tests {
             is_empty: false,
             messages: Vec::new(),
             addresses: Vec::new(),
+            suggested_gap_limit: None,
+            failed_addresses: Vec::new(),
+            sync_parameters: None,
         };
         let res = synced
             .transfer(
@@ -1865,10 +3965,1380 @@ mod tests {
                     .finish(),
             )
             .await;
-        assert_eq!(res.is_err(), true);
-        match res.unwrap_err() {
-            crate::Error::DustError(_) => {}
+        match &res {
+            Err(crate::Error::DustOutputNotAllowed { address, amount }) => {
+                assert_eq!(address, &address3.address().to_bech32());
+                assert_eq!(*amount, 500);
+            }
             _ => panic!("unexpected response"),
         }
     }
+
+    #[tokio::test]
+    async fn address_sync_order() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address1 = crate::test_utils::generate_random_address();
+        address1.set_balance(1);
+        let mut address2 = crate::test_utils::generate_random_address();
+        address2.set_balance(5);
+        let address3 = crate::test_utils::generate_random_address();
+        let message = crate::test_utils::GenerateMessageBuilder::default()
+            .address(address3.clone())
+            .build()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address1.clone(), address2.clone(), address3.clone()])
+            .messages(vec![message])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+
+        let mut addresses = vec![address1.clone(), address2.clone(), address3.clone()];
+        super::sort_addresses_for_sync(&account, &mut addresses, super::AddressSyncOrder::Storage);
+        assert_eq!(addresses, vec![address1.clone(), address2.clone(), address3.clone()]);
+
+        super::sort_addresses_for_sync(&account, &mut addresses, super::AddressSyncOrder::Balance);
+        assert_eq!(addresses, vec![address2.clone(), address1.clone(), address3.clone()]);
+
+        super::sort_addresses_for_sync(&account, &mut addresses, super::AddressSyncOrder::RecentlyActive);
+        assert_eq!(addresses[0], address3);
+    }
+
+    #[tokio::test]
+    async fn address_sync_order_starts_first() {
+        // one address more than the sync concurrency, with the highest balance on the last stored address
+        let addresses: Vec<crate::address::Address> = (0..=super::ADDRESS_SYNC_CONCURRENCY)
+            .map(|index| {
+                let mut address = crate::test_utils::generate_random_address();
+                address.set_key_index(index);
+                address.set_balance(index as u64);
+                address
+            })
+            .collect();
+        let busiest_address = addresses.last().unwrap().address().to_bech32();
+
+        for order in vec![super::AddressSyncOrder::Storage, super::AddressSyncOrder::Balance] {
+            let manager = crate::test_utils::get_account_manager().await;
+            let tracker = std::sync::Arc::new(crate::test_utils::AddressRequestTracker::default());
+            let client_options = crate::test_utils::MockNodeClient::default()
+                .with_address_request_tracker(tracker.clone(), std::time::Duration::from_millis(50))
+                .register()
+                .await;
+            let account_handle = crate::test_utils::AccountCreator::new(&manager)
+                .client_options(client_options)
+                .addresses(addresses.clone())
+                .create()
+                .await;
+            let mut options = account_handle.account_options;
+            options.address_sync_order = order;
+
+            super::sync_messages(&*account_handle.read().await, &[], options)
+                .await
+                .unwrap();
+            let position = tracker
+                .requested_addresses()
+                .iter()
+                .position(|address| address == &busiest_address)
+                .unwrap();
+            match order {
+                // the last stored address waits for a free sync slot
+                super::AddressSyncOrder::Storage => assert_eq!(position, super::ADDRESS_SYNC_CONCURRENCY),
+                _ => assert!(position < super::ADDRESS_SYNC_CONCURRENCY),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_address_list_order() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let addresses: Vec<crate::address::Address> = [2, 0, 1]
+            .iter()
+            .flat_map(|key_index| {
+                vec![false, true].into_iter().map(move |internal| {
+                    crate::address::AddressBuilder::new()
+                        .key_index(*key_index)
+                        .internal(internal)
+                        .address(crate::test_utils::generate_random_iota_address())
+                        .balance(0)
+                        .outputs(Vec::new())
+                        .build()
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let (found_addresses, _, _) = super::sync_address_list(
+            addresses,
+            Vec::new(),
+            Default::default(),
+            account_handle.account_options,
+            client_options,
+        )
+        .await
+        .unwrap();
+        // change addresses without outputs are ignored
+        assert_eq!(
+            found_addresses
+                .iter()
+                .map(|address| (*address.key_index(), *address.internal()))
+                .collect::<Vec<(usize, bool)>>(),
+            vec![(0, false), (1, false), (2, false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn consolidation_target() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        for _ in 0..2 {
+            crate::test_utils::add_address_output(
+                &mut address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+        }
+        let mut target = crate::test_utils::generate_random_address();
+        target.set_key_index(1);
+
+        let mut account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone(), target.clone()])
+            .create()
+            .await;
+        account_handle.account_options.output_consolidation_threshold = 2;
+        let synced = super::SyncedAccount::from(account_handle).await;
+
+        let transfers = synced
+            .get_output_consolidation_transfers(Some(target.address()))
+            .await
+            .unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(&transfers[0].address, target.address());
+        assert_eq!(transfers[0].amount.get(), 2000000);
+        assert_eq!(
+            transfers[0].input.as_ref().map(|(address, _)| address),
+            Some(address.address())
+        );
+
+        let res = synced
+            .get_output_consolidation_transfers(Some(&crate::test_utils::generate_random_iota_address()))
+            .await;
+        assert!(matches!(res, Err(crate::Error::InvalidConsolidationTargetAddress)));
+    }
+
+    #[tokio::test]
+    async fn consolidation_plan() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        for _ in 0..3 {
+            crate::test_utils::add_address_output(
+                &mut address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+        }
+
+        // the consolidated address is the latest address, so a new one is generated
+        let mut account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        account_handle.account_options.output_consolidation_threshold = 2;
+        let plan = super::SyncedAccount::from(account_handle.clone())
+            .await
+            .consolidation_plan()
+            .await;
+        assert_eq!(*plan.transaction_count(), 1);
+        assert_eq!(plan.inputs_per_transaction(), &vec![3]);
+        assert_eq!(*plan.new_addresses(), 1);
+
+        let mut latest_address = crate::test_utils::generate_random_address();
+        latest_address.set_key_index(1);
+        account_handle.write().await.append_addresses(vec![latest_address]);
+        let plan = super::SyncedAccount::from(account_handle)
+            .await
+            .consolidation_plan()
+            .await;
+        assert_eq!(*plan.transaction_count(), 1);
+        assert_eq!(*plan.new_addresses(), 0);
+    }
+
+    #[tokio::test]
+    async fn consolidation_plan_change_addresses() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(
+            &mut address,
+            3000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let mut latest_address = crate::test_utils::generate_random_address();
+        latest_address.set_key_index(1);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone(), latest_address])
+            .create()
+            .await;
+        let transfers =
+            vec![
+                super::Transfer::builder(address.address().clone(), std::num::NonZeroU64::new(1000000).unwrap())
+                    .with_input(address.address().clone(), vec![output])
+                    .with_remainder_value_strategy(super::RemainderValueStrategy::ChangeAddress)
+                    .finish(),
+            ];
+
+        // the remainder is sent to a new change address
+        assert_eq!(
+            super::consolidation_new_addresses(&*account_handle.read().await, &transfers),
+            1
+        );
+
+        // the remainder reuses the account change address
+        let mut change_address = crate::test_utils::generate_random_address();
+        change_address.set_internal(true);
+        account_handle.write().await.append_addresses(vec![change_address]);
+        assert_eq!(
+            super::consolidation_new_addresses(&*account_handle.read().await, &transfers),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn consolidation_reserve_per_address() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        for _ in 0..3 {
+            crate::test_utils::add_address_output(
+                &mut address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+        }
+        let mut target = crate::test_utils::generate_random_address();
+        target.set_key_index(1);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let mut account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone(), target.clone()])
+            .create()
+            .await;
+        account_handle.account_options.output_consolidation_threshold = 2;
+        // below the dust threshold, so it's raised to 1 Mi
+        account_handle.account_options.consolidation_reserve_per_address = Some(500000);
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+
+        let transfers = synced
+            .get_output_consolidation_transfers(Some(target.address()))
+            .await
+            .unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].amount.get(), 2000000);
+        // the reserve stays on the source address
+        let prepared = synced.prepare_transfer(transfers[0].clone()).await.unwrap();
+        assert_eq!(*prepared.remainder_value(), 1000000);
+        assert_eq!(
+            prepared.remainder_deposit_address(),
+            &Some(address.address().to_bech32())
+        );
+
+        // the reserve doesn't apply when consolidating to the source address itself
+        let transfers = synced.get_output_consolidation_transfers(None).await.unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].amount.get(), 3000000);
+
+        // the remaining value would be dust, so nothing is consolidated
+        account_handle.account_options.consolidation_reserve_per_address = Some(2500000);
+        let synced = super::SyncedAccount::from(account_handle).await;
+        let transfers = synced
+            .get_output_consolidation_transfers(Some(target.address()))
+            .await
+            .unwrap();
+        assert!(transfers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn can_spend() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 2000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let deposit_address = crate::test_utils::generate_random_iota_address();
+        let amount = std::num::NonZeroU64::new(1000000).unwrap();
+
+        assert!(synced.can_spend(amount, &deposit_address).await);
+        assert!(
+            !synced
+                .can_spend(std::num::NonZeroU64::new(3000000).unwrap(), &deposit_address)
+                .await
+        );
+        // the only output is on the deposit address
+        assert!(!synced.can_spend(amount, address.address()).await);
+
+        account_handle
+            .locked_addresses
+            .lock()
+            .await
+            .push(address.address().clone());
+        assert!(!synced.can_spend(amount, &deposit_address).await);
+    }
+
+    #[tokio::test]
+    async fn remote_pow_fallback() {
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://remote-pow-fallback.mock")
+            .unwrap()
+            .with_network("testnet")
+            .with_node_sync_disabled()
+            .with_local_pow_timeout(std::time::Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let local_client = std::sync::Arc::new(
+            crate::test_utils::MockNodeClient::default().with_pow_delay(std::time::Duration::from_secs(1)),
+        );
+        crate::client::set_node_client(client_options.clone(), local_client.clone()).await;
+        crate::client::set_node_client(
+            client_options.remote_pow_options(),
+            std::sync::Arc::new(crate::test_utils::MockNodeClient::default()),
+        )
+        .await;
+
+        let transfer_obj = super::Transfer::builder(
+            crate::test_utils::generate_random_iota_address(),
+            std::num::NonZeroU64::new(1000000).unwrap(),
+        )
+        .finish();
+        let payload = super::Payload::Indexation(Box::new(iota::IndexationPayload::new(b"index", &[]).unwrap()));
+        // the local PoW exceeds its budget, so the message is built by the remote PoW client
+        let message = super::finish_transfer_pow(
+            local_client,
+            &client_options,
+            Default::default(),
+            &transfer_obj,
+            "account",
+            payload.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(message.payload(), &Some(payload));
+
+        // the abandoned local PoW is still running, so the next transfer goes straight to the remote PoW
+        let started_at = std::time::Instant::now();
+        super::finish_transfer_pow(
+            crate::client::get_node_client(&client_options, None).await.unwrap(),
+            &client_options,
+            Default::default(),
+            &transfer_obj,
+            "account",
+            payload,
+        )
+        .await
+        .unwrap();
+        assert!(started_at.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn estimate_confirmation_time() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let metrics = crate::client::NodeMetrics {
+            is_healthy: true,
+            milestone_interval: Some(std::time::Duration::from_secs(10)),
+            messages_per_second: 20.0,
+            referenced_messages_per_second: 10.0,
+            referenced_rate: 50.0,
+        };
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_node_metrics(metrics)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle).await;
+        let estimate = synced.estimate_confirmation_time().await.unwrap().unwrap();
+        // half of the messages are referenced, so a message waits two milestones on average
+        assert_eq!(estimate.time(), &std::time::Duration::from_secs(20));
+        assert_eq!(estimate.confidence(), &super::EstimateConfidence::Medium);
+
+        // the estimate is unknown without the milestone interval
+        assert!(
+            super::ConfirmationTimeEstimate::from_node_metrics(&crate::client::NodeMetrics {
+                milestone_interval: None,
+                ..metrics
+            })
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn locked_address_with_different_hrp() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 2000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let account = account_handle.read().await;
+        let deposit_address = crate::test_utils::generate_random_iota_address();
+        let transfer =
+            super::Transfer::builder(deposit_address.clone(), std::num::NonZeroU64::new(1000000).unwrap()).finish();
+
+        // the same address, locked with another bech32 HRP
+        let locked_address = crate::address::AddressWrapper::new(*address.address().as_ref(), "iota".to_string());
+        assert_ne!(&locked_address, address.address());
+        let res = synced.find_inputs(
+            &[locked_address],
+            &transfer,
+            &account,
+            account.addresses(),
+            &deposit_address,
+        );
+        assert!(matches!(res, Err(crate::Error::InsufficientFunds)));
+
+        let res = synced.find_inputs(&[], &transfer, &account, account.addresses(), &deposit_address);
+        assert_eq!(res.unwrap().0.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn transfer_validation() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 2000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let mut allowance_address = crate::test_utils::generate_random_address();
+        allowance_address.set_key_index(1);
+        crate::test_utils::add_address_output(
+            &mut allowance_address,
+            1000000,
+            crate::address::OutputKind::SignatureLockedDustAllowance,
+        );
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone(), allowance_address.clone()])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+
+        // every problem is reported at once
+        let other_network_address = crate::address::AddressWrapper::new(
+            *crate::test_utils::generate_random_iota_address().as_ref(),
+            "iota".to_string(),
+        );
+        let res = super::Transfer::builder(other_network_address, std::num::NonZeroU64::new(500).unwrap())
+            .with_remainder_value_strategy(super::RemainderValueStrategy::AccountAddressIndex {
+                key_index: 5,
+                internal: false,
+            })
+            .with_max_inputs(0)
+            .finish_validated(&account)
+            .await;
+        match &res {
+            Err(crate::Error::InvalidTransfer(errors)) => assert_eq!(
+                errors,
+                &vec![
+                    crate::message::TransferValidationError::AddressNetworkMismatch {
+                        expected: "atoi".to_string(),
+                        found: "iota".to_string(),
+                    },
+                    crate::message::TransferValidationError::DustAmount { amount: 500 },
+                    crate::message::TransferValidationError::InvalidRemainderValueAddress,
+                    crate::message::TransferValidationError::InvalidMaxInputs,
+                ]
+            ),
+            _ => panic!("unexpected response"),
+        }
+
+        let deposit_address = crate::test_utils::generate_random_iota_address();
+        let res = super::Transfer::builder(deposit_address.clone(), std::num::NonZeroU64::new(4000000).unwrap())
+            .finish_validated(&account)
+            .await;
+        match &res {
+            Err(crate::Error::InvalidTransfer(errors)) => assert_eq!(
+                errors,
+                &vec![crate::message::TransferValidationError::InsufficientFunds {
+                    amount: 4000000,
+                    available: 3000000,
+                }]
+            ),
+            _ => panic!("unexpected response"),
+        }
+
+        let res = super::Transfer::builder(deposit_address, std::num::NonZeroU64::new(1000000).unwrap())
+            .finish_validated(&account)
+            .await;
+        assert!(res.is_ok());
+
+        // the dust output is covered by the dust allowance output of the recipient
+        let res = super::Transfer::builder(
+            allowance_address.address().clone(),
+            std::num::NonZeroU64::new(500).unwrap(),
+        )
+        .finish_validated(&account)
+        .await;
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn deposit_address_input_policy() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut single_output_address = crate::test_utils::generate_random_address();
+        let mut multi_output_address = crate::test_utils::generate_random_address();
+        multi_output_address.set_key_index(1);
+        crate::test_utils::add_address_output(
+            &mut single_output_address,
+            1000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        for _ in 0..2 {
+            crate::test_utils::add_address_output(
+                &mut multi_output_address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+        }
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![single_output_address.clone(), multi_output_address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let account = account_handle.read().await;
+        let amount = std::num::NonZeroU64::new(2000000).unwrap();
+
+        for policy in &[
+            crate::message::DepositAddressInputPolicy::Lenient,
+            crate::message::DepositAddressInputPolicy::Strict,
+        ] {
+            // the single output deposit address is never used as input
+            let deposit_address = single_output_address.address();
+            let transfer = crate::message::Transfer::builder(deposit_address.clone(), amount)
+                .with_deposit_address_input_policy(*policy)
+                .finish();
+            let (inputs, _) = synced
+                .find_inputs(&[], &transfer, &account, account.addresses(), deposit_address)
+                .unwrap();
+            assert!(inputs.iter().all(|input| &input.address != deposit_address));
+
+            // the multi output deposit address is only used as input on the lenient policy
+            let deposit_address = multi_output_address.address();
+            let transfer = crate::message::Transfer::builder(deposit_address.clone(), amount)
+                .with_deposit_address_input_policy(*policy)
+                .finish();
+            let res = synced.find_inputs(&[], &transfer, &account, account.addresses(), deposit_address);
+            match policy {
+                crate::message::DepositAddressInputPolicy::Lenient => {
+                    let (inputs, _) = res.unwrap();
+                    assert!(inputs.iter().any(|input| &input.address == deposit_address));
+                }
+                crate::message::DepositAddressInputPolicy::Strict => {
+                    assert!(matches!(res, Err(crate::Error::InsufficientFunds)));
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn max_inputs() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        for _ in 0..3 {
+            crate::test_utils::add_address_output(
+                &mut address,
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+        }
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let account = account_handle.read().await;
+        let deposit_address = crate::test_utils::generate_random_iota_address();
+        let amount = std::num::NonZeroU64::new(3000000).unwrap();
+
+        let transfer = crate::message::Transfer::builder(deposit_address.clone(), amount)
+            .with_max_inputs(2)
+            .finish();
+        let res = synced.find_inputs(&[], &transfer, &account, account.addresses(), &deposit_address);
+        assert!(matches!(
+            res,
+            Err(crate::Error::TooManyInputsRequired { needed: 3, max: 2 })
+        ));
+
+        let transfer = crate::message::Transfer::builder(deposit_address.clone(), amount)
+            .with_max_inputs(3)
+            .finish();
+        assert!(synced
+            .find_inputs(&[], &transfer, &account, account.addresses(), &deposit_address)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn auto_dust_allowance_not_funded() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 2000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+        let id = account_handle.id().await;
+        let index = account_handle.index().await;
+        let synced = super::SyncedAccount {
+            id,
+            index,
+            account_handle,
+            deposit_address: crate::test_utils::generate_random_address(),
+            is_empty: false,
+            messages: Vec::new(),
+            addresses: Vec::new(),
+            suggested_gap_limit: None,
+            failed_addresses: Vec::new(),
+            sync_parameters: None,
+        };
+        let deposit_address = crate::test_utils::generate_random_iota_address();
+
+        let res = synced
+            .transfer(
+                super::Transfer::builder(deposit_address.clone(), std::num::NonZeroU64::new(500000).unwrap()).finish(),
+            )
+            .await;
+        match res {
+            Err(crate::Error::DustOutputNotAllowed { .. }) => {}
+            _ => panic!("unexpected response"),
+        }
+
+        // the dust output goes to an address outside the account, so no dust allowance output is created for it
+        let res = synced
+            .transfer(
+                super::Transfer::builder(deposit_address.clone(), std::num::NonZeroU64::new(500000).unwrap())
+                    .with_auto_dust_allowance(true)
+                    .finish(),
+            )
+            .await;
+        match &res {
+            Err(crate::Error::DustAllowanceNotFunded(address)) => assert_eq!(address, &deposit_address.to_bech32()),
+            _ => panic!("unexpected response"),
+        }
+
+        // the 500000i remainder is dust, and the dust allowance output on its change address can't be funded either
+        let res = synced
+            .transfer(
+                super::Transfer::builder(deposit_address.clone(), std::num::NonZeroU64::new(1500000).unwrap())
+                    .with_auto_dust_allowance(true)
+                    .finish(),
+            )
+            .await;
+        match &res {
+            Err(crate::Error::DustAllowanceNotFunded(address)) => {
+                let account = synced.account_handle().read().await;
+                let change_address = account
+                    .addresses()
+                    .iter()
+                    .find(|a| &a.address().to_bech32() == address)
+                    .unwrap();
+                assert!(*change_address.internal());
+            }
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn remainder_dust_covered_by_allowance() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(
+            &mut address,
+            1000000,
+            crate::address::OutputKind::SignatureLockedDustAllowance,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let client = crate::client::get_node_client(&client_options, None).await.unwrap();
+
+        let remainder_output = iota::SignatureLockedSingleOutput::new(*address.address().as_ref(), 500)
+            .unwrap()
+            .into();
+        let res = super::find_dust_warnings(&*account_handle.read().await, &*client, &[], &[remainder_output]).await;
+        assert_eq!(res.unwrap(), Vec::new());
+    }
+
+    #[tokio::test]
+    async fn remainder_dust_with_consumed_allowance() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let allowance_output = crate::test_utils::add_address_output(
+            &mut address,
+            1000000,
+            crate::address::OutputKind::SignatureLockedDustAllowance,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let client = crate::client::get_node_client(&client_options, None).await.unwrap();
+
+        // the allowance output is consumed and the remainder value is kept on the same address
+        let remainder_output = iota::SignatureLockedSingleOutput::new(*address.address().as_ref(), 500)
+            .unwrap()
+            .into();
+        let res = super::find_dust_warnings(
+            &*account_handle.read().await,
+            &*client,
+            &[allowance_output],
+            &[remainder_output],
+        )
+        .await
+        .and_then(|dust_warnings| super::check_dust_warnings(&dust_warnings));
+        match &res {
+            Err(crate::Error::DustOutputNotAllowed {
+                address: dust_address,
+                amount,
+            }) => {
+                assert_eq!(dust_address, &address.address().to_bech32());
+                assert_eq!(*amount, 500);
+            }
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn possibly_missed_funds() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        // (used address index, suggested gap limit)
+        for (used_index, suggested_gap_limit) in &[(1, Some(4)), (0, None)] {
+            let mut addresses = Vec::new();
+            for key_index in 0..2 {
+                let mut address = crate::test_utils::generate_random_address();
+                address.set_key_index(key_index);
+                addresses.push(address);
+            }
+            let output = crate::test_utils::generate_address_output(
+                addresses[*used_index].address(),
+                1000000,
+                crate::address::OutputKind::SignatureLockedSingle,
+            );
+            let client_options = crate::test_utils::MockNodeClient::default()
+                .with_output(output)
+                .register()
+                .await;
+            let account_handle = crate::test_utils::AccountCreator::new(&manager)
+                .client_options(client_options)
+                .addresses(addresses)
+                .create()
+                .await;
+
+            // the first window has funds, so the second one is scanned and found empty
+            let account = account_handle.read().await;
+            let (_, _, gap_limit, _) =
+                super::sync_addresses(&account, 0, 2, account_handle.account_options, Default::default())
+                    .await
+                    .unwrap();
+            assert_eq!(&gap_limit, suggested_gap_limit);
+            drop(account);
+
+            let synced = account_handle
+                .sync()
+                .await
+                .address_index(0)
+                .gap_limit(2)
+                .execute()
+                .await
+                .unwrap();
+            assert_eq!(synced.possibly_missed_funds(), suggested_gap_limit.is_some());
+            assert_eq!(synced.suggested_gap_limit(), suggested_gap_limit);
+        }
+    }
+
+    #[tokio::test]
+    async fn balance_change_event_emitted_once_per_state() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        address.set_balance(100);
+        let addresses_before_sync = vec![(address.address().to_bech32(), 0, std::collections::HashMap::new())];
+        let addresses = vec![address.clone()];
+
+        for expected_events in &[1, 0] {
+            let events = super::AccountSynchronizer::get_events(
+                account_handle.account_options,
+                &account_handle.emitted_balance_states,
+                &addresses_before_sync,
+                &addresses,
+                &[],
+                &[],
+            )
+            .await
+            .unwrap();
+            assert_eq!(events.balance_change_events.len(), *expected_events);
+        }
+
+        // a different state is notified again
+        address.set_balance(50);
+        let events = super::AccountSynchronizer::get_events(
+            account_handle.account_options,
+            &account_handle.emitted_balance_states,
+            &addresses_before_sync,
+            &[address],
+            &[],
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(events.balance_change_events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn essence_dump() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(
+            &mut address,
+            3000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+
+        let dump = synced
+            .essence_dump(
+                super::Transfer::builder(
+                    crate::test_utils::generate_random_iota_address(),
+                    std::num::NonZeroU64::new(1000000).unwrap(),
+                )
+                .with_remainder_value_strategy(super::RemainderValueStrategy::ReuseAddress)
+                .finish(),
+            )
+            .await
+            .unwrap();
+        assert!(!dump.bytes().is_empty());
+        assert_eq!(dump.inputs(), &vec![output]);
+        assert_eq!(dump.outputs().len(), 2);
+        let remainder_output = dump.outputs().iter().find(|o| o.is_remainder()).unwrap();
+        assert_eq!(remainder_output.address(), Some(address.address()));
+        assert_eq!(remainder_output.amount(), 2000000);
+
+        // the input address is unlocked and nothing is sent
+        assert!(account_handle.locked_addresses.lock().await.is_empty());
+        assert!(account_handle.read().await.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn prepare_transfer() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(
+            &mut address,
+            3000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+
+        // the 500i remainder kept on the input address isn't covered by a dust allowance
+        let prepared = synced
+            .prepare_transfer(
+                super::Transfer::builder(
+                    crate::test_utils::generate_random_iota_address(),
+                    std::num::NonZeroU64::new(2999500).unwrap(),
+                )
+                .with_remainder_value_strategy(super::RemainderValueStrategy::ReuseAddress)
+                .finish(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(prepared.inputs(), &vec![output]);
+        assert_eq!(prepared.outputs().len(), 2);
+        assert_eq!(*prepared.remainder_value(), 500);
+        assert_eq!(
+            prepared.remainder_deposit_address(),
+            &Some(address.address().to_bech32())
+        );
+        assert_eq!(
+            prepared.dust_warnings(),
+            &vec![super::DustWarning {
+                address: address.address().to_bech32(),
+                amount: Some(500),
+            }]
+        );
+
+        // the input address is unlocked and nothing is sent
+        assert!(account_handle.locked_addresses.lock().await.is_empty());
+        assert!(account_handle.read().await.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn imported_address_isnt_an_input() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        address.set_imported(true);
+        crate::test_utils::add_address_output(&mut address, 3000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+        assert_eq!(account_handle.balance().await.total, 0);
+        let synced = super::SyncedAccount::from(account_handle).await;
+
+        // the imported address is watch-only, so its funds can't be spent
+        let res = synced
+            .prepare_transfer(
+                super::Transfer::builder(
+                    crate::test_utils::generate_random_iota_address(),
+                    std::num::NonZeroU64::new(1000000).unwrap(),
+                )
+                .finish(),
+            )
+            .await;
+        assert!(matches!(res, Err(crate::Error::InsufficientFunds)));
+    }
+
+    #[tokio::test]
+    async fn remainder_to_consumed_input() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 3000000, crate::address::OutputKind::SignatureLockedSingle);
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let transfer = || {
+            super::Transfer::builder(
+                crate::test_utils::generate_random_iota_address(),
+                std::num::NonZeroU64::new(1000000).unwrap(),
+            )
+            .with_remainder_value_strategy(super::RemainderValueStrategy::ReuseAddress)
+        };
+
+        // the only output of the input address is consumed, so the remainder re-funds it
+        let prepared = synced.prepare_transfer(transfer().finish()).await.unwrap();
+        assert!(*prepared.remainder_to_consumed_input());
+        assert_eq!(
+            prepared.remainder_deposit_address(),
+            &Some(address.address().to_bech32())
+        );
+
+        let prepared = synced
+            .prepare_transfer(transfer().with_reroute_consumed_input_remainder(true).finish())
+            .await
+            .unwrap();
+        assert!(!*prepared.remainder_to_consumed_input());
+        assert_eq!(*prepared.remainder_value(), 2000000);
+        assert_ne!(
+            prepared.remainder_deposit_address(),
+            &Some(address.address().to_bech32())
+        );
+    }
+
+    #[tokio::test]
+    async fn transfer_with_multiple_outputs() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::add_address_output(
+            &mut address,
+            5000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+
+        let recipients = vec![
+            (
+                crate::test_utils::generate_random_iota_address(),
+                std::num::NonZeroU64::new(1000000).unwrap(),
+            ),
+            (
+                crate::test_utils::generate_random_iota_address(),
+                std::num::NonZeroU64::new(2000000).unwrap(),
+            ),
+        ];
+        let dump = synced
+            .essence_dump(
+                super::Transfer::builder_with_outputs(recipients.clone())
+                    .unwrap()
+                    .with_remainder_value_strategy(super::RemainderValueStrategy::ReuseAddress)
+                    .finish(),
+            )
+            .await
+            .unwrap();
+        // a single transaction sends to every recipient, with the remainder of the output sum
+        assert_eq!(dump.inputs(), &vec![output]);
+        assert_eq!(dump.outputs().len(), 3);
+        for (recipient, amount) in &recipients {
+            assert!(dump
+                .outputs()
+                .iter()
+                .any(|o| !o.is_remainder() && o.address() == Some(recipient) && o.amount() == amount.get()));
+        }
+        let remainder_output = dump.outputs().iter().find(|o| o.is_remainder()).unwrap();
+        assert_eq!(remainder_output.amount(), 2000000);
+
+        assert!(matches!(
+            super::Transfer::builder_with_outputs(Vec::new()),
+            Err(crate::Error::InvalidTransfer(_))
+        ));
+
+        // an address can only receive one output
+        let duplicated_recipients = vec![recipients[0].clone(), recipients[1].clone(), recipients[0].clone()];
+        match &super::Transfer::builder_with_outputs(duplicated_recipients) {
+            Err(crate::Error::InvalidTransfer(errors)) => assert_eq!(
+                errors,
+                &vec![crate::message::TransferValidationError::DuplicateRecipient {
+                    address: recipients[0].0.to_bech32()
+                }]
+            ),
+            _ => panic!("unexpected response"),
+        }
+        // the sum of the amounts must fit on a u64
+        let overflowing_recipients = vec![
+            (recipients[0].0.clone(), std::num::NonZeroU64::new(u64::MAX).unwrap()),
+            recipients[1].clone(),
+        ];
+        match &super::Transfer::builder_with_outputs(overflowing_recipients) {
+            Err(crate::Error::InvalidTransfer(errors)) => {
+                assert_eq!(errors, &vec![crate::message::TransferValidationError::AmountOverflow])
+            }
+            _ => panic!("unexpected response"),
+        }
+        // the additional outputs are deserialized too
+        let json = serde_json::json!({
+            "amount": 1000000,
+            "address": recipients[0].0.to_bech32(),
+            "remainder_value_strategy": { "strategy": "ChangeAddress" },
+            "additionalOutputs": [{ "address": recipients[1].0.to_bech32(), "amount": 2000000 }],
+        });
+        let transfer = serde_json::from_value::<crate::message::TransferBuilder>(json)
+            .unwrap()
+            .finish();
+        assert_eq!(transfer.additional_outputs, vec![recipients[1].clone()]);
+        let json = serde_json::json!({
+            "amount": 1000000,
+            "address": recipients[0].0.to_bech32(),
+            "remainder_value_strategy": { "strategy": "ChangeAddress" },
+            "additionalOutputs": [{ "address": recipients[0].0.to_bech32(), "amount": 2000000 }],
+        });
+        assert!(serde_json::from_value::<crate::message::TransferBuilder>(json).is_err());
+
+        // the remainder can't be sent to one of the recipients
+        let res = synced
+            .essence_dump(
+                super::Transfer::builder_with_outputs(vec![
+                    recipients[0].clone(),
+                    (address.address().clone(), std::num::NonZeroU64::new(1000000).unwrap()),
+                ])
+                .unwrap()
+                .with_remainder_value_strategy(super::RemainderValueStrategy::AccountAddress(address.address().clone()))
+                .finish(),
+            )
+            .await;
+        assert!(matches!(res, Err(crate::Error::InvalidTransfer(_))));
+    }
+
+    #[tokio::test]
+    async fn spent_output_confirmation() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let mut output = crate::test_utils::generate_address_output(
+            address.address(),
+            1000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        output.is_spent = true;
+        let output_id = output.id().unwrap();
+        address.outputs.insert(output_id, output);
+
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+        let account = account_handle.read().await;
+
+        for is_healthy in &[false, true] {
+            let client_options = crate::test_utils::MockNodeClient::default()
+                .with_node_metrics(crate::client::NodeMetrics {
+                    is_healthy: *is_healthy,
+                    milestone_interval: None,
+                    messages_per_second: 0.0,
+                    referenced_messages_per_second: 0.0,
+                    referenced_rate: 0.0,
+                })
+                .register()
+                .await;
+            let client = crate::client::get_node_client(&client_options, None).await.unwrap();
+            let mut addresses = vec![address.clone()];
+            // the spending message isn't known, so the spent flag is only trusted if the node is healthy
+            super::unmark_unconfirmed_spent_outputs(&account, &*client, &[], &mut addresses)
+                .await
+                .unwrap();
+            assert_eq!(addresses[0].outputs()[&output_id].is_spent, *is_healthy);
+        }
+    }
+
+    #[tokio::test]
+    async fn spent_output_reconciliation() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let output_id = output.id().unwrap();
+        let mut spent_output = output.clone();
+        spent_output.is_spent = true;
+        address.outputs.insert(output_id, spent_output);
+        address.set_balance(1000000);
+
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        let mut options = account_handle.account_options;
+        let mut outputs = address.outputs().clone();
+        super::sync_address(
+            Vec::new(),
+            &client_options,
+            &mut outputs,
+            address.address().clone(),
+            "atoi".to_string(),
+            None,
+            options,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        // the reconciliation is disabled by default
+        assert!(outputs[&output_id].is_spent);
+
+        options.reconcile_spent_outputs = true;
+        super::sync_address(
+            Vec::new(),
+            &client_options,
+            &mut outputs,
+            address.address().clone(),
+            "atoi".to_string(),
+            None,
+            options,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        assert!(!outputs[&output_id].is_spent);
+
+        let addresses_before_sync = vec![(address.address().to_bech32(), 1000000, address.outputs().clone())];
+        address.outputs = outputs;
+        let events = super::AccountSynchronizer::get_events(
+            options,
+            &account_handle.emitted_balance_states,
+            &addresses_before_sync,
+            &[address],
+            &[],
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(events.balance_change_events.len(), 1);
+        assert_eq!(
+            events.balance_change_events[0].message_id,
+            Some(iota::MessageId::from([3; 32]))
+        );
+    }
+
+    #[tokio::test]
+    async fn change_addresses_are_reused() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 3000000, crate::address::OutputKind::SignatureLockedSingle);
+        let mut change_addresses = Vec::new();
+        for key_index in 3..5 {
+            let mut change_address = crate::test_utils::generate_random_address();
+            change_address.set_key_index(key_index);
+            change_address.set_internal(true);
+            change_addresses.push(change_address);
+        }
+
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let mut addresses = vec![address];
+        addresses.extend(change_addresses.clone());
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(addresses)
+            .create()
+            .await;
+        let synced = super::SyncedAccount::from(account_handle.clone()).await;
+        let recipient = crate::test_utils::generate_random_iota_address();
+
+        // the unfunded change address with the lowest key index is reused instead of generating a new one
+        let prepared = synced
+            .prepare_transfer(
+                super::Transfer::builder(recipient.clone(), std::num::NonZeroU64::new(1000000).unwrap()).finish(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            prepared.remainder_deposit_address(),
+            &Some(change_addresses[0].address().to_bech32())
+        );
+
+        // a change address receiving one of the transfer outputs isn't reused
+        let prepared = synced
+            .prepare_transfer(
+                super::Transfer::builder_with_outputs(vec![
+                    (recipient, std::num::NonZeroU64::new(1000000).unwrap()),
+                    (
+                        change_addresses[0].address().clone(),
+                        std::num::NonZeroU64::new(1000000).unwrap(),
+                    ),
+                ])
+                .unwrap()
+                .finish(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            prepared.remainder_deposit_address(),
+            &Some(change_addresses[1].address().to_bech32())
+        );
+        assert_eq!(
+            account_handle
+                .read()
+                .await
+                .addresses()
+                .iter()
+                .filter(|a| *a.internal())
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn treasury_outputs_are_skipped() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output = crate::test_utils::generate_address_output(
+            address.address(),
+            1000000,
+            crate::address::OutputKind::SignatureLockedSingle,
+        );
+        let treasury_output = crate::test_utils::generate_address_output(
+            address.address(),
+            1000000,
+            crate::address::OutputKind::Treasury,
+        );
+
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output.clone())
+            .with_output(treasury_output.clone())
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        let mut options = account_handle.account_options;
+        let mut outputs = std::collections::HashMap::new();
+        super::sync_address(
+            Vec::new(),
+            &client_options,
+            &mut outputs,
+            address.address().clone(),
+            "atoi".to_string(),
+            None,
+            options,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        // the treasury outputs are skipped by default
+        assert_eq!(outputs.len(), 1);
+        assert!(outputs.contains_key(&output.id().unwrap()));
+
+        options.include_treasury_outputs = true;
+        super::sync_address(
+            Vec::new(),
+            &client_options,
+            &mut outputs,
+            address.address().clone(),
+            "atoi".to_string(),
+            None,
+            options,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(
+            outputs[&treasury_output.id().unwrap()].kind,
+            crate::address::OutputKind::Treasury
+        );
+    }
 }
@@ -4,18 +4,19 @@
 #[allow(unused_imports)]
 use crate::{
     account::{
-        repost_message, Account, AccountHandle, AccountIdentifier, AccountInitialiser, AccountSynchronizer,
-        RepostAction, SyncedAccount, SyncedAccountData,
+        reconcile_pending_transfers, repost_message, Account, AccountBalance, AccountHandle, AccountIdentifier,
+        AccountInitialiser, AccountSynchronizer, RepostAction, SyncedAccount, SyncedAccountData,
     },
-    address::AddressOutput,
+    address::{AddressOutput, AddressWrapper},
     client::ClientOptions,
     event::{
-        emit_balance_change, emit_confirmation_state_change, emit_reattachment_event, emit_transaction_event,
-        BalanceEvent, TransactionConfirmationChangeEvent, TransactionEvent, TransactionEventType,
-        TransactionReattachmentEvent,
+        emit_address_expired, emit_balance_change, emit_confirmation_state_change, emit_gap_limit_reached,
+        emit_reattachment_event, emit_transaction_event, BalanceEvent, TimestampedEvent,
+        TransactionConfirmationChangeEvent, TransactionEvent, TransactionEventType, TransactionReattachmentEvent,
+        WalletEvent,
     },
     message::{Message, MessagePayload, MessageType, Transfer},
-    signing::SignerType,
+    signing::{GenerateAddressMetadata, SignerType},
     storage::{StorageAdapter, Timestamp},
 };
 
@@ -38,14 +39,15 @@ use chrono::prelude::*;
 use futures::FutureExt;
 use getset::Getters;
 use iota::{bee_rest_api::types::dtos::LedgerInclusionStateDto, MessageId, OutputId};
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{
         broadcast::{channel as broadcast_channel, Receiver as BroadcastReceiver, Sender as BroadcastSender},
-        Mutex, RwLock,
+        Mutex, RwLock, Semaphore,
     },
     time::interval,
 };
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 /// The default storage folder.
 pub const DEFAULT_STORAGE_FOLDER: &str = "./storage";
@@ -59,10 +61,52 @@ pub const STRONGHOLD_FILENAME: &str = "wallet.stronghold";
 
 /// The default SQLite storage file name.
 pub const SQLITE_FILENAME: &str = "wallet.db";
+/// The default file name of the SQLite storage created by
+/// [AccountManagerBuilder#with_event_storage](struct.AccountManagerBuilder.html#method.with_event_storage).
+pub const EVENTS_SQLITE_FILENAME: &str = "events.db";
 
 #[doc(hidden)]
 pub type AccountStore = Arc<RwLock<HashMap<String, AccountHandle>>>;
 
+/// The order in which the account addresses are synced.
+/// The addresses are synced a few at a time, so the first ones in this order are updated first.
+/// The sync result is the same; only the order in which the addresses are processed changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AddressSyncOrder {
+    /// The addresses are synced in the order they're stored on the account.
+    Storage,
+    /// The addresses with the most recent messages are synced first.
+    RecentlyActive,
+    /// The addresses with the highest balance are synced first.
+    Balance,
+}
+
+/// The retry policy of the node requests made by the account sync.
+/// A request failing with a server (5xx) or network error is retried after `base_delay * 2^retry` (capped at one
+/// minute) plus a random delay of up to `jitter`; other errors (e.g. 404) and the error of the last retry are returned
+/// as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SyncRetryPolicy {
+    /// The maximum number of retries of a request; 0 disables the retries.
+    #[serde(rename = "maxRetries")]
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled on each subsequent retry.
+    #[serde(rename = "baseDelay")]
+    pub base_delay: Duration,
+    /// The maximum random delay added to each retry delay.
+    pub jitter: Duration,
+}
+
+impl Default for SyncRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(500),
+            jitter: Duration::from_millis(100),
+        }
+    }
+}
+
 /// The storage used by the manager.
 enum ManagerStorage {
     /// Stronghold storage.
@@ -71,14 +115,22 @@ enum ManagerStorage {
     Sqlite,
 }
 
-fn storage_file_path(storage: &ManagerStorage, storage_path: &PathBuf) -> PathBuf {
+// The name of the storage file on the storage folder: the custom storage filename with the storage extension,
+// or the default file name.
+fn storage_file_name(storage: &ManagerStorage, storage_filename: Option<&str>) -> String {
+    match (storage, storage_filename) {
+        (ManagerStorage::Stronghold, Some(filename)) => format!("{}.stronghold", filename),
+        (ManagerStorage::Sqlite, Some(filename)) => format!("{}.db", filename),
+        (ManagerStorage::Stronghold, None) => STRONGHOLD_FILENAME.to_string(),
+        (ManagerStorage::Sqlite, None) => SQLITE_FILENAME.to_string(),
+    }
+}
+
+fn storage_file_path(storage: &ManagerStorage, storage_path: &PathBuf, storage_filename: Option<&str>) -> PathBuf {
     if storage_path.is_file() || storage_path.extension().is_some() {
         storage_path.clone()
     } else {
-        match storage {
-            ManagerStorage::Stronghold => storage_path.join(STRONGHOLD_FILENAME),
-            ManagerStorage::Sqlite => storage_path.join(SQLITE_FILENAME),
-        }
+        storage_path.join(storage_file_name(storage, storage_filename))
     }
 }
 
@@ -90,6 +142,9 @@ fn storage_password_to_encryption_key(password: &str) -> [u8; 32] {
     key
 }
 
+/// The callback invoked by the polling system after a sync cycle.
+type SyncCompleteCallback = Arc<dyn Fn(&[SyncedAccount]) + Send + Sync>;
+
 /// Account manager builder.
 pub struct AccountManagerBuilder {
     storage_path: PathBuf,
@@ -97,7 +152,10 @@ pub struct AccountManagerBuilder {
     polling_interval: Duration,
     skip_polling: bool,
     storage_encryption_key: Option<[u8; 32]>,
+    event_storage: Option<(PathBuf, Option<Box<dyn StorageAdapter + Send + Sync + 'static>>)>,
     account_options: AccountOptions,
+    on_sync_complete: Option<SyncCompleteCallback>,
+    storage_filename: Option<String>,
 }
 
 impl Default for AccountManagerBuilder {
@@ -108,12 +166,27 @@ impl Default for AccountManagerBuilder {
             polling_interval: Duration::from_millis(30_000),
             skip_polling: false,
             storage_encryption_key: None,
+            event_storage: None,
             account_options: AccountOptions {
                 output_consolidation_threshold: DEFAULT_OUTPUT_CONSOLIDATION_THRESHOLD,
                 automatic_output_consolidation: true,
                 sync_spent_outputs: false,
                 persist_events: false,
+                require_spent_output_confirmation: false,
+                max_stored_messages: None,
+                sync_events_summary: false,
+                address_sync_order: AddressSyncOrder::Storage,
+                consolidation_reserve_per_address: None,
+                confirmation_threshold: 0,
+                reconcile_spent_outputs: false,
+                max_concurrent_account_syncs: None,
+                include_treasury_outputs: false,
+                account_discovery_window: 1,
+                account_discovery_gap_limit: 1,
+                sync_retry_policy: SyncRetryPolicy::default(),
             },
+            on_sync_complete: None,
+            storage_filename: None,
         }
     }
 }
@@ -134,6 +207,24 @@ impl AccountManagerBuilder {
         Ok(self)
     }
 
+    /// Sets the name of the storage files created on the storage folder, without the extension,
+    /// e.g. to keep the storage of each user on a separate file keyed on the user id.
+    /// The SQLite storage is named `<filename>.db` and the Stronghold snapshot `<filename>.stronghold`;
+    /// backups, imports and the storage deletion use the same names.
+    /// Defaults to `wallet`. Ignored if the [storage path](#method.with_storage) is a file.
+    /// The filename can't be empty or contain path separators or `..`, so the files stay on the storage folder.
+    pub fn with_storage_filename(mut self, storage_filename: String) -> crate::Result<Self> {
+        if storage_filename.is_empty()
+            || storage_filename.contains('/')
+            || storage_filename.contains('\\')
+            || storage_filename.contains("..")
+        {
+            return Err(crate::Error::InvalidStorageFilename(storage_filename));
+        }
+        self.storage_filename = Some(storage_filename);
+        Ok(self)
+    }
+
     /// Sets the polling interval.
     pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
         self.polling_interval = polling_interval;
@@ -157,6 +248,14 @@ impl AccountManagerBuilder {
         self
     }
 
+    /// Sets the amount kept on each source address when consolidating its outputs to another address.
+    /// The reserve is left as a remainder output on the source address; a non-zero reserve below the
+    /// dust threshold (1 Mi) is raised to it so the remainder output is always valid.
+    pub fn with_consolidation_reserve_per_address(mut self, reserve: u64) -> Self {
+        self.account_options.consolidation_reserve_per_address = Some(reserve);
+        self
+    }
+
     /// Disables the automatic output consolidation process.
     pub fn with_automatic_output_consolidation_disabled(mut self) -> Self {
         self.account_options.automatic_output_consolidation = true;
@@ -169,18 +268,137 @@ impl AccountManagerBuilder {
         self
     }
 
+    /// Stores the treasury outputs found on the account addresses on sync.
+    /// By default they're skipped since accounts can't spend them.
+    pub fn with_treasury_outputs(mut self) -> Self {
+        self.account_options.include_treasury_outputs = true;
+        self
+    }
+
     /// Enables event persistence.
     pub fn with_event_persistence(mut self) -> Self {
         self.account_options.persist_events = true;
         self
     }
 
+    /// Persists the events on a separate storage instead of the accounts storage,
+    /// so the event log can use another backend and grow, be pruned or rotated independently of the account data.
+    /// If no storage adapter is given, a SQLite database is created at the given path
+    /// (`events.db` if the path is a directory).
+    /// The event storage is encrypted with the storage password, if one was set with
+    /// [with_storage](#method.with_storage). Only used with [event persistence](#method.with_event_persistence).
+    pub fn with_event_storage(
+        mut self,
+        storage_path: impl AsRef<Path>,
+        storage: Option<Box<dyn StorageAdapter + Send + Sync + 'static>>,
+    ) -> Self {
+        self.event_storage = Some((storage_path.as_ref().to_path_buf(), storage));
+        self
+    }
+
+    /// Sets the maximum number of messages kept on each account.
+    /// When the limit is exceeded, the oldest confirmed messages are pruned; unconfirmed messages are always kept.
+    /// Note that pruned messages are no longer available locally (e.g. on `list_messages` and `get_message`)
+    /// and aren't fetched again by the account sync.
+    pub fn with_max_stored_messages(mut self, max_stored_messages: usize) -> Self {
+        self.account_options.max_stored_messages = Some(max_stored_messages);
+        self
+    }
+
+    /// Only treats an output as spent if the message spending it is confirmed,
+    /// instead of trusting the node's spent flag.
+    /// If the spending message isn't known or the node doesn't return it anymore (e.g. it was pruned),
+    /// the spent flag is trusted only if the node is healthy.
+    /// Useful to prevent hiding balance when the node isn't fully synced.
+    pub fn with_spent_output_confirmation_required(mut self) -> Self {
+        self.account_options.require_spent_output_confirmation = true;
+        self
+    }
+
+    /// Sets the number of milestones that must reference or follow a message before it's considered confirmed,
+    /// e.g. to match an exchange crediting policy.
+    /// Until then, an included message is flagged as [confirming](../message/struct.Message.html#method.confirming)
+    /// and its confirmation state change event isn't emitted.
+    /// Defaults to 0, meaning messages are confirmed as soon as they're included in the ledger.
+    pub fn with_confirmation_threshold(mut self, confirmation_threshold: u32) -> Self {
+        self.account_options.confirmation_threshold = confirmation_threshold;
+        self
+    }
+
+    /// Restores the outputs spent locally that the node reports as unspent on the next sync,
+    /// emitting a balance change event for them.
+    /// On mainnet a spent output never becomes unspent again, but a private network might reorganize
+    /// (e.g. when it's reset), leaving the local spent state desynced and the available balance understated.
+    pub fn with_spent_output_reconciliation(mut self) -> Self {
+        self.account_options.reconcile_spent_outputs = true;
+        self
+    }
+
+    /// Sets the maximum number of accounts synced at the same time by `sync_accounts` and the polling loop.
+    /// The remaining accounts wait until a sync slot is released,
+    /// bounding the number of simultaneous node connections on wallets with many accounts.
+    /// Defaults to no limit.
+    pub fn with_max_concurrent_account_syncs(mut self, max_concurrent_account_syncs: usize) -> Self {
+        self.account_options.max_concurrent_account_syncs = Some(max_concurrent_account_syncs.max(1));
+        self
+    }
+
+    /// Sets the number of account indexes probed at the same time by the account discovery.
+    /// Defaults to 1, discovering one account at a time.
+    pub fn with_account_discovery_window(mut self, window: usize) -> Self {
+        self.account_options.account_discovery_window = window.max(1);
+        self
+    }
+
+    /// Sets the number of consecutive empty accounts that ends the account discovery.
+    /// Empty accounts followed by a funded one are stored so the account indexes stay contiguous.
+    /// Defaults to 1, stopping at the first empty account.
+    pub fn with_account_discovery_gap_limit(mut self, gap_limit: usize) -> Self {
+        self.account_options.account_discovery_gap_limit = gap_limit.max(1);
+        self
+    }
+
+    /// Sets the retry policy of the node requests made by the account sync,
+    /// so a node transiently failing under load doesn't fail the sync of its addresses.
+    /// Defaults to no retries.
+    pub fn with_sync_retry_policy(mut self, policy: SyncRetryPolicy) -> Self {
+        self.account_options.sync_retry_policy = policy;
+        self
+    }
+
+    /// Sets the order in which the account addresses are synced, e.g. to update the busiest addresses first.
+    /// Defaults to [AddressSyncOrder::Storage](enum.AddressSyncOrder.html#variant.Storage).
+    pub fn with_address_sync_order(mut self, order: AddressSyncOrder) -> Self {
+        self.account_options.address_sync_order = order;
+        self
+    }
+
+    /// Delivers the events generated by an account sync as a single `SyncCompleted` event
+    /// instead of individual `NewTransaction`, confirmation state change and balance change events.
+    /// Events triggered by the MQTT subscriptions are still emitted individually,
+    /// and the event stream (see `subscribe_events`) still delivers the summarized events individually.
+    pub fn with_sync_events_summary(mut self) -> Self {
+        self.account_options.sync_events_summary = true;
+        self
+    }
+
+    /// Sets a callback invoked by the polling system after every successful sync cycle, with the synced accounts.
+    /// Useful to refresh caches or UIs once per cycle instead of reacting to each sync event.
+    pub fn on_sync_complete(mut self, callback: Box<dyn Fn(&[SyncedAccount]) + Send + Sync>) -> Self {
+        self.on_sync_complete = Some(callback.into());
+        self
+    }
+
     /// Builds the manager.
     pub async fn finish(self) -> crate::Result<AccountManager> {
         let (storage, storage_file_path, is_stronghold): (Box<dyn StorageAdapter + Send + Sync>, PathBuf, bool) =
             match self.storage {
                 ManagerStorage::Stronghold => {
-                    let path = storage_file_path(&ManagerStorage::Stronghold, &self.storage_path);
+                    let path = storage_file_path(
+                        &ManagerStorage::Stronghold,
+                        &self.storage_path,
+                        self.storage_filename.as_deref(),
+                    );
                     if let Some(parent) = path.parent() {
                         fs::create_dir_all(&parent)?;
                     }
@@ -188,7 +406,11 @@ impl AccountManagerBuilder {
                     (Box::new(storage) as Box<dyn StorageAdapter + Send + Sync>, path, true)
                 }
                 ManagerStorage::Sqlite => {
-                    let path = storage_file_path(&ManagerStorage::Sqlite, &self.storage_path);
+                    let path = storage_file_path(
+                        &ManagerStorage::Sqlite,
+                        &self.storage_path,
+                        self.storage_filename.as_deref(),
+                    );
                     if let Some(parent) = path.parent() {
                         fs::create_dir_all(&parent)?;
                     }
@@ -199,6 +421,46 @@ impl AccountManagerBuilder {
 
         crate::storage::set(&storage_file_path, self.storage_encryption_key, storage).await;
 
+        let storage_folder = if self.storage_path.is_file() || self.storage_path.extension().is_some() {
+            match self.storage_path.parent() {
+                Some(p) => p.to_path_buf(),
+                None => self.storage_path,
+            }
+        } else {
+            self.storage_path
+        };
+
+        // the accounts use the Stronghold storage itself or the snapshot named after the storage filename
+        #[cfg(feature = "stronghold")]
+        {
+            let snapshot_path = if is_stronghold {
+                storage_file_path.clone()
+            } else {
+                storage_folder.join(storage_file_name(
+                    &ManagerStorage::Stronghold,
+                    self.storage_filename.as_deref(),
+                ))
+            };
+            crate::signing::stronghold::set_snapshot_path(&storage_file_path, snapshot_path).await;
+        }
+
+        if let Some((event_storage_path, event_storage)) = self.event_storage {
+            let event_storage_path = if event_storage_path.is_file() || event_storage_path.extension().is_some() {
+                event_storage_path
+            } else {
+                event_storage_path.join(EVENTS_SQLITE_FILENAME)
+            };
+            if event_storage_path != storage_file_path {
+                crate::storage::init_event_storage(
+                    &storage_file_path,
+                    &event_storage_path,
+                    event_storage,
+                    self.storage_encryption_key,
+                )
+                .await?;
+            }
+        }
+
         // is_monitoring is set to false if an mqtt error happens, so we can initialize it with `true`.
         let is_monitoring = Arc::new(AtomicBool::new(true));
 
@@ -212,14 +474,7 @@ impl AccountManagerBuilder {
                 .unwrap_or_else(|_| (AccountStore::default(), false))
         };
         let mut instance = AccountManager {
-            storage_folder: if self.storage_path.is_file() || self.storage_path.extension().is_some() {
-                match self.storage_path.parent() {
-                    Some(p) => p.to_path_buf(),
-                    None => self.storage_path,
-                }
-            } else {
-                self.storage_path
-            },
+            storage_folder,
             loaded_accounts,
             storage_path: storage_file_path,
             accounts,
@@ -229,6 +484,8 @@ impl AccountManagerBuilder {
             generated_mnemonic: None,
             account_options: self.account_options,
             sync_accounts_lock: Arc::new(Mutex::new(())),
+            on_sync_complete: self.on_sync_complete,
+            storage_filename: self.storage_filename,
         };
 
         if !self.skip_polling {
@@ -250,6 +507,179 @@ pub(crate) struct AccountOptions {
     pub(crate) automatic_output_consolidation: bool,
     pub(crate) sync_spent_outputs: bool,
     pub(crate) persist_events: bool,
+    pub(crate) require_spent_output_confirmation: bool,
+    pub(crate) max_stored_messages: Option<usize>,
+    pub(crate) sync_events_summary: bool,
+    pub(crate) address_sync_order: AddressSyncOrder,
+    pub(crate) consolidation_reserve_per_address: Option<u64>,
+    pub(crate) confirmation_threshold: u32,
+    pub(crate) reconcile_spent_outputs: bool,
+    pub(crate) max_concurrent_account_syncs: Option<usize>,
+    pub(crate) include_treasury_outputs: bool,
+    pub(crate) account_discovery_window: usize,
+    pub(crate) account_discovery_gap_limit: usize,
+    pub(crate) sync_retry_policy: SyncRetryPolicy,
+}
+
+/// The manager options included in the [DiagnosticSnapshot](struct.DiagnosticSnapshot.html).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct DiagnosticOptions {
+    /// The number of outputs an address must have to trigger the automatic consolidation.
+    #[serde(rename = "outputConsolidationThreshold")]
+    output_consolidation_threshold: usize,
+    /// Whether the automatic output consolidation is enabled.
+    #[serde(rename = "automaticOutputConsolidation")]
+    automatic_output_consolidation: bool,
+    /// Whether the spent outputs are fetched on sync.
+    #[serde(rename = "syncSpentOutputs")]
+    sync_spent_outputs: bool,
+    /// Whether the events are persisted.
+    #[serde(rename = "persistEvents")]
+    persist_events: bool,
+    /// Whether an output is only treated as spent if the message spending it is confirmed.
+    #[serde(rename = "requireSpentOutputConfirmation")]
+    require_spent_output_confirmation: bool,
+    /// The maximum number of messages kept on each account.
+    #[serde(rename = "maxStoredMessages")]
+    max_stored_messages: Option<usize>,
+    /// Whether the sync events are delivered as a single summary event.
+    #[serde(rename = "syncEventsSummary")]
+    sync_events_summary: bool,
+    /// The order in which the account addresses are synced.
+    #[serde(rename = "addressSyncOrder")]
+    address_sync_order: AddressSyncOrder,
+    /// The amount kept on each source address when consolidating outputs to another address.
+    #[serde(rename = "consolidationReservePerAddress")]
+    consolidation_reserve_per_address: Option<u64>,
+    /// The number of milestones a message needs before it's considered confirmed.
+    #[serde(rename = "confirmationThreshold")]
+    confirmation_threshold: u32,
+    /// Whether the outputs spent locally are restored when the node reports them as unspent.
+    #[serde(rename = "reconcileSpentOutputs")]
+    reconcile_spent_outputs: bool,
+    /// The maximum number of accounts synced at the same time.
+    #[serde(rename = "maxConcurrentAccountSyncs")]
+    max_concurrent_account_syncs: Option<usize>,
+    /// Whether the treasury outputs are stored on sync.
+    #[serde(rename = "includeTreasuryOutputs")]
+    include_treasury_outputs: bool,
+    /// Number of account indexes probed at the same time by the account discovery.
+    #[serde(rename = "accountDiscoveryWindow")]
+    account_discovery_window: usize,
+    /// Number of consecutive empty accounts that ends the account discovery.
+    #[serde(rename = "accountDiscoveryGapLimit")]
+    account_discovery_gap_limit: usize,
+    /// The retry policy of the node requests made by the account sync.
+    #[serde(rename = "syncRetryPolicy")]
+    sync_retry_policy: SyncRetryPolicy,
+}
+
+impl From<AccountOptions> for DiagnosticOptions {
+    fn from(options: AccountOptions) -> Self {
+        Self {
+            output_consolidation_threshold: options.output_consolidation_threshold,
+            automatic_output_consolidation: options.automatic_output_consolidation,
+            sync_spent_outputs: options.sync_spent_outputs,
+            persist_events: options.persist_events,
+            require_spent_output_confirmation: options.require_spent_output_confirmation,
+            max_stored_messages: options.max_stored_messages,
+            sync_events_summary: options.sync_events_summary,
+            address_sync_order: options.address_sync_order,
+            consolidation_reserve_per_address: options.consolidation_reserve_per_address,
+            confirmation_threshold: options.confirmation_threshold,
+            reconcile_spent_outputs: options.reconcile_spent_outputs,
+            max_concurrent_account_syncs: options.max_concurrent_account_syncs,
+            include_treasury_outputs: options.include_treasury_outputs,
+            account_discovery_window: options.account_discovery_window,
+            account_discovery_gap_limit: options.account_discovery_gap_limit,
+            sync_retry_policy: options.sync_retry_policy,
+        }
+    }
+}
+
+/// The sanitized account metadata included in the [DiagnosticSnapshot](struct.DiagnosticSnapshot.html).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct AccountDiagnostics {
+    /// The account index.
+    index: usize,
+    /// The account signer type.
+    #[serde(rename = "signerType")]
+    signer_type: SignerType,
+    /// The number of public addresses.
+    #[serde(rename = "publicAddressCount")]
+    public_address_count: usize,
+    /// The number of change addresses.
+    #[serde(rename = "changeAddressCount")]
+    change_address_count: usize,
+    /// The account addresses, redacted to the bech32 HRP and the first characters of the address.
+    addresses: Vec<String>,
+    /// The number of unspent outputs.
+    #[serde(rename = "unspentOutputCount")]
+    unspent_output_count: usize,
+    /// The account balance.
+    balance: AccountBalance,
+    /// The number of stored messages.
+    #[serde(rename = "messageCount")]
+    message_count: usize,
+    /// The number of unconfirmed messages.
+    #[serde(rename = "unconfirmedMessageCount")]
+    unconfirmed_message_count: usize,
+    /// Time the account was last synced with the Tangle.
+    #[serde(rename = "lastSyncedAt")]
+    last_synced_at: Option<DateTime<Local>>,
+}
+
+/// A read-only snapshot of the manager state to attach to bug reports.
+/// It doesn't include seeds, mnemonics, aliases or full addresses.
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct DiagnosticSnapshot {
+    /// Time the snapshot was taken.
+    #[serde(rename = "createdAt")]
+    created_at: DateTime<Local>,
+    /// The manager options.
+    options: DiagnosticOptions,
+    /// The accounts metadata, sorted by index.
+    accounts: Vec<AccountDiagnostics>,
+}
+
+/// The contents of a backup file, as returned by [AccountManager#verify_backup](struct.AccountManager.html#method.verify_backup).
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct BackupInfo {
+    /// The identifiers of the accounts stored on the backup.
+    #[serde(rename = "accountIds")]
+    account_ids: Vec<String>,
+}
+
+impl BackupInfo {
+    /// The number of accounts stored on the backup.
+    pub fn account_count(&self) -> usize {
+        self.account_ids.len()
+    }
+}
+
+/// How [AccountManager#merge_accounts](struct.AccountManager.html#method.merge_accounts) handles a backup account
+/// conflicting with an existing account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ConflictPolicy {
+    /// Skips the conflicting account.
+    Skip,
+    /// Imports the account with a new alias if only the alias conflicts.
+    /// Accounts with the same id or the same signer type and index are skipped.
+    Rename,
+    /// Aborts the merge without importing any account.
+    Error,
+}
+
+// Redacts a bech32 address to its HRP and the first characters of its data part.
+fn redact_address(address: &str) -> String {
+    match address.rfind('1') {
+        Some(separator) => format!("{}...", &address[..(separator + 5).min(address.len())]),
+        None => "...".to_string(),
+    }
 }
 
 /// The account manager.
@@ -271,6 +701,8 @@ pub struct AccountManager {
     generated_mnemonic: Option<String>,
     account_options: AccountOptions,
     sync_accounts_lock: Arc<Mutex<()>>,
+    on_sync_complete: Option<SyncCompleteCallback>,
+    storage_filename: Option<String>,
 }
 
 impl Clone for AccountManager {
@@ -291,6 +723,8 @@ impl Clone for AccountManager {
             generated_mnemonic: None,
             account_options: self.account_options,
             sync_accounts_lock: self.sync_accounts_lock.clone(),
+            on_sync_complete: self.on_sync_complete.clone(),
+            storage_filename: self.storage_filename.clone(),
         }
     }
 }
@@ -325,12 +759,33 @@ impl AccountManager {
     ) -> crate::Result<AccountStore> {
         let parsed_accounts = Arc::new(RwLock::new(HashMap::new()));
 
-        let accounts = crate::storage::get(&storage_file_path)
-            .await?
-            .lock()
-            .await
-            .get_accounts()
-            .await?;
+        let (mut accounts, account_storage_paths, encryption_key) = {
+            let storage = crate::storage::get(&storage_file_path).await?;
+            let mut storage = storage.lock().await;
+            (
+                storage.get_accounts().await?,
+                storage.get_account_storage_paths().await?,
+                storage.encryption_key(),
+            )
+        };
+        // accounts created with a custom storage path
+        let event_storage_path = crate::storage::get_event_storage_path(storage_file_path).await;
+        for account_storage_path in account_storage_paths {
+            crate::storage::init_account_storage(&account_storage_path, encryption_key).await?;
+            if let Some(event_storage_path) = &event_storage_path {
+                crate::storage::link_event_storage(&account_storage_path, event_storage_path).await;
+            }
+            #[cfg(feature = "stronghold")]
+            crate::signing::stronghold::link_snapshot_path(&account_storage_path, storage_file_path).await;
+            accounts.extend(
+                crate::storage::get(&account_storage_path)
+                    .await?
+                    .lock()
+                    .await
+                    .get_accounts()
+                    .await?,
+            );
+        }
         for account in accounts {
             parsed_accounts.write().await.insert(
                 account.id().clone(),
@@ -373,11 +828,17 @@ impl AccountManager {
         let stronghold_snapshot_path = if storage_id == crate::storage::stronghold::STORAGE_ID {
             self.storage_path.clone()
         } else {
-            self.storage_folder.join(STRONGHOLD_FILENAME)
+            self.storage_folder_file(ManagerStorage::Stronghold)
         };
         Ok(stronghold_snapshot_path)
     }
 
+    // The path of the storage file with the given type on the storage folder, honoring the custom storage filename.
+    fn storage_folder_file(&self, storage: ManagerStorage) -> PathBuf {
+        self.storage_folder
+            .join(storage_file_name(&storage, self.storage_filename.as_deref()))
+    }
+
     // error out if the storage is encrypted
     fn check_storage_encryption(&self) -> crate::Result<()> {
         if self.loaded_accounts {
@@ -427,12 +888,38 @@ impl AccountManager {
     /// Sets the password for the stored accounts.
     pub async fn set_storage_password<P: AsRef<str>>(&mut self, password: P) -> crate::Result<()> {
         let key = storage_password_to_encryption_key(password.as_ref());
+        let is_unlocking = self.accounts.read().await.is_empty();
+        if is_unlocking {
+            if let Some(event_storage_path) = crate::storage::get_event_storage_path(&self.storage_path).await {
+                crate::storage::set_encryption_key(&event_storage_path, key).await?;
+            }
+        } else {
+            // the records are read with the current key, so they're re-encrypted before the key changes
+            let mut storage_paths = crate::storage::get(&self.storage_path)
+                .await?
+                .lock()
+                .await
+                .get_account_storage_paths()
+                .await?;
+            storage_paths.push(self.storage_path.clone());
+            if let Some(event_storage_path) = crate::storage::get_event_storage_path(&self.storage_path).await {
+                storage_paths.push(event_storage_path);
+            }
+            for storage_path in storage_paths {
+                crate::storage::get(&storage_path)
+                    .await?
+                    .lock()
+                    .await
+                    .reencrypt_records(key)
+                    .await?;
+            }
+        }
         // safe to unwrap because the storage is always defined at this point
         crate::storage::set_encryption_key(&self.storage_path, key)
             .await
             .unwrap();
 
-        if self.accounts.read().await.is_empty() {
+        if is_unlocking {
             let accounts =
                 Self::load_accounts(&self.storage_path, self.account_options, self.is_monitoring.clone()).await?;
             self.loaded_accounts = true;
@@ -453,12 +940,23 @@ impl AccountManager {
         Ok(())
     }
 
+    /// Compacts the storage, reclaiming the disk space left by updated and removed records.
+    /// Runs a `VACUUM` on the SQLite storage; it's a no-op on storage adapters that don't need compaction.
+    pub async fn compact_storage(&self) -> crate::Result<()> {
+        crate::storage::get(&self.storage_path)
+            .await?
+            .lock()
+            .await
+            .compact()
+            .await
+    }
+
     /// Sets the stronghold password.
     pub async fn set_stronghold_password<P: Into<String>>(&mut self, password: P) -> crate::Result<()> {
         let stronghold_path = if self.storage_path.extension().unwrap_or_default() == "stronghold" {
             self.storage_path.clone()
         } else {
-            self.storage_folder.join(STRONGHOLD_FILENAME)
+            self.storage_folder_file(ManagerStorage::Stronghold)
         };
         crate::stronghold::load_snapshot(&stronghold_path, stronghold_password(password)).await?;
 
@@ -526,6 +1024,7 @@ impl AccountManager {
         let is_monitoring = self.is_monitoring.clone();
         let account_options = self.account_options;
         let sync_accounts_lock = self.sync_accounts_lock.clone();
+        let on_sync_complete = self.on_sync_complete.clone();
 
         let handle = thread::spawn(move || {
             let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -553,7 +1052,8 @@ impl AccountManager {
                                         account_options,
                                         should_sync,
                                         is_monitoring.clone(),
-                                        automatic_output_consolidation)
+                                        automatic_output_consolidation,
+                                        on_sync_complete.as_ref())
                                     )
                                     .catch_unwind()
                                     .await {
@@ -591,6 +1091,30 @@ impl AccountManager {
     /// Stores a mnemonic for the given signer type.
     /// If the mnemonic is not provided, we'll generate one.
     pub async fn store_mnemonic(&mut self, signer_type: SignerType, mnemonic: Option<String>) -> crate::Result<()> {
+        self.store_mnemonic_internal(signer_type, mnemonic, None).await
+    }
+
+    /// Stores a mnemonic for the given signer type, deriving the seed with a BIP39 passphrase.
+    /// The same mnemonic with a different passphrase derives a different (hidden) wallet.
+    /// The passphrase is only used to derive the seed and is never persisted,
+    /// so a forgotten passphrase means the funds on the derived accounts are lost.
+    pub async fn store_mnemonic_with_passphrase(
+        &mut self,
+        signer_type: SignerType,
+        mnemonic: Option<String>,
+        passphrase: String,
+    ) -> crate::Result<()> {
+        self.store_mnemonic_internal(signer_type, mnemonic, Some(Zeroizing::new(passphrase)))
+            .await
+    }
+
+    // The passphrase is zeroized when it's dropped, whether the mnemonic is stored or not.
+    async fn store_mnemonic_internal(
+        &mut self,
+        signer_type: SignerType,
+        mnemonic: Option<String>,
+        passphrase: Option<Zeroizing<String>>,
+    ) -> crate::Result<()> {
         let mnemonic = match mnemonic {
             Some(m) => {
                 self.verify_mnemonic(&m)?;
@@ -601,7 +1125,14 @@ impl AccountManager {
 
         let signer = crate::signing::get_signer(&signer_type).await;
         let mut signer = signer.lock().await;
-        signer.store_mnemonic(&self.storage_path, mnemonic).await?;
+        match &passphrase {
+            Some(passphrase) => {
+                signer
+                    .store_mnemonic_with_passphrase(&self.storage_path, mnemonic, passphrase)
+                    .await?
+            }
+            None => signer.store_mnemonic(&self.storage_path, mnemonic).await?,
+        }
 
         if let Some(mut mnemonic) = self.generated_mnemonic.take() {
             mnemonic.zeroize();
@@ -651,29 +1182,108 @@ impl AccountManager {
         ))
     }
 
+    /// Updates the alias of an account.
+    /// Returns `AccountAliasAlreadyExists` if the alias is already used by another account.
+    pub async fn set_account_alias<I: Into<AccountIdentifier>>(
+        &self,
+        account_id: I,
+        alias: impl AsRef<str>,
+    ) -> crate::Result<()> {
+        let account_handle = self.get_account(account_id).await?;
+        account_handle.set_alias(alias).await
+    }
+
+    /// Collects the sanitized metadata of the manager and its accounts (account indexes, address counts,
+    /// balances, message counts and options) to diagnose bug reports.
+    /// The addresses are redacted to their prefixes.
+    pub async fn diagnostic_snapshot(&self) -> DiagnosticSnapshot {
+        let mut accounts = Vec::new();
+        for account_handle in self.accounts.read().await.values() {
+            let account = account_handle.read().await;
+            let change_address_count = account.addresses().iter().filter(|a| *a.internal()).count();
+            accounts.push(AccountDiagnostics {
+                index: *account.index(),
+                signer_type: account.signer_type().clone(),
+                public_address_count: account.addresses().len() - change_address_count,
+                change_address_count,
+                addresses: account
+                    .addresses()
+                    .iter()
+                    .map(|a| redact_address(&a.address().to_bech32()))
+                    .collect(),
+                unspent_output_count: account
+                    .addresses()
+                    .iter()
+                    .map(|a| a.outputs().values().filter(|o| !o.is_spent).count())
+                    .sum(),
+                balance: account.balance(),
+                message_count: account.messages().len(),
+                unconfirmed_message_count: account.messages().iter().filter(|m| m.confirmed().is_none()).count(),
+                last_synced_at: *account.last_synced_at(),
+            });
+        }
+        accounts.sort_by_key(|account| account.index);
+        DiagnosticSnapshot {
+            created_at: Local::now(),
+            options: self.account_options.into(),
+            accounts,
+        }
+    }
+
     /// Deletes an account.
+    /// Fails if the account has balance or broadcasted messages that aren't confirmed yet
+    /// (e.g. pending incoming funds that would be lost track of).
     pub async fn remove_account<I: Into<AccountIdentifier>>(&self, account_id: I) -> crate::Result<()> {
+        self.remove_account_internal(account_id, false).await
+    }
+
+    /// Deletes an account even if it has balance or pending transactions.
+    pub async fn force_remove_account<I: Into<AccountIdentifier>>(&self, account_id: I) -> crate::Result<()> {
+        self.remove_account_internal(account_id, true).await
+    }
+
+    async fn remove_account_internal<I: Into<AccountIdentifier>>(
+        &self,
+        account_id: I,
+        force: bool,
+    ) -> crate::Result<()> {
         self.check_storage_encryption()?;
 
-        let account_id = {
+        let (account_id, storage_path) = {
             let account_handle = self.get_account(account_id).await?;
             let account = account_handle.read().await;
 
-            if account.balance().total > 0 {
-                return Err(crate::Error::AccountNotEmpty);
+            if !force {
+                if account.balance().total > 0 {
+                    return Err(crate::Error::AccountNotEmpty);
+                }
+                if account
+                    .list_messages(0, 0, Some(MessageType::Unconfirmed))
+                    .iter()
+                    .any(|message| *message.broadcasted())
+                {
+                    return Err(crate::Error::AccountHasPendingTransactions);
+                }
             }
 
-            account.id().to_string()
+            (account.id().to_string(), account.storage_path().clone())
         };
 
         self.accounts.write().await.remove(&account_id);
 
-        crate::storage::get(&self.storage_path)
-            .await?
-            .lock()
-            .await
-            .remove_account(&account_id)
-            .await?;
+        let storage = crate::storage::get(&storage_path).await?;
+        let mut storage = storage.lock().await;
+        storage.remove_account(&account_id).await?;
+        // the account storage file isn't loaded anymore once it doesn't hold any account
+        if storage_path != self.storage_path && storage.get_account_keys().await?.is_empty() {
+            drop(storage);
+            crate::storage::get(&self.storage_path)
+                .await?
+                .lock()
+                .await
+                .remove_account_storage_path(&storage_path)
+                .await?;
+        }
 
         Ok(())
     }
@@ -690,6 +1300,25 @@ impl AccountManager {
         ))
     }
 
+    /// Initialises the synchronization of the given accounts only.
+    /// The account discovery is skipped on this mode.
+    pub async fn sync_accounts_by_ids(&self, ids: Vec<AccountIdentifier>) -> crate::Result<AccountsSynchronizer> {
+        self.check_storage_encryption()?;
+        let mut account_handles = Vec::new();
+        for id in ids {
+            account_handles.push(self.get_account(id).await?);
+        }
+        let mut synchronizer = AccountsSynchronizer::new(
+            self.sync_accounts_lock.clone(),
+            self.accounts.clone(),
+            self.storage_path.clone(),
+            self.account_options,
+            self.is_monitoring.clone(),
+        );
+        synchronizer.account_handles.replace(account_handles);
+        Ok(synchronizer)
+    }
+
     /// Transfers an amount from an account to another.
     pub async fn internal_transfer<F: Into<AccountIdentifier>, T: Into<AccountIdentifier>>(
         &self,
@@ -722,7 +1351,10 @@ impl AccountManager {
         Ok(message)
     }
 
-    /// Backups the storage to the given destination
+    /// Backups the storage to the given destination.
+    /// If the events are persisted on a separate SQLite
+    /// [event storage](struct.AccountManagerBuilder.html#method.with_event_storage), the event database is copied
+    /// next to the backup file, with the `events.db` extension.
     pub async fn backup<P: AsRef<Path>>(&self, destination: P, stronghold_password: String) -> crate::Result<PathBuf> {
         let destination = destination.as_ref().to_path_buf();
         if !(destination.is_dir() || destination.parent().map(|parent| parent.is_dir()).unwrap_or_default()) {
@@ -732,14 +1364,14 @@ impl AccountManager {
         let storage_path = {
             // create a account manager to setup the stronghold storage for the backup
             let mut manager = Self::builder()
-                .with_storage(&self.storage_folder.join(STRONGHOLD_FILENAME), None)
+                .with_storage(&self.storage_folder_file(ManagerStorage::Stronghold), None)
                 .unwrap() // safe to unwrap - password is None
                 .skip_polling()
                 .with_stronghold_storage()
                 .finish()
                 .await?;
             manager.set_stronghold_password(stronghold_password).await?;
-            let stronghold_storage = crate::storage::get(&self.storage_folder.join(STRONGHOLD_FILENAME)).await?;
+            let stronghold_storage = crate::storage::get(&self.storage_folder_file(ManagerStorage::Stronghold)).await?;
             let mut stronghold_storage = stronghold_storage.lock().await;
 
             for account_handle in self.accounts.read().await.values() {
@@ -747,7 +1379,7 @@ impl AccountManager {
                     .save_account(&account_handle.read().await.id(), &*account_handle.read().await)
                     .await?;
             }
-            self.storage_folder.join(STRONGHOLD_FILENAME)
+            self.storage_folder_file(ManagerStorage::Stronghold)
         };
 
         if storage_path.exists() {
@@ -760,7 +1392,7 @@ impl AccountManager {
                 let res = fs::copy(storage_path, &destination);
 
                 let mut stronghold_storage = crate::storage::stronghold::StrongholdStorageAdapter::new(
-                    &self.storage_folder.join(STRONGHOLD_FILENAME),
+                    &self.storage_folder_file(ManagerStorage::Stronghold),
                 )
                 .unwrap();
                 for account_handle in self.accounts.read().await.values() {
@@ -768,6 +1400,7 @@ impl AccountManager {
                 }
 
                 res?;
+                self.backup_events(&destination).await?;
                 destination
             } else {
                 return Err(crate::Error::StorageDoesntExist);
@@ -779,11 +1412,10 @@ impl AccountManager {
     }
 
     /// Import backed up accounts.
-    pub async fn import_accounts<S: AsRef<Path>>(
-        &mut self,
-        source: S,
-        stronghold_password: String,
-    ) -> crate::Result<()> {
+    /// The event database saved along with the backup replaces the separate SQLite
+    /// [event storage](struct.AccountManagerBuilder.html#method.with_event_storage), if one is set.
+    /// The events are copied as is, so they're only readable with the storage password they were written with.
+    pub async fn import_accounts<S: AsRef<Path>>(&mut self, source: S, password: String) -> crate::Result<()> {
         let source = source.as_ref();
         if source.is_dir() || !source.exists() || source.extension().unwrap_or_default() != "stronghold" {
             return Err(crate::Error::InvalidBackupFile);
@@ -792,37 +1424,35 @@ impl AccountManager {
             return Err(crate::Error::StorageExists);
         }
 
-        let storage_file_path = self.storage_folder.join(SQLITE_FILENAME);
+        let storage_file_path = self.storage_folder_file(ManagerStorage::Sqlite);
 
         fs::create_dir_all(&self.storage_folder)?;
 
-        let mut stronghold_manager = Self::builder()
-            .with_storage(&source, None)
-            .unwrap() // safe to unwrap - password is None
-            .skip_polling()
-            .with_stronghold_storage()
-            .finish()
-            .await?;
-        stronghold_manager
-            .set_stronghold_password(stronghold_password.clone())
-            .await?;
-        for account_handle in stronghold_manager.accounts.read().await.values() {
-            account_handle.write().await.set_storage_path(self.storage_path.clone());
-        }
-        self.accounts = stronghold_manager.accounts.clone();
-        self.set_stronghold_password(stronghold_password.clone()).await?;
-        for account in self.accounts.read().await.values() {
-            account.write().await.save().await?;
-        }
+        // read the backup storage directly so the accounts are parsed and saved one by one
+        // instead of loading the whole backup into memory
+        let backup_path = source.to_path_buf();
+        crate::stronghold::load_snapshot(&backup_path, stronghold_password(password.clone())).await?;
+        let backup_storage = crate::storage::stronghold::StrongholdStorageAdapter::new(&backup_path)?;
+        crate::storage::set(&backup_path, None, Box::new(backup_storage)).await;
+        let res = match self.set_stronghold_password(password.clone()).await {
+            Ok(()) => self.import_backup_accounts(&backup_path).await,
+            Err(e) => Err(e),
+        };
+        // the backup snapshot is only read, so it's never persisted
+        let _ = crate::stronghold::unload_snapshot(&backup_path, false).await;
+        crate::storage::remove(&backup_path).await;
+        res?;
+
         // wait for stronghold to finish its tasks
-        let _ = crate::stronghold::actor_runtime().lock().await;
-        fs::copy(source, self.storage_folder.join(STRONGHOLD_FILENAME))?;
+        crate::stronghold::wait_for_pending_tasks().await?;
+        fs::copy(source, self.storage_folder_file(ManagerStorage::Stronghold))?;
+        self.restore_events(source).await?;
 
         #[cfg(feature = "stronghold")]
         {
             // force stronghold to read the snapshot again, ignoring any previous cached value
             crate::stronghold::unload_snapshot(&self.storage_path, false).await?;
-            if let Err(e) = self.set_stronghold_password(stronghold_password).await {
+            if let Err(e) = self.set_stronghold_password(password).await {
                 fs::remove_file(&storage_file_path)?;
                 return Err(e);
             }
@@ -831,50 +1461,393 @@ impl AccountManager {
         Ok(())
     }
 
-    /// Gets the account associated with the given identifier.
-    pub async fn get_account<I: Into<AccountIdentifier>>(&self, account_id: I) -> crate::Result<AccountHandle> {
-        self.check_storage_encryption()?;
-        let account_id = account_id.into();
-        let accounts = self.accounts.read().await;
-
-        let account = match account_id {
-            AccountIdentifier::Id(id) => accounts.get(&id),
-            AccountIdentifier::Index(index) => {
-                let mut associated_account = None;
-                for account_handle in accounts.values() {
-                    let account = account_handle.read().await;
-                    if account.index() == &index {
-                        // if we already found an account with this index,
-                        // we error out since this is an incorrect usage of the API
-                        // you can't use the index to get an account if you're using multiple signer types
-                        // since there's multiple index sequences in that case
-                        if associated_account.is_some() {
-                            return Err(crate::Error::CannotUseIndexIdentifier);
-                        }
-                        associated_account = Some(account_handle);
-                    }
-                }
-                associated_account
-            }
-            AccountIdentifier::Alias(alias) => {
-                let mut associated_account = None;
-                for account_handle in accounts.values() {
-                    let account = account_handle.read().await;
-                    if account.alias() == &alias {
-                        associated_account = Some(account_handle);
-                        break;
-                    }
+    // Gets the path of the separate event storage, if it's a SQLite database file.
+    async fn sqlite_event_storage_path(&self) -> crate::Result<Option<PathBuf>> {
+        match crate::storage::get_event_storage_path(&self.storage_path).await {
+            Some(event_storage_path) if event_storage_path.is_file() => {
+                let storage_id = crate::storage::get(&event_storage_path).await?.lock().await.id();
+                if storage_id == crate::storage::sqlite::STORAGE_ID {
+                    Ok(Some(event_storage_path))
+                } else {
+                    Ok(None)
                 }
-                associated_account
             }
-            AccountIdentifier::Address(address) => {
-                let mut associated_account = None;
-                for account_handle in accounts.values() {
-                    let account = account_handle.read().await;
-                    if account.addresses().iter().any(|a| a.address() == &address) {
-                        associated_account = Some(account_handle);
-                        break;
-                    }
+            _ => Ok(None),
+        }
+    }
+
+    // Copies the separate SQLite event storage next to the backup file.
+    async fn backup_events(&self, backup_path: &Path) -> crate::Result<()> {
+        if let Some(event_storage_path) = self.sqlite_event_storage_path().await? {
+            let event_storage = crate::storage::get(&event_storage_path).await?;
+            // no event is written while the database is copied
+            let _event_storage = event_storage.lock().await;
+            fs::copy(&event_storage_path, event_backup_path(backup_path))?;
+        }
+        Ok(())
+    }
+
+    // Replaces the separate SQLite event storage with the event database saved along with the backup, if any.
+    async fn restore_events(&self, backup_path: &Path) -> crate::Result<()> {
+        let event_backup_path = event_backup_path(backup_path);
+        if !event_backup_path.is_file() {
+            return Ok(());
+        }
+        match self.sqlite_event_storage_path().await? {
+            Some(event_storage_path) => {
+                let encryption_key = crate::storage::get(&event_storage_path)
+                    .await?
+                    .lock()
+                    .await
+                    .encryption_key();
+                // the database connection is closed before the file is replaced
+                crate::storage::remove(&event_storage_path).await;
+                fs::copy(&event_backup_path, &event_storage_path)?;
+                crate::storage::init_event_storage(&self.storage_path, &event_storage_path, None, encryption_key).await
+            }
+            None => {
+                log::warn!("[IMPORT] the backup events weren't restored since there's no separate event storage");
+                Ok(())
+            }
+        }
+    }
+
+    // Parses the accounts of the backup storage one at a time, saving each to this manager's storage.
+    // If an account can't be imported, the accounts imported before it are removed, so the import is all or nothing.
+    async fn import_backup_accounts(&self, backup_path: &PathBuf) -> crate::Result<()> {
+        let backup_storage = crate::storage::get(backup_path).await?;
+        let account_keys = backup_storage.lock().await.get_account_keys().await?;
+        let mut imported_account_ids = Vec::new();
+        for key in account_keys {
+            let res = async {
+                let mut account = backup_storage.lock().await.get_account(&key).await?;
+                account.set_storage_path(self.storage_path.clone());
+                account.save().await?;
+                crate::Result::Ok(account)
+            }
+            .await;
+            match res {
+                Ok(account) => {
+                    imported_account_ids.push(account.id().clone());
+                    self.accounts.write().await.insert(
+                        account.id().clone(),
+                        AccountHandle::new(
+                            account,
+                            self.accounts.clone(),
+                            self.account_options,
+                            self.is_monitoring.clone(),
+                        ),
+                    );
+                }
+                Err(e) => {
+                    self.rollback_imported_accounts(&imported_account_ids).await;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Removes the given imported accounts from memory and from the storage.
+    async fn rollback_imported_accounts(&self, account_ids: &[String]) {
+        let mut accounts = self.accounts.write().await;
+        for account_id in account_ids {
+            accounts.remove(account_id);
+        }
+        if let Ok(storage) = crate::storage::get(&self.storage_path).await {
+            let mut storage = storage.lock().await;
+            for account_id in account_ids {
+                if let Err(e) = storage.remove_account(account_id).await {
+                    log::error!("[IMPORT] failed to remove imported account {}: {:?}", account_id, e);
+                }
+            }
+        }
+    }
+
+    /// Checks that the backup file can be decrypted with the given password and lists the accounts it contains,
+    /// without importing them or modifying the backup file.
+    pub async fn verify_backup<S: AsRef<Path>>(
+        &self,
+        source: S,
+        stronghold_password: String,
+    ) -> crate::Result<BackupInfo> {
+        let accounts = Self::read_backup_accounts(source.as_ref(), stronghold_password).await?;
+        Ok(BackupInfo {
+            account_ids: accounts.iter().map(|account| account.id().clone()).collect(),
+        })
+    }
+
+    /// Imports the accounts of a backup into this manager, keeping the existing accounts.
+    /// Accounts conflicting with an existing account (same id, same signer type and index, or same alias)
+    /// are handled according to the `on_conflict` policy.
+    /// Every imported account must have been derived by the manager signer, so it can be used to sign transactions.
+    /// Returns the imported accounts.
+    pub async fn merge_accounts<S: AsRef<Path>>(
+        &mut self,
+        source: S,
+        stronghold_password: String,
+        on_conflict: ConflictPolicy,
+    ) -> crate::Result<Vec<AccountHandle>> {
+        self.check_storage_encryption()?;
+        let backup_accounts = Self::read_backup_accounts(source.as_ref(), stronghold_password).await?;
+        self.merge_backup_accounts(backup_accounts, on_conflict).await
+    }
+
+    /// Exports a single account to a Stronghold snapshot encrypted with the given password,
+    /// e.g. to move it to another wallet with [import_account](#method.import_account).
+    /// The snapshot only holds the account data; the seed isn't exported, so the destination wallet
+    /// must use the same seed to sign for the account.
+    /// If the destination is a directory, the snapshot file name is generated from the account id.
+    /// Returns the snapshot path.
+    pub async fn export_account<I: Into<AccountIdentifier>, P: AsRef<Path>>(
+        &self,
+        account_id: I,
+        destination: P,
+        stronghold_password: String,
+    ) -> crate::Result<PathBuf> {
+        let account = self.get_account(account_id).await?.read().await.clone();
+
+        let destination = destination.as_ref().to_path_buf();
+        let destination = if destination.is_dir() {
+            destination.join(backup_filename(&format!("{}.stronghold", account.id())))
+        } else if destination.parent().map(|parent| parent.is_dir()).unwrap_or_default()
+            && destination.extension().unwrap_or_default() == "stronghold"
+        {
+            destination
+        } else {
+            return Err(crate::Error::InvalidBackupDestination);
+        };
+        if destination.exists() {
+            return Err(crate::Error::StorageExists);
+        }
+
+        {
+            // create a account manager to setup the stronghold storage for the export
+            let mut manager = Self::builder()
+                .with_storage(&destination, None)
+                .unwrap() // safe to unwrap - password is None
+                .skip_polling()
+                .with_stronghold_storage()
+                .finish()
+                .await?;
+            manager.set_stronghold_password(stronghold_password).await?;
+            crate::storage::get(&destination)
+                .await?
+                .lock()
+                .await
+                .save_account(account.id(), &account)
+                .await?;
+        }
+
+        crate::stronghold::unload_snapshot(&destination, true).await?;
+        crate::storage::remove(&destination).await;
+        // wait for stronghold to finish its tasks
+        crate::stronghold::wait_for_pending_tasks().await?;
+
+        Ok(destination)
+    }
+
+    /// Imports an account exported with [export_account](#method.export_account), keeping the existing accounts.
+    /// Fails with `AccountConflict` if an account with the same id, the same signer type and index, or the same alias
+    /// already exists.
+    /// The account must have been derived by the manager signer, so it can be used to sign transactions.
+    pub async fn import_account<S: AsRef<Path>>(
+        &mut self,
+        source: S,
+        stronghold_password: String,
+    ) -> crate::Result<AccountHandle> {
+        self.check_storage_encryption()?;
+        let exported_accounts = Self::read_backup_accounts(source.as_ref(), stronghold_password).await?;
+        if exported_accounts.len() != 1 {
+            return Err(crate::Error::InvalidBackupFile);
+        }
+        let mut imported_accounts = self
+            .merge_backup_accounts(exported_accounts, ConflictPolicy::Error)
+            .await?;
+        Ok(imported_accounts.remove(0))
+    }
+
+    // Imports the given accounts into this manager, handling the conflicting accounts with the `on_conflict` policy.
+    async fn merge_backup_accounts(
+        &mut self,
+        mut backup_accounts: Vec<Account>,
+        on_conflict: ConflictPolicy,
+    ) -> crate::Result<Vec<AccountHandle>> {
+        backup_accounts.sort_by_key(|account| *account.index());
+        // the aliases are checked and the accounts inserted without a concurrent rename or account creation
+        let _alias_update_guard = crate::account::ALIAS_UPDATE_LOCK.lock().await;
+
+        let mut accounts_to_merge = Vec::new();
+        {
+            let accounts = self.accounts.read().await;
+            let mut aliases = Vec::new();
+            let mut keys = Vec::new();
+            for account_handle in accounts.values() {
+                let account = account_handle.read().await;
+                aliases.push(account.alias().clone());
+                keys.push((account.id().clone(), account.signer_type().clone(), *account.index()));
+            }
+
+            for account in backup_accounts {
+                let account_conflict = keys.iter().any(|(id, signer_type, index)| {
+                    id == account.id() || (signer_type == account.signer_type() && index == account.index())
+                });
+                let alias_conflict = aliases.contains(account.alias());
+                if !account_conflict && !alias_conflict {
+                    aliases.push(account.alias().clone());
+                    accounts_to_merge.push((account, None));
+                    continue;
+                }
+                match on_conflict {
+                    ConflictPolicy::Error => return Err(crate::Error::AccountConflict(account.id().clone())),
+                    ConflictPolicy::Skip => {
+                        log::debug!("[MERGE] skipping conflicting account {}", account.id());
+                    }
+                    // the account id and index are derived from the seed, so only the alias can be renamed
+                    ConflictPolicy::Rename if account_conflict => {
+                        log::debug!("[MERGE] skipping account {} since it already exists", account.id());
+                    }
+                    ConflictPolicy::Rename => {
+                        let mut suffix = 1;
+                        let mut alias = format!("{} ({})", account.alias(), suffix);
+                        while aliases.contains(&alias) {
+                            suffix += 1;
+                            alias = format!("{} ({})", account.alias(), suffix);
+                        }
+                        aliases.push(alias.clone());
+                        accounts_to_merge.push((account, Some(alias)));
+                    }
+                }
+            }
+        }
+
+        // validate the signer compatibility before changing the manager state
+        for (account, _) in &accounts_to_merge {
+            if let Some(address) = account
+                .addresses()
+                .iter()
+                .find(|a| *a.key_index() == 0 && !a.internal() && !a.imported())
+            {
+                let derived_address = crate::address::get_iota_address(
+                    &account,
+                    0,
+                    false,
+                    address.address().bech32_hrp().to_string(),
+                    GenerateAddressMetadata { syncing: false },
+                )
+                .await?;
+                if &derived_address != address.address() {
+                    return Err(crate::Error::IncompatibleSigner(account.id().clone()));
+                }
+            }
+        }
+
+        let mut merged_accounts = Vec::new();
+        for (mut account, alias) in accounts_to_merge {
+            account.set_storage_path(self.storage_path.clone());
+            account.set_skip_persistence(false);
+            match alias {
+                Some(alias) => account.set_alias(alias).await?,
+                None => account.save().await?,
+            }
+            let account_handle = AccountHandle::new(
+                account,
+                self.accounts.clone(),
+                self.account_options,
+                self.is_monitoring.clone(),
+            );
+            self.accounts
+                .write()
+                .await
+                .insert(account_handle.read().await.id().clone(), account_handle.clone());
+            crate::monitor::monitor_account_addresses_balance(account_handle.clone()).await;
+            merged_accounts.push(account_handle);
+        }
+
+        Ok(merged_accounts)
+    }
+
+    // Reads the accounts stored on a backup file without modifying it.
+    async fn read_backup_accounts(source: &Path, password: String) -> crate::Result<Vec<Account>> {
+        if source.is_dir() || !source.exists() || source.extension().unwrap_or_default() != "stronghold" {
+            return Err(crate::Error::InvalidBackupFile);
+        }
+
+        // read a copy of the backup so the stronghold snapshot is never written to the original file
+        let mut id = [0; 16];
+        crypto::utils::rand::fill(&mut id).map_err(|e| crate::Error::Storage(format!("{:?}", e)))?;
+        let snapshot_path = std::env::temp_dir().join(format!("backup-{}.stronghold", hex::encode(id)));
+        fs::copy(source, &snapshot_path)?;
+
+        let res = Self::load_backup_accounts(&snapshot_path, password).await;
+
+        let _ = crate::stronghold::unload_snapshot(&snapshot_path, false).await;
+        crate::storage::remove(&snapshot_path).await;
+        // wait for stronghold to finish its tasks
+        let waited = crate::stronghold::wait_for_pending_tasks().await;
+        let _ = fs::remove_file(&snapshot_path);
+        waited?;
+
+        res
+    }
+
+    async fn load_backup_accounts(snapshot_path: &PathBuf, password: String) -> crate::Result<Vec<Account>> {
+        crate::stronghold::load_snapshot(snapshot_path, stronghold_password(password)).await?;
+        let storage = crate::storage::stronghold::StrongholdStorageAdapter::new(snapshot_path)?;
+        crate::storage::set(snapshot_path, None, Box::new(storage)).await;
+        let accounts = crate::storage::get(snapshot_path)
+            .await?
+            .lock()
+            .await
+            .get_accounts()
+            .await?;
+        Ok(accounts)
+    }
+
+    /// Gets the account associated with the given identifier.
+    pub async fn get_account<I: Into<AccountIdentifier>>(&self, account_id: I) -> crate::Result<AccountHandle> {
+        self.check_storage_encryption()?;
+        let account_id = account_id.into();
+        let accounts = self.accounts.read().await;
+
+        let account = match account_id {
+            AccountIdentifier::Id(id) => accounts.get(&id),
+            AccountIdentifier::Index(index) => {
+                let mut associated_account = None;
+                for account_handle in accounts.values() {
+                    let account = account_handle.read().await;
+                    if account.index() == &index {
+                        // if we already found an account with this index,
+                        // we error out since this is an incorrect usage of the API
+                        // you can't use the index to get an account if you're using multiple signer types
+                        // since there's multiple index sequences in that case
+                        if associated_account.is_some() {
+                            return Err(crate::Error::CannotUseIndexIdentifier);
+                        }
+                        associated_account = Some(account_handle);
+                    }
+                }
+                associated_account
+            }
+            AccountIdentifier::Alias(alias) => {
+                let mut associated_account = None;
+                for account_handle in accounts.values() {
+                    let account = account_handle.read().await;
+                    if account.alias() == &alias {
+                        associated_account = Some(account_handle);
+                        break;
+                    }
+                }
+                associated_account
+            }
+            AccountIdentifier::Address(address) => {
+                let mut associated_account = None;
+                for account_handle in accounts.values() {
+                    let account = account_handle.read().await;
+                    if account.addresses().iter().any(|a| a.address() == &address) {
+                        associated_account = Some(account_handle);
+                        break;
+                    }
                 }
                 associated_account
             }
@@ -895,6 +1868,61 @@ impl AccountManager {
         Ok(accounts.into_iter().map(|(_, account)| account).collect())
     }
 
+    /// Invokes `f` with each account, in no particular order, without cloning the accounts.
+    /// Useful for read-only aggregations (e.g. total balance or message counts) that run frequently.
+    /// Each account is read-locked while `f` runs on it, so `f` should be cheap.
+    pub async fn for_each_account<F: FnMut(&Account)>(&self, mut f: F) -> crate::Result<()> {
+        self.check_storage_encryption()?;
+        // the handles are collected first, so the account store isn't locked during the iteration
+        let account_handles: Vec<AccountHandle> = self.accounts.read().await.values().cloned().collect();
+        for account_handle in account_handles {
+            let account = account_handle.read().await;
+            f(&account);
+        }
+        Ok(())
+    }
+
+    /// Groups the accounts sharing the same first public address, i.e. derived from the same seed and index.
+    /// Such accounts (e.g. the same seed imported twice under different aliases) track the same funds
+    /// and can produce conflicting transactions. Accounts without duplicates aren't returned.
+    /// The account id is derived from the account addresses, so a new account with the same id as an existing one
+    /// is refused with `Error::AccountConflict`; the duplicates found here were created with different address sets.
+    pub async fn find_duplicate_accounts(&self) -> Vec<Vec<AccountIdentifier>> {
+        let mut groups: Vec<(AddressWrapper, Vec<(usize, String)>)> = Vec::new();
+        for account_handle in self.accounts.read().await.values() {
+            let account = account_handle.read().await;
+            let first_address = match account
+                .addresses()
+                .iter()
+                .find(|a| !a.internal() && *a.key_index() == 0)
+            {
+                Some(address) => address.address(),
+                None => continue,
+            };
+            let account_id = (*account.index(), account.id().clone());
+            match groups
+                .iter_mut()
+                .find(|(address, _)| address.eq_ignore_hrp(first_address))
+            {
+                Some((_, accounts)) => accounts.push(account_id),
+                None => groups.push((first_address.clone(), vec![account_id])),
+            }
+        }
+        let mut duplicates: Vec<Vec<(usize, String)>> = groups
+            .into_iter()
+            .map(|(_, mut accounts)| {
+                accounts.sort();
+                accounts
+            })
+            .filter(|accounts| accounts.len() > 1)
+            .collect();
+        duplicates.sort();
+        duplicates
+            .into_iter()
+            .map(|accounts| accounts.into_iter().map(|(_, id)| AccountIdentifier::Id(id)).collect())
+            .collect()
+    }
+
     /// Reattaches an unconfirmed transaction.
     pub async fn reattach<I: Into<AccountIdentifier>>(
         &self,
@@ -921,6 +1949,82 @@ impl AccountManager {
     ) -> crate::Result<Message> {
         self.get_account(account_id).await?.retry(message_id).await
     }
+
+    /// Gets the persisted events of every type saved since the given timestamp, ordered by the time they were saved
+    /// at. Useful to catch up on the event timeline, e.g. when a UI reconnects.
+    pub async fn get_events_since(&self, from_timestamp: Timestamp) -> crate::Result<Vec<TimestampedEvent>> {
+        crate::storage::get_event_storage(&self.storage_path)
+            .await?
+            .lock()
+            .await
+            .get_events_since(from_timestamp)
+            .await
+    }
+
+    /// Subscribes to the stream of balance change, confirmation change, reattachment and transaction events,
+    /// so they can be handled as they happen instead of polling the persisted events.
+    /// See [subscribe_events](../event/fn.subscribe_events.html).
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<WalletEvent> {
+        crate::event::subscribe_events()
+    }
+
+    /// Gets the balance history of an account from the persisted
+    /// [balance change events](struct.AccountManagerBuilder.html#method.with_event_persistence),
+    /// e.g. to draw a balance chart.
+    /// Returns the cumulative balance at `from` and at the end of each `bucket` long interval until `to`.
+    /// The balance is computed from the first persisted event, so it's only accurate if the events were persisted
+    /// since the account was created. The bucket is rounded down to whole seconds (at least one second).
+    pub async fn balance_history<I: Into<AccountIdentifier>>(
+        &self,
+        account_id: I,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        bucket: Duration,
+    ) -> crate::Result<Vec<(DateTime<Local>, i64)>> {
+        let account_id = self.get_account(account_id).await?.id().await;
+        let from_timestamp = from.timestamp();
+        let to_timestamp = to.timestamp();
+        if from_timestamp > to_timestamp {
+            return Ok(Vec::new());
+        }
+        let bucket = bucket.as_secs().max(1) as i64;
+
+        let events = crate::storage::get_event_storage(&self.storage_path)
+            .await?
+            .lock()
+            .await
+            .get_balance_change_events_until(to_timestamp)
+            .await?;
+        let mut changes = events
+            .into_iter()
+            .filter(|(_, event)| event.account_id == account_id)
+            .map(|(timestamp, event)| {
+                (
+                    timestamp,
+                    event.balance_change.received as i64 - event.balance_change.spent as i64,
+                )
+            })
+            .peekable();
+
+        let mut balance = 0;
+        let mut history = Vec::new();
+        let mut bucket_end = from_timestamp;
+        loop {
+            while let Some((timestamp, change)) = changes.peek() {
+                if *timestamp > bucket_end {
+                    break;
+                }
+                balance += change;
+                changes.next();
+            }
+            history.push((Local.timestamp(bucket_end, 0), balance));
+            if bucket_end == to_timestamp {
+                break;
+            }
+            bucket_end = (bucket_end + bucket).min(to_timestamp);
+        }
+        Ok(history)
+    }
 }
 
 macro_rules! event_getters_impl {
@@ -933,7 +2037,7 @@ macro_rules! event_getters_impl {
                 skip: usize,
                 from_timestamp: T,
             ) -> crate::Result<Vec<$event_ty>> {
-                crate::storage::get(&self.storage_path)
+                crate::storage::get_event_storage(&self.storage_path)
                     .await?
                     .lock()
                     .await
@@ -946,7 +2050,7 @@ macro_rules! event_getters_impl {
                 &self,
                 from_timestamp: T,
             ) -> crate::Result<usize> {
-                let count = crate::storage::get(&self.storage_path)
+                let count = crate::storage::get_event_storage(&self.storage_path)
                     .await?
                     .lock()
                     .await
@@ -985,6 +2089,8 @@ pub struct AccountsSynchronizer {
     gap_limit: Option<usize>,
     account_options: AccountOptions,
     is_monitoring: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    account_handles: Option<Vec<AccountHandle>>,
 }
 
 impl AccountsSynchronizer {
@@ -1003,6 +2109,16 @@ impl AccountsSynchronizer {
             gap_limit: None,
             account_options,
             is_monitoring,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            account_handles: None,
+        }
+    }
+
+    // the accounts to sync: the selected accounts or every account on the store
+    async fn account_handles(&self) -> Vec<AccountHandle> {
+        match &self.account_handles {
+            Some(account_handles) => account_handles.clone(),
+            None => self.accounts.read().await.values().cloned().collect(),
         }
     }
 
@@ -1018,31 +2134,61 @@ impl AccountsSynchronizer {
         self
     }
 
+    /// Sets the flag used to cancel the synchronization.
+    /// When the flag is set to `true`, the accounts that weren't synced yet and the account discovery are skipped;
+    /// the accounts that were already synced are still persisted and returned.
+    pub fn cancellation_flag(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
     /// Syncs the accounts with the Tangle.
+    /// Every account is synced and persisted even if another account fails to sync;
+    /// the first account error is returned in that case.
+    /// Use [execute_each](#method.execute_each) to get the result of each account.
     pub async fn execute(self) -> crate::Result<Vec<SyncedAccount>> {
-        let accounts = self.accounts.clone();
-        for account_handle in accounts.read().await.values() {
+        self.execute_each().await.into_iter().map(|(_, res)| res).collect()
+    }
+
+    /// Syncs the accounts with the Tangle, returning the sync result of each account paired with its id.
+    /// An account that fails to sync doesn't prevent the other accounts from being synced and persisted.
+    pub async fn execute_each(self) -> Vec<(String, crate::Result<SyncedAccount>)> {
+        let account_handles = self.account_handles().await;
+        for account_handle in &account_handles {
             account_handle.disable_mqtt();
         }
-        let result = self.execute_internal().await;
-        for account_handle in accounts.read().await.values() {
+        let results = self.execute_internal().await;
+        for account_handle in &account_handles {
             account_handle.enable_mqtt();
         }
-        result
+        results
     }
 
-    async fn execute_internal(self) -> crate::Result<Vec<SyncedAccount>> {
+    async fn execute_internal(self) -> Vec<(String, crate::Result<SyncedAccount>)> {
         let _lock = self.mutex.lock().await;
 
         let mut tasks = Vec::new();
         {
-            let accounts = self.accounts.read().await;
             let address_index = self.address_index;
             let gap_limit = self.gap_limit;
-            for account_handle in accounts.values() {
-                let account_handle = account_handle.clone();
+            // bounds the number of accounts syncing at once; the others are queued on the semaphore
+            let semaphore = self
+                .account_options
+                .max_concurrent_account_syncs
+                .map(|limit| Arc::new(Semaphore::new(limit)));
+            for account_handle in self.account_handles().await {
+                let cancelled = self.cancelled.clone();
+                let semaphore = semaphore.clone();
                 tasks.push(async move {
                     tokio::spawn(async move {
+                        let _permit = match &semaphore {
+                            Some(semaphore) => Some(semaphore.acquire().await.expect("sync semaphore closed")),
+                            None => None,
+                        };
+                        if cancelled.load(Ordering::Relaxed) {
+                            log::debug!("[SYNC] skipping account sync because the synchronization was cancelled");
+                            return None;
+                        }
                         let mut sync = account_handle.sync().await;
                         if let Some(index) = address_index {
                             sync = sync.address_index(index);
@@ -1050,20 +2196,30 @@ impl AccountsSynchronizer {
                         if let Some(limit) = gap_limit {
                             sync = sync.gap_limit(limit);
                         }
-                        let synced_data = sync.get_new_history().await?;
-                        crate::Result::Ok((account_handle, synced_data))
+                        let synced_data = sync.get_new_history().await;
+                        Some((account_handle, synced_data))
                     })
                     .await
                 });
             }
         }
 
+        let mut results = Vec::new();
         let mut synced_data = Vec::new();
         for res in futures::future::try_join_all(tasks)
             .await
             .expect("failed to sync accounts")
         {
-            let (account_handle, data) = res?;
+            let (account_handle, data) = match res {
+                Some((account_handle, Ok(data))) => (account_handle, data),
+                Some((account_handle, Err(e))) => {
+                    let account_id = account_handle.id().await;
+                    log::error!("[SYNC] failed to sync account {}: {:?}", account_id, e);
+                    results.push((account_id, Err(e)));
+                    continue;
+                }
+                None => continue,
+            };
             let account_handle_ = account_handle.clone();
             let mut account = account_handle_.write().await;
             let addresses_before_sync: Vec<(String, u64, HashMap<OutputId, AddressOutput>)> = account
@@ -1075,7 +2231,6 @@ impl AccountsSynchronizer {
             synced_data.push((account_handle, addresses_before_sync, data));
         }
 
-        let mut synced_accounts = Vec::new();
         let mut last_account = None;
         let mut last_account_index = 0;
         for (account_handle, _, _) in &synced_data {
@@ -1089,21 +2244,31 @@ impl AccountsSynchronizer {
                         .all(|addr| *addr.balance() == 0 && addr.outputs().is_empty()),
                     account.client_options().clone(),
                     account.signer_type().clone(),
+                    *account.coin_type(),
                 ));
             }
         }
 
         let discovered_accounts_res = match last_account {
-            Some((is_empty, client_options, signer_type)) => {
-                if !is_empty {
+            Some(_) if self.account_handles.is_some() => {
+                log::debug!("[SYNC] skipping account discovery because only a set of accounts is being synced");
+                Ok(vec![])
+            }
+            Some((is_empty, client_options, signer_type, coin_type)) => {
+                if self.cancelled.load(Ordering::Relaxed) {
+                    log::debug!("[SYNC] skipping account discovery because the synchronization was cancelled");
+                    Ok(vec![])
+                } else if !is_empty {
                     log::debug!("[SYNC] running account discovery because the latest account is not empty");
                     discover_accounts(
                         self.accounts.clone(),
                         &self.storage_file_path,
                         &client_options,
                         Some(signer_type),
+                        coin_type,
                         self.account_options,
                         self.is_monitoring.clone(),
+                        self.cancelled.clone(),
                     )
                     .await
                 } else {
@@ -1121,8 +2286,8 @@ impl AccountsSynchronizer {
                     let account_handle_ = account_handle.clone();
                     let mut account = account_handle_.write().await;
                     account.set_skip_persistence(false);
+                    // the account is saved once its messages are appended below
                     account.set_addresses(synced_account_data.addresses.to_vec());
-                    account.save().await?;
                     accounts.insert(account.id().clone(), account_handle.clone());
                     discovered_account_ids.push(account.id().clone());
                     synced_data.push((account_handle, Vec::new(), synced_account_data));
@@ -1131,90 +2296,98 @@ impl AccountsSynchronizer {
         }
 
         for (account_handle, addresses_before_sync, data) in synced_data {
-            let mut account = account_handle.write().await;
-            let messages_before_sync: Vec<(MessageId, Option<bool>)> =
-                account.messages().iter().map(|m| (*m.id(), *m.confirmed())).collect();
-
-            let parsed_messages = data.parse_messages(account_handle.accounts.clone(), &account).await?;
-            account.append_messages(parsed_messages.to_vec());
-            account.set_last_synced_at(Some(chrono::Local::now()));
-            account.save().await?;
-
-            let mut new_messages = Vec::new();
-            let mut confirmation_changed_messages = Vec::new();
-            for message in parsed_messages {
-                if !messages_before_sync.iter().any(|(id, _)| id == message.id()) {
-                    new_messages.push(message.clone());
-                }
-                if messages_before_sync
-                    .iter()
-                    .any(|(id, confirmed)| id == message.id() && confirmed != message.confirmed())
-                {
-                    confirmation_changed_messages.push(message);
-                }
-            }
-            if !discovered_account_ids.contains(account.id()) {
-                let persist_events = account_handle.account_options.persist_events;
-                let events = AccountSynchronizer::get_events(
-                    account_handle.account_options,
-                    &addresses_before_sync,
-                    account.addresses(),
-                    &new_messages,
-                    &confirmation_changed_messages,
-                )
-                .await?;
-                for message in events.new_transaction_events {
-                    emit_transaction_event(TransactionEventType::NewTransaction, &account, message, persist_events)
-                        .await?;
-                }
-                for confirmation_change_event in events.confirmation_change_events {
-                    emit_confirmation_state_change(
-                        &account,
-                        confirmation_change_event.message,
-                        confirmation_change_event.confirmed,
-                        persist_events,
-                    )
-                    .await?;
-                }
-                for balance_change_event in events.balance_change_events {
-                    emit_balance_change(
-                        &account,
-                        &balance_change_event.address,
-                        balance_change_event.message_id,
-                        balance_change_event.balance_change,
-                        persist_events,
-                    )
-                    .await?;
-                }
+            let account_id = account_handle.id().await;
+            let is_discovered = discovered_account_ids.contains(&account_id);
+            let res = persist_synced_account(account_handle, addresses_before_sync, data, is_discovered).await;
+            if let Err(e) = &res {
+                log::error!("[SYNC] failed to persist the sync of account {}: {:?}", account_id, e);
             }
+            results.push((account_id, res));
+        }
 
-            // drop the account so SyncedAccount::from doesn't deadlock
-            drop(account);
-            let mut synced_account = SyncedAccount::from(account_handle.clone()).await;
-            let mut updated_messages = new_messages;
-            updated_messages.extend(confirmation_changed_messages);
-            synced_account.messages = updated_messages;
+        results
+    }
+}
 
-            let account = account_handle.read().await;
-            synced_account.addresses = account
-                .addresses()
-                .iter()
-                .filter(|a| {
-                    match addresses_before_sync
-                        .iter()
-                        .find(|(addr, _, _)| addr == &a.address().to_bech32())
-                    {
-                        Some((_, balance, outputs)) => balance != a.balance() || outputs != a.outputs(),
-                        None => true,
-                    }
-                })
-                .cloned()
-                .collect();
-            synced_accounts.push(synced_account);
+// Stores the synced data of the account and emits its sync events.
+// Discovered accounts don't emit events since all of their history is new.
+async fn persist_synced_account(
+    account_handle: AccountHandle,
+    addresses_before_sync: Vec<(String, u64, HashMap<OutputId, AddressOutput>)>,
+    data: SyncedAccountData,
+    is_discovered: bool,
+) -> crate::Result<SyncedAccount> {
+    let mut account = account_handle.write().await;
+    let messages_before_sync: Vec<(MessageId, Option<bool>)> =
+        account.messages().iter().map(|m| (*m.id(), *m.confirmed())).collect();
+
+    let parsed_messages = data.parse_messages(account_handle.accounts.clone(), &account).await?;
+    account.append_messages(parsed_messages.to_vec());
+    account.set_last_synced_at(Some(chrono::Local::now()));
+    account.mark_dirty();
+    // the addresses appended by the sync and its messages are written at once
+    account.save_if_dirty().await?;
+
+    let mut new_messages = Vec::new();
+    let mut confirmation_changed_messages = Vec::new();
+    for message in parsed_messages {
+        if !messages_before_sync.iter().any(|(id, _)| id == message.id()) {
+            new_messages.push(message.clone());
+        }
+        if messages_before_sync
+            .iter()
+            .any(|(id, confirmed)| id == message.id() && confirmed != message.confirmed())
+        {
+            confirmation_changed_messages.push(message);
         }
-
-        Ok(synced_accounts)
     }
+    if !is_discovered {
+        let events = AccountSynchronizer::get_events(
+            account_handle.account_options,
+            &account_handle.emitted_balance_states,
+            &addresses_before_sync,
+            account.addresses(),
+            &new_messages,
+            &confirmation_changed_messages,
+        )
+        .await?;
+        events.emit(&account, account_handle.account_options).await?;
+        if let Some(suggested_gap) = data.suggested_gap_limit {
+            log::warn!(
+                "[SYNC] funds found up to the gap limit boundary; syncing with a gap limit of {} is suggested",
+                suggested_gap
+            );
+            emit_gap_limit_reached(&account, suggested_gap).await;
+        }
+    }
+
+    // drop the account so SyncedAccount::from doesn't deadlock
+    drop(account);
+    let mut synced_account = SyncedAccount::from(account_handle.clone()).await;
+    let mut updated_messages = new_messages;
+    updated_messages.extend(confirmation_changed_messages);
+    synced_account.messages = updated_messages;
+    synced_account.sync_parameters = Some(data.sync_parameters);
+    synced_account.suggested_gap_limit = data.suggested_gap_limit;
+    // the addresses that couldn't be synced are reported like on a single account sync
+    synced_account.failed_addresses = data.failed_addresses;
+
+    let account = account_handle.read().await;
+    synced_account.addresses = account
+        .addresses()
+        .iter()
+        .filter(|a| {
+            match addresses_before_sync
+                .iter()
+                .find(|(addr, _, _)| addr == &a.address().to_bech32())
+            {
+                Some((_, balance, outputs)) => balance != a.balance() || outputs != a.outputs(),
+                None => true,
+            }
+        })
+        .cloned()
+        .collect();
+    Ok(synced_account)
 }
 
 async fn poll(
@@ -1225,7 +2398,14 @@ async fn poll(
     should_sync: bool,
     is_monitoring: Arc<AtomicBool>,
     automatic_output_consolidation: bool,
+    on_sync_complete: Option<&SyncCompleteCallback>,
 ) -> crate::Result<()> {
+    for account_handle in accounts.read().await.values() {
+        if let Err(e) = reconcile_pending_transfers(account_handle).await {
+            log::error!("[POLLING] failed to reconcile the interrupted transfers: {:?}", e);
+        }
+    }
+
     let retried = if should_sync {
         let synced_accounts = AccountsSynchronizer::new(
             sync_accounts_lock,
@@ -1234,13 +2414,22 @@ async fn poll(
             account_options,
             is_monitoring,
         )
-        .execute()
-        .await?;
+        .execute_each()
+        .await
+        .into_iter()
+        // the failed account syncs are logged by the synchronizer; the other accounts are still polled
+        .filter_map(|(_, res)| res.ok())
+        .collect::<Vec<SyncedAccount>>();
 
         log::debug!("[POLLING] synced accounts");
 
         let retried_messages = retry_unconfirmed_transactions(&synced_accounts).await?;
         consolidate_outputs_if_needed(automatic_output_consolidation, &synced_accounts).await?;
+
+        if let Some(on_sync_complete) = on_sync_complete {
+            on_sync_complete(&synced_accounts);
+        }
+
         retried_messages
     } else {
         log::info!("[POLLING] skipping syncing process because MQTT is running");
@@ -1272,10 +2461,8 @@ async fn poll(
                             promotions.push(new_message);
                         }
                     }
-                    Err(crate::Error::ClientError(ref e)) => {
-                        if let iota::client::Error::NoNeedPromoteOrReattach(_) = e.as_ref() {
-                            no_need_promote_or_reattach.push(message_id);
-                        }
+                    Err(crate::Error::MessageAlreadyConfirmed(_)) => {
+                        no_need_promote_or_reattach.push(message_id);
                     }
                     _ => {}
                 }
@@ -1295,6 +2482,13 @@ async fn poll(
     };
 
     for retried_data in retried {
+        // skip the account storage write if nothing was retried
+        if retried_data.reattached.is_empty()
+            && retried_data.promoted.is_empty()
+            && retried_data.no_need_promote_or_reattach.is_empty()
+        {
+            continue;
+        }
         let mut account = retried_data.account_handle.write().await;
         let client = crate::client::get_client(
             account.client_options(),
@@ -1322,23 +2516,56 @@ async fn poll(
         account.append_messages(retried_data.promoted);
 
         for message_id in retried_data.no_need_promote_or_reattach {
+            let metadata = client.read().await.get_message().metadata(&message_id).await;
             let message = account.get_message_mut(&message_id).unwrap();
-            if let Ok(metadata) = client.read().await.get_message().metadata(&message_id).await {
+            if let Ok(metadata) = metadata {
                 if let Some(ledger_inclusion_state) = metadata.ledger_inclusion_state {
-                    let confirmed = ledger_inclusion_state == LedgerInclusionStateDto::Included;
-                    message.set_confirmed(Some(confirmed));
-                    let message = message.clone();
-                    emit_confirmation_state_change(
-                        &account,
-                        message,
-                        confirmed,
-                        retried_data.account_handle.account_options.persist_events,
+                    let (confirmed, confirming) = crate::client::apply_confirmation_threshold(
+                        &*client,
+                        &message_id,
+                        Some(ledger_inclusion_state == LedgerInclusionStateDto::Included),
+                        retried_data.account_handle.account_options.confirmation_threshold,
                     )
                     .await?;
+                    let changed = message.confirming() != &confirming
+                        || (confirmed.is_some() && message.confirmed() != &confirmed);
+                    message.set_confirming(confirming);
+                    if let Some(confirmed) = confirmed {
+                        message.set_confirmed(Some(confirmed));
+                        let message = message.clone();
+                        emit_confirmation_state_change(
+                            &account,
+                            message,
+                            confirmed,
+                            retried_data.account_handle.account_options.persist_events,
+                        )
+                        .await?;
+                    }
+                    if changed {
+                        account.mark_dirty();
+                    }
                 }
             }
         }
+        account.save_if_dirty().await?;
+    }
+
+    expire_addresses(&accounts).await?;
+
+    Ok(())
+}
+
+async fn expire_addresses(accounts: &AccountStore) -> crate::Result<()> {
+    for account_handle in accounts.read().await.values() {
+        let mut account = account_handle.write().await;
+        let expired_addresses = account.flag_expired_addresses(Local::now());
+        if expired_addresses.is_empty() {
+            continue;
+        }
         account.save().await?;
+        for address in expired_addresses {
+            emit_address_expired(&account, address).await;
+        }
     }
     Ok(())
 }
@@ -1348,57 +2575,106 @@ async fn discover_accounts(
     storage_path: &PathBuf,
     client_options: &ClientOptions,
     signer_type: Option<SignerType>,
+    coin_type: u32,
     account_options: AccountOptions,
     is_monitoring: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
 ) -> crate::Result<Vec<(AccountHandle, SyncedAccountData)>> {
     let mut synced_accounts = vec![];
+    // the empty accounts found after the last funded one; stored only if a funded account follows them
+    let mut empty_accounts = vec![];
     let mut index = accounts.read().await.len();
-    loop {
-        let mut account_initialiser = AccountInitialiser::new(
-            client_options.clone(),
-            accounts.clone(),
-            storage_path.clone(),
-            account_options,
-            is_monitoring.clone(),
-        )
-        .skip_persistence()
-        .index(index);
-        if let Some(signer_type) = &signer_type {
-            account_initialiser = account_initialiser.signer_type(signer_type.clone());
-        }
-        let account_handle = account_initialiser.initialise().await?;
-        log::debug!(
-            "[SYNC] discovering account {}, signer type {:?}",
-            account_handle.read().await.alias(),
-            account_handle.read().await.signer_type()
-        );
-        match account_handle.sync().await.get_new_history().await {
-            Ok(synced_account_data) => {
-                let is_empty = synced_account_data
-                    .addresses
-                    .iter()
-                    .all(|a| *a.balance() == 0 && a.outputs().is_empty());
-                log::debug!("[SYNC] discovered account is empty? {}", is_empty);
-                if is_empty {
-                    break;
-                } else {
-                    index += 1;
-                    synced_accounts.push((account_handle, synced_account_data));
+    'discovery: loop {
+        if cancelled.load(Ordering::Relaxed) {
+            log::debug!("[SYNC] stopping account discovery because the synchronization was cancelled");
+            break;
+        }
+        let window = index..index + account_options.account_discovery_window;
+        index = window.end;
+        let probes = window.map(|index| {
+            discover_account(
+                accounts.clone(),
+                storage_path,
+                client_options,
+                signer_type.clone(),
+                coin_type,
+                account_options,
+                is_monitoring.clone(),
+                index,
+            )
+        });
+        // the results are processed in index order so a funded account is never stored before the ones preceding it
+        for probe in futures::future::join_all(probes).await {
+            match probe {
+                Ok(Some((account_handle, synced_account_data))) => {
+                    let is_empty = synced_account_data
+                        .addresses
+                        .iter()
+                        .all(|a| *a.balance() == 0 && a.outputs().is_empty());
+                    log::debug!("[SYNC] discovered account is empty? {}", is_empty);
+                    if is_empty {
+                        empty_accounts.push((account_handle, synced_account_data));
+                        if empty_accounts.len() >= account_options.account_discovery_gap_limit {
+                            break 'discovery;
+                        }
+                    } else {
+                        synced_accounts.append(&mut empty_accounts);
+                        synced_accounts.push((account_handle, synced_account_data));
+                    }
+                }
+                // the index was used by an account created with an explicit index
+                Ok(None) => {}
+                Err(e) => {
+                    log::error!("[SYNC] failed to sync to discover account: {:?}", e);
+                    // break if the account failed to sync
+                    // this ensures that the previously discovered accounts get stored.
+                    break 'discovery;
                 }
-            }
-            Err(e) => {
-                log::error!("[SYNC] failed to sync to discover account: {:?}", e);
-                // break if the account failed to sync
-                // this ensures that the previously discovered accounts get stored.
-                break;
             }
         }
     }
     Ok(synced_accounts)
 }
 
+// Creates the account with the given index and syncs it, returning `None` if the index is already used.
+async fn discover_account(
+    accounts: AccountStore,
+    storage_path: &PathBuf,
+    client_options: &ClientOptions,
+    signer_type: Option<SignerType>,
+    coin_type: u32,
+    account_options: AccountOptions,
+    is_monitoring: Arc<AtomicBool>,
+    index: usize,
+) -> crate::Result<Option<(AccountHandle, SyncedAccountData)>> {
+    let mut account_initialiser = AccountInitialiser::new(
+        client_options.clone(),
+        accounts,
+        storage_path.clone(),
+        account_options,
+        is_monitoring,
+    )
+    .skip_persistence()
+    .index(index)
+    .coin_type(coin_type);
+    if let Some(signer_type) = signer_type {
+        account_initialiser = account_initialiser.signer_type(signer_type);
+    }
+    let account_handle = match account_initialiser.initialise().await {
+        Ok(account_handle) => account_handle,
+        Err(crate::Error::AccountIndexAlreadyExists) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    log::debug!(
+        "[SYNC] discovering account {}, signer type {:?}",
+        account_handle.read().await.alias(),
+        account_handle.read().await.signer_type()
+    );
+    let synced_account_data = account_handle.sync().await.get_new_history().await?;
+    Ok(Some((account_handle, synced_account_data)))
+}
+
 struct RetriedData {
-    #[allow(dead_code)]
     promoted: Vec<Message>,
     reattached: Vec<(MessageId, Message)>,
     no_need_promote_or_reattach: Vec<MessageId>,
@@ -1424,7 +2700,7 @@ async fn consolidate_outputs_if_needed(
                 automatic_consolidation = false;
             }
         }
-        if automatic_consolidation {
+        if automatic_consolidation && synced.account_handle.read().await.is_consolidation_due(Local::now()) {
             synced.consolidate_outputs().await?;
         }
     }
@@ -1458,10 +2734,8 @@ async fn retry_unconfirmed_transactions(synced_accounts: &[SyncedAccount]) -> cr
                         promotions.push(new_message);
                     }
                 }
-                Err(crate::Error::ClientError(ref e)) => {
-                    if let iota::client::Error::NoNeedPromoteOrReattach(_) = e.as_ref() {
-                        no_need_promote_or_reattach.push(message_id);
-                    }
+                Err(crate::Error::MessageAlreadyConfirmed(_)) => {
+                    no_need_promote_or_reattach.push(message_id);
                 }
                 _ => {}
             }
@@ -1476,6 +2750,11 @@ async fn retry_unconfirmed_transactions(synced_accounts: &[SyncedAccount]) -> cr
     Ok(retried_messages)
 }
 
+// The path of the event database saved along with the backup file.
+fn event_backup_path(backup_path: &Path) -> PathBuf {
+    backup_path.with_extension(EVENTS_SQLITE_FILENAME)
+}
+
 fn backup_filename(original: &str) -> String {
     let date = Local::now();
     format!(
@@ -1501,6 +2780,47 @@ mod tests {
     use iota::{Ed25519Address, IndexationPayload, MessageBuilder, MessageId, Parents, Payload, TransactionId};
     use std::{collections::HashMap, path::PathBuf};
 
+    #[tokio::test]
+    async fn for_each_account() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 5, OutputKind::SignatureLockedSingle);
+        // the first account has outputs so we can create the next account
+        manager
+            .create_account(client_options.clone())
+            .unwrap()
+            .alias("alias1")
+            .addresses(vec![address])
+            .initialise()
+            .await
+            .expect("failed to add account");
+        manager
+            .create_account(client_options)
+            .unwrap()
+            .alias("alias2")
+            .initialise()
+            .await
+            .expect("failed to add account");
+
+        let mut count = 0;
+        let mut total_balance = 0;
+        manager
+            .for_each_account(|account| {
+                count += 1;
+                total_balance += account.addresses().iter().fold(0, |acc, a| acc + *a.balance());
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(total_balance, 5);
+    }
+
     #[tokio::test]
     async fn store_accounts() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -1514,6 +2834,19 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn compact_storage() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+            manager
+                .remove_account(account_handle.read().await.id())
+                .await
+                .expect("failed to remove account");
+            manager.compact_storage().await.expect("failed to compact storage");
+        })
+        .await;
+    }
+
     #[tokio::test]
     async fn delete_storage() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -1527,6 +2860,187 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn cancelled_sync_accounts() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            crate::test_utils::AccountCreator::new(&manager).create().await;
+            let cancelled = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+            let synced_accounts = manager
+                .sync_accounts()
+                .unwrap()
+                .cancellation_flag(cancelled)
+                .execute()
+                .await
+                .unwrap();
+            assert!(synced_accounts.is_empty());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn sync_accounts_by_ids() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output =
+            crate::test_utils::generate_address_output(address.address(), 1500000, OutputKind::SignatureLockedSingle);
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+        let account_id = account_handle.id().await;
+
+        let synced_accounts = manager
+            .sync_accounts_by_ids(vec![account_id.clone().into()])
+            .await
+            .unwrap()
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(synced_accounts.len(), 1);
+        assert_eq!(synced_accounts[0].id(), &account_id);
+        // the account discovery is skipped even though the latest account isn't empty
+        assert_eq!(manager.get_accounts().await.unwrap().len(), 1);
+
+        let res = manager.sync_accounts_by_ids(vec!["unknown".into()]).await;
+        match res {
+            Err(crate::Error::RecordNotFound) => {}
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_accounts_suggested_gap_limit() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let mut used_address = crate::test_utils::generate_random_address();
+        used_address.set_key_index(1);
+        let output = crate::test_utils::generate_address_output(
+            used_address.address(),
+            1500000,
+            OutputKind::SignatureLockedSingle,
+        );
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address, used_address])
+            .create()
+            .await;
+
+        // the funds are on the last address of the first window, right before the gap
+        let synced_accounts = manager
+            .sync_accounts_by_ids(vec![account_handle.id().await.into()])
+            .await
+            .unwrap()
+            .address_index(0)
+            .gap_limit(2)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(synced_accounts[0].suggested_gap_limit(), &Some(4));
+        assert!(synced_accounts[0].possibly_missed_funds());
+    }
+
+    #[tokio::test]
+    async fn sync_accounts_with_failing_account() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let address = crate::test_utils::generate_random_address();
+        let output =
+            crate::test_utils::generate_address_output(address.address(), 1500000, OutputKind::SignatureLockedSingle);
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(output)
+            .register()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![address])
+            .create()
+            .await;
+        let account_id = account_handle.id().await;
+
+        let unavailable_client_options = crate::test_utils::MockNodeClient::default()
+            .unavailable()
+            .register()
+            .await;
+        let failing_account_handle = manager
+            .create_account(unavailable_client_options)
+            .unwrap()
+            .alias("failing")
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .initialise()
+            .await
+            .unwrap();
+        let failing_account_id = failing_account_handle.id().await;
+
+        let results = manager
+            .sync_accounts_by_ids(vec![account_id.clone().into(), failing_account_id.clone().into()])
+            .await
+            .unwrap()
+            .execute_each()
+            .await;
+        assert_eq!(results.len(), 2);
+        for (id, res) in results {
+            if id == account_id {
+                assert_eq!(res.unwrap().id(), &account_id);
+            } else {
+                assert_eq!(id, failing_account_id);
+                assert!(res.is_err());
+            }
+        }
+        // the failure doesn't prevent the other account from being persisted
+        assert!(account_handle.read().await.last_synced_at().is_some());
+        assert!(failing_account_handle.read().await.last_synced_at().is_none());
+    }
+
+    #[tokio::test]
+    async fn sync_accounts_with_max_concurrent_syncs() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let tracker = std::sync::Arc::new(crate::test_utils::AddressRequestTracker::default());
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_address_request_tracker(tracker.clone(), std::time::Duration::from_millis(50))
+            .register()
+            .await;
+        let first_account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+        let second_account_handle = manager
+            .create_account(client_options)
+            .unwrap()
+            .alias("second")
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .initialise()
+            .await
+            .unwrap();
+
+        let mut synchronizer = manager
+            .sync_accounts_by_ids(vec![
+                first_account_handle.id().await.into(),
+                second_account_handle.id().await.into(),
+            ])
+            .await
+            .unwrap()
+            .gap_limit(1);
+        synchronizer.account_options.max_concurrent_account_syncs = Some(1);
+        let results = synchronizer.execute_each().await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, res)| res.is_ok()));
+        assert!(first_account_handle.read().await.last_synced_at().is_some());
+        assert!(second_account_handle.read().await.last_synced_at().is_some());
+        // an account sync requests the outputs of a public and a change address at once,
+        // so more than two requests in flight would mean both accounts were syncing at the same time
+        assert!(tracker.peak() >= 1);
+        assert!(tracker.peak() <= 2);
+    }
+
     #[tokio::test]
     async fn duplicated_alias() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -1559,6 +3073,81 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn concurrent_alias_update() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+
+        let account_handle1 = manager
+            .create_account(client_options.clone())
+            .unwrap()
+            .alias("alias1")
+            .initialise()
+            .await
+            .expect("failed to add account");
+        {
+            // update address outputs so we can create the next account
+            let mut account = account_handle1.write().await;
+            for address in account.addresses_mut() {
+                crate::test_utils::add_address_output(address, 5, OutputKind::SignatureLockedSingle);
+            }
+        }
+        let account_handle2 = manager
+            .create_account(client_options)
+            .unwrap()
+            .alias("alias2")
+            .initialise()
+            .await
+            .expect("failed to add account");
+
+        let id1 = account_handle1.id().await;
+        let id2 = account_handle2.id().await;
+        let (res1, res2) = futures::join!(
+            manager.set_account_alias(&id1, "alias"),
+            manager.set_account_alias(&id2, "alias")
+        );
+        // the account keeps its alias if the rename fails
+        let (alias1, alias2) = match (res1, res2) {
+            (Ok(()), Err(crate::Error::AccountAliasAlreadyExists)) => ("alias", "alias2"),
+            (Err(crate::Error::AccountAliasAlreadyExists), Ok(())) => ("alias1", "alias"),
+            _ => panic!("expected exactly one rename to succeed"),
+        };
+        assert_eq!(account_handle1.alias().await, alias1);
+        assert_eq!(account_handle2.alias().await, alias2);
+        assert!(manager.get_account("alias").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrent_create_account_same_alias() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+
+        let create_account = || {
+            manager
+                .create_account(client_options.clone())
+                .unwrap()
+                .alias("alias")
+                .addresses(vec![crate::test_utils::generate_random_address()])
+                .initialise()
+        };
+        let (res1, res2) = futures::join!(create_account(), create_account());
+        match (res1, res2) {
+            (Ok(_), Err(crate::Error::AccountAliasAlreadyExists))
+            | (Err(crate::Error::AccountAliasAlreadyExists), Ok(_)) => {}
+            _ => panic!("expected exactly one account creation to succeed"),
+        }
+        assert_eq!(manager.get_accounts().await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn get_account() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -1736,6 +3325,30 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn remove_account_with_pending_transactions() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let pending_message = crate::test_utils::GenerateMessageBuilder::default()
+            .confirmed(None)
+            .broadcasted(true)
+            .incoming(true)
+            .build()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .messages(vec![pending_message])
+            .create()
+            .await;
+        let account_id = account_handle.id().await;
+
+        let remove_response = manager.remove_account(&account_id).await;
+        assert!(matches!(
+            remove_response,
+            Err(crate::Error::AccountHasPendingTransactions)
+        ));
+        manager.force_remove_account(&account_id).await.unwrap();
+        assert!(manager.get_account(&account_id).await.is_err());
+    }
+
     #[tokio::test]
     async fn create_account_with_latest_without_history() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -1759,6 +3372,207 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn create_account_with_index() {
+        crate::test_utils::with_account_manager(
+            crate::test_utils::TestType::Storage,
+            |manager, signer_type| async move {
+                let client_options = ClientOptionsBuilder::new()
+                    .with_node("https://api.lb-0.testnet.chrysalis2.com")
+                    .expect("invalid node URL")
+                    .with_network("testnet")
+                    .build()
+                    .unwrap();
+
+                let account_handle = manager
+                    .create_account(client_options.clone())
+                    .unwrap()
+                    .signer_type(signer_type.clone())
+                    .index(3)
+                    .initialise()
+                    .await
+                    .expect("failed to add account");
+                assert_eq!(account_handle.index().await, 3);
+
+                let create_response = manager
+                    .create_account(client_options)
+                    .unwrap()
+                    .signer_type(signer_type)
+                    .alias("another alias")
+                    .index(3)
+                    .initialise()
+                    .await;
+                assert!(matches!(create_response, Err(crate::Error::AccountIndexAlreadyExists)));
+            },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn create_account_fills_index_gap() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://api.lb-0.testnet.chrysalis2.com")
+            .expect("invalid node URL")
+            .build()
+            .unwrap();
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 5, OutputKind::SignatureLockedSingle);
+        // the account has outputs so we can create the next accounts
+        let explicit_account = manager
+            .create_account(client_options.clone())
+            .unwrap()
+            .alias("alias1")
+            .index(1)
+            .addresses(vec![address])
+            .initialise()
+            .await
+            .expect("failed to add account");
+
+        let gap_account = manager
+            .create_account(client_options.clone())
+            .unwrap()
+            .alias("alias2")
+            .initialise()
+            .await
+            .expect("failed to add account");
+        assert_eq!(gap_account.index().await, 0);
+        assert_ne!(gap_account.id().await, explicit_account.id().await);
+
+        let next_account = manager
+            .create_account(client_options)
+            .unwrap()
+            .alias("alias3")
+            .initialise()
+            .await
+            .expect("failed to add account");
+        assert_eq!(next_account.index().await, 2);
+    }
+
+    #[tokio::test]
+    async fn coin_types_have_their_own_account_indexes() {
+        let manager = crate::test_utils::get_account_manager().await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 5, OutputKind::SignatureLockedSingle);
+        let iota_account = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address])
+            .create()
+            .await;
+
+        let client_options = iota_account.client_options().await;
+        let other_coin_account = manager
+            .create_account(client_options)
+            .unwrap()
+            .alias("alias2")
+            .coin_type(1)
+            .initialise()
+            .await
+            .expect("failed to add account");
+        assert_eq!(iota_account.index().await, 0);
+        assert_eq!(other_coin_account.index().await, 0);
+        assert_ne!(other_coin_account.id().await, iota_account.id().await);
+    }
+
+    #[tokio::test]
+    async fn diagnostic_snapshot() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        let address = account_handle.latest_address().await.address().to_bech32();
+
+        let snapshot = manager.diagnostic_snapshot().await;
+        assert_eq!(snapshot.accounts().len(), 1);
+        let account_diagnostics = &snapshot.accounts()[0];
+        assert_eq!(account_diagnostics.index(), &0);
+        assert_eq!(account_diagnostics.public_address_count(), &1);
+        assert!(address.starts_with(account_diagnostics.addresses()[0].trim_end_matches("...")));
+
+        let serialized_snapshot = serde_json::to_string(&snapshot).unwrap();
+        assert!(!serialized_snapshot.contains(&address));
+    }
+
+    #[tokio::test]
+    async fn poll_skips_unchanged_account_saves() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        struct CountingAdapter(Arc<AtomicUsize>);
+        #[async_trait::async_trait]
+        impl crate::storage::StorageAdapter for CountingAdapter {
+            async fn get(&self, _key: &str) -> crate::Result<String> {
+                Err(crate::Error::RecordNotFound)
+            }
+            async fn set(&mut self, _key: &str, _record: String) -> crate::Result<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+            async fn remove(&mut self, _key: &str) -> crate::Result<()> {
+                Ok(())
+            }
+        }
+
+        let manager = crate::test_utils::get_account_manager().await;
+        crate::test_utils::AccountCreator::new(&manager).create().await;
+
+        let writes = Arc::new(AtomicUsize::new(0));
+        crate::storage::set(manager.storage_path(), None, Box::new(CountingAdapter(writes.clone()))).await;
+
+        // without unconfirmed messages or expired addresses, the polling doesn't write to the storage
+        super::poll(
+            manager.sync_accounts_lock.clone(),
+            manager.accounts.clone(),
+            manager.storage_path.clone(),
+            manager.account_options,
+            false,
+            manager.is_monitoring.clone(),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(writes.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn poll_invokes_sync_complete_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let manager = crate::test_utils::get_account_manager().await;
+        let client_options = crate::test_utils::MockNodeClient::default().register().await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options)
+            .addresses(vec![crate::test_utils::generate_random_address()])
+            .create()
+            .await;
+
+        let synced_ids = Arc::new(Mutex::new(Vec::new()));
+        let synced_ids_ = synced_ids.clone();
+        let callback: super::SyncCompleteCallback =
+            Arc::new(move |synced_accounts: &[crate::account::SyncedAccount]| {
+                synced_ids_
+                    .lock()
+                    .unwrap()
+                    .extend(synced_accounts.iter().map(|synced| synced.id().clone()));
+            });
+
+        super::poll(
+            manager.sync_accounts_lock.clone(),
+            manager.accounts.clone(),
+            manager.storage_path.clone(),
+            manager.account_options,
+            true,
+            manager.is_monitoring.clone(),
+            false,
+            Some(&callback),
+        )
+        .await
+        .unwrap();
+        assert_eq!(*synced_ids.lock().unwrap(), vec![account_handle.id().await]);
+    }
+
     #[tokio::test]
     async fn create_account_skip_persistence() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -1775,94 +3589,402 @@ mod tests {
                 .skip_persistence()
                 .initialise()
                 .await
-                .expect("failed to add account");
+                .expect("failed to add account");
+
+            let account_get_res = manager.get_account(account_handle.read().await.id()).await;
+            assert!(account_get_res.is_err(), true);
+            match account_get_res.unwrap_err() {
+                crate::Error::RecordNotFound => {}
+                _ => panic!("unexpected get_account response; expected RecordNotFound"),
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn create_account_with_storage_path() {
+        crate::test_utils::with_account_manager(
+            crate::test_utils::TestType::Storage,
+            |manager, signer_type| async move {
+                let client_options = ClientOptionsBuilder::new()
+                    .with_node("https://api.lb-0.testnet.chrysalis2.com")
+                    .expect("invalid node URL")
+                    .with_network("testnet")
+                    .build()
+                    .unwrap();
+                let account_storage_path = manager.storage_folder.join("account.db");
+
+                let account_handle = manager
+                    .create_account(client_options)
+                    .unwrap()
+                    .signer_type(signer_type)
+                    .storage_path(&account_storage_path)
+                    .initialise()
+                    .await
+                    .expect("failed to add account");
+                let account_id = account_handle.read().await.id().clone();
+                assert_eq!(account_handle.read().await.storage_path(), &account_storage_path);
+                assert!(account_storage_path.exists());
+
+                // the account isn't stored on the manager storage file
+                let manager_storage_accounts = crate::storage::get(manager.storage_path())
+                    .await
+                    .unwrap()
+                    .lock()
+                    .await
+                    .get_accounts()
+                    .await
+                    .unwrap();
+                assert!(manager_storage_accounts.is_empty());
+
+                let account_store = super::AccountManager::load_accounts(
+                    manager.storage_path(),
+                    manager.account_options,
+                    manager.is_monitoring.clone(),
+                )
+                .await
+                .unwrap();
+                let account_store = account_store.read().await;
+                let loaded_account = account_store.get(&account_id).expect("account not loaded");
+                assert_eq!(loaded_account.read().await.storage_path(), &account_storage_path);
+                drop(account_store);
+
+                // the storage file isn't registered anymore once its account is removed
+                manager.remove_account(&account_id).await.unwrap();
+                let account_storage_paths = crate::storage::get(manager.storage_path())
+                    .await
+                    .unwrap()
+                    .lock()
+                    .await
+                    .get_account_storage_paths()
+                    .await
+                    .unwrap();
+                assert!(account_storage_paths.is_empty());
+            },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore_happy_path() {
+        let backup_path = "./backup/happy-path";
+        let _ = std::fs::remove_dir_all(backup_path);
+        std::fs::create_dir_all(backup_path).unwrap();
+
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+
+            // backup the stored accounts to ./backup/happy-path/${backup_name}
+            let backup_path = manager.backup(backup_path, "password".to_string()).await.unwrap();
+            let backup_file_path = if backup_path.is_dir() {
+                std::fs::read_dir(backup_path)
+                    .unwrap()
+                    .next()
+                    .unwrap()
+                    .unwrap()
+                    .path()
+                    .to_path_buf()
+            } else {
+                backup_path
+            };
+            assert_eq!(backup_file_path.extension().unwrap_or_default(), "stronghold");
+
+            let is_encrypted = crate::storage::get(manager.storage_path())
+                .await
+                .unwrap()
+                .lock()
+                .await
+                .is_encrypted();
+
+            // get another manager instance so we can import the accounts to a different storage
+            #[allow(unused_mut)]
+            let mut manager = crate::test_utils::get_account_manager().await;
+
+            #[cfg(feature = "stronghold")]
+            {
+                // wait for stronghold to finish pending operations and delete the storage file
+                crate::stronghold::unload_snapshot(&manager.stronghold_snapshot_path().await.unwrap(), false)
+                    .await
+                    .unwrap();
+                let _ = crate::stronghold::actor_runtime().lock().await;
+
+                if crate::storage::get(manager.storage_path())
+                    .await
+                    .unwrap()
+                    .lock()
+                    .await
+                    .id()
+                    == crate::storage::stronghold::STORAGE_ID
+                {
+                    let _ = std::fs::remove_file(manager.storage_path());
+                }
+            }
+
+            if is_encrypted {
+                manager.set_storage_password("password").await.unwrap();
+            }
+
+            // import the accounts from the backup and assert that it's the same
+            manager
+                .import_accounts(&backup_file_path, "password".to_string())
+                .await
+                .unwrap();
+            assert!(
+                super::storage_file_path(&ManagerStorage::Stronghold, manager.storage_path(), None).exists(),
+                true
+            );
+
+            let imported_account = manager.get_account(account_handle.read().await.id()).await.unwrap();
+            // set the account storage path field so the assert works
+            account_handle
+                .write()
+                .await
+                .set_storage_path(manager.storage_path().clone());
+            assert_eq!(&*account_handle.read().await, &*imported_account.read().await);
+        })
+        .await;
+    }
+
+    #[cfg(feature = "stronghold")]
+    #[tokio::test]
+    async fn store_mnemonic_with_passphrase() {
+        let mnemonic = crate::test_utils::get_account_manager()
+            .await
+            .generate_mnemonic()
+            .unwrap();
+
+        let mut addresses = Vec::new();
+        for passphrase in &[None, Some("hidden")] {
+            let storage_path = PathBuf::from(format!("./test-storage/passphrase-{}", passphrase.unwrap_or("none")));
+            let _ = std::fs::remove_dir_all(&storage_path);
+            let mut manager = super::AccountManager::builder()
+                .with_storage(&storage_path, None)
+                .unwrap()
+                .skip_polling()
+                .finish()
+                .await
+                .unwrap();
+            manager.set_stronghold_password("password").await.unwrap();
+            match passphrase {
+                Some(passphrase) => manager
+                    .store_mnemonic_with_passphrase(
+                        crate::signing::SignerType::Stronghold,
+                        Some(mnemonic.clone()),
+                        passphrase.to_string(),
+                    )
+                    .await
+                    .unwrap(),
+                None => manager
+                    .store_mnemonic(crate::signing::SignerType::Stronghold, Some(mnemonic.clone()))
+                    .await
+                    .unwrap(),
+            }
+            let account_handle = manager
+                .create_account(ClientOptionsBuilder::new().with_network("testnet").build().unwrap())
+                .unwrap()
+                .signer_type(crate::signing::SignerType::Stronghold)
+                .initialise()
+                .await
+                .unwrap();
+            addresses.push(account_handle.latest_address().await.address().clone());
+        }
+
+        // the passphrase derives a different wallet from the same mnemonic
+        assert_ne!(addresses[0], addresses[1]);
+    }
+
+    #[cfg(feature = "stronghold")]
+    #[tokio::test]
+    async fn custom_storage_filename() {
+        let storage_folder = PathBuf::from("./test-storage/custom-filename");
+        let _ = std::fs::remove_dir_all(&storage_folder);
+        let manager = super::AccountManager::builder()
+            .with_storage(&storage_folder, None)
+            .unwrap()
+            .with_storage_filename("user-42".to_string())
+            .unwrap()
+            .skip_polling()
+            .finish()
+            .await
+            .unwrap();
+
+        let storage_file_path = storage_folder.join("user-42.db");
+        let snapshot_path = storage_folder.join("user-42.stronghold");
+        assert_eq!(manager.storage_path(), &storage_file_path);
+        assert!(storage_file_path.exists());
+        assert_eq!(manager.stronghold_snapshot_path().await.unwrap(), snapshot_path);
+        // the signer reads the seed from the snapshot named after the storage filename
+        assert_eq!(
+            crate::signing::stronghold::stronghold_path(&storage_file_path)
+                .await
+                .unwrap(),
+            snapshot_path
+        );
+
+        assert!(manager.delete().await.is_ok());
+        assert!(!storage_file_path.exists());
+
+        // the storage files can't be created outside the storage folder
+        for storage_filename in &["", "../user-42", "users/user-42", "users\\user-42", ".."] {
+            assert!(matches!(
+                super::AccountManager::builder().with_storage_filename(storage_filename.to_string()),
+                Err(crate::Error::InvalidStorageFilename(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_backup() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            let backup_path = PathBuf::from("./backup/verify");
+            let _ = std::fs::remove_dir_all(&backup_path);
+            std::fs::create_dir_all(&backup_path).unwrap();
+            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+
+            let backup_file_path = backup_path.join("wallet.stronghold");
+            manager.backup(&backup_file_path, "password".to_string()).await.unwrap();
+            let backup = std::fs::read(&backup_file_path).unwrap();
+
+            let backup_info = manager
+                .verify_backup(&backup_file_path, "password".to_string())
+                .await
+                .unwrap();
+            assert_eq!(backup_info.account_count(), 1);
+            assert_eq!(
+                backup_info.account_ids(),
+                &vec![account_handle.read().await.id().clone()]
+            );
 
-            let account_get_res = manager.get_account(account_handle.read().await.id()).await;
-            assert!(account_get_res.is_err(), true);
-            match account_get_res.unwrap_err() {
-                crate::Error::RecordNotFound => {}
-                _ => panic!("unexpected get_account response; expected RecordNotFound"),
-            }
+            assert!(manager
+                .verify_backup(&backup_file_path, "wrong-password".to_string())
+                .await
+                .is_err());
+            // the backup file and the manager accounts are untouched
+            assert_eq!(std::fs::read(&backup_file_path).unwrap(), backup);
+            assert_eq!(manager.get_accounts().await.unwrap().len(), 1);
         })
         .await;
     }
 
     #[tokio::test]
-    async fn backup_and_restore_happy_path() {
-        let backup_path = "./backup/happy-path";
-        let _ = std::fs::remove_dir_all(backup_path);
-        std::fs::create_dir_all(backup_path).unwrap();
+    async fn merge_accounts() {
+        struct DeterministicSigner;
 
-        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
-            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        #[async_trait::async_trait]
+        impl crate::signing::Signer for DeterministicSigner {
+            async fn store_mnemonic(&mut self, _: &PathBuf, _: String) -> crate::Result<()> {
+                Ok(())
+            }
 
-            // backup the stored accounts to ./backup/happy-path/${backup_name}
-            let backup_path = manager.backup(backup_path, "password".to_string()).await.unwrap();
-            let backup_file_path = if backup_path.is_dir() {
-                std::fs::read_dir(backup_path)
-                    .unwrap()
-                    .next()
-                    .unwrap()
-                    .unwrap()
-                    .path()
-                    .to_path_buf()
-            } else {
-                backup_path
-            };
-            assert_eq!(backup_file_path.extension().unwrap_or_default(), "stronghold");
+            async fn generate_address(
+                &mut self,
+                account: &crate::account::Account,
+                address_index: usize,
+                internal: bool,
+                _: crate::signing::GenerateAddressMetadata,
+            ) -> crate::Result<iota::Address> {
+                let mut address = [*account.index() as u8; 32];
+                address[1] = address_index as u8;
+                address[2] = internal as u8;
+                Ok(iota::Address::Ed25519(iota::Ed25519Address::new(address)))
+            }
 
-            let is_encrypted = crate::storage::get(manager.storage_path())
-                .await
-                .unwrap()
-                .lock()
-                .await
-                .is_encrypted();
+            async fn sign_message<'a>(
+                &mut self,
+                _: &crate::account::Account,
+                _: &iota::Essence,
+                _: &mut Vec<crate::signing::TransactionInput>,
+                _: crate::signing::SignMessageMetadata<'a>,
+            ) -> crate::Result<Vec<iota::UnlockBlock>> {
+                unreachable!()
+            }
+        }
 
-            // get another manager instance so we can import the accounts to a different storage
-            #[allow(unused_mut)]
-            let mut manager = crate::test_utils::get_account_manager().await;
+        let signer_type = crate::signing::SignerType::Custom("deterministic".to_string());
+        crate::signing::set_signer(signer_type.clone(), DeterministicSigner).await;
 
-            #[cfg(feature = "stronghold")]
-            {
-                // wait for stronghold to finish pending operations and delete the storage file
-                crate::stronghold::unload_snapshot(&manager.stronghold_snapshot_path().await.unwrap(), false)
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, move |manager, _| {
+            let signer_type = signer_type.clone();
+            async move {
+                let backup_path = PathBuf::from("./backup/merge");
+                let _ = std::fs::remove_dir_all(&backup_path);
+                std::fs::create_dir_all(&backup_path).unwrap();
+
+                let address = AddressBuilder::new()
+                    .address(AddressWrapper::new(
+                        IotaAddress::Ed25519(Ed25519Address::new([0; 32])),
+                        "atoi".to_string(),
+                    ))
+                    .key_index(0)
+                    .balance(0)
+                    .outputs(vec![])
+                    .build()
+                    .unwrap();
+                let account_handle = crate::test_utils::AccountCreator::new(&manager)
+                    .signer_type(signer_type.clone())
+                    .addresses(vec![address])
+                    .create()
+                    .await;
+                let backup_file_path = backup_path.join("wallet.stronghold");
+                manager.backup(&backup_file_path, "password".to_string()).await.unwrap();
+
+                let mut manager = crate::test_utils::get_account_manager().await;
+                let merged_accounts = manager
+                    .merge_accounts(&backup_file_path, "password".to_string(), super::ConflictPolicy::Skip)
                     .await
                     .unwrap();
-                let _ = crate::stronghold::actor_runtime().lock().await;
+                assert_eq!(merged_accounts.len(), 1);
+                assert_eq!(merged_accounts[0].read().await.id(), account_handle.read().await.id());
+                assert!(manager.get_account(account_handle.read().await.id()).await.is_ok());
 
-                if crate::storage::get(manager.storage_path())
+                // the account already exists now
+                let merged_accounts = manager
+                    .merge_accounts(&backup_file_path, "password".to_string(), super::ConflictPolicy::Rename)
                     .await
-                    .unwrap()
-                    .lock()
-                    .await
-                    .id()
-                    == crate::storage::stronghold::STORAGE_ID
-                {
-                    let _ = std::fs::remove_file(manager.storage_path());
-                }
+                    .unwrap();
+                assert!(merged_accounts.is_empty());
+                let res = manager
+                    .merge_accounts(&backup_file_path, "password".to_string(), super::ConflictPolicy::Error)
+                    .await;
+                assert!(matches!(res, Err(crate::Error::AccountConflict(_))));
             }
+        })
+        .await;
+    }
 
-            if is_encrypted {
-                manager.set_storage_password("password").await.unwrap();
-            }
+    #[tokio::test]
+    async fn export_and_import_account() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |mut manager, _| async move {
+            let export_path = PathBuf::from("./backup/export-account");
+            let _ = std::fs::remove_dir_all(&export_path);
+            std::fs::create_dir_all(&export_path).unwrap();
+
+            // the signer compatibility is checked with the first public address, so we skip it here
+            let mut address = crate::test_utils::generate_random_address();
+            address.set_key_index(1);
+            let account_handle = crate::test_utils::AccountCreator::new(&manager)
+                .addresses(vec![address])
+                .create()
+                .await;
+            let account_id = account_handle.id().await;
 
-            // import the accounts from the backup and assert that it's the same
-            manager
-                .import_accounts(&backup_file_path, "password".to_string())
+            let export_file_path = manager
+                .export_account(&account_id, &export_path, "password".to_string())
                 .await
                 .unwrap();
-            assert!(
-                super::storage_file_path(&ManagerStorage::Stronghold, manager.storage_path()).exists(),
-                true
-            );
+            assert!(export_file_path.exists());
 
-            let imported_account = manager.get_account(account_handle.read().await.id()).await.unwrap();
-            // set the account storage path field so the assert works
-            account_handle
-                .write()
+            // the account already exists on the exporting manager
+            let res = manager.import_account(&export_file_path, "password".to_string()).await;
+            assert!(matches!(res, Err(crate::Error::AccountConflict(_))));
+
+            let mut manager = crate::test_utils::get_account_manager().await;
+            let imported_account = manager
+                .import_account(&export_file_path, "password".to_string())
                 .await
-                .set_storage_path(manager.storage_path().clone());
-            assert_eq!(&*account_handle.read().await, &*imported_account.read().await);
+                .unwrap();
+            assert_eq!(imported_account.id().await, account_id);
+            assert_eq!(manager.get_accounts().await.unwrap().len(), 1);
         })
         .await;
     }
@@ -1916,6 +4038,100 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn storage_password_reencrypts_account_storage() {
+        crate::test_utils::with_account_manager(
+            crate::test_utils::TestType::Storage,
+            |mut manager, signer_type| async move {
+                let client_options = ClientOptionsBuilder::new()
+                    .with_node("https://api.lb-0.testnet.chrysalis2.com")
+                    .expect("invalid node URL")
+                    .with_network("testnet")
+                    .build()
+                    .unwrap();
+                let account_storage_path = manager.storage_folder.join("account.db");
+                manager
+                    .create_account(client_options)
+                    .unwrap()
+                    .signer_type(signer_type)
+                    .storage_path(&account_storage_path)
+                    .initialise()
+                    .await
+                    .expect("failed to add account");
+
+                manager.set_storage_password("new-password").await.unwrap();
+
+                // the storage paths record is read from the disk with the new key
+                let account_storage_paths = crate::storage::get(manager.storage_path())
+                    .await
+                    .unwrap()
+                    .lock()
+                    .await
+                    .get_account_storage_paths()
+                    .await
+                    .unwrap();
+                assert_eq!(account_storage_paths, vec![account_storage_path.clone()]);
+
+                // reload the account storage file so its records are read from the disk with the new key
+                crate::storage::set(
+                    &account_storage_path,
+                    Some(super::storage_password_to_encryption_key("new-password")),
+                    Box::new(crate::storage::sqlite::SqliteStorageAdapter::new(&account_storage_path).unwrap()),
+                )
+                .await;
+                let accounts = crate::storage::get(&account_storage_path)
+                    .await
+                    .unwrap()
+                    .lock()
+                    .await
+                    .get_accounts()
+                    .await
+                    .unwrap();
+                assert_eq!(accounts.len(), 1);
+            },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn storage_password_reencrypts_events() {
+        let event_storage_folder = PathBuf::from("./test-storage/reencrypted-event-storage");
+        let _ = std::fs::remove_dir_all(&event_storage_folder);
+        let mut manager = crate::test_utils::build_account_manager(
+            super::AccountManager::builder()
+                .with_event_persistence()
+                .with_event_storage(&event_storage_folder, None),
+        )
+        .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        {
+            let account = account_handle.read().await;
+            emit_balance_change(
+                &account,
+                account.latest_address().address(),
+                None,
+                BalanceChange::received(1),
+                manager.account_options.persist_events,
+            )
+            .await
+            .unwrap();
+        }
+
+        manager.set_storage_password("new-password").await.unwrap();
+
+        // reload the event database so the events are read from the disk with the new key
+        crate::storage::init_event_storage(
+            &manager.storage_path,
+            &event_storage_folder.join(super::EVENTS_SQLITE_FILENAME),
+            None,
+            Some(super::storage_password_to_encryption_key("new-password")),
+        )
+        .await
+        .unwrap();
+        let events = manager.get_balance_change_events(0, 0, None).await.unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
     #[tokio::test]
     async fn get_balance_change_events() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -1956,6 +4172,151 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn get_events_since() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+            let account = account_handle.read().await;
+            let message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+            emit_balance_change(
+                &account,
+                account.latest_address().address(),
+                None,
+                BalanceChange::received(5),
+                true,
+            )
+            .await
+            .unwrap();
+            emit_confirmation_state_change(&account, message.clone(), true, true)
+                .await
+                .unwrap();
+            emit_transaction_event(TransactionEventType::Broadcast, &account, message.clone(), true)
+                .await
+                .unwrap();
+
+            let events = manager.get_events_since(0).await.unwrap();
+            assert_eq!(events.len(), 3);
+            assert!(events.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+            assert!(events
+                .iter()
+                .any(|e| matches!(&e.event, StoredEvent::BalanceChange(event) if event.balance_change == BalanceChange::received(5))));
+            assert!(events.iter().any(
+                |e| matches!(&e.event, StoredEvent::TransactionConfirmationChange(event) if event.message == message)
+            ));
+            assert!(events
+                .iter()
+                .any(|e| matches!(&e.event, StoredEvent::Broadcast(event) if event.message == message)));
+
+            let future_timestamp = chrono::Utc::now().timestamp() + 60;
+            assert!(manager.get_events_since(future_timestamp).await.unwrap().is_empty());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn balance_history() {
+        crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
+            let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+            let from = chrono::Local::now() - chrono::Duration::seconds(30);
+            {
+                let account = account_handle.read().await;
+                for change in &[BalanceChange::received(5), BalanceChange::spent(2)] {
+                    emit_balance_change(&account, account.latest_address().address(), None, *change, true)
+                        .await
+                        .unwrap();
+                }
+            }
+
+            let to = chrono::Local::now() + chrono::Duration::seconds(60);
+            let history = manager
+                .balance_history(account_handle.id().await, from, to, std::time::Duration::from_secs(60))
+                .await
+                .unwrap();
+            let balances: Vec<i64> = history.iter().map(|(_, balance)| *balance).collect();
+            assert_eq!(balances, vec![0, 3, 3]);
+            assert_eq!(history[0].0.timestamp(), from.timestamp());
+            assert_eq!(history[2].0.timestamp(), to.timestamp());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn event_storage() {
+        let event_storage_folder = PathBuf::from("./test-storage/event-storage");
+        let _ = std::fs::remove_dir_all(&event_storage_folder);
+        let manager = crate::test_utils::build_account_manager(
+            super::AccountManager::builder()
+                .with_event_persistence()
+                .with_event_storage(event_storage_folder.join("source"), None),
+        )
+        .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+        assert!(event_storage_folder
+            .join("source")
+            .join(super::EVENTS_SQLITE_FILENAME)
+            .is_file());
+
+        {
+            let account = account_handle.read().await;
+            emit_balance_change(
+                &account,
+                account.latest_address().address(),
+                None,
+                BalanceChange::received(1),
+                manager.account_options.persist_events,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(manager.get_balance_change_event_count(None).await.unwrap(), 1);
+        // the event isn't stored with the accounts
+        let account_storage_event_count = crate::storage::get(&manager.storage_path)
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .get_balance_change_event_count(None)
+            .await
+            .unwrap();
+        assert_eq!(account_storage_event_count, 0);
+
+        // the event database is saved along with the backup
+        let backup_path = PathBuf::from("./backup/event-storage");
+        let _ = std::fs::remove_dir_all(&backup_path);
+        std::fs::create_dir_all(&backup_path).unwrap();
+        let backup_file_path = manager
+            .backup(backup_path.join("wallet.stronghold"), "password".to_string())
+            .await
+            .unwrap();
+        assert!(backup_path.join("wallet.events.db").is_file());
+
+        // get another manager instance so we can restore the events to a different event storage
+        #[allow(unused_mut)]
+        let mut manager = crate::test_utils::build_account_manager(
+            super::AccountManager::builder()
+                .with_event_persistence()
+                .with_event_storage(event_storage_folder.join("destination"), None),
+        )
+        .await;
+        assert_eq!(manager.get_balance_change_event_count(None).await.unwrap(), 0);
+
+        #[cfg(feature = "stronghold")]
+        {
+            // wait for stronghold to finish pending operations and delete the storage file
+            crate::stronghold::unload_snapshot(&manager.stronghold_snapshot_path().await.unwrap(), false)
+                .await
+                .unwrap();
+            let _ = crate::stronghold::actor_runtime().lock().await;
+        }
+
+        manager
+            .import_accounts(&backup_file_path, "password".to_string())
+            .await
+            .unwrap();
+        assert_eq!(manager.get_balance_change_event_count(None).await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn get_transaction_confirmation_events() {
         crate::test_utils::with_account_manager(crate::test_utils::TestType::Storage, |manager, _| async move {
@@ -2099,4 +4460,163 @@ mod tests {
         get_broadcast_event_count,
         get_broadcast_events
     );
+
+    #[tokio::test]
+    async fn discover_accounts_after_empty_account() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let signer_type = crate::signing::SignerType::Custom("".to_string());
+
+        // generate the first address of the account with index 2 so the mock node can fund it
+        let funded_account_handle = manager
+            .create_account(crate::test_utils::MockNodeClient::default().register().await)
+            .unwrap()
+            .signer_type(signer_type.clone())
+            .index(2)
+            .skip_persistence()
+            .initialise()
+            .await
+            .unwrap();
+        let funded_address = {
+            let funded_account = funded_account_handle.read().await;
+            crate::address::get_iota_address(
+                &funded_account,
+                0,
+                false,
+                "atoi".to_string(),
+                crate::signing::GenerateAddressMetadata { syncing: true },
+            )
+            .await
+            .unwrap()
+        };
+        let client_options = crate::test_utils::MockNodeClient::default()
+            .with_output(crate::test_utils::generate_address_output(
+                &funded_address,
+                1500000,
+                OutputKind::SignatureLockedSingle,
+            ))
+            .register()
+            .await;
+
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 5, OutputKind::SignatureLockedSingle);
+        crate::test_utils::AccountCreator::new(&manager)
+            .client_options(client_options.clone())
+            .signer_type(signer_type.clone())
+            .addresses(vec![address])
+            .create()
+            .await;
+
+        let mut account_options = manager.account_options;
+        // the account with index 1 is empty so the default options stop the discovery before the funded account
+        let discovered_accounts = super::discover_accounts(
+            manager.accounts.clone(),
+            &manager.storage_path,
+            &client_options,
+            Some(signer_type.clone()),
+            crate::address::IOTA_COIN_TYPE,
+            account_options,
+            manager.is_monitoring.clone(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        assert!(discovered_accounts.is_empty());
+
+        account_options.account_discovery_window = 2;
+        account_options.account_discovery_gap_limit = 2;
+        let discovered_accounts = super::discover_accounts(
+            manager.accounts.clone(),
+            &manager.storage_path,
+            &client_options,
+            Some(signer_type),
+            crate::address::IOTA_COIN_TYPE,
+            account_options,
+            manager.is_monitoring.clone(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        // the empty account preceding the funded one is kept so the account indexes stay contiguous
+        assert_eq!(discovered_accounts.len(), 2);
+        let (empty_account_handle, _) = &discovered_accounts[0];
+        assert_eq!(*empty_account_handle.read().await.index(), 1);
+        let (funded_account_handle, funded_account_data) = &discovered_accounts[1];
+        assert_eq!(*funded_account_handle.read().await.index(), 2);
+        assert!(funded_account_data
+            .addresses
+            .iter()
+            .any(|a| a.address() == &funded_address && *a.balance() == 1500000));
+    }
+
+    #[tokio::test]
+    async fn find_duplicate_accounts() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let build_address = |bytes: [u8; 32], key_index: usize| {
+            AddressBuilder::new()
+                .address(AddressWrapper::new(
+                    IotaAddress::Ed25519(Ed25519Address::new(bytes)),
+                    "atoi".to_string(),
+                ))
+                .key_index(key_index)
+                .balance(0)
+                .outputs(vec![])
+                .build()
+                .unwrap()
+        };
+
+        let mut first_address = build_address([1; 32], 0);
+        crate::test_utils::add_address_output(&mut first_address, 5, OutputKind::SignatureLockedSingle);
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![first_address])
+            .create()
+            .await;
+        assert!(manager.find_duplicate_accounts().await.is_empty());
+
+        // the same seed imported under another alias, with a different address set
+        let duplicate_account_handle = manager
+            .create_account(account_handle.read().await.client_options().clone())
+            .unwrap()
+            .alias("duplicate")
+            .addresses(vec![build_address([1; 32], 0), build_address([0; 32], 1)])
+            .initialise()
+            .await
+            .unwrap();
+
+        let duplicates = manager.find_duplicate_accounts().await;
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].len(), 2);
+        for account_id in vec![account_handle.id().await, duplicate_account_handle.id().await] {
+            assert!(duplicates[0].contains(&crate::account::AccountIdentifier::Id(account_id)));
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicate_account_conflicts() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        crate::test_utils::add_address_output(&mut address, 5, OutputKind::SignatureLockedSingle);
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .create()
+            .await;
+
+        // the same seed and index imported again under another alias derives the same account id
+        let mut duplicate_address = address.clone();
+        duplicate_address.outputs.clear();
+        let res = manager
+            .create_account(account_handle.read().await.client_options().clone())
+            .unwrap()
+            .alias("duplicate")
+            .addresses(vec![duplicate_address])
+            .initialise()
+            .await;
+        let account_id = account_handle.id().await;
+        assert!(matches!(res, Err(crate::Error::AccountConflict(id)) if id == account_id));
+
+        // the existing account isn't replaced
+        let accounts = manager.get_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].alias().await, "alias");
+        assert_eq!(accounts[0].balance().await.total, 5);
+    }
 }
@@ -5,11 +5,13 @@ use crate::{
     account::{Account, AccountBalance, AccountIdentifier, SyncedAccount},
     address::Address,
     client::ClientOptions,
+    event::TimestampedEvent,
     message::{Message as WalletMessage, MessageType as WalletMessageType, TransferBuilder},
     signing::SignerType,
     Error,
 };
 use chrono::{DateTime, Local};
+use iota::OutputId;
 use serde::{ser::Serializer, Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -32,6 +34,12 @@ pub struct AccountToCreate {
     /// The account's signer type.
     #[serde(rename = "signerType")]
     pub signer_type: Option<SignerType>,
+    /// The account's bech32 human readable part.
+    #[serde(rename = "bech32Hrp")]
+    pub bech32_hrp: Option<String>,
+    /// The account's BIP44 coin type.
+    #[serde(rename = "coinType")]
+    pub coin_type: Option<u32>,
 }
 
 /// Each public account method.
@@ -42,6 +50,8 @@ pub enum AccountMethod {
     GenerateAddress,
     /// Get a unused address.
     GetUnusedAddress,
+    /// Retire the current deposit address and generate a new one.
+    RotateDepositAddress,
     /// List messages.
     ListMessages {
         /// Message type filter.
@@ -54,12 +64,18 @@ pub enum AccountMethod {
         #[serde(default)]
         from: usize,
     },
+    /// List the sent transactions that aren't confirmed yet.
+    ListPendingTransactions,
     /// List addresses.
     ListAddresses,
     /// List spent addresses.
     ListSpentAddresses,
     /// List unspent addresses.
     ListUnspentAddresses,
+    /// List addresses holding funds, sorted descending by available balance.
+    ListAddressesByBalance,
+    /// List the ids of every output (spent and unspent) known by the account.
+    ListOutputIds,
     /// Get account balance information.
     GetBalance,
     /// Get latest address.
@@ -207,6 +223,12 @@ pub enum MessageType {
     },
     /// Updates the client options for all accounts.
     SetClientOptions(Box<ClientOptions>),
+    /// Get the persisted events of every type since a timestamp.
+    GetEventsSince {
+        /// The UNIX timestamp (in seconds) to get the events from.
+        #[serde(rename = "fromTimestamp")]
+        from_timestamp: i64,
+    },
 }
 
 impl Serialize for MessageType {
@@ -285,6 +307,9 @@ impl Serialize for MessageType {
             MessageType::SetClientOptions(_) => {
                 serializer.serialize_unit_variant("MessageType", 23, "SetClientOptions")
             }
+            MessageType::GetEventsSince { from_timestamp: _ } => {
+                serializer.serialize_unit_variant("MessageType", 24, "GetEventsSince")
+            }
         }
     }
 }
@@ -328,12 +353,16 @@ pub enum ResponseType {
     ReadAccounts(Vec<Account>),
     /// ListMessages response.
     Messages(Vec<WalletMessage>),
-    /// ListAddresses/ListSpentAddresses/ListUnspentAddresses response.
+    /// ListAddresses/ListSpentAddresses/ListUnspentAddresses/ListAddressesByBalance response.
     Addresses(Vec<Address>),
+    /// ListOutputIds response.
+    OutputIds(Vec<OutputId>),
     /// GenerateAddress response.
     GeneratedAddress(Address),
     /// GetUnusedAddress response.
     UnusedAddress(Address),
+    /// RotateDepositAddress response.
+    RotatedDepositAddress(Address),
     /// GetLatestAddress response.
     LatestAddress(Address),
     /// GetBalance response.
@@ -398,6 +427,8 @@ pub enum ResponseType {
     StrongholdPasswordChanged,
     /// SetClientOptions response.
     UpdatedAllClientOptions,
+    /// GetEventsSince response.
+    Events(Vec<TimestampedEvent>),
 }
 
 /// The message type.
@@ -212,6 +212,15 @@ impl WalletMessageHandler {
                 })
                 .await
             }
+            MessageType::GetEventsSince { from_timestamp } => {
+                convert_async_panics(|| async {
+                    self.account_manager
+                        .get_events_since(*from_timestamp)
+                        .await
+                        .map(ResponseType::Events)
+                })
+                .await
+            }
         };
 
         let response = match response {
@@ -273,6 +282,10 @@ impl WalletMessageHandler {
                 let address = account_handle.get_unused_address().await?;
                 Ok(ResponseType::UnusedAddress(address))
             }
+            AccountMethod::RotateDepositAddress => {
+                let address = account_handle.rotate_deposit_address().await?;
+                Ok(ResponseType::RotatedDepositAddress(address))
+            }
             AccountMethod::ListMessages {
                 count,
                 from,
@@ -287,6 +300,10 @@ impl WalletMessageHandler {
                     .collect();
                 Ok(ResponseType::Messages(messages))
             }
+            AccountMethod::ListPendingTransactions => {
+                let messages = account_handle.list_pending_transactions().await;
+                Ok(ResponseType::Messages(messages))
+            }
             AccountMethod::ListAddresses => {
                 let addresses = account_handle.addresses().await;
                 Ok(ResponseType::Addresses(addresses))
@@ -299,6 +316,11 @@ impl WalletMessageHandler {
                 let addresses = account_handle.list_unspent_addresses().await;
                 Ok(ResponseType::Addresses(addresses))
             }
+            AccountMethod::ListAddressesByBalance => {
+                let addresses = account_handle.addresses_by_balance().await;
+                Ok(ResponseType::Addresses(addresses))
+            }
+            AccountMethod::ListOutputIds => Ok(ResponseType::OutputIds(account_handle.read().await.all_output_ids())),
             AccountMethod::GetBalance => Ok(ResponseType::Balance(account_handle.read().await.balance())),
             AccountMethod::GetLatestAddress => Ok(ResponseType::LatestAddress(
                 account_handle.read().await.latest_address().clone(),
@@ -361,6 +383,12 @@ impl WalletMessageHandler {
         if let Some(signer_type) = &account.signer_type {
             builder = builder.signer_type(signer_type.clone());
         }
+        if let Some(bech32_hrp) = &account.bech32_hrp {
+            builder = builder.bech32_hrp(bech32_hrp.clone());
+        }
+        if let Some(coin_type) = account.coin_type {
+            builder = builder.coin_type(coin_type);
+        }
 
         match builder.initialise().await {
             Ok(account_handle) => {
@@ -515,6 +543,8 @@ mod tests {
                     created_at: None,
                     skip_persistence: false,
                     signer_type: Some(signer_type.clone()),
+                    bech32_hrp: None,
+                    coin_type: Some(1),
                 };
                 #[cfg(feature = "stronghold")]
                 send_message(&tx, MessageType::SetStrongholdPassword("password".to_string())).await;
@@ -529,6 +559,7 @@ mod tests {
                 let response = send_message(&tx, MessageType::CreateAccount(Box::new(account))).await;
                 match response.response() {
                     ResponseType::CreatedAccount(created_account) => {
+                        assert_eq!(*created_account.coin_type(), 1);
                         let id = created_account.id().clone();
                         std::thread::spawn(move || {
                             std::thread::sleep(std::time::Duration::from_secs(6));
@@ -6,13 +6,15 @@ use crate::{
     message::{MessagePayload, MessageType, TransactionEssence, TransactionInput},
     signing::GenerateAddressMetadata,
 };
+use chrono::prelude::{DateTime, Local};
 use getset::{Getters, Setters};
 use iota::{
     bee_rest_api::types::dtos::{AddressDto, OutputDto},
-    MessageId, OutputId, OutputResponse, TransactionId,
+    MessageId, Output, OutputId, OutputResponse, TransactionId,
 };
 pub use iota::{Address as IotaAddress, Ed25519Address, Input, UTXOInput};
 use serde::{Deserialize, Serialize};
+use slip10::BIP32Path;
 use std::{
     cmp::Ordering,
     collections::HashMap,
@@ -21,6 +23,30 @@ use std::{
     str::FromStr,
 };
 
+/// The BIP44 coin type of IOTA addresses, used by default to derive the account addresses.
+pub const IOTA_COIN_TYPE: u32 = 4218;
+
+/// Gets the BIP32 path of the IOTA key with the given account index, change flag and key index,
+/// following the `m/44'/coin_type'/account'/change'/address_index'` scheme (all segments hardened).
+pub fn derivation_path(account_index: usize, internal: bool, key_index: usize) -> BIP32Path {
+    derivation_path_with_coin_type(IOTA_COIN_TYPE, account_index, internal, key_index)
+}
+
+/// Gets the BIP32 path of the key with the given coin type, account index, change flag and key index,
+/// e.g. for the accounts of a private Tangle.
+pub fn derivation_path_with_coin_type(
+    coin_type: u32,
+    account_index: usize,
+    internal: bool,
+    key_index: usize,
+) -> BIP32Path {
+    BIP32Path::from_str(&format!(
+        "m/44H/{}H/{}H/{}H/{}H",
+        coin_type, account_index, internal as u32, key_index
+    ))
+    .expect("invalid BIP32 path")
+}
+
 /// The address output kind.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OutputKind {
@@ -46,6 +72,50 @@ impl FromStr for OutputKind {
     }
 }
 
+/// Gets the kind, the address (if the output is locked to one) and the amount of an output.
+/// Along with [parse_output_dto] for the outputs of the node API responses, this is where the node output types are
+/// mapped to an [OutputKind]; the wallet's transaction outputs expose theirs through
+/// [TransactionOutput::kind](crate::message::TransactionOutput::kind).
+pub(crate) fn parse_output(output: &Output) -> (OutputKind, Option<&IotaAddress>, u64) {
+    match output {
+        Output::SignatureLockedSingle(o) => (OutputKind::SignatureLockedSingle, Some(o.address()), o.amount()),
+        Output::SignatureLockedDustAllowance(o) => {
+            (OutputKind::SignatureLockedDustAllowance, Some(o.address()), o.amount())
+        }
+        Output::Treasury(o) => (OutputKind::Treasury, None, o.amount()),
+        _ => unimplemented!(),
+    }
+}
+
+/// Gets the kind, the address (if the output is locked to one) and the amount of an output of a node API response.
+pub(crate) fn parse_output_dto(output: &OutputDto) -> crate::Result<(OutputKind, Option<IotaAddress>, u64)> {
+    let parsed = match output {
+        OutputDto::SignatureLockedSingle(o) => (
+            OutputKind::SignatureLockedSingle,
+            Some(address_from_dto(&o.address)?),
+            o.amount,
+        ),
+        OutputDto::SignatureLockedDustAllowance(o) => (
+            OutputKind::SignatureLockedDustAllowance,
+            Some(address_from_dto(&o.address)?),
+            o.amount,
+        ),
+        OutputDto::Treasury(o) => (OutputKind::Treasury, None, o.amount),
+    };
+    Ok(parsed)
+}
+
+fn address_from_dto(address: &AddressDto) -> crate::Result<IotaAddress> {
+    match address {
+        AddressDto::Ed25519(ed25519_address) => Ok(IotaAddress::Ed25519(Ed25519Address::new(
+            hex::decode(&ed25519_address.address)
+                .map_err(|_| crate::Error::InvalidAddress)?
+                .try_into()
+                .map_err(|_| crate::Error::InvalidAddressLength)?,
+        ))),
+    }
+}
+
 /// An Address output.
 #[derive(Debug, Getters, Setters, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[getset(get = "pub")]
@@ -101,36 +171,9 @@ impl AddressOutput {
     }
 
     pub(crate) fn from_output_response(output: OutputResponse, bech32_hrp: String) -> crate::Result<Self> {
-        let (address, amount, kind) = match output.output {
-            OutputDto::SignatureLockedSingle(output) => {
-                let address = match output.address {
-                    AddressDto::Ed25519(ed25519_address) => IotaAddress::Ed25519(Ed25519Address::new(
-                        hex::decode(ed25519_address.address)
-                            .map_err(|_| crate::Error::InvalidAddress)?
-                            .try_into()
-                            .map_err(|_| crate::Error::InvalidAddressLength)?,
-                    )),
-                };
-                (address, output.amount, OutputKind::SignatureLockedSingle)
-            }
-            OutputDto::SignatureLockedDustAllowance(output) => {
-                let address = match output.address {
-                    AddressDto::Ed25519(ed25519_address) => IotaAddress::Ed25519(Ed25519Address::new(
-                        hex::decode(ed25519_address.address)
-                            .map_err(|_| crate::Error::InvalidAddress)?
-                            .try_into()
-                            .map_err(|_| crate::Error::InvalidAddressLength)?,
-                    )),
-                };
-                (address, output.amount, OutputKind::SignatureLockedDustAllowance)
-            }
-            OutputDto::Treasury(output) => (
-                // dummy address
-                IotaAddress::Ed25519(Ed25519Address::new([0; 32])),
-                output.amount,
-                OutputKind::Treasury,
-            ),
-        };
+        let (kind, address, amount) = parse_output_dto(&output.output)?;
+        // treasury outputs aren't locked to an address, so they get a dummy one
+        let address = address.unwrap_or_else(|| IotaAddress::Ed25519(Ed25519Address::new([0; 32])));
         let output = Self {
             transaction_id: TransactionId::new(
                 hex::decode(output.transaction_id).map_err(|_| crate::Error::InvalidTransactionId)?[..]
@@ -160,6 +203,7 @@ pub struct AddressBuilder {
     key_index: Option<usize>,
     internal: bool,
     outputs: Option<Vec<AddressOutput>>,
+    imported: bool,
 }
 
 impl AddressBuilder {
@@ -198,6 +242,12 @@ impl AddressBuilder {
         self
     }
 
+    /// Sets the `imported` flag.
+    pub fn imported(mut self, imported: bool) -> Self {
+        self.imported = imported;
+        self
+    }
+
     /// Builds the address.
     pub fn build(self) -> crate::Result<Address> {
         let iota_address = self.address.ok_or(crate::Error::AddressBuildRequiredField(
@@ -220,6 +270,13 @@ impl AddressBuilder {
             ))?,
             internal: self.internal,
             outputs,
+            imported: self.imported,
+            expires_at: None,
+            expired: false,
+            retired: false,
+            created_at: Some(Local::now()),
+            label: None,
+            reserved: false,
         };
         Ok(address)
     }
@@ -255,6 +312,38 @@ impl AddressWrapper {
     pub(crate) fn bech32_hrp(&self) -> &str {
         &self.bech32_hrp
     }
+
+    /// Compares the underlying Ed25519 address, ignoring the bech32 human readable part.
+    pub(crate) fn eq_ignore_hrp(&self, other: &AddressWrapper) -> bool {
+        self.inner == other.inner
+    }
+
+    /// Encodes the raw Ed25519 address bytes as hex, without the bech32 human readable part.
+    pub fn to_hex(&self) -> String {
+        match &self.inner {
+            IotaAddress::Ed25519(address) => hex::encode(address.as_ref()),
+            _ => unimplemented!(),
+        }
+    }
+
+    /// Creates an address wrapper from the hex encoded Ed25519 address bytes and the bech32 human readable part.
+    pub fn from_hex<H: AsRef<str>>(hex: H, bech32_hrp: impl Into<String>) -> crate::Result<Self> {
+        let address = Ed25519Address::new(
+            hex::decode(hex.as_ref())
+                .map_err(|_| crate::Error::InvalidAddress)?
+                .try_into()
+                .map_err(|_| crate::Error::InvalidAddressLength)?,
+        );
+        Ok(Self::new(IotaAddress::Ed25519(address), bech32_hrp.into()))
+    }
+}
+
+/// Serializes the address as its raw hex bytes instead of the bech32 representation.
+/// Opt-in with `#[serde(serialize_with = "iota_wallet::address::serialize_hex")]`;
+/// since the hex encoding doesn't include the bech32 human readable part,
+/// use [AddressWrapper::from_hex](struct.AddressWrapper.html#method.from_hex) to parse it back.
+pub fn serialize_hex<S: serde::Serializer>(address: &AddressWrapper, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.serialize_str(&address.to_hex())
 }
 
 /// An address.
@@ -277,6 +366,36 @@ pub struct Address {
     /// The address outputs.
     #[getset(set = "pub(crate)")]
     pub(crate) outputs: HashMap<OutputId, AddressOutput>,
+    /// Determines if the address was imported instead of generated by the sequential derivation.
+    #[serde(default)]
+    #[getset(set = "pub(crate)")]
+    imported: bool,
+    /// The time after which the address is no longer expected to receive funds (e.g. a temporary invoice).
+    #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+    #[getset(set = "pub(crate)")]
+    expires_at: Option<DateTime<Local>>,
+    /// Whether the address passed its expiration time without receiving any funds.
+    #[serde(default)]
+    #[getset(set = "pub(crate)")]
+    expired: bool,
+    /// Whether the address was retired as deposit address by a deposit address rotation.
+    #[serde(default)]
+    #[getset(set = "pub(crate)")]
+    retired: bool,
+    /// The time the address was generated. `None` for addresses stored before the timestamp was tracked.
+    #[serde(rename = "createdAt", default, skip_serializing_if = "Option::is_none")]
+    #[getset(set = "pub(crate)")]
+    created_at: Option<DateTime<Local>>,
+    /// The address label, e.g. the invoice it was reserved for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[getset(set = "pub(crate)")]
+    label: Option<String>,
+    /// Whether the address was reserved with
+    /// [AccountHandle#reserve_addresses](../account/struct.AccountHandle.html#method.reserve_addresses).
+    /// Reserved addresses aren't used as deposit or remainder address.
+    #[serde(default)]
+    #[getset(set = "pub(crate)")]
+    reserved: bool,
 }
 
 impl PartialOrd for Address {
@@ -318,6 +437,46 @@ impl Address {
             .collect()
     }
 
+    /// Gets the balance of the address unspent outputs.
+    /// The outputs are fetched from the node's ledger, so their value is confirmed.
+    pub fn confirmed_balance(&self) -> u64 {
+        self.outputs
+            .values()
+            .filter(|o| !o.is_spent)
+            .fold(0, |acc, o| acc + o.amount)
+    }
+
+    /// Gets the value sent to the address by the account's broadcasted messages that aren't confirmed yet,
+    /// e.g. incoming funds or the remainder of a pending transfer.
+    pub fn pending_balance(&self, account: &Account) -> u64 {
+        account
+            .list_messages(0, 0, Some(MessageType::Unconfirmed))
+            .iter()
+            .filter(|m| *m.broadcasted())
+            .fold(0, |acc, m| match m.payload() {
+                Some(MessagePayload::Transaction(tx)) => {
+                    let TransactionEssence::Regular(essence) = tx.essence();
+                    essence
+                        .outputs()
+                        .iter()
+                        .filter(|output| output.address() == Some(&self.address))
+                        .fold(acc, |acc, output| acc + output.amount())
+                }
+                _ => acc,
+            })
+    }
+
+    /// Gets the BIP32 path of the address key on the account with the given index,
+    /// e.g. to display it or to sign with an external signer.
+    pub fn derivation_path(&self, account_index: usize) -> BIP32Path {
+        derivation_path(account_index, self.internal, self.key_index)
+    }
+
+    /// Gets the BIP32 path of the address key on the account with the given coin type and index.
+    pub fn derivation_path_with_coin_type(&self, coin_type: u32, account_index: usize) -> BIP32Path {
+        derivation_path_with_coin_type(coin_type, account_index, self.internal, self.key_index)
+    }
+
     pub(crate) fn available_balance(&self, account: &Account) -> u64 {
         self.available_outputs(account)
             .iter()
@@ -364,7 +523,12 @@ pub(crate) async fn get_iota_address(
 
 /// Gets an unused public address for the given account.
 pub(crate) async fn get_new_address(account: &Account, metadata: GenerateAddressMetadata) -> crate::Result<Address> {
-    let key_index = account.addresses().iter().filter(|a| !a.internal()).count();
+    // imported addresses aren't derived from the account seed, so they don't take a key index
+    let key_index = account
+        .addresses()
+        .iter()
+        .filter(|a| !a.internal() && !a.imported())
+        .count();
     let bech32_hrp = match account.addresses().first() {
         Some(address) => address.address().bech32_hrp().to_string(),
         None => {
@@ -384,6 +548,13 @@ pub(crate) async fn get_new_address(account: &Account, metadata: GenerateAddress
         key_index,
         internal: false,
         outputs: Default::default(),
+        imported: false,
+        expires_at: None,
+        expired: false,
+        retired: false,
+        created_at: Some(Local::now()),
+        label: None,
+        reserved: false,
     };
     Ok(address)
 }
@@ -409,6 +580,13 @@ pub(crate) async fn get_new_change_address(
         key_index,
         internal: true,
         outputs: Default::default(),
+        imported: false,
+        expires_at: None,
+        expired: false,
+        retired: false,
+        created_at: Some(Local::now()),
+        label: None,
+        reserved: false,
     };
     Ok(address)
 }
@@ -422,6 +600,86 @@ pub(crate) fn is_unspent(account: &Account, address: &AddressWrapper) -> bool {
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn hex_roundtrip() {
+        let address = crate::test_utils::generate_random_iota_address();
+        let hex = address.to_hex();
+        assert_eq!(hex.len(), 64);
+        let parsed = super::AddressWrapper::from_hex(&hex, address.bech32_hrp()).unwrap();
+        assert_eq!(parsed, address);
+        assert!(super::AddressWrapper::from_hex("00", "iota").is_err());
+
+        #[derive(serde::Serialize)]
+        struct HexAddress {
+            #[serde(serialize_with = "super::serialize_hex")]
+            address: super::AddressWrapper,
+        }
+        let serialized = serde_json::to_string(&HexAddress { address }).unwrap();
+        assert_eq!(serialized, format!("{{\"address\":\"{}\"}}", hex));
+    }
+
+    #[tokio::test]
+    async fn confirmed_and_pending_balance() {
+        let manager = crate::test_utils::get_account_manager().await;
+        let mut address = crate::test_utils::generate_random_address();
+        for (transaction_index, is_spent) in [(0, false), (1, true)].iter() {
+            let output = super::AddressOutput {
+                transaction_id: iota::TransactionId::from([*transaction_index; 32]),
+                message_id: iota::MessageId::from([0; 32]),
+                index: 0,
+                amount: 1000000,
+                is_spent: *is_spent,
+                address: address.address().clone(),
+                kind: super::OutputKind::SignatureLockedSingle,
+            };
+            address.outputs.insert(output.id().unwrap(), output);
+        }
+        let pending_tx = crate::test_utils::GenerateMessageBuilder::default()
+            .address(address.clone())
+            .value(300000)
+            .confirmed(None)
+            .broadcasted(true)
+            .incoming(true)
+            .build()
+            .await;
+        // messages that weren't broadcasted won't be confirmed
+        let failed_tx = crate::test_utils::GenerateMessageBuilder::default()
+            .address(address.clone())
+            .confirmed(None)
+            .broadcasted(false)
+            .build()
+            .await;
+        let account_handle = crate::test_utils::AccountCreator::new(&manager)
+            .addresses(vec![address.clone()])
+            .messages(vec![pending_tx, failed_tx])
+            .create()
+            .await;
+
+        let account = account_handle.read().await;
+        assert_eq!(address.confirmed_balance(), 1000000);
+        assert_eq!(address.pending_balance(&account), 300000);
+    }
+
+    #[test]
+    fn derivation_path() {
+        let address = super::AddressBuilder::new()
+            .address(crate::test_utils::generate_random_iota_address())
+            .key_index(3)
+            .balance(0)
+            .outputs(Vec::new())
+            .internal(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            address.derivation_path(2),
+            slip10::BIP32Path::from_str("m/44H/4218H/2H/1H/3H").unwrap()
+        );
+        assert_eq!(
+            address.derivation_path_with_coin_type(1, 2),
+            slip10::BIP32Path::from_str("m/44H/1H/2H/1H/3H").unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn is_unspent_false() {
         let manager = crate::test_utils::get_account_manager().await;
@@ -448,4 +706,16 @@ mod tests {
         let response = super::is_unspent(&*account_handle.read().await, &address);
         assert_eq!(response, true);
     }
+
+    #[test]
+    fn parse_output() {
+        let address = crate::test_utils::generate_random_iota_address();
+        let output = iota::SignatureLockedDustAllowanceOutput::new(*address.as_ref(), 1000000)
+            .unwrap()
+            .into();
+        let (kind, output_address, amount) = super::parse_output(&output);
+        assert_eq!(kind, super::OutputKind::SignatureLockedDustAllowance);
+        assert_eq!(output_address, Some(address.as_ref()));
+        assert_eq!(amount, 1000000);
+    }
 }
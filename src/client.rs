@@ -2,7 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use getset::Getters;
-use iota::client::{Client, ClientBuilder, MqttEvent};
+use iota::{
+    bee_rest_api::types::dtos::LedgerInclusionStateDto,
+    client::{api::finish_pow, AddressOutputsOptions, Client, ClientBuilder, MqttEvent},
+    message::prelude::{Message as IotaMessage, MessageId, Payload, UTXOInput},
+    Bech32Address, OutputResponse,
+};
 use once_cell::sync::Lazy;
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use tokio::sync::{Mutex, RwLock};
@@ -19,7 +24,11 @@ use std::{
     time::Duration,
 };
 
+/// The number of milestones used to compute the average milestone interval.
+const MILESTONE_INTERVAL_SAMPLE_SIZE: u32 = 10;
+
 type ClientInstanceMap = Arc<Mutex<HashMap<ClientOptions, (bool, Arc<RwLock<Client>>)>>>;
+type NodeClientInstanceMap = Arc<Mutex<HashMap<ClientOptions, Arc<dyn NodeClient>>>>;
 
 /// Gets the client instances map.
 fn instances() -> &'static ClientInstanceMap {
@@ -27,14 +36,240 @@ fn instances() -> &'static ClientInstanceMap {
     &INSTANCES
 }
 
+/// Gets the custom node client instances map.
+fn node_client_instances() -> &'static NodeClientInstanceMap {
+    static INSTANCES: Lazy<NodeClientInstanceMap> = Lazy::new(Default::default);
+    &INSTANCES
+}
+
+/// The node API used by the account sync and transfer processes.
+/// It's implemented by the iota.rs client, and a custom implementation (e.g. a mock)
+/// can be injected with [set_node_client](fn.set_node_client.html).
+#[async_trait::async_trait]
+pub trait NodeClient: Send + Sync {
+    /// Gets the outputs associated with the given address.
+    async fn get_address_outputs(&self, address: &Bech32Address, include_spent: bool) -> crate::Result<Vec<UTXOInput>>;
+
+    /// Gets the output with the given identifier.
+    async fn get_output(&self, output_id: &UTXOInput) -> crate::Result<OutputResponse>;
+
+    /// Gets the message with the given identifier and its confirmation state.
+    /// Returns `None` if the node doesn't know the message.
+    async fn get_message(&self, message_id: &MessageId) -> crate::Result<Option<(IotaMessage, Option<bool>)>>;
+
+    /// Gets the confirmation state of the message with the given identifier.
+    /// Returns `None` if the node doesn't know the message or its inclusion state isn't defined yet.
+    async fn get_message_confirmation(&self, message_id: &MessageId) -> crate::Result<Option<bool>> {
+        Ok(self.get_message(message_id).await?.and_then(|(_, confirmed)| confirmed))
+    }
+
+    /// Gets the number of milestones confirming the message with the given identifier,
+    /// i.e. the milestone referencing it and the ones issued after it.
+    /// Returns `None` if the node doesn't know the message or it isn't referenced by a milestone yet.
+    /// The default implementation always returns `None`,
+    /// so messages are never confirmed if the account manager has a confirmation threshold.
+    async fn get_message_confirmation_depth(&self, _message_id: &MessageId) -> crate::Result<Option<u32>> {
+        Ok(None)
+    }
+
+    /// Gets the node metrics used to estimate the confirmation time of a message.
+    /// Returns `None` if the node doesn't expose them; the default implementation always returns `None`.
+    async fn get_node_metrics(&self) -> crate::Result<Option<NodeMetrics>> {
+        Ok(None)
+    }
+
+    /// Gets the node info: its network, bech32 HRP and health.
+    async fn get_node_info(&self) -> crate::Result<NodeInfo>;
+
+    /// Gets the balance of the given address.
+    async fn balance(&self, address: &Bech32Address) -> crate::Result<u64>;
+
+    /// Gets the balance of the given addresses, in the same order.
+    /// The node has no batch balance endpoint, so the default implementation queries the addresses concurrently.
+    async fn balances(&self, addresses: &[Bech32Address]) -> crate::Result<Vec<u64>> {
+        futures::future::try_join_all(addresses.iter().map(|address| self.balance(address))).await
+    }
+
+    /// Posts the message to the node.
+    async fn post_message(&self, message: &IotaMessage) -> crate::Result<MessageId>;
+
+    /// Builds a message with the given payload, selecting its parents and performing the PoW.
+    async fn finish_pow(&self, payload: Option<Payload>) -> crate::Result<IotaMessage>;
+}
+
+#[async_trait::async_trait]
+impl NodeClient for RwLock<Client> {
+    async fn get_address_outputs(&self, address: &Bech32Address, include_spent: bool) -> crate::Result<Vec<UTXOInput>> {
+        let outputs = self
+            .read()
+            .await
+            .get_address()
+            .outputs(
+                address,
+                AddressOutputsOptions {
+                    include_spent,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(outputs.to_vec())
+    }
+
+    async fn get_output(&self, output_id: &UTXOInput) -> crate::Result<OutputResponse> {
+        self.read().await.get_output(output_id).await.map_err(Into::into)
+    }
+
+    async fn get_message(&self, message_id: &MessageId) -> crate::Result<Option<(IotaMessage, Option<bool>)>> {
+        let client = self.read().await;
+        match client.get_message().data(message_id).await {
+            Ok(message) => {
+                let confirmed = client
+                    .get_message()
+                    .metadata(message_id)
+                    .await
+                    .ok()
+                    .and_then(|metadata| metadata.ledger_inclusion_state)
+                    .map(|l| l == LedgerInclusionStateDto::Included);
+                Ok(Some((message, confirmed)))
+            }
+            Err(iota::client::Error::ResponseError(status_code, _)) if status_code == 404 => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn get_message_confirmation(&self, message_id: &MessageId) -> crate::Result<Option<bool>> {
+        match self.read().await.get_message().metadata(message_id).await {
+            Ok(metadata) => Ok(metadata
+                .ledger_inclusion_state
+                .map(|l| l == LedgerInclusionStateDto::Included)),
+            Err(iota::client::Error::ResponseError(status_code, _)) if status_code == 404 => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn get_message_confirmation_depth(&self, message_id: &MessageId) -> crate::Result<Option<u32>> {
+        let client = self.read().await;
+        let referenced_milestone_index = match client.get_message().metadata(message_id).await {
+            Ok(metadata) => metadata.referenced_by_milestone_index,
+            Err(iota::client::Error::ResponseError(status_code, _)) if status_code == 404 => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        match referenced_milestone_index {
+            Some(referenced_milestone_index) => {
+                let info = client.get_info().await?;
+                Ok(Some(
+                    info.latest_milestone_index.saturating_sub(referenced_milestone_index) + 1,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_node_metrics(&self) -> crate::Result<Option<NodeMetrics>> {
+        let client = self.read().await;
+        let info = client.get_info().await?;
+        // milestone indexes start at 1
+        let first_milestone_index = info
+            .latest_milestone_index
+            .saturating_sub(MILESTONE_INTERVAL_SAMPLE_SIZE)
+            .max(1);
+        let milestone_count = info.latest_milestone_index.saturating_sub(first_milestone_index);
+        if milestone_count == 0 || info.latest_milestone_timestamp == 0 {
+            return Ok(None);
+        }
+        let first_milestone = client.get_milestone(first_milestone_index).await?;
+        let elapsed_seconds = info
+            .latest_milestone_timestamp
+            .saturating_sub(first_milestone.timestamp);
+        let milestone_interval = if elapsed_seconds == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(elapsed_seconds).div_f64(milestone_count as f64))
+        };
+        Ok(Some(NodeMetrics {
+            is_healthy: info.is_healthy,
+            milestone_interval,
+            messages_per_second: info.messages_per_second,
+            referenced_messages_per_second: info.referenced_messages_per_second,
+            referenced_rate: info.referenced_rate,
+        }))
+    }
+
+    async fn get_node_info(&self) -> crate::Result<NodeInfo> {
+        let info = self.read().await.get_info().await?;
+        Ok(NodeInfo {
+            network_id: info.network_id,
+            bech32_hrp: info.bech32_hrp,
+            is_healthy: info.is_healthy,
+        })
+    }
+
+    async fn balance(&self, address: &Bech32Address) -> crate::Result<u64> {
+        Ok(self.read().await.get_address().balance(address).await?.balance)
+    }
+
+    async fn post_message(&self, message: &IotaMessage) -> crate::Result<MessageId> {
+        self.read().await.post_message(message).await.map_err(Into::into)
+    }
+
+    async fn finish_pow(&self, payload: Option<Payload>) -> crate::Result<IotaMessage> {
+        finish_pow(&*self.read().await, payload).await.map_err(Into::into)
+    }
+}
+
+/// Sets a custom node client implementation used by the accounts with the given client options,
+/// e.g. a mock to run the sync and transfer processes without a live node.
+pub async fn set_node_client(options: ClientOptions, client: Arc<dyn NodeClient>) {
+    node_client_instances().lock().await.insert(options, client);
+}
+
+/// Removes the custom node client implementation associated with the given client options.
+pub async fn remove_node_client(options: &ClientOptions) {
+    node_client_instances().lock().await.remove(options);
+}
+
+/// Gets the node client for the given options: the custom implementation if one was set,
+/// otherwise the iota.rs client.
+pub(crate) async fn get_node_client(
+    options: &ClientOptions,
+    is_monitoring: Option<Arc<AtomicBool>>,
+) -> crate::Result<Arc<dyn NodeClient>> {
+    if let Some(client) = node_client_instances().lock().await.get(options) {
+        return Ok(client.clone());
+    }
+    let client: Arc<dyn NodeClient> = get_client(options, is_monitoring).await?;
+    Ok(client)
+}
+
+/// Applies the confirmation threshold to the confirmation state of a message.
+/// Returns the (confirmed, confirming) pair: an included message confirmed by less than `confirmation_threshold`
+/// milestones is still pending (`None`) and flagged as confirming.
+pub(crate) async fn apply_confirmation_threshold(
+    client: &dyn NodeClient,
+    message_id: &MessageId,
+    confirmed: Option<bool>,
+    confirmation_threshold: u32,
+) -> crate::Result<(Option<bool>, bool)> {
+    if confirmed != Some(true) || confirmation_threshold <= 1 {
+        return Ok((confirmed, false));
+    }
+    let depth = client.get_message_confirmation_depth(message_id).await?.unwrap_or(0);
+    if depth >= confirmation_threshold {
+        Ok((Some(true), false))
+    } else {
+        Ok((None, true))
+    }
+}
+
 fn check_mqtt_events(client: &Client, is_monitoring: Arc<AtomicBool>) {
     let mut event_rx = client.mqtt_event_receiver();
     tokio::spawn(async move {
         while event_rx.changed().await.is_ok() {
-            let event = event_rx.borrow();
-            if *event == MqttEvent::Disconnected {
+            let connected = *event_rx.borrow() == MqttEvent::Connected;
+            if !connected {
                 is_monitoring.store(false, Ordering::Relaxed);
             }
+            crate::event::emit_mqtt_connection_state_change(connected).await;
         }
     });
 }
@@ -130,6 +365,7 @@ pub(crate) async fn get_client(
 /// Drops all clients.
 pub async fn drop_all() {
     instances().lock().await.clear();
+    node_client_instances().lock().await.clear();
 }
 
 /// The options builder for a client connected to multiple nodes.
@@ -139,6 +375,7 @@ pub struct ClientOptionsBuilder {
     network: Option<String>,
     mqtt_broker_options: Option<BrokerOptions>,
     local_pow: bool,
+    local_pow_timeout: Option<Duration>,
     node_sync_interval: Option<Duration>,
     node_sync_enabled: bool,
     request_timeout: Option<Duration>,
@@ -174,6 +411,7 @@ impl Default for ClientOptionsBuilder {
             network: None,
             mqtt_broker_options: None,
             local_pow: default_local_pow(),
+            local_pow_timeout: None,
             node_sync_interval: None,
             node_sync_enabled: default_node_sync_enabled(),
             request_timeout: None,
@@ -271,6 +509,14 @@ impl ClientOptionsBuilder {
         self
     }
 
+    /// Sets the time budget of the local PoW.
+    /// If the local PoW of a transfer exceeds it, the PoW is done by the node instead.
+    /// The exceeded local PoW can't be cancelled, so while it's still running the next transfers skip the local PoW.
+    pub fn with_local_pow_timeout(mut self, timeout: Duration) -> Self {
+        self.local_pow_timeout = Some(timeout);
+        self
+    }
+
     /// Sets the request timeout.
     pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
         self.request_timeout = Some(timeout);
@@ -292,6 +538,7 @@ impl ClientOptionsBuilder {
             network: self.network,
             mqtt_broker_options: self.mqtt_broker_options,
             local_pow: self.local_pow,
+            local_pow_timeout: self.local_pow_timeout,
             node_sync_interval: self.node_sync_interval,
             node_sync_enabled: self.node_sync_enabled,
             request_timeout: self.request_timeout,
@@ -441,6 +688,9 @@ pub struct ClientOptions {
     /// Enable local proof-of-work or not.
     #[serde(rename = "localPow", default = "default_local_pow")]
     local_pow: bool,
+    /// The time budget of the local proof-of-work, after which the node does the proof-of-work.
+    #[serde(rename = "localPowTimeout", default)]
+    local_pow_timeout: Option<Duration>,
     /// The node sync interval.
     #[serde(rename = "nodeSyncInterval")]
     node_sync_interval: Option<Duration>,
@@ -455,11 +705,171 @@ pub struct ClientOptions {
     api_timeout: HashMap<Api, Duration>,
 }
 
+/// The default timeout of a node health check if the client options don't set a request timeout.
+const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The node metrics used to estimate the confirmation time of a message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeMetrics {
+    /// Whether the node reports itself as healthy (synced).
+    pub is_healthy: bool,
+    /// The average interval between the latest milestones, if it could be computed.
+    pub milestone_interval: Option<Duration>,
+    /// The number of messages the node receives per second.
+    pub messages_per_second: f64,
+    /// The number of messages referenced by milestones per second.
+    pub referenced_messages_per_second: f64,
+    /// The percentage of the received messages referenced by milestones.
+    pub referenced_rate: f64,
+}
+
+/// The node info used to validate the account network and to check the node health.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeInfo {
+    /// The network id, e.g. `testnet7`.
+    pub network_id: String,
+    /// The bech32 human readable part of the network addresses.
+    pub bech32_hrp: String,
+    /// Whether the node reports itself as healthy (synced).
+    pub is_healthy: bool,
+}
+
+/// The result of a node health check.
+#[derive(Debug, Clone, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct NodeHealth {
+    /// The node url.
+    url: Url,
+    /// Whether the node responded to the info request within the timeout.
+    reachable: bool,
+    /// Whether the node reports itself as healthy (synced).
+    #[serde(rename = "isHealthy")]
+    is_healthy: bool,
+    /// The node network identifier, e.g. to check that the node is on the expected network.
+    #[serde(rename = "networkId")]
+    network_id: Option<String>,
+    /// Whether the node network id matches the `network` of the client options.
+    /// `None` if the node isn't reachable or the client options don't set a network.
+    #[serde(rename = "isExpectedNetwork")]
+    is_expected_network: Option<bool>,
+    /// The time the node took to respond.
+    latency: Option<Duration>,
+    /// The request error if the node isn't reachable.
+    error: Option<String>,
+}
+
 impl ClientOptions {
     /// Gets a new client options builder instance.
     pub fn builder() -> ClientOptionsBuilder {
         ClientOptionsBuilder::new()
     }
+
+    /// The options of the client doing the PoW on the node, used when the local PoW exceeds its time budget.
+    pub(crate) fn remote_pow_options(&self) -> ClientOptions {
+        let mut options = self.clone();
+        options.local_pow = false;
+        options.local_pow_timeout = None;
+        options
+    }
+
+    /// Requests the info of each configured node (in parallel) and reports its reachability, network and latency.
+    /// Each node pool URL is reported as a single entry, checking the nodes listed by the pool.
+    /// The node network id is compared with the `network` option, if it's set.
+    /// Useful to find which node is failing before a sync attempt.
+    /// Each request is limited by the `request_timeout` option, or 10 seconds if it isn't set.
+    pub async fn health_check(&self) -> Vec<NodeHealth> {
+        let timeout = self.request_timeout.unwrap_or(DEFAULT_HEALTH_CHECK_TIMEOUT);
+        let targets = self
+            .node
+            .iter()
+            .chain(self.nodes.iter())
+            .map(|node| (node.url.clone(), self.health_check_options(Some(node), None, timeout)))
+            .chain(
+                self.node_pool_urls
+                    .iter()
+                    .map(|url| (url.clone(), self.health_check_options(None, Some(url), timeout))),
+            );
+        let tasks = targets.map(|(url, options)| check_node_health(url, options, self.network.as_deref(), timeout));
+        futures::future::join_all(tasks).await
+    }
+
+    /// The options of the client checking the health of a single node or node pool.
+    pub(crate) fn health_check_options(
+        &self,
+        node: Option<&Node>,
+        node_pool_url: Option<&Url>,
+        timeout: Duration,
+    ) -> ClientOptions {
+        let mut options = self.clone();
+        options.node = node.cloned();
+        options.nodes = Vec::new();
+        options.node_pool_urls = node_pool_url.into_iter().cloned().collect();
+        options.mqtt_broker_options = None;
+        options.node_sync_enabled = false;
+        options.request_timeout = Some(timeout);
+        options
+    }
+}
+
+// Whether the network id reported by a node belongs to the given network.
+// The `testnet` and `mainnet` networks match any network id of that kind, e.g. `testnet7` or `chrysalis-mainnet`.
+fn is_expected_network(network: &str, network_id: &str) -> bool {
+    match network {
+        "testnet" | "mainnet" => network_id.contains(network),
+        _ => network_id == network,
+    }
+}
+
+// Builds a client for the health check without registering it on the client instances.
+async fn build_health_check_client(options: &ClientOptions) -> crate::Result<Client> {
+    let node_pool_urls: Vec<String> = options.node_pool_urls.iter().map(|url| url.to_string()).collect();
+    let mut client_builder = ClientBuilder::new().with_node_pool_urls(&node_pool_urls).await?;
+    if let Some(node) = &options.node {
+        client_builder = match &node.auth {
+            Some(auth) => client_builder.with_node_auth(node.url.as_str(), &auth.username, &auth.password)?,
+            None => client_builder.with_node(node.url.as_str())?,
+        };
+    }
+    if let Some(request_timeout) = options.request_timeout {
+        client_builder = client_builder.with_request_timeout(request_timeout);
+    }
+    let client = client_builder.with_node_sync_disabled().finish().await?;
+    Ok(client)
+}
+
+async fn check_node_health(url: Url, options: ClientOptions, network: Option<&str>, timeout: Duration) -> NodeHealth {
+    let mut health = NodeHealth {
+        url,
+        reachable: false,
+        is_healthy: false,
+        network_id: None,
+        is_expected_network: None,
+        latency: None,
+        error: None,
+    };
+    let start = std::time::Instant::now();
+    let res = tokio::time::timeout(timeout, async {
+        // a custom node client (e.g. a mock) registered for the health check options is used instead of the node
+        let registered_client = node_client_instances().lock().await.get(&options).cloned();
+        let client: Arc<dyn NodeClient> = match registered_client {
+            Some(client) => client,
+            None => Arc::new(RwLock::new(build_health_check_client(&options).await?)),
+        };
+        client.get_node_info().await
+    })
+    .await;
+    match res {
+        Ok(Ok(info)) => {
+            health.reachable = true;
+            health.is_healthy = info.is_healthy;
+            health.is_expected_network = network.map(|network| is_expected_network(network, &info.network_id));
+            health.network_id = Some(info.network_id);
+            health.latency = Some(start.elapsed());
+        }
+        Ok(Err(e)) => health.error = Some(e.to_string()),
+        Err(_) => health.error = Some(format!("timed out after {:?}", timeout)),
+    }
+    health
 }
 
 impl Hash for ClientOptions {
@@ -498,6 +908,71 @@ fn default_node_sync_enabled() -> bool {
 mod tests {
     use super::ClientOptionsBuilder;
 
+    #[test]
+    fn expected_network() {
+        assert!(super::is_expected_network("testnet", "testnet7"));
+        assert!(super::is_expected_network("mainnet", "chrysalis-mainnet"));
+        assert!(super::is_expected_network("private-tangle", "private-tangle"));
+        assert!(!super::is_expected_network("mainnet", "testnet7"));
+        assert!(!super::is_expected_network("private-tangle", "testnet7"));
+    }
+
+    #[tokio::test]
+    async fn health_check_unreachable_node() {
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("http://127.0.0.1:1")
+            .unwrap()
+            .with_request_timeout(std::time::Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let health = client_options.health_check().await;
+        assert_eq!(health.len(), 1);
+        assert!(!health[0].reachable());
+        assert!(health[0].network_id().is_none());
+        assert!(health[0].is_expected_network().is_none());
+        assert!(health[0].error().is_some());
+    }
+
+    #[tokio::test]
+    async fn health_check_node_and_node_pool() {
+        let client_options = ClientOptionsBuilder::new()
+            .with_node("https://node.health-check.mock")
+            .unwrap()
+            .with_node_pool_urls(&["https://pool.health-check.mock"])
+            .unwrap()
+            .with_network("testnet")
+            .with_request_timeout(std::time::Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let timeout = std::time::Duration::from_secs(1);
+        let node = client_options.node().clone().unwrap();
+        let node_pool_url = client_options.node_pool_urls()[0].clone();
+
+        super::set_node_client(
+            client_options.health_check_options(Some(&node), None, timeout),
+            std::sync::Arc::new(crate::test_utils::MockNodeClient::default()),
+        )
+        .await;
+        super::set_node_client(
+            client_options.health_check_options(None, Some(&node_pool_url), timeout),
+            std::sync::Arc::new(crate::test_utils::MockNodeClient::default().unavailable()),
+        )
+        .await;
+
+        let health = client_options.health_check().await;
+        assert_eq!(health.len(), 2);
+
+        assert_eq!(health[0].url(), &node.url);
+        assert!(health[0].reachable());
+        assert!(health[0].is_healthy());
+        assert_eq!(health[0].network_id().as_deref(), Some("testnet"));
+        assert_eq!(health[0].is_expected_network(), &Some(true));
+
+        assert_eq!(health[1].url(), &node_pool_url);
+        assert!(!health[1].reachable());
+        assert!(health[1].error().is_some());
+    }
+
     #[test]
     fn single_node_valid_url() {
         let builder_res = ClientOptionsBuilder::new().with_node("https://api.lb-0.testnet.chrysalis2.com");
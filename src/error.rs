@@ -51,6 +51,9 @@ pub enum Error {
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
     #[error("`{0}`")]
     StrongholdError(crate::stronghold::Error),
+    /// The stronghold actor runtime didn't finish its pending tasks in time.
+    #[error("the stronghold actor runtime is busy")]
+    StrongholdBusy,
     /// iota.rs error.
     #[error("`{0}`")]
     ClientError(Box<iota::client::Error>),
@@ -78,6 +81,9 @@ pub enum Error {
     /// Account isn't empty (has history or balance) - can't delete account.
     #[error("can't delete account: account has history or balance")]
     AccountNotEmpty,
+    /// Account has unconfirmed messages (e.g. pending incoming funds) - can't delete account.
+    #[error("can't delete account: account has pending transactions")]
+    AccountHasPendingTransactions,
     /// Latest account is empty (doesn't have history and balance) - can't create account.
     #[error("can't create accounts when the latest account doesn't have message history and balance")]
     LatestAccountIsEmpty,
@@ -85,9 +91,12 @@ pub enum Error {
     #[error("account not found")]
     RecordNotFound,
     /// invalid remainder value target address defined on `RemainderValueStrategy`.
-    /// the address must belong to the account.
-    #[error("the remainder value address doesn't belong to the account")]
+    /// the address must belong to the account and can't be reserved.
+    #[error("the remainder value address doesn't belong to the account or is reserved")]
     InvalidRemainderValueAddress,
+    /// The consolidation target address doesn't belong to the account.
+    #[error("the consolidation target address doesn't belong to the account")]
+    InvalidConsolidationTargetAddress,
     /// Storage access error.
     #[error("error accessing storage: {0}")]
     Storage(String),
@@ -121,6 +130,9 @@ pub enum Error {
     /// Invalid mnemonic error
     #[error("invalid mnemonic: {0}")]
     InvalidMnemonic(String),
+    /// The signer has no stored mnemonic.
+    #[error("mnemonic not set")]
+    MnemonicNotSet,
     /// Can't import accounts because the storage already exist
     #[error("failed to restore backup: storage file already exists")]
     StorageExists,
@@ -140,6 +152,9 @@ pub enum Error {
         "can't perform operation while storage is encrypted; use AccountManager::set_storage_password to decrypt storage"
     )]
     StorageIsEncrypted,
+    /// The storage filename is empty or isn't a plain file name (e.g. it contains a path separator or `..`).
+    #[error("invalid storage filename `{0}`")]
+    InvalidStorageFilename(String),
     /// cannot use index to get account - multiple index sequences found (two or more different signer types stored on
     /// accounts)
     #[error("cannot use index identifier when two signer types are used")]
@@ -167,6 +182,15 @@ pub enum Error {
     /// Account alias must be unique.
     #[error("can't create account: account alias already exists")]
     AccountAliasAlreadyExists,
+    /// Account with the same signer type and index already exists.
+    #[error("an account with the same signer type and index already exists")]
+    AccountIndexAlreadyExists,
+    /// The new signer type doesn't derive the account addresses.
+    #[error("the signer doesn't derive the account addresses")]
+    SignerMismatch,
+    /// The account sync didn't finish in time.
+    #[error("the account sync didn't finish in time")]
+    SyncTimeout,
     /// Dust error, for example not enough balance on an address.
     #[error("Dust error: {0}")]
     DustError(String),
@@ -176,6 +200,65 @@ pub enum Error {
     /// Node not synced when creating account or updating client options.
     #[error("nodes {0} not synced")]
     NodesNotSynced(String),
+    /// The address already belongs to the account.
+    #[error("the address already belongs to the account")]
+    AddressAlreadyExists,
+    /// An address with the same key index and internal flag already belongs to the account.
+    #[error("an address with the same key index already belongs to the account")]
+    AddressIndexAlreadyExists,
+    /// The operation isn't supported.
+    #[error("operation not supported")]
+    Unsupported,
+    /// A dust output created by the transaction isn't covered by a dust allowance on its address.
+    #[error("dust output of {amount}i on address {address} isn't allowed: not enough dust allowance")]
+    DustOutputNotAllowed {
+        /// The output address.
+        address: String,
+        /// The output amount.
+        amount: u64,
+    },
+    /// There isn't enough balance to create the dust allowance output needed by the transfer,
+    /// or the dust output goes to an address that doesn't belong to the account.
+    #[error("can't fund a dust allowance output on address {0}")]
+    DustAllowanceNotFunded(String),
+    /// The node couldn't be reached (connection failure or timeout).
+    #[error("node unreachable: {0}")]
+    NodeUnreachable(String),
+    /// The node rejected the request because of its rate limit.
+    #[error("node rate limit exceeded: {0}")]
+    NodeRateLimited(String),
+    /// The message is already confirmed, so it doesn't need to be promoted or reattached.
+    #[error("message {0} is already confirmed")]
+    MessageAlreadyConfirmed(String),
+    /// The output isn't owned by any of the account addresses.
+    #[error("output {0} doesn't belong to the account")]
+    OutputNotOwned(String),
+    /// The transfer value can't be reached within the maximum number of inputs.
+    #[error("the transfer requires {needed} inputs but the maximum is {max}; consolidate the account outputs first")]
+    TooManyInputsRequired {
+        /// The number of inputs needed to reach the transfer value.
+        needed: usize,
+        /// The maximum number of inputs allowed.
+        max: usize,
+    },
+    /// The backup account or the new account conflicts with an existing account.
+    #[error("account `{0}` conflicts with an existing account")]
+    AccountConflict(String),
+    /// The account wasn't derived by the manager signer, so its transactions can't be signed.
+    #[error("account `{0}` wasn't derived by the manager signer")]
+    IncompatibleSigner(String),
+    /// The transfer failed the validation.
+    #[error(
+        "invalid transfer: {}",
+        .0.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(", ")
+    )]
+    InvalidTransfer(Vec<crate::message::TransferValidationError>),
+    /// The input selection strategy couldn't reach the transfer value without leaving a dust remainder.
+    #[error("the {0:?} input selection strategy couldn't select the inputs without leaving dust")]
+    InputSelectionFailed(crate::message::InputSelectionStrategy),
+    /// The local PoW exceeded its time budget and the remote PoW failed.
+    #[error("the PoW couldn't be done in time, locally or by the node")]
+    PowTimeout,
 }
 
 impl Drop for Error {
@@ -184,9 +267,18 @@ impl Drop for Error {
     }
 }
 
+// map the node errors that the frontends need to handle to dedicated variants;
+// everything else is kept as a `ClientError`.
 impl From<iota::client::Error> for Error {
     fn from(error: iota::client::Error) -> Self {
-        Self::ClientError(Box::new(error))
+        match error {
+            iota::client::Error::ReqwestError(ref e) if e.is_connect() || e.is_timeout() => {
+                Self::NodeUnreachable(e.to_string())
+            }
+            iota::client::Error::ResponseError(429, message) => Self::NodeRateLimited(message),
+            iota::client::Error::NoNeedPromoteOrReattach(message_id) => Self::MessageAlreadyConfirmed(message_id),
+            _ => Self::ClientError(Box::new(error)),
+        }
     }
 }
 
@@ -201,6 +293,7 @@ impl From<crate::stronghold::Error> for Error {
     fn from(error: crate::stronghold::Error) -> Self {
         match error {
             crate::stronghold::Error::RecordNotFound => Self::RecordNotFound,
+            crate::stronghold::Error::Busy => Self::StrongholdBusy,
             _ => Self::StrongholdError(error),
         }
     }
@@ -248,6 +341,7 @@ impl serde::Serialize for Error {
             Self::JsonError(_) => serialize_variant(self, serializer, "JsonError"),
             #[cfg(feature = "stronghold")]
             Self::StrongholdError(_) => serialize_variant(self, serializer, "StrongholdError"),
+            Self::StrongholdBusy => serialize_variant(self, serializer, "StrongholdBusy"),
             Self::ClientError(_) => serialize_variant(self, serializer, "ClientError"),
             Self::UrlError(_) => serialize_variant(self, serializer, "UrlError"),
             Self::MessageNotFound => serialize_variant(self, serializer, "MessageNotFound"),
@@ -257,9 +351,13 @@ impl serde::Serialize for Error {
             Self::StorageDoesntExist => serialize_variant(self, serializer, "StorageDoesntExist"),
             Self::InsufficientFunds => serialize_variant(self, serializer, "InsufficientFunds"),
             Self::AccountNotEmpty => serialize_variant(self, serializer, "AccountNotEmpty"),
+            Self::AccountHasPendingTransactions => serialize_variant(self, serializer, "AccountHasPendingTransactions"),
             Self::LatestAccountIsEmpty => serialize_variant(self, serializer, "LatestAccountIsEmpty"),
             Self::RecordNotFound => serialize_variant(self, serializer, "RecordNotFound"),
             Self::InvalidRemainderValueAddress => serialize_variant(self, serializer, "InvalidRemainderValueAddress"),
+            Self::InvalidConsolidationTargetAddress => {
+                serialize_variant(self, serializer, "InvalidConsolidationTargetAddress")
+            }
             Self::Storage(_) => serialize_variant(self, serializer, "Storage"),
             Self::Panic(_) => serialize_variant(self, serializer, "Panic"),
             Self::InvalidMessageId => serialize_variant(self, serializer, "InvalidMessageId"),
@@ -271,6 +369,7 @@ impl serde::Serialize for Error {
             Self::BeeMessage(_) => serialize_variant(self, serializer, "BeeMessage"),
             Self::MnemonicEncode(_) => serialize_variant(self, serializer, "MnemonicEncode"),
             Self::InvalidMnemonic(_) => serialize_variant(self, serializer, "InvalidMnemonic"),
+            Self::MnemonicNotSet => serialize_variant(self, serializer, "MnemonicNotSet"),
             Self::InvalidBackupFile => serialize_variant(self, serializer, "InvalidBackupFile"),
             Self::InvalidBackupDestination => serialize_variant(self, serializer, "InvalidBackupDestination"),
             Self::StorageExists => serialize_variant(self, serializer, "StorageExists"),
@@ -278,6 +377,7 @@ impl serde::Serialize for Error {
             Self::RecordDecrypt(_) => serialize_variant(self, serializer, "RecordDecrypt"),
             Self::RecordEncrypt(_) => serialize_variant(self, serializer, "RecordEncrypt"),
             Self::StorageIsEncrypted => serialize_variant(self, serializer, "StorageIsEncrypted"),
+            Self::InvalidStorageFilename(_) => serialize_variant(self, serializer, "InvalidStorageFilename"),
             Self::CannotUseIndexIdentifier => serialize_variant(self, serializer, "CannotUseIndexIdentifier"),
             #[cfg(any(feature = "ledger-nano", feature = "ledger-nano-simulator"))]
             Self::LedgerMiscError => serialize_variant(self, serializer, "LedgerMiscError"),
@@ -290,9 +390,44 @@ impl serde::Serialize for Error {
             #[cfg(any(feature = "ledger-nano", feature = "ledger-nano-simulator"))]
             Self::LedgerEssenceTooLarge => serialize_variant(self, serializer, "LedgerEssenceTooLarge"),
             Self::AccountAliasAlreadyExists => serialize_variant(self, serializer, "AccountAliasAlreadyExists"),
+            Self::AccountIndexAlreadyExists => serialize_variant(self, serializer, "AccountIndexAlreadyExists"),
+            Self::SignerMismatch => serialize_variant(self, serializer, "SignerMismatch"),
+            Self::SyncTimeout => serialize_variant(self, serializer, "SyncTimeout"),
             Self::DustError(_) => serialize_variant(self, serializer, "DustError"),
             Self::InvalidOutputKind(_) => serialize_variant(self, serializer, "InvalidOutputKind"),
             Self::NodesNotSynced(_) => serialize_variant(self, serializer, "NodesNotSynced"),
+            Self::AddressAlreadyExists => serialize_variant(self, serializer, "AddressAlreadyExists"),
+            Self::AddressIndexAlreadyExists => serialize_variant(self, serializer, "AddressIndexAlreadyExists"),
+            Self::Unsupported => serialize_variant(self, serializer, "Unsupported"),
+            Self::DustOutputNotAllowed { .. } => serialize_variant(self, serializer, "DustOutputNotAllowed"),
+            Self::DustAllowanceNotFunded(_) => serialize_variant(self, serializer, "DustAllowanceNotFunded"),
+            Self::NodeUnreachable(_) => serialize_variant(self, serializer, "NodeUnreachable"),
+            Self::NodeRateLimited(_) => serialize_variant(self, serializer, "NodeRateLimited"),
+            Self::MessageAlreadyConfirmed(_) => serialize_variant(self, serializer, "MessageAlreadyConfirmed"),
+            Self::OutputNotOwned(_) => serialize_variant(self, serializer, "OutputNotOwned"),
+            Self::TooManyInputsRequired { .. } => serialize_variant(self, serializer, "TooManyInputsRequired"),
+            Self::AccountConflict(_) => serialize_variant(self, serializer, "AccountConflict"),
+            Self::IncompatibleSigner(_) => serialize_variant(self, serializer, "IncompatibleSigner"),
+            Self::InvalidTransfer(_) => serialize_variant(self, serializer, "InvalidTransfer"),
+            Self::InputSelectionFailed(_) => serialize_variant(self, serializer, "InputSelectionFailed"),
+            Self::PowTimeout => serialize_variant(self, serializer, "PowTimeout"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn node_error_mapping() {
+        let error: Error = iota::client::Error::ResponseError(429, "too many requests".to_string()).into();
+        assert!(matches!(error, Error::NodeRateLimited(_)));
+
+        let error: Error = iota::client::Error::NoNeedPromoteOrReattach("message".to_string()).into();
+        assert!(matches!(error, Error::MessageAlreadyConfirmed(ref id) if id == "message"));
+
+        let error: Error = iota::client::Error::ResponseError(500, "internal error".to_string()).into();
+        assert!(matches!(error, Error::ClientError(_)));
+    }
+}
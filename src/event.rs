@@ -10,7 +10,7 @@ use crate::{
 use getset::Getters;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
 use std::{
     ops::Deref,
@@ -20,6 +20,10 @@ use std::{
 /// The event identifier type.
 pub type EventId = [u8; 32];
 
+/// The number of events buffered for each event stream subscriber.
+/// A subscriber lagging behind misses the oldest events instead of blocking the emitters.
+const EVENT_STREAM_CAPACITY: usize = 1000;
+
 fn generate_indexation_id() -> String {
     let mut key = [0; 32];
     crypto::utils::rand::fill(&mut key).unwrap();
@@ -90,9 +94,86 @@ pub struct AddressConsolidationNeeded {
     pub address: AddressWrapper,
 }
 
-/// A transaction-related event data.
+/// The `consolidation started` event data.
+#[derive(Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct ConsolidationStarted {
+    /// The associated account identifier.
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    /// The number of addresses being consolidated.
+    #[serde(rename = "addressCount")]
+    pub address_count: usize,
+}
+
+/// The `consolidation completed` event data.
+#[derive(Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct ConsolidationCompleted {
+    /// The associated account identifier.
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    /// The identifiers of the messages sent by the consolidation.
+    #[serde(rename = "messageIds")]
+    pub message_ids: Vec<MessageId>,
+}
+
+/// The `sync completed` event data.
+/// Summarizes the events generated by an account sync when
+/// [AccountManagerBuilder#with_sync_events_summary](struct.AccountManagerBuilder.html#method.with_sync_events_summary)
+/// is set.
+#[derive(Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct SyncCompleted {
+    /// The associated account identifier.
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    /// The new messages found by the sync.
+    #[serde(rename = "newMessages")]
+    pub new_messages: Vec<Message>,
+    /// The address balance changes found by the sync.
+    #[serde(rename = "balanceChanges")]
+    pub balance_changes: Vec<BalanceEvent>,
+    /// The message confirmation state changes found by the sync.
+    #[serde(rename = "confirmationChanges")]
+    pub confirmation_changes: Vec<TransactionConfirmationChangeEvent>,
+}
+
+/// The MQTT connection state change event data.
+#[derive(Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct MqttConnectionStateChange {
+    /// Whether the MQTT client is connected or not.
+    pub connected: bool,
+}
+
+/// The address expired event data.
 #[derive(Clone, Getters, Serialize, Deserialize)]
 #[getset(get = "pub")]
+pub struct AddressExpired {
+    /// The associated account identifier.
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    /// The address that passed its expiration time without receiving funds.
+    #[serde(with = "crate::serde::iota_address_serde")]
+    pub address: AddressWrapper,
+}
+
+/// The gap limit reached event data.
+#[derive(Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct GapLimitReached {
+    /// The associated account identifier.
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    /// The gap limit suggested to sync the account with.
+    #[serde(rename = "suggestedGap")]
+    pub suggested_gap: usize,
+}
+
+/// A transaction-related event data.
+#[derive(Clone, Debug, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
 pub struct TransactionEvent {
     /// Event unique identifier.
     #[serde(rename = "indexationId")]
@@ -105,7 +186,7 @@ pub struct TransactionEvent {
 }
 
 /// A transaction confirmation state change event data.
-#[derive(Clone, Getters, Serialize, Deserialize)]
+#[derive(Clone, Debug, Getters, Serialize, Deserialize)]
 #[getset(get = "pub")]
 pub struct TransactionConfirmationChangeEvent {
     /// Event unique identifier.
@@ -121,7 +202,7 @@ pub struct TransactionConfirmationChangeEvent {
 }
 
 /// Transaction reattachment event data.
-#[derive(Clone, Getters, Serialize, Deserialize)]
+#[derive(Clone, Debug, Getters, Serialize, Deserialize)]
 #[getset(get = "pub")]
 pub struct TransactionReattachmentEvent {
     /// Event unique identifier.
@@ -137,6 +218,49 @@ pub struct TransactionReattachmentEvent {
     pub reattached_message_id: MessageId,
 }
 
+/// A persisted event of any type.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", content = "event")]
+pub enum StoredEvent {
+    /// A balance change event.
+    BalanceChange(BalanceEvent),
+    /// A transaction confirmation state change event.
+    TransactionConfirmationChange(TransactionConfirmationChangeEvent),
+    /// A new transaction event.
+    NewTransaction(TransactionEvent),
+    /// A transaction reattachment event.
+    Reattachment(TransactionReattachmentEvent),
+    /// A transaction broadcast event.
+    Broadcast(TransactionEvent),
+}
+
+/// An event delivered by the event stream, see [subscribe_events](fn.subscribe_events.html).
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", content = "event")]
+pub enum WalletEvent {
+    /// A balance change event.
+    BalanceChange(BalanceEvent),
+    /// A transaction confirmation state change event.
+    TransactionConfirmationChange(TransactionConfirmationChangeEvent),
+    /// A new transaction event.
+    NewTransaction(TransactionEvent),
+    /// A transaction reattachment event.
+    Reattachment(TransactionReattachmentEvent),
+    /// A transaction broadcast event.
+    Broadcast(TransactionEvent),
+}
+
+/// A persisted event along with the time it was saved at,
+/// returned by [AccountManager#get_events_since](struct.AccountManager.html#method.get_events_since).
+#[derive(Clone, Debug, Serialize)]
+pub struct TimestampedEvent {
+    /// The UNIX timestamp (in seconds) of the event.
+    pub timestamp: i64,
+    /// The event.
+    #[serde(flatten)]
+    pub event: StoredEvent,
+}
+
 /// Transfer event type.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -147,10 +271,14 @@ pub enum TransferProgressType {
     SelectingInputs,
     /// Generating remainder value deposit address.
     GeneratingRemainderDepositAddress,
+    /// The remainder value goes back to an input address whose outputs are all consumed by the transfer.
+    RemainderToConsumedInputAddress,
     /// Signing the transaction.
     SigningTransaction,
     /// Performing PoW.
     PerformingPoW,
+    /// The local PoW exceeded its time budget, so the PoW is done by the node.
+    FallingBackToRemotePoW,
     /// Broadcasting.
     Broadcasting,
 }
@@ -254,6 +382,54 @@ struct TransferProgressHandler {
 
 event_handler_impl!(TransferProgressHandler);
 
+struct ConsolidationStartedHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&ConsolidationStarted) + Send>,
+}
+
+event_handler_impl!(ConsolidationStartedHandler);
+
+struct ConsolidationCompletedHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&ConsolidationCompleted) + Send>,
+}
+
+event_handler_impl!(ConsolidationCompletedHandler);
+
+struct SyncCompletedHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&SyncCompleted) + Send>,
+}
+
+event_handler_impl!(SyncCompletedHandler);
+
+struct MqttConnectionStateChangeHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&MqttConnectionStateChange) + Send>,
+}
+
+event_handler_impl!(MqttConnectionStateChangeHandler);
+
+struct AddressExpiredHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&AddressExpired) + Send>,
+}
+
+event_handler_impl!(AddressExpiredHandler);
+
+struct GapLimitReachedHandler {
+    id: EventId,
+    /// The on event callback.
+    on_event: Box<dyn Fn(&GapLimitReached) + Send>,
+}
+
+event_handler_impl!(GapLimitReachedHandler);
+
 type BalanceListeners = Arc<Mutex<Vec<BalanceEventHandler>>>;
 type TransactionListeners = Arc<Mutex<Vec<TransactionEventHandler>>>;
 type TransactionConfirmationChangeListeners = Arc<Mutex<Vec<TransactionConfirmationChangeEventHandler>>>;
@@ -264,6 +440,12 @@ type StrongholdStatusChangeListeners = Arc<Mutex<Vec<StrongholdStatusChangeEvent
 #[cfg(any(feature = "ledger-nano", feature = "ledger-nano-simulator"))]
 type AddressConsolidationNeededListeners = Arc<Mutex<Vec<AddressConsolidationNeededHandler>>>;
 type TransferProgressListeners = Arc<Mutex<Vec<TransferProgressHandler>>>;
+type ConsolidationStartedListeners = Arc<Mutex<Vec<ConsolidationStartedHandler>>>;
+type ConsolidationCompletedListeners = Arc<Mutex<Vec<ConsolidationCompletedHandler>>>;
+type SyncCompletedListeners = Arc<Mutex<Vec<SyncCompletedHandler>>>;
+type MqttConnectionStateChangeListeners = Arc<Mutex<Vec<MqttConnectionStateChangeHandler>>>;
+type AddressExpiredListeners = Arc<Mutex<Vec<AddressExpiredHandler>>>;
+type GapLimitReachedListeners = Arc<Mutex<Vec<GapLimitReachedHandler>>>;
 
 fn generate_event_id() -> EventId {
     let mut id = [0; 32];
@@ -327,6 +509,61 @@ fn transfer_progress_listeners() -> &'static TransferProgressListeners {
     &LISTENERS
 }
 
+/// Gets the consolidation started listeners array.
+fn consolidation_started_listeners() -> &'static ConsolidationStartedListeners {
+    static LISTENERS: Lazy<ConsolidationStartedListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the consolidation completed listeners array.
+fn consolidation_completed_listeners() -> &'static ConsolidationCompletedListeners {
+    static LISTENERS: Lazy<ConsolidationCompletedListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the sync completed listeners array.
+fn sync_completed_listeners() -> &'static SyncCompletedListeners {
+    static LISTENERS: Lazy<SyncCompletedListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the MQTT connection state change listeners array.
+fn mqtt_connection_state_change_listeners() -> &'static MqttConnectionStateChangeListeners {
+    static LISTENERS: Lazy<MqttConnectionStateChangeListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the address expired listeners array.
+fn address_expired_listeners() -> &'static AddressExpiredListeners {
+    static LISTENERS: Lazy<AddressExpiredListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the gap limit reached listeners array.
+fn gap_limit_reached_listeners() -> &'static GapLimitReachedListeners {
+    static LISTENERS: Lazy<GapLimitReachedListeners> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Gets the event stream sender.
+fn event_stream() -> &'static broadcast::Sender<WalletEvent> {
+    static SENDER: Lazy<broadcast::Sender<WalletEvent>> = Lazy::new(|| broadcast::channel(EVENT_STREAM_CAPACITY).0);
+    &SENDER
+}
+
+/// Subscribes to the stream of balance change, confirmation change, reattachment and transaction events.
+/// The events emitted after the subscription are pushed to the receiver as they happen.
+/// If the receiver lags behind, the oldest events are dropped and the next `recv` call returns a
+/// [Lagged](https://docs.rs/tokio/1/tokio/sync/broadcast/error/enum.RecvError.html#variant.Lagged) error.
+pub fn subscribe_events() -> broadcast::Receiver<WalletEvent> {
+    event_stream().subscribe()
+}
+
+fn stream_event(event: WalletEvent) {
+    // the send only fails if there are no subscribers
+    let _ = event_stream().send(event);
+}
+
 /// Listen to balance changes.
 pub async fn on_balance_change<F: Fn(&BalanceEvent) + Send + 'static>(cb: F) -> EventId {
     let mut l = balance_listeners().lock().await;
@@ -343,15 +580,12 @@ pub async fn remove_balance_change_listener(id: &EventId) {
     remove_event_listener(id, balance_listeners()).await;
 }
 
-/// Emits a balance change event.
-pub(crate) async fn emit_balance_change(
+fn balance_event(
     account: &Account,
     address: &AddressWrapper,
     message_id: Option<MessageId>,
     balance_change: BalanceChange,
-    persist: bool,
-) -> crate::Result<()> {
-    let listeners = balance_listeners().lock().await;
+) -> BalanceEvent {
     let remainder = if balance_change.spent > 0 {
         Some(false)
     } else {
@@ -361,17 +595,31 @@ pub(crate) async fn emit_balance_change(
                 .and_then(|message| message.is_remainder(&address))
         })
     };
-    let event = BalanceEvent {
+    BalanceEvent {
         indexation_id: generate_indexation_id(),
         account_id: account.id().to_string(),
         address: address.clone(),
         message_id,
         remainder,
         balance_change,
-    };
+    }
+}
+
+/// Emits a balance change event.
+pub(crate) async fn emit_balance_change(
+    account: &Account,
+    address: &AddressWrapper,
+    message_id: Option<MessageId>,
+    balance_change: BalanceChange,
+    persist: bool,
+) -> crate::Result<()> {
+    let listeners = balance_listeners().lock().await;
+    let event = balance_event(account, address, message_id, balance_change);
+    // streamed before the event is persisted, so a storage error doesn't drop it
+    stream_event(WalletEvent::BalanceChange(event.clone()));
 
     if persist {
-        crate::storage::get(account.storage_path())
+        crate::storage::get_event_storage(account.storage_path())
             .await?
             .lock()
             .await
@@ -399,9 +647,13 @@ pub(crate) async fn emit_transaction_event(
         account_id: account.id().to_string(),
         message,
     };
+    stream_event(match event_type {
+        TransactionEventType::Broadcast => WalletEvent::Broadcast(event.clone()),
+        TransactionEventType::NewTransaction => WalletEvent::NewTransaction(event.clone()),
+    });
 
     if persist {
-        let storage_handle = crate::storage::get(account.storage_path()).await?;
+        let storage_handle = crate::storage::get_event_storage(account.storage_path()).await?;
         let mut storage = storage_handle.lock().await;
         match event_type {
             TransactionEventType::Broadcast => {
@@ -436,9 +688,10 @@ pub(crate) async fn emit_confirmation_state_change(
         message,
         confirmed,
     };
+    stream_event(WalletEvent::TransactionConfirmationChange(event.clone()));
 
     if persist {
-        crate::storage::get(account.storage_path())
+        crate::storage::get_event_storage(account.storage_path())
             .await?
             .lock()
             .await
@@ -467,9 +720,10 @@ pub(crate) async fn emit_reattachment_event(
         message: message.clone(),
         reattached_message_id,
     };
+    stream_event(WalletEvent::Reattachment(event.clone()));
 
     if persist {
-        crate::storage::get(account.storage_path())
+        crate::storage::get_event_storage(account.storage_path())
             .await?
             .lock()
             .await
@@ -667,6 +921,242 @@ pub(crate) async fn emit_transfer_progress(account_id: String, event: TransferPr
     }
 }
 
+/// Listen to `consolidation started` events.
+pub async fn on_consolidation_started<F: Fn(&ConsolidationStarted) + Send + 'static>(cb: F) -> EventId {
+    let mut l = consolidation_started_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(ConsolidationStartedHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the consolidation started listener associated with the given identifier.
+pub async fn remove_consolidation_started_listener(id: &EventId) {
+    remove_event_listener(id, consolidation_started_listeners()).await;
+}
+
+/// Emits a consolidation started event.
+pub(crate) async fn emit_consolidation_started(account_id: String, address_count: usize) {
+    let listeners = consolidation_started_listeners().lock().await;
+    let event = ConsolidationStarted {
+        account_id,
+        address_count,
+    };
+
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+}
+
+/// Listen to `consolidation completed` events.
+pub async fn on_consolidation_completed<F: Fn(&ConsolidationCompleted) + Send + 'static>(cb: F) -> EventId {
+    let mut l = consolidation_completed_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(ConsolidationCompletedHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the consolidation completed listener associated with the given identifier.
+pub async fn remove_consolidation_completed_listener(id: &EventId) {
+    remove_event_listener(id, consolidation_completed_listeners()).await;
+}
+
+/// Emits a consolidation completed event.
+pub(crate) async fn emit_consolidation_completed(account_id: String, message_ids: Vec<MessageId>) {
+    let listeners = consolidation_completed_listeners().lock().await;
+    let event = ConsolidationCompleted {
+        account_id,
+        message_ids,
+    };
+
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+}
+
+/// Listen to `sync completed` events.
+pub async fn on_sync_completed<F: Fn(&SyncCompleted) + Send + 'static>(cb: F) -> EventId {
+    let mut l = sync_completed_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(SyncCompletedHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the sync completed listener associated with the given identifier.
+pub async fn remove_sync_completed_listener(id: &EventId) {
+    remove_event_listener(id, sync_completed_listeners()).await;
+}
+
+/// Emits a sync completed event summarizing the events generated by an account sync.
+/// The events are still persisted individually.
+pub(crate) async fn emit_sync_completed(
+    account: &Account,
+    new_messages: Vec<Message>,
+    balance_changes: Vec<(AddressWrapper, Option<MessageId>, BalanceChange)>,
+    confirmation_changes: Vec<(Message, bool)>,
+    persist: bool,
+) -> crate::Result<()> {
+    let listeners = sync_completed_listeners().lock().await;
+    let event = SyncCompleted {
+        account_id: account.id().to_string(),
+        new_messages,
+        balance_changes: balance_changes
+            .into_iter()
+            .map(|(address, message_id, balance_change)| balance_event(account, &address, message_id, balance_change))
+            .collect(),
+        confirmation_changes: confirmation_changes
+            .into_iter()
+            .map(|(message, confirmed)| TransactionConfirmationChangeEvent {
+                indexation_id: generate_indexation_id(),
+                account_id: account.id().to_string(),
+                message,
+                confirmed,
+            })
+            .collect(),
+    };
+
+    let new_transaction_events: Vec<TransactionEvent> = event
+        .new_messages
+        .iter()
+        .map(|message| TransactionEvent {
+            indexation_id: generate_indexation_id(),
+            account_id: account.id().to_string(),
+            message: message.clone(),
+        })
+        .collect();
+
+    // the event stream doesn't deliver the summary, so the summarized events are streamed individually
+    for new_transaction_event in &new_transaction_events {
+        stream_event(WalletEvent::NewTransaction(new_transaction_event.clone()));
+    }
+    for balance_change_event in &event.balance_changes {
+        stream_event(WalletEvent::BalanceChange(balance_change_event.clone()));
+    }
+    for confirmation_change_event in &event.confirmation_changes {
+        stream_event(WalletEvent::TransactionConfirmationChange(
+            confirmation_change_event.clone(),
+        ));
+    }
+
+    if persist {
+        let storage_handle = crate::storage::get_event_storage(account.storage_path()).await?;
+        let mut storage = storage_handle.lock().await;
+        for new_transaction_event in &new_transaction_events {
+            storage.save_new_transaction_event(new_transaction_event).await?;
+        }
+        for balance_change_event in &event.balance_changes {
+            storage.save_balance_change_event(balance_change_event).await?;
+        }
+        for confirmation_change_event in &event.confirmation_changes {
+            storage
+                .save_transaction_confirmation_event(confirmation_change_event)
+                .await?;
+        }
+    }
+
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+
+    Ok(())
+}
+
+/// Listen to MQTT connection state changes.
+/// If the connection is restored but the account events stopped arriving, the account subscriptions can be restored
+/// with [AccountHandle#resubscribe_mqtt](struct.AccountHandle.html#method.resubscribe_mqtt).
+pub async fn on_mqtt_connection_state_change<F: Fn(&MqttConnectionStateChange) + Send + 'static>(cb: F) -> EventId {
+    let mut l = mqtt_connection_state_change_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(MqttConnectionStateChangeHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the MQTT connection state change listener associated with the given identifier.
+pub async fn remove_mqtt_connection_state_change_listener(id: &EventId) {
+    remove_event_listener(id, mqtt_connection_state_change_listeners()).await;
+}
+
+/// Emits a MQTT connection state change event.
+pub(crate) async fn emit_mqtt_connection_state_change(connected: bool) {
+    let listeners = mqtt_connection_state_change_listeners().lock().await;
+    let event = MqttConnectionStateChange { connected };
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+}
+
+/// Listen to address expirations.
+/// An address generated with
+/// [AccountHandle#generate_address_with_expiration](../account/struct.AccountHandle.html#method.generate_address_with_expiration)
+/// expires when it doesn't receive funds before its expiration time.
+pub async fn on_address_expired<F: Fn(&AddressExpired) + Send + 'static>(cb: F) -> EventId {
+    let mut l = address_expired_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(AddressExpiredHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the address expired listener associated with the given identifier.
+pub async fn remove_address_expired_listener(id: &EventId) {
+    remove_event_listener(id, address_expired_listeners()).await;
+}
+
+/// Emits an address expired event.
+pub(crate) async fn emit_address_expired(account: &Account, address: AddressWrapper) {
+    let listeners = address_expired_listeners().lock().await;
+    let event = AddressExpired {
+        account_id: account.id().to_string(),
+        address,
+    };
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+}
+
+/// Listen to gap limit reached events.
+/// Triggered when an account sync found funds up to the gap limit boundary but couldn't look beyond it,
+/// so there might be funds on addresses that weren't synced.
+pub async fn on_gap_limit_reached<F: Fn(&GapLimitReached) + Send + 'static>(cb: F) -> EventId {
+    let mut l = gap_limit_reached_listeners().lock().await;
+    let id = generate_event_id();
+    l.push(GapLimitReachedHandler {
+        id,
+        on_event: Box::new(cb),
+    });
+    id
+}
+
+/// Removes the gap limit reached listener associated with the given identifier.
+pub async fn remove_gap_limit_reached_listener(id: &EventId) {
+    remove_event_listener(id, gap_limit_reached_listeners()).await;
+}
+
+/// Emits a gap limit reached event.
+pub(crate) async fn emit_gap_limit_reached(account: &Account, suggested_gap: usize) {
+    let listeners = gap_limit_reached_listeners().lock().await;
+    let event = GapLimitReached {
+        account_id: account.id().to_string(),
+        suggested_gap,
+    };
+    for listener in listeners.deref() {
+        (listener.on_event)(&event);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -676,6 +1166,25 @@ mod tests {
         let _ = crate::Error::RecordNotFound;
     }
 
+    // Receives the next stream event of the given account, skipping the events of other accounts.
+    async fn recv_account_event(
+        receiver: &mut broadcast::Receiver<WalletEvent>,
+        account_id: &str,
+    ) -> Result<WalletEvent, broadcast::error::RecvError> {
+        loop {
+            let event = receiver.recv().await?;
+            let event_account_id = match &event {
+                WalletEvent::BalanceChange(event) => &event.account_id,
+                WalletEvent::TransactionConfirmationChange(event) => &event.account_id,
+                WalletEvent::NewTransaction(event) | WalletEvent::Broadcast(event) => &event.account_id,
+                WalletEvent::Reattachment(event) => &event.account_id,
+            };
+            if event_account_id == account_id {
+                return Ok(event);
+            }
+        }
+    }
+
     // have to fork this test so other errors dropped doesn't affect it
     rusty_fork_test! {
         #[test]
@@ -715,6 +1224,47 @@ mod tests {
             });
         }
 
+        #[test]
+        fn event_stream() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let manager = crate::test_utils::get_account_manager().await;
+                let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+                let account = account_handle.read().await;
+                let message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+                let mut receiver = subscribe_events();
+
+                let address = crate::test_utils::generate_random_iota_address();
+                emit_balance_change(&account, &address, None, BalanceChange::received(5), false)
+                    .await
+                    .unwrap();
+                emit_transaction_event(TransactionEventType::NewTransaction, &account, message.clone(), false)
+                    .await
+                    .unwrap();
+                // other tasks might emit events on the global stream, so only this account's events are checked
+                match recv_account_event(&mut receiver, account.id()).await.unwrap() {
+                    WalletEvent::BalanceChange(event) => assert_eq!(event.balance_change.received, 5),
+                    _ => panic!("unexpected event"),
+                }
+                match recv_account_event(&mut receiver, account.id()).await.unwrap() {
+                    WalletEvent::NewTransaction(event) => assert!(event.message == message),
+                    _ => panic!("unexpected event"),
+                }
+
+                // a lagging receiver misses the oldest events without blocking the emitter
+                for _ in 0..=EVENT_STREAM_CAPACITY {
+                    emit_balance_change(&account, &address, None, BalanceChange::received(5), false)
+                        .await
+                        .unwrap();
+                }
+                assert!(matches!(
+                    receiver.recv().await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) if skipped >= 1
+                ));
+                assert!(recv_account_event(&mut receiver, account.id()).await.is_ok());
+            });
+        }
+
         #[test]
         fn on_new_transaction_event() {
             let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -784,6 +1334,27 @@ mod tests {
             });
         }
 
+        #[test]
+        fn on_consolidation_events() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let message_id = MessageId::new([0; 32]);
+                on_consolidation_started(|event| {
+                    assert!(event.account_id == "account-id");
+                    assert!(event.address_count == 2);
+                })
+                .await;
+                on_consolidation_completed(move |event| {
+                    assert!(event.account_id == "account-id");
+                    assert!(event.message_ids == vec![message_id]);
+                })
+                .await;
+
+                emit_consolidation_started("account-id".to_string(), 2).await;
+                emit_consolidation_completed("account-id".to_string(), vec![message_id]).await;
+            });
+        }
+
         #[test]
         fn on_confirmation_state_change_event() {
             let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -808,5 +1379,102 @@ mod tests {
                     .unwrap();
             });
         }
+
+        #[test]
+        fn on_sync_completed_event() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let manager = crate::test_utils::get_account_manager().await;
+                let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+                let account = account_handle.read().await;
+                let account_id = account.id().to_string();
+                let address = crate::test_utils::generate_random_iota_address();
+                let address_ = address.clone();
+                let message = crate::test_utils::GenerateMessageBuilder::default().build().await;
+                let message_ = message.clone();
+
+                on_sync_completed(move |event| {
+                    assert!(event.account_id == account_id);
+                    assert!(event.new_messages == vec![message_.clone()]);
+                    assert!(event.balance_changes.len() == 1);
+                    assert!(event.balance_changes[0].address == address_);
+                    assert!(event.balance_changes[0].balance_change == BalanceChange::received(5));
+                    assert!(event.confirmation_changes.is_empty());
+                })
+                .await;
+                let mut receiver = subscribe_events();
+
+                emit_sync_completed(
+                    &account,
+                    vec![message.clone()],
+                    vec![(address, None, BalanceChange::received(5))],
+                    vec![],
+                    false,
+                )
+                .await
+                .unwrap();
+
+                // the summarized events are also delivered by the event stream
+                match recv_account_event(&mut receiver, account.id()).await.unwrap() {
+                    WalletEvent::NewTransaction(event) => assert!(event.message == message),
+                    _ => panic!("unexpected event"),
+                }
+                match recv_account_event(&mut receiver, account.id()).await.unwrap() {
+                    WalletEvent::BalanceChange(event) => assert_eq!(event.balance_change.received, 5),
+                    _ => panic!("unexpected event"),
+                }
+            });
+        }
+
+        #[test]
+        fn on_mqtt_connection_state_change_event() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                on_mqtt_connection_state_change(|event| {
+                    assert!(!event.connected);
+                })
+                .await;
+
+                emit_mqtt_connection_state_change(false).await;
+            });
+        }
+
+        #[test]
+        fn on_address_expired_event() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let manager = crate::test_utils::get_account_manager().await;
+                let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+                let address = crate::test_utils::generate_random_address();
+                let expected_address = address.address().clone();
+
+                on_address_expired(move |event| {
+                    assert!(event.address == expected_address);
+                })
+                .await;
+
+                let account = account_handle.read().await;
+                emit_address_expired(&account, address.address().clone()).await;
+            });
+        }
+
+        #[test]
+        fn on_gap_limit_reached_event() {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let manager = crate::test_utils::get_account_manager().await;
+                let account_handle = crate::test_utils::AccountCreator::new(&manager).create().await;
+                let account_id = account_handle.id().await;
+
+                on_gap_limit_reached(move |event| {
+                    assert!(event.account_id == account_id);
+                    assert!(event.suggested_gap == 20);
+                })
+                .await;
+
+                let account = account_handle.read().await;
+                emit_gap_limit_reached(&account, 20).await;
+            });
+        }
     }
 }
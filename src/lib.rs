@@ -100,24 +100,27 @@ pub fn get_ledger_status(is_simulator: bool) -> LedgerStatus {
 mod test_utils {
     use super::{
         account::AccountHandle,
-        account_manager::AccountManager,
-        address::{Address, AddressBuilder, AddressWrapper},
-        client::ClientOptionsBuilder,
+        account_manager::{AccountManager, AccountManagerBuilder},
+        address::{Address, AddressBuilder, AddressOutput, AddressWrapper, OutputKind},
+        client::{ClientOptions, ClientOptionsBuilder, NodeClient, NodeInfo, NodeMetrics},
         message::{Message, MessagePayload, TransactionBuilderMetadata, TransactionEssence},
         signing::SignerType,
     };
     use iota::{
         pow::providers::{Provider as PowProvider, ProviderBuilder as PowProviderBuilder},
-        Address as IotaAddress, Ed25519Address, Ed25519Signature, Essence, MessageId, Payload,
-        SignatureLockedSingleOutput, SignatureUnlock, TransactionId, TransactionPayloadBuilder, UTXOInput, UnlockBlock,
-        UnlockBlocks,
+        Address as IotaAddress, Bech32Address, Ed25519Address, Ed25519Signature, Essence, Message as IotaMessage,
+        MessageBuilder as IotaMessageBuilder, MessageId, OutputResponse, Parents, Payload, SignatureLockedSingleOutput,
+        SignatureUnlock, TransactionId, TransactionPayloadBuilder, UTXOInput, UnlockBlock, UnlockBlocks,
     };
     use once_cell::sync::OnceCell;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
     use std::{
         collections::HashMap,
         path::PathBuf,
-        sync::{atomic::AtomicBool, Arc},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex as StdMutex,
+        },
     };
     use tokio::sync::Mutex;
 
@@ -192,6 +195,11 @@ mod test_utils {
     }
 
     pub async fn get_account_manager() -> AccountManager {
+        build_account_manager(AccountManager::builder()).await
+    }
+
+    /// Finishes the builder with a random storage folder and registers the test signer.
+    pub async fn build_account_manager(builder: AccountManagerBuilder) -> AccountManager {
         let storage_path = loop {
             let storage_path: String = thread_rng()
                 .sample_iter(&Alphanumeric)
@@ -204,7 +212,7 @@ mod test_utils {
             }
         };
 
-        let mut manager = AccountManager::builder()
+        let mut manager = builder
             .with_storage(storage_path, None)
             .unwrap()
             .skip_polling()
@@ -349,6 +357,7 @@ mod test_utils {
         addresses: Vec<Address>,
         messages: Vec<Message>,
         signer_type: Option<SignerType>,
+        client_options: Option<ClientOptions>,
     }
 
     impl<'a> AccountCreator<'a> {
@@ -358,9 +367,15 @@ mod test_utils {
                 addresses: Vec::new(),
                 messages: Vec::new(),
                 signer_type: None,
+                client_options: None,
             }
         }
 
+        pub fn client_options(mut self, client_options: ClientOptions) -> Self {
+            self.client_options.replace(client_options);
+            self
+        }
+
         pub fn addresses(mut self, addresses: Vec<Address>) -> Self {
             self.addresses = addresses;
             self
@@ -377,11 +392,13 @@ mod test_utils {
         }
 
         pub async fn create(self) -> AccountHandle {
-            let client_options = ClientOptionsBuilder::new()
-                .with_node("https://api.lb-0.testnet.chrysalis2.com")
-                .expect("invalid node URL")
-                .build()
-                .unwrap();
+            let client_options = self.client_options.unwrap_or_else(|| {
+                ClientOptionsBuilder::new()
+                    .with_node("https://api.lb-0.testnet.chrysalis2.com")
+                    .expect("invalid node URL")
+                    .build()
+                    .unwrap()
+            });
 
             let mut account_initialiser = self.manager.create_account(client_options).unwrap();
             if let Some(signer_type) = self.signer_type {
@@ -397,6 +414,236 @@ mod test_utils {
         }
     }
 
+    /// Tracks the address output requests a mock handles at the same time.
+    #[derive(Default)]
+    pub struct AddressRequestTracker {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+        requested_addresses: StdMutex<Vec<String>>,
+    }
+
+    impl AddressRequestTracker {
+        /// The highest number of address output requests handled at the same time.
+        pub fn peak(&self) -> usize {
+            self.peak.load(Ordering::SeqCst)
+        }
+
+        /// The addresses in the order their outputs were first requested.
+        pub fn requested_addresses(&self) -> Vec<String> {
+            self.requested_addresses.lock().unwrap().clone()
+        }
+    }
+
+    /// A node client mock serving the registered outputs and messages.
+    #[derive(Default)]
+    pub struct MockNodeClient {
+        outputs: Vec<AddressOutput>,
+        messages: HashMap<MessageId, (IotaMessage, Option<bool>)>,
+        confirmation_depths: HashMap<MessageId, u32>,
+        node_metrics: Option<NodeMetrics>,
+        node_info: Option<NodeInfo>,
+        pow_delay: Option<std::time::Duration>,
+        address_request_tracker: Option<(Arc<AddressRequestTracker>, std::time::Duration)>,
+        unavailable: bool,
+        unavailable_addresses: Vec<String>,
+        pub posted_messages: StdMutex<Vec<IotaMessage>>,
+    }
+
+    impl MockNodeClient {
+        pub fn with_output(mut self, output: AddressOutput) -> Self {
+            self.outputs.push(output);
+            self
+        }
+
+        pub fn with_message(mut self, message: IotaMessage, confirmed: Option<bool>) -> Self {
+            self.messages.insert(message.id().0, (message, confirmed));
+            self
+        }
+
+        pub fn with_confirmation_depth(mut self, message_id: MessageId, depth: u32) -> Self {
+            self.confirmation_depths.insert(message_id, depth);
+            self
+        }
+
+        /// Sets the metrics returned by `get_node_metrics`; without them the node doesn't expose any.
+        pub fn with_node_metrics(mut self, node_metrics: NodeMetrics) -> Self {
+            self.node_metrics.replace(node_metrics);
+            self
+        }
+
+        /// Sets the info returned by `get_node_info`; defaults to a healthy `testnet` node with the `atoi` HRP.
+        pub fn with_node_info(mut self, node_info: NodeInfo) -> Self {
+            self.node_info.replace(node_info);
+            self
+        }
+
+        /// Blocks the thread during the PoW as if it was done on a slow device.
+        pub fn with_pow_delay(mut self, delay: std::time::Duration) -> Self {
+            self.pow_delay.replace(delay);
+            self
+        }
+
+        /// Records the address output requests on the tracker, each one taking `delay` so concurrent requests overlap.
+        pub fn with_address_request_tracker(
+            mut self,
+            tracker: Arc<AddressRequestTracker>,
+            delay: std::time::Duration,
+        ) -> Self {
+            self.address_request_tracker.replace((tracker, delay));
+            self
+        }
+
+        /// Makes the address requests fail as if the node was unavailable.
+        pub fn unavailable(mut self) -> Self {
+            self.unavailable = true;
+            self
+        }
+
+        /// Makes the requests for the given bech32 address fail as if the node was overloaded.
+        pub fn with_unavailable_address(mut self, address: String) -> Self {
+            self.unavailable_addresses.push(address);
+            self
+        }
+
+        fn check_availability(&self, address: &Bech32Address) -> crate::Result<()> {
+            if self.unavailable {
+                return Err(iota::client::Error::ResponseError(503, "node unavailable".to_string()).into());
+            }
+            if self.unavailable_addresses.contains(&address.to_string()) {
+                return Err(iota::client::Error::ResponseError(502, "bad gateway".to_string()).into());
+            }
+            Ok(())
+        }
+
+        /// Creates client options for an account using this mock and registers it.
+        pub async fn register(self) -> ClientOptions {
+            let mut id = [0; 32];
+            crypto::utils::rand::fill(&mut id).unwrap();
+            let client_options = ClientOptionsBuilder::new()
+                .with_node(&format!("https://{}.mock", hex::encode(id)))
+                .expect("invalid node URL")
+                .with_network("testnet")
+                .with_node_sync_disabled()
+                .build()
+                .unwrap();
+            crate::client::set_node_client(client_options.clone(), Arc::new(self)).await;
+            client_options
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NodeClient for MockNodeClient {
+        async fn get_address_outputs(
+            &self,
+            address: &Bech32Address,
+            include_spent: bool,
+        ) -> crate::Result<Vec<UTXOInput>> {
+            self.check_availability(address)?;
+            if let Some((tracker, delay)) = &self.address_request_tracker {
+                {
+                    let mut requested_addresses = tracker.requested_addresses.lock().unwrap();
+                    if !requested_addresses.contains(&address.to_string()) {
+                        requested_addresses.push(address.to_string());
+                    }
+                }
+                let in_flight = tracker.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                tracker.peak.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(*delay).await;
+                tracker.in_flight.fetch_sub(1, Ordering::SeqCst);
+            }
+            let mut outputs = Vec::new();
+            for output in &self.outputs {
+                if output.address.to_bech32() == address.to_string() && (include_spent || !output.is_spent) {
+                    outputs.push(UTXOInput::new(output.transaction_id, output.index)?);
+                }
+            }
+            Ok(outputs)
+        }
+
+        async fn get_output(&self, output_id: &UTXOInput) -> crate::Result<OutputResponse> {
+            let output = self
+                .outputs
+                .iter()
+                .find(|o| &o.id().unwrap() == output_id.output_id())
+                .ok_or_else(|| iota::client::Error::ResponseError(404, "output not found".to_string()))?;
+            let address = match output.address.as_ref() {
+                IotaAddress::Ed25519(address) => hex::encode(address.as_ref()),
+                _ => unimplemented!(),
+            };
+            let output_dto = match output.kind {
+                OutputKind::Treasury => serde_json::json!({ "type": 2, "amount": output.amount }),
+                _ => serde_json::json!({
+                    "type": if output.kind == OutputKind::SignatureLockedDustAllowance { 1 } else { 0 },
+                    "address": { "type": 0, "address": address },
+                    "amount": output.amount,
+                }),
+            };
+            let response = serde_json::json!({
+                "messageId": output.message_id.to_string(),
+                "transactionId": output.transaction_id.to_string(),
+                "outputIndex": output.index,
+                "isSpent": output.is_spent,
+                "ledgerIndex": 0,
+                "output": output_dto,
+            });
+            Ok(serde_json::from_value(response)?)
+        }
+
+        async fn get_message(&self, message_id: &MessageId) -> crate::Result<Option<(IotaMessage, Option<bool>)>> {
+            Ok(self.messages.get(message_id).cloned())
+        }
+
+        async fn get_message_confirmation_depth(&self, message_id: &MessageId) -> crate::Result<Option<u32>> {
+            Ok(self.confirmation_depths.get(message_id).copied())
+        }
+
+        async fn get_node_metrics(&self) -> crate::Result<Option<NodeMetrics>> {
+            Ok(self.node_metrics)
+        }
+
+        async fn get_node_info(&self) -> crate::Result<NodeInfo> {
+            if self.unavailable {
+                return Err(iota::client::Error::ResponseError(503, "node unavailable".to_string()).into());
+            }
+            Ok(self.node_info.clone().unwrap_or_else(|| NodeInfo {
+                network_id: "testnet".to_string(),
+                bech32_hrp: "atoi".to_string(),
+                is_healthy: true,
+            }))
+        }
+
+        async fn balance(&self, address: &Bech32Address) -> crate::Result<u64> {
+            self.check_availability(address)?;
+            Ok(self
+                .outputs
+                .iter()
+                .filter(|o| o.address.to_bech32() == address.to_string() && !o.is_spent)
+                .fold(0, |acc, o| acc + o.amount))
+        }
+
+        async fn post_message(&self, message: &IotaMessage) -> crate::Result<MessageId> {
+            self.posted_messages.lock().unwrap().push(message.clone());
+            Ok(message.id().0)
+        }
+
+        async fn finish_pow(&self, payload: Option<Payload>) -> crate::Result<IotaMessage> {
+            // the local PoW never yields to the runtime, so the delay blocks the thread
+            if let Some(delay) = self.pow_delay {
+                std::thread::sleep(delay);
+            }
+            let mut builder = IotaMessageBuilder::<NoopNonceProvider>::new()
+                .with_network_id(0)
+                .with_parents(Parents::new(vec![MessageId::new([0; 32])])?);
+            if let Some(payload) = payload {
+                builder = builder.with_payload(payload);
+            }
+            builder
+                .with_nonce_provider(NoopNonceProvider {}, 0f64, None)
+                .finish()
+                .map_err(Into::into)
+        }
+    }
+
     pub fn generate_random_iota_address() -> AddressWrapper {
         AddressWrapper::new(
             IotaAddress::Ed25519(Ed25519Address::new(rand::random::<[u8; 32]>())),
@@ -414,6 +661,27 @@ mod test_utils {
             .unwrap()
     }
 
+    /// Generates an unspent output locked to the address, with a random transaction id.
+    pub fn generate_address_output(address: &AddressWrapper, amount: u64, kind: OutputKind) -> AddressOutput {
+        AddressOutput {
+            transaction_id: TransactionId::new(rand::random::<[u8; 32]>()),
+            message_id: MessageId::new([0; 32]),
+            index: 0,
+            amount,
+            is_spent: false,
+            address: address.clone(),
+            kind,
+        }
+    }
+
+    /// Adds a generated unspent output to the address, increasing its balance.
+    pub fn add_address_output(address: &mut Address, amount: u64, kind: OutputKind) -> AddressOutput {
+        let output = generate_address_output(address.address(), amount, kind);
+        address.outputs.insert(output.id().unwrap(), output.clone());
+        address.set_balance(*address.balance() + amount);
+        output
+    }
+
     macro_rules! builder_setters {
         ($ty:ident, $($x:ident => $type:ty),*) => {
             impl $ty {
@@ -515,7 +783,10 @@ mod test_utils {
                 timestamp: chrono::Utc::now(),
                 nonce: 0,
                 confirmed: self.confirmed,
+                confirming: false,
                 broadcasted: self.broadcasted,
+                memo: None,
+                idempotency_key: None,
             }
         }
     }
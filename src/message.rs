@@ -2,20 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    account::{find_dust_warnings, Account, DUST_ALLOWANCE_VALUE},
     account_manager::AccountStore,
-    address::{Address, AddressOutput, AddressWrapper, IotaAddress},
+    address::{parse_output, Address, AddressOutput, AddressWrapper, IotaAddress, OutputKind},
     client::ClientOptions,
     event::{emit_transfer_progress, TransferProgressType},
 };
 use bee_common::packable::Packable;
 use chrono::prelude::{DateTime, Utc};
 use getset::{Getters, Setters};
+use iota::message::constants::INPUT_OUTPUT_COUNT_MAX;
 pub use iota::{
-    Essence, IndexationPayload, Input, Message as IotaMessage, MessageId, MilestonePayload, Output, Payload,
+    Essence, IndexationPayload, Input, Message as IotaMessage, MessageId, MilestonePayload, Output, OutputId, Payload,
     ReceiptPayload, RegularEssence, SignatureLockedDustAllowanceOutput, SignatureLockedSingleOutput,
     TransactionPayload, TreasuryInput, TreasuryOutput, TreasuryTransactionPayload, UTXOInput, UnlockBlock,
 };
-use serde::{de::Deserializer, Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, Deserializer},
+    Deserialize, Serialize,
+};
 use serde_repr::Deserialize_repr;
 use std::{
     cmp::Ordering,
@@ -36,6 +41,15 @@ pub enum RemainderValueStrategy {
     /// Move the remainder value to an address that must belong to the source account.
     #[serde(with = "crate::serde::iota_address_serde")]
     AccountAddress(AddressWrapper),
+    /// Move the remainder value to the source account's address with the given index.
+    AccountAddressIndex {
+        /// The address key index.
+        #[serde(rename = "keyIndex")]
+        key_index: usize,
+        /// Whether the address is a change address or a public address.
+        #[serde(default)]
+        internal: bool,
+    },
 }
 
 impl Default for RemainderValueStrategy {
@@ -44,6 +58,104 @@ impl Default for RemainderValueStrategy {
     }
 }
 
+/// Whether the transfer's deposit address can be used as an input (e.g. on self-transfers).
+/// Note that transfers to an account address always keep the remainder value on the input address
+/// ([RemainderValueStrategy::ReuseAddress](enum.RemainderValueStrategy.html#variant.ReuseAddress)),
+/// so if the deposit address is selected and there's remainder value, the input selection runs again
+/// without it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DepositAddressInputPolicy {
+    /// The deposit address is used as input only if it has more than one output.
+    Lenient,
+    /// The deposit address is never used as input.
+    Strict,
+}
+
+impl Default for DepositAddressInputPolicy {
+    fn default() -> Self {
+        Self::Lenient
+    }
+}
+
+/// The strategy used to select the transfer inputs.
+/// Every strategy other than the default one is deterministic, respects the maximum number of inputs of the transfer
+/// and fails with [InputSelectionFailed](../enum.Error.html#variant.InputSelectionFailed)
+/// if it can't reach the transfer value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputSelectionStrategy {
+    /// Looks for the inputs matching the transfer value exactly, falling back to a random selection.
+    BranchAndBound,
+    /// Consumes as few outputs as possible, preferring the addresses holding the most value per output.
+    MinimizeInputs,
+    /// Consumes the smallest balances first, so the small outputs don't pile up on the account.
+    MinimizeDust,
+    /// Consumes the balance of the addresses created first.
+    OldestFirst,
+    /// Consumes the largest balances first.
+    LargestFirst,
+}
+
+impl Default for InputSelectionStrategy {
+    fn default() -> Self {
+        Self::BranchAndBound
+    }
+}
+
+/// A problem found by [TransferBuilder#finish_validated](struct.TransferBuilder.html#method.finish_validated).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize)]
+#[serde(tag = "type")]
+pub enum TransferValidationError {
+    /// The transfer address belongs to another network than the account addresses.
+    #[error("the address HRP `{found}` doesn't match the account HRP `{expected}`")]
+    AddressNetworkMismatch {
+        /// The account bech32 HRP.
+        expected: String,
+        /// The transfer address bech32 HRP.
+        found: String,
+    },
+    /// The amount is below the dust threshold (1 Mi) and no dust allowance output would be created,
+    /// so the transfer is only accepted if the recipient address has a dust allowance.
+    #[error("the amount {amount} is below the dust threshold of 1 Mi")]
+    DustAmount {
+        /// The transfer amount.
+        amount: u64,
+    },
+    /// The amount is greater than the account available balance.
+    #[error("the amount {amount} exceeds the available balance {available}")]
+    InsufficientFunds {
+        /// The transfer amount.
+        amount: u64,
+        /// The account available balance.
+        available: u64,
+    },
+    /// The remainder value address doesn't belong to the account or is reserved.
+    #[error("the remainder value address doesn't belong to the account or is reserved")]
+    InvalidRemainderValueAddress,
+    /// The maximum number of inputs is zero.
+    #[error("the maximum number of inputs must be greater than zero")]
+    InvalidMaxInputs,
+    /// The transfer doesn't have any output.
+    #[error("the transfer must have at least one output")]
+    NoOutputs,
+    /// The transfer has more outputs than a transaction can hold along with the remainder output.
+    #[error("the transfer has {count} outputs but the maximum is {max}")]
+    TooManyOutputs {
+        /// The number of transfer outputs.
+        count: usize,
+        /// The maximum number of transfer outputs.
+        max: usize,
+    },
+    /// The address receives more than one of the transfer outputs.
+    #[error("the address {address} receives more than one transfer output")]
+    DuplicateRecipient {
+        /// The recipient address.
+        address: String,
+    },
+    /// The sum of the transfer output amounts doesn't fit on a u64.
+    #[error("the sum of the transfer amounts overflows")]
+    AmountOverflow,
+}
+
 /// A transfer to make a transaction.
 #[derive(Debug, Clone)]
 pub struct TransferBuilder {
@@ -59,6 +171,24 @@ pub struct TransferBuilder {
     input: Option<(AddressWrapper, Vec<AddressOutput>)>,
     /// Whether the transfer should emit events or not.
     with_events: bool,
+    /// Whether a dust allowance output should be created if the transfer dust outputs aren't allowed.
+    auto_dust_allowance: bool,
+    /// (Optional) memo stored locally on the transfer message.
+    memo: Option<String>,
+    /// (Optional) maximum number of inputs the transaction can consume.
+    max_inputs: Option<usize>,
+    /// Whether the deposit address can be used as input.
+    deposit_address_input: DepositAddressInputPolicy,
+    /// (Optional) key identifying the transfer, so a retried transfer isn't sent twice.
+    idempotency_key: Option<String>,
+    /// Whether the transfer output is a dust allowance output.
+    dust_allowance_output: bool,
+    /// The strategy used to select the transfer inputs.
+    input_selection_strategy: InputSelectionStrategy,
+    /// The outputs sent along with the transfer address output.
+    additional_outputs: Vec<(AddressWrapper, NonZeroU64)>,
+    /// Whether the remainder value is sent to a change address instead of an input address the transfer fully consumes.
+    reroute_consumed_input_remainder: bool,
 }
 
 impl<'de> Deserialize<'de> for TransferBuilder {
@@ -93,6 +223,15 @@ impl<'de> Deserialize<'de> for TransferBuilder {
             }
         }
 
+        #[derive(Debug, Clone, Deserialize)]
+        struct TransferOutputWrapper {
+            /// The output address.
+            #[serde(with = "crate::serde::iota_address_serde")]
+            address: AddressWrapper,
+            /// The output value.
+            amount: NonZeroU64,
+        }
+
         #[derive(Debug, Clone, Deserialize)]
         pub struct TransferBuilderWrapper {
             /// The transfer value.
@@ -104,9 +243,43 @@ impl<'de> Deserialize<'de> for TransferBuilder {
             indexation: Option<IndexationPayloadBuilder>,
             /// The strategy to use for the remainder value.
             remainder_value_strategy: RemainderValueStrategy,
+            /// Whether a dust allowance output should be created if the transfer dust outputs aren't allowed.
+            #[serde(default)]
+            auto_dust_allowance: bool,
+            /// (Optional) memo stored locally on the transfer message.
+            #[serde(default)]
+            memo: Option<String>,
+            /// (Optional) maximum number of inputs the transaction can consume.
+            #[serde(rename = "maxInputs", default)]
+            max_inputs: Option<usize>,
+            /// Whether the deposit address can be used as input.
+            #[serde(rename = "depositAddressInput", default)]
+            deposit_address_input: DepositAddressInputPolicy,
+            /// (Optional) key identifying the transfer, so a retried transfer isn't sent twice.
+            #[serde(rename = "idempotencyKey", default)]
+            idempotency_key: Option<String>,
+            /// The strategy used to select the transfer inputs.
+            #[serde(rename = "inputSelectionStrategy", default)]
+            input_selection_strategy: InputSelectionStrategy,
+            /// Whether the remainder value is sent to a change address instead of an input address the transfer
+            /// fully consumes.
+            #[serde(rename = "rerouteConsumedInputRemainder", default)]
+            reroute_consumed_input_remainder: bool,
+            /// The outputs sent along with the transfer address output.
+            #[serde(rename = "additionalOutputs", default)]
+            additional_outputs: Vec<TransferOutputWrapper>,
         }
 
         TransferBuilderWrapper::deserialize(deserializer).and_then(|builder| {
+            let additional_outputs: Vec<(AddressWrapper, NonZeroU64)> = builder
+                .additional_outputs
+                .into_iter()
+                .map(|output| (output.address, output.amount))
+                .collect();
+            let recipients: Vec<&AddressWrapper> = std::iter::once(&builder.address)
+                .chain(additional_outputs.iter().map(|(address, _)| address))
+                .collect();
+            validate_recipients(&recipients).map_err(serde::de::Error::custom)?;
             Ok(TransferBuilder {
                 amount: builder.amount,
                 address: builder.address,
@@ -117,6 +290,15 @@ impl<'de> Deserialize<'de> for TransferBuilder {
                 remainder_value_strategy: builder.remainder_value_strategy,
                 input: None,
                 with_events: true,
+                auto_dust_allowance: builder.auto_dust_allowance,
+                memo: builder.memo,
+                max_inputs: builder.max_inputs,
+                deposit_address_input: builder.deposit_address_input,
+                idempotency_key: builder.idempotency_key,
+                dust_allowance_output: false,
+                input_selection_strategy: builder.input_selection_strategy,
+                additional_outputs,
+                reroute_consumed_input_remainder: builder.reroute_consumed_input_remainder,
             })
         })
     }
@@ -132,6 +314,15 @@ impl TransferBuilder {
             remainder_value_strategy: RemainderValueStrategy::ChangeAddress,
             input: None,
             with_events: true,
+            auto_dust_allowance: false,
+            memo: None,
+            max_inputs: None,
+            deposit_address_input: DepositAddressInputPolicy::Lenient,
+            idempotency_key: None,
+            dust_allowance_output: false,
+            input_selection_strategy: InputSelectionStrategy::default(),
+            additional_outputs: Vec::new(),
+            reroute_consumed_input_remainder: false,
         }
     }
 
@@ -147,6 +338,14 @@ impl TransferBuilder {
         self
     }
 
+    /// (Optional) message indexation with a string index and the given data serialized as JSON.
+    /// The data can be read back with [Message#indexation_json](struct.Message.html#method.indexation_json).
+    pub fn with_index_and_json<T: Serialize>(self, index: String, data: &T) -> crate::Result<Self> {
+        let data = serde_json::to_vec(data)?;
+        let indexation = IndexationPayload::new(index.as_bytes(), &data)?;
+        Ok(self.with_indexation(indexation))
+    }
+
     /// Sets the addresses and utxo to use as transaction input.
     pub(crate) fn with_input(mut self, address: AddressWrapper, inputs: Vec<AddressOutput>) -> Self {
         self.input.replace((address, inputs));
@@ -158,6 +357,147 @@ impl TransferBuilder {
         self
     }
 
+    /// Sends the transfer value as a dust allowance output instead of a signature locked single output.
+    pub(crate) fn with_dust_allowance_output(mut self, flag: bool) -> Self {
+        self.dust_allowance_output = flag;
+        self
+    }
+
+    /// Creates a dust allowance output (funded from the account balance) on the address of a dust output
+    /// if the transfer would otherwise be rejected by the dust protection rules.
+    /// Only the account's own addresses (e.g. the remainder address) receive a dust allowance output;
+    /// a dust output to another address fails with `Error::DustAllowanceNotFunded`.
+    pub fn with_auto_dust_allowance(mut self, flag: bool) -> Self {
+        self.auto_dust_allowance = flag;
+        self
+    }
+
+    /// (Optional) private memo (e.g. "rent payment") stored on the transfer message.
+    /// The memo is only stored locally and isn't broadcasted to the Tangle.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// (Optional) maximum number of inputs the transaction can consume.
+    /// If the transfer value can't be reached within this limit, the transfer fails with
+    /// [TooManyInputsRequired](../enum.Error.html#variant.TooManyInputsRequired)
+    /// and the account outputs should be consolidated first.
+    pub fn with_max_inputs(mut self, max_inputs: usize) -> Self {
+        self.max_inputs = Some(max_inputs);
+        self
+    }
+
+    /// Sets the strategy used to select the transfer inputs.
+    /// Defaults to [InputSelectionStrategy::BranchAndBound](enum.InputSelectionStrategy.html#variant.BranchAndBound).
+    pub fn with_input_selection_strategy(mut self, strategy: InputSelectionStrategy) -> Self {
+        self.input_selection_strategy = strategy;
+        self
+    }
+
+    /// Sets whether the deposit address can be used as input.
+    /// Defaults to [DepositAddressInputPolicy::Lenient](enum.DepositAddressInputPolicy.html#variant.Lenient).
+    pub fn with_deposit_address_input_policy(mut self, policy: DepositAddressInputPolicy) -> Self {
+        self.deposit_address_input = policy;
+        self
+    }
+
+    /// Sends the remainder value to a change address if it would otherwise go back to an input address
+    /// whose outputs are all consumed by the transfer (e.g. with the
+    /// [ReuseAddress](enum.RemainderValueStrategy.html#variant.ReuseAddress) strategy used on self-transfers).
+    /// Regardless of the flag, the transfer emits a
+    /// [RemainderToConsumedInputAddress](../event/enum.TransferProgressType.html#variant.RemainderToConsumedInputAddress)
+    /// progress event when that happens.
+    pub fn with_reroute_consumed_input_remainder(mut self, flag: bool) -> Self {
+        self.reroute_consumed_input_remainder = flag;
+        self
+    }
+
+    /// (Optional) key identifying the transfer (e.g. a payment request id).
+    /// If a transfer with the same key was sent by the account in the last 24 hours,
+    /// the transfer isn't executed again and the message of the previous transfer is returned instead.
+    /// Useful when the transfer call might be retried, e.g. after a network error on an RPC layer.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Validates the transfer against the account that sends it and builds it.
+    /// Unlike [finish](#method.finish), which defers the checks to the transfer execution,
+    /// all the problems found are returned together on a
+    /// [InvalidTransfer](../enum.Error.html#variant.InvalidTransfer) error, e.g. to show them at once on a form.
+    /// Dust amounts are checked against the dust allowance of their recipient, fetched from the node if the recipient
+    /// isn't an account address.
+    pub async fn finish_validated(self, account: &Account) -> crate::Result<Transfer> {
+        let mut errors = Vec::new();
+
+        let account_hrp = account.bech32_hrp();
+        let outputs =
+            std::iter::once((&self.address, &self.amount)).chain(self.additional_outputs.iter().map(|(a, v)| (a, v)));
+        let mut dust_outputs: Vec<Output> = Vec::new();
+        for (address, amount) in outputs {
+            if address.bech32_hrp() != account_hrp {
+                let error = TransferValidationError::AddressNetworkMismatch {
+                    expected: account_hrp.clone(),
+                    found: address.bech32_hrp().to_string(),
+                };
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+
+            let amount = amount.get();
+            if amount < DUST_ALLOWANCE_VALUE && !self.auto_dust_allowance {
+                dust_outputs.push(SignatureLockedSingleOutput::new(*address.as_ref(), amount)?.into());
+            }
+        }
+
+        // a dust output is only allowed if the dust allowance of the recipient covers it
+        if !dust_outputs.is_empty() {
+            let client = crate::client::get_node_client(account.client_options(), None).await?;
+            for dust_warning in find_dust_warnings(account, &*client, &[], &dust_outputs).await? {
+                if let Some(amount) = dust_warning.amount() {
+                    errors.push(TransferValidationError::DustAmount { amount: *amount });
+                }
+            }
+        }
+
+        match sum_amounts(self.amount, &self.additional_outputs) {
+            Ok(amount) => {
+                let available = account.balance().available;
+                if amount > available {
+                    errors.push(TransferValidationError::InsufficientFunds { amount, available });
+                }
+            }
+            Err(error) => errors.push(error),
+        }
+
+        let remainder_address_is_valid = match &self.remainder_value_strategy {
+            RemainderValueStrategy::AccountAddress(address) => account
+                .addresses()
+                .iter()
+                .any(|a| a.address() == address && !a.reserved()),
+            RemainderValueStrategy::AccountAddressIndex { key_index, internal } => account
+                .addresses()
+                .iter()
+                .any(|a| a.key_index() == key_index && a.internal() == internal && !a.imported() && !a.reserved()),
+            _ => true,
+        };
+        if !remainder_address_is_valid {
+            errors.push(TransferValidationError::InvalidRemainderValueAddress);
+        }
+
+        if self.max_inputs == Some(0) {
+            errors.push(TransferValidationError::InvalidMaxInputs);
+        }
+
+        if errors.is_empty() {
+            Ok(self.finish())
+        } else {
+            Err(crate::Error::InvalidTransfer(errors))
+        }
+    }
+
     /// Builds the transfer.
     pub fn finish(self) -> Transfer {
         Transfer {
@@ -167,6 +507,16 @@ impl TransferBuilder {
             remainder_value_strategy: self.remainder_value_strategy,
             input: self.input,
             with_events: self.with_events,
+            auto_dust_allowance: self.auto_dust_allowance,
+            dust_allowance_address: None,
+            memo: self.memo,
+            max_inputs: self.max_inputs,
+            deposit_address_input: self.deposit_address_input,
+            idempotency_key: self.idempotency_key,
+            dust_allowance_output: self.dust_allowance_output,
+            input_selection_strategy: self.input_selection_strategy,
+            additional_outputs: self.additional_outputs,
+            reroute_consumed_input_remainder: self.reroute_consumed_input_remainder,
         }
     }
 }
@@ -186,6 +536,26 @@ pub struct Transfer {
     pub(crate) input: Option<(AddressWrapper, Vec<AddressOutput>)>,
     /// Whether the transfer should emit events or not.
     pub(crate) with_events: bool,
+    /// Whether a dust allowance output should be created if the transfer dust outputs aren't allowed.
+    pub(crate) auto_dust_allowance: bool,
+    /// The address that receives a dust allowance output created by the transfer.
+    pub(crate) dust_allowance_address: Option<AddressWrapper>,
+    /// (Optional) memo stored locally on the transfer message.
+    pub(crate) memo: Option<String>,
+    /// (Optional) maximum number of inputs the transaction can consume.
+    pub(crate) max_inputs: Option<usize>,
+    /// Whether the deposit address can be used as input.
+    pub(crate) deposit_address_input: DepositAddressInputPolicy,
+    /// (Optional) key identifying the transfer, so a retried transfer isn't sent twice.
+    pub(crate) idempotency_key: Option<String>,
+    /// Whether the transfer output is a dust allowance output.
+    pub(crate) dust_allowance_output: bool,
+    /// The strategy used to select the transfer inputs.
+    pub(crate) input_selection_strategy: InputSelectionStrategy,
+    /// The outputs sent along with the transfer address output.
+    pub(crate) additional_outputs: Vec<(AddressWrapper, NonZeroU64)>,
+    /// Whether the remainder value is sent to a change address instead of an input address the transfer fully consumes.
+    pub(crate) reroute_consumed_input_remainder: bool,
 }
 
 impl Transfer {
@@ -194,6 +564,38 @@ impl Transfer {
         TransferBuilder::new(address, amount)
     }
 
+    /// Initialises the builder of a transfer sending to several addresses on a single transaction.
+    /// The inputs are selected to cover the sum of the amounts, and the transfer settings
+    /// (e.g. the deposit address input policy) that refer to the transfer address apply to the first output.
+    /// Each address can only receive one of the outputs.
+    pub fn builder_with_outputs(outputs: Vec<(AddressWrapper, NonZeroU64)>) -> crate::Result<TransferBuilder> {
+        let recipients: Vec<&AddressWrapper> = outputs.iter().map(|(address, _)| address).collect();
+        validate_recipients(&recipients).map_err(|error| crate::Error::InvalidTransfer(vec![error]))?;
+        if let Some((amount, additional_outputs)) = outputs.split_first() {
+            sum_amounts(amount.1, additional_outputs).map_err(|error| crate::Error::InvalidTransfer(vec![error]))?;
+        }
+        let mut outputs = outputs.into_iter();
+        match outputs.next() {
+            Some((address, amount)) => {
+                let mut builder = TransferBuilder::new(address, amount);
+                builder.additional_outputs = outputs.collect();
+                Ok(builder)
+            }
+            None => Err(crate::Error::InvalidTransfer(vec![TransferValidationError::NoOutputs])),
+        }
+    }
+
+    /// The sum of the transfer output amounts.
+    /// Fails with an [InvalidTransfer](../enum.Error.html#variant.InvalidTransfer) error if it overflows.
+    pub(crate) fn total_amount(&self) -> crate::Result<u64> {
+        sum_amounts(self.amount, &self.additional_outputs).map_err(|error| crate::Error::InvalidTransfer(vec![error]))
+    }
+
+    /// Whether the address receives one of the transfer outputs.
+    pub(crate) fn is_recipient(&self, address: &AddressWrapper) -> bool {
+        &self.address == address || self.additional_outputs.iter().any(|(a, _)| a == address)
+    }
+
     pub(crate) async fn emit_event_if_needed(&self, account_id: String, event: TransferProgressType) {
         if self.with_events {
             emit_transfer_progress(account_id, event).await;
@@ -201,6 +603,38 @@ impl Transfer {
     }
 }
 
+// Sums the transfer output amounts, failing if the sum doesn't fit on a u64.
+fn sum_amounts(
+    amount: NonZeroU64,
+    additional_outputs: &[(AddressWrapper, NonZeroU64)],
+) -> std::result::Result<u64, TransferValidationError> {
+    additional_outputs.iter().try_fold(amount.get(), |acc, (_, amount)| {
+        acc.checked_add(amount.get())
+            .ok_or(TransferValidationError::AmountOverflow)
+    })
+}
+
+// Checks that the transfer recipients fit on a transaction along with the remainder output and that they're unique,
+// since a transaction can't have two outputs of the same kind to the same address.
+fn validate_recipients(recipients: &[&AddressWrapper]) -> std::result::Result<(), TransferValidationError> {
+    // a transaction output is kept for the remainder value
+    let max = INPUT_OUTPUT_COUNT_MAX - 1;
+    if recipients.len() > max {
+        return Err(TransferValidationError::TooManyOutputs {
+            count: recipients.len(),
+            max,
+        });
+    }
+    for (index, address) in recipients.iter().enumerate() {
+        if recipients[..index].contains(address) {
+            return Err(TransferValidationError::DuplicateRecipient {
+                address: address.to_bech32(),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Possible Value units.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ValueUnit {
@@ -322,7 +756,7 @@ pub enum TransactionOutput {
 }
 
 impl TransactionOutput {
-    fn new(output: &Output, bech32_hrp: String, remainder: bool) -> Self {
+    pub(crate) fn new(output: &Output, bech32_hrp: String, remainder: bool) -> Self {
         match output {
             Output::SignatureLockedSingle(output) => Self::SignatureLockedSingle(
                 TransactionSignatureLockedSingleOutput::new(output, bech32_hrp, remainder),
@@ -334,6 +768,49 @@ impl TransactionOutput {
             _ => unimplemented!(),
         }
     }
+
+    /// The output kind.
+    pub fn kind(&self) -> OutputKind {
+        match self {
+            Self::SignatureLockedSingle(_) => OutputKind::SignatureLockedSingle,
+            Self::SignatureLockedDustAllowance(_) => OutputKind::SignatureLockedDustAllowance,
+            Self::Treasury(_) => OutputKind::Treasury,
+        }
+    }
+
+    /// The address the output is locked to; treasury outputs aren't locked to an address.
+    pub fn address(&self) -> Option<&AddressWrapper> {
+        match self {
+            Self::SignatureLockedSingle(o) => Some(&o.address),
+            Self::SignatureLockedDustAllowance(o) => Some(&o.address),
+            Self::Treasury(_) => None,
+        }
+    }
+
+    fn address_mut(&mut self) -> Option<&mut AddressWrapper> {
+        match self {
+            Self::SignatureLockedSingle(o) => Some(&mut o.address),
+            Self::SignatureLockedDustAllowance(o) => Some(&mut o.address),
+            Self::Treasury(_) => None,
+        }
+    }
+
+    /// The output amount.
+    pub fn amount(&self) -> u64 {
+        match self {
+            Self::SignatureLockedSingle(o) => o.amount,
+            Self::SignatureLockedDustAllowance(o) => o.amount,
+            Self::Treasury(o) => o.amount(),
+        }
+    }
+
+    /// Whether the output holds the transaction remainder value or not.
+    pub fn is_remainder(&self) -> bool {
+        match self {
+            Self::SignatureLockedSingle(o) => o.remainder,
+            _ => false,
+        }
+    }
 }
 
 /// UTXO input.
@@ -425,8 +902,7 @@ impl TransactionRegularEssence {
                         if let Some(output) = output {
                             Some(output)
                         } else {
-                            let client = crate::client::get_client(metadata.client_options, None).await?;
-                            let client = client.read().await;
+                            let client = crate::client::get_node_client(metadata.client_options, None).await?;
                             if let Ok(output) = client.get_output(&i).await {
                                 let output = AddressOutput::from_output_response(output, metadata.bech32_hrp.clone())?;
                                 Some(output)
@@ -459,14 +935,7 @@ impl TransactionRegularEssence {
             _ => {
                 let tx_outputs: Vec<(&IotaAddress, &Output)> = tx_outputs
                     .iter()
-                    .map(|output| {
-                        let address = match output {
-                            Output::SignatureLockedDustAllowance(o) => o.address(),
-                            Output::SignatureLockedSingle(o) => o.address(),
-                            _ => unimplemented!(),
-                        };
-                        (address, output)
-                    })
+                    .filter_map(|output| parse_output(output).1.map(|address| (address, output)))
                     .collect();
                 // if all outputs belongs to the account, we can't determine whether this transfer is incoming or
                 // outgoing; so we assume that the highest address index holds the remainder, and the rest is the
@@ -556,15 +1025,10 @@ impl TransactionRegularEssence {
         let mut value = 0;
         let mut remainder_value = 0;
         for output in &outputs {
-            let (output_value, remainder) = match output {
-                TransactionOutput::SignatureLockedSingle(o) => (o.amount, o.remainder),
-                TransactionOutput::SignatureLockedDustAllowance(o) => (o.amount, false),
-                TransactionOutput::Treasury(o) => (o.amount(), false),
-            };
-            if remainder {
-                remainder_value += output_value;
+            if output.is_remainder() {
+                remainder_value += output.amount();
             } else {
-                value += output_value;
+                value += output.amount();
             }
         }
 
@@ -712,9 +1176,19 @@ pub struct Message {
     #[getset(set = "pub")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirmed: Option<bool>,
+    /// Whether the transaction is included in the ledger but not confirmed by enough milestones yet
+    /// to reach the [confirmation threshold](../account_manager/struct.AccountManagerBuilder.html#method.with_confirmation_threshold).
+    #[serde(default)]
+    pub confirming: bool,
     /// Whether the transaction is broadcasted or not.
     #[getset(set = "pub")]
     pub broadcasted: bool,
+    /// Private memo attached to the transfer. It's only stored locally, not on the Tangle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// The idempotency key of the transfer that sent the message. It's only stored locally, not on the Tangle.
+    #[serde(rename = "idempotencyKey", default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 impl Message {
@@ -723,14 +1197,8 @@ impl Message {
             match tx.essence_mut() {
                 TransactionEssence::Regular(essence) => {
                     for output in essence.outputs.iter_mut() {
-                        match output {
-                            TransactionOutput::SignatureLockedSingle(output) => {
-                                output.address.bech32_hrp = bech32_hrp.clone();
-                            }
-                            TransactionOutput::SignatureLockedDustAllowance(output) => {
-                                output.address.bech32_hrp = bech32_hrp.clone();
-                            }
-                            _ => {}
+                        if let Some(address) = output.address_mut() {
+                            address.bech32_hrp = bech32_hrp.clone();
                         }
                     }
                 }
@@ -743,13 +1211,8 @@ impl Message {
             match tx.essence() {
                 TransactionEssence::Regular(essence) => {
                     for output in essence.outputs() {
-                        let (output_address, remainder) = match output {
-                            TransactionOutput::SignatureLockedSingle(o) => (o.address(), o.remainder),
-                            TransactionOutput::SignatureLockedDustAllowance(o) => (o.address(), false),
-                            _ => unimplemented!(),
-                        };
-                        if output_address == address {
-                            return Some(remainder);
+                        if output.address() == Some(address) {
+                            return Some(output.is_remainder());
                         }
                     }
                 }
@@ -757,6 +1220,95 @@ impl Message {
         }
         None
     }
+
+    /// Gets the message indexation payload, either the message payload or the transaction essence payload.
+    pub fn indexation(&self) -> Option<&IndexationPayload> {
+        match &self.payload {
+            Some(MessagePayload::Indexation(indexation)) => Some(&**indexation),
+            Some(MessagePayload::Transaction(tx)) => match tx.essence() {
+                TransactionEssence::Regular(essence) => match essence.payload() {
+                    Some(Payload::Indexation(indexation)) => Some(&**indexation),
+                    _ => None,
+                },
+            },
+            _ => None,
+        }
+    }
+
+    /// Deserializes the JSON data of the message indexation,
+    /// e.g. set with [TransferBuilder#with_index_and_json](struct.TransferBuilder.html#method.with_index_and_json).
+    /// Returns `None` if the message doesn't have an indexation payload.
+    pub fn indexation_json<T: DeserializeOwned>(&self) -> crate::Result<Option<T>> {
+        match self.indexation() {
+            Some(indexation) => Ok(Some(serde_json::from_slice(indexation.data())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the ids of the account outputs consumed by the message transaction inputs.
+    pub fn consumed_outputs(&self, account: &Account) -> Vec<OutputId> {
+        let mut output_ids = Vec::new();
+        if let Some(MessagePayload::Transaction(tx)) = &self.payload {
+            match tx.essence() {
+                TransactionEssence::Regular(essence) => {
+                    for input in essence.inputs() {
+                        if let TransactionInput::UTXO(i) = input {
+                            let output_id = *i.input.output_id();
+                            let belongs_to_account = account.addresses().iter().any(|address| {
+                                address.outputs().contains_key(&output_id)
+                                    || i.metadata.as_ref().map(|metadata| &metadata.address) == Some(address.address())
+                            });
+                            if belongs_to_account {
+                                output_ids.push(output_id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        output_ids
+    }
+
+    /// Gets the net effect of the message transaction on the account balance once it's confirmed:
+    /// the value of the outputs it creates on account addresses (e.g. the remainder)
+    /// minus the value of the account outputs it consumes.
+    pub fn pending_balance_effect(&self, account: &Account) -> i64 {
+        let mut effect: i64 = 0;
+        if let Some(MessagePayload::Transaction(tx)) = &self.payload {
+            match tx.essence() {
+                TransactionEssence::Regular(essence) => {
+                    for input in essence.inputs() {
+                        if let TransactionInput::UTXO(i) = input {
+                            let output_id = i.input.output_id();
+                            let amount = account
+                                .addresses()
+                                .iter()
+                                .find_map(|address| address.outputs().get(output_id).map(|output| output.amount))
+                                .or_else(|| {
+                                    i.metadata
+                                        .as_ref()
+                                        .filter(|metadata| {
+                                            account.addresses().iter().any(|a| a.address() == &metadata.address)
+                                        })
+                                        .map(|metadata| metadata.amount)
+                                });
+                            if let Some(amount) = amount {
+                                effect -= amount as i64;
+                            }
+                        }
+                    }
+                    for output in essence.outputs() {
+                        if let Some(address) = output.address() {
+                            if account.addresses().iter().any(|a| a.address() == address) {
+                                effect += output.amount() as i64;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        effect
+    }
 }
 
 impl Hash for Message {
@@ -809,14 +1361,9 @@ fn transaction_outputs_belonging_to_account(
 ) -> Vec<TransactionOutput> {
     let mut outputs = Vec::new();
     for output in essence.outputs() {
-        let output_address = match output {
-            TransactionOutput::SignatureLockedDustAllowance(o) => o.address(),
-            TransactionOutput::SignatureLockedSingle(o) => o.address(),
-            _ => unimplemented!(),
-        };
         if account_addresses
             .iter()
-            .any(|address| address.address() == output_address)
+            .any(|address| output.address() == Some(address.address()))
         {
             outputs.push(output.clone());
         }
@@ -872,6 +1419,7 @@ pub(crate) struct MessageBuilder<'a> {
     account_id: &'a str,
     account_addresses: &'a [Address],
     confirmed: Option<bool>,
+    confirming: bool,
     bech32_hrp: String,
     client_options: &'a ClientOptions,
 }
@@ -893,6 +1441,7 @@ impl<'a> MessageBuilder<'a> {
             account_id,
             account_addresses,
             confirmed: None,
+            confirming: false,
             bech32_hrp,
             client_options,
         }
@@ -903,6 +1452,11 @@ impl<'a> MessageBuilder<'a> {
         self
     }
 
+    pub fn with_confirming(mut self, confirming: bool) -> Self {
+        self.confirming = confirming;
+        self
+    }
+
     pub async fn finish(self) -> crate::Result<Message> {
         let packed_payload = self.iota_message.payload().pack_new();
 
@@ -933,7 +1487,10 @@ impl<'a> MessageBuilder<'a> {
             timestamp: Utc::now(),
             nonce: self.iota_message.nonce(),
             confirmed: self.confirmed,
+            confirming: self.confirming,
             broadcasted: true,
+            memo: None,
+            idempotency_key: None,
         };
         Ok(message)
     }
@@ -969,15 +1526,9 @@ impl Message {
     pub fn addresses(&self) -> Vec<&AddressWrapper> {
         match &self.payload {
             Some(MessagePayload::Transaction(tx)) => match tx.essence() {
-                TransactionEssence::Regular(essence) => essence
-                    .outputs()
-                    .iter()
-                    .map(|output| match output {
-                        TransactionOutput::SignatureLockedDustAllowance(o) => o.address(),
-                        TransactionOutput::SignatureLockedSingle(o) => o.address(),
-                        _ => unimplemented!(),
-                    })
-                    .collect(),
+                TransactionEssence::Regular(essence) => {
+                    essence.outputs().iter().filter_map(|output| output.address()).collect()
+                }
             },
             _ => vec![],
         }
@@ -3,18 +3,15 @@
 
 use crate::{
     account::{AccountHandle, AccountSynchronizeStep},
-    address::{AddressOutput, AddressWrapper, IotaAddress},
+    address::{AddressOutput, AddressWrapper},
     client::ClientOptions,
     event::{emit_confirmation_state_change, emit_transaction_event, TransactionEventType},
-    message::{Message, MessagePayload, MessageType, TransactionEssence, TransactionInput, TransactionOutput},
+    message::{Message, MessagePayload, MessageType, TransactionEssence, TransactionInput},
 };
 
-use iota::{bee_rest_api::types::dtos::OutputDto, OutputResponse, Topic, TopicEvent};
+use iota::{OutputResponse, Topic, TopicEvent};
 
-use std::{
-    convert::TryInto,
-    sync::{atomic::AtomicBool, Arc},
-};
+use std::sync::{atomic::AtomicBool, Arc};
 
 /// Unsubscribe from all topics associated with the account.
 pub async fn unsubscribe(account_handle: AccountHandle) -> crate::Result<()> {
@@ -116,13 +113,8 @@ async fn process_output(payload: String, account_handle: AccountHandle) -> crate
     let mut account = account_handle.write().await;
 
     let output = serde_json::from_str::<OutputResponse>(&payload)?;
-    let output_address: IotaAddress = match output.output.clone() {
-        OutputDto::SignatureLockedSingle(output) => {
-            (&output.address).try_into().map_err(|_| crate::Error::InvalidAddress)?
-        }
-        OutputDto::SignatureLockedDustAllowance(output) => {
-            (&output.address).try_into().map_err(|_| crate::Error::InvalidAddress)?
-        }
+    let output_address = match crate::address::parse_output_dto(&output.output)? {
+        (_, Some(address), _) => address,
         _ => {
             log::debug!("[MQTT] ignoring output type");
             return Ok(false);
@@ -140,14 +132,19 @@ async fn process_output(payload: String, account_handle: AccountHandle) -> crate
         }
     };
     let output = AddressOutput::from_output_response(output, address.bech32_hrp().to_string())?;
+    // the output was just included, so the message is only confirmed by one milestone
+    let confirmed = account_handle.account_options.confirmation_threshold <= 1;
     let (addresses_to_sync, message_data) = if output.is_spent {
         (vec![address], None)
     } else {
         let (message, is_new) = match account.messages_mut().iter().position(|m| m.id() == &output.message_id) {
             Some(message_index) => {
                 let message = &mut account.messages_mut()[message_index];
-                if !message.confirmed().unwrap_or(false) {
-                    message.set_confirmed(Some(true));
+                if !message.confirmed().unwrap_or(false) && (confirmed || !*message.confirming()) {
+                    if confirmed {
+                        message.set_confirmed(Some(true));
+                    }
+                    message.set_confirming(!confirmed);
                     let message = message.clone();
                     (message, false)
                 } else {
@@ -172,7 +169,8 @@ async fn process_output(payload: String, account_handle: AccountHandle) -> crate
                         account.addresses(),
                         account.client_options(),
                     )
-                    .with_confirmed(Some(true))
+                    .with_confirmed(if confirmed { Some(true) } else { None })
+                    .with_confirming(!confirmed)
                     .finish()
                     .await?;
                     account.messages_mut().push(message.clone());
@@ -189,11 +187,7 @@ async fn process_output(payload: String, account_handle: AccountHandle) -> crate
                     let output_addresses: Vec<AddressWrapper> = essence
                         .outputs()
                         .iter()
-                        .map(|output| match output {
-                            TransactionOutput::SignatureLockedDustAllowance(o) => o.address().clone(),
-                            TransactionOutput::SignatureLockedSingle(o) => o.address().clone(),
-                            _ => unimplemented!(),
-                        })
+                        .filter_map(|output| output.address().cloned())
                         .collect();
                     let input_addresses: Vec<AddressWrapper> = essence
                         .inputs()
@@ -232,7 +226,7 @@ async fn process_output(payload: String, account_handle: AccountHandle) -> crate
                 account_handle.account_options.persist_events,
             )
             .await?;
-        } else {
+        } else if confirmed {
             emit_confirmation_state_change(
                 &account,
                 message.clone(),
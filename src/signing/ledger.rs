@@ -52,6 +52,13 @@ impl super::Signer for LedgerNanoSigner {
         Err(crate::Error::InvalidMnemonic(String::from("")))
     }
 
+    async fn check_mnemonic(&mut self, _: &PathBuf, account_index: usize) -> crate::Result<()> {
+        let _lock = self.mutex.lock().await;
+        // fails if the device isn't connected or is locked
+        iota_ledger::get_ledger(account_index as u32 | HARDENED, self.is_simulator)?;
+        Ok(())
+    }
+
     async fn generate_address(
         &mut self,
         account: &Account,
@@ -59,6 +66,11 @@ impl super::Signer for LedgerNanoSigner {
         internal: bool,
         meta: super::GenerateAddressMetadata,
     ) -> crate::Result<iota::Address> {
+        // the Ledger app only derives keys with the IOTA coin type
+        if *account.coin_type() != crate::address::IOTA_COIN_TYPE {
+            return Err(crate::Error::Unsupported);
+        }
+
         // lock the mutex
         let _lock = self.mutex.lock().await;
 
@@ -133,6 +145,11 @@ impl super::Signer for LedgerNanoSigner {
         inputs: &mut Vec<super::TransactionInput>,
         meta: super::SignMessageMetadata<'a>,
     ) -> crate::Result<Vec<iota::UnlockBlock>> {
+        // the Ledger app only derives keys with the IOTA coin type
+        if *account.coin_type() != crate::address::IOTA_COIN_TYPE {
+            return Err(crate::Error::Unsupported);
+        }
+
         // lock the mutex
         let _lock = self.mutex.lock().await;
 
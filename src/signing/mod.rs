@@ -80,6 +80,17 @@ pub struct SignMessageMetadata<'a> {
 pub trait Signer {
     /// Initialises a mnemonic.
     async fn store_mnemonic(&mut self, storage_path: &PathBuf, mnemonic: String) -> crate::Result<()>;
+    /// Initialises a mnemonic, deriving the seed with a BIP39 passphrase.
+    /// The passphrase must only be used for the seed derivation and never persisted.
+    /// Returns `Error::Unsupported` by default.
+    async fn store_mnemonic_with_passphrase(
+        &mut self,
+        _storage_path: &PathBuf,
+        _mnemonic: String,
+        _passphrase: &str,
+    ) -> crate::Result<()> {
+        Err(crate::Error::Unsupported)
+    }
     /// Generates an address.
     async fn generate_address(
         &mut self,
@@ -96,6 +107,12 @@ pub trait Signer {
         inputs: &mut Vec<TransactionInput>,
         metadata: SignMessageMetadata<'a>,
     ) -> crate::Result<Vec<iota::UnlockBlock>>;
+    /// Checks that the signer is able to sign for the account with the given index,
+    /// e.g. that a mnemonic is stored or that a device is connected.
+    /// Returns `Ok(())` by default.
+    async fn check_mnemonic(&mut self, _storage_path: &PathBuf, _account_index: usize) -> crate::Result<()> {
+        Ok(())
+    }
 }
 
 fn default_signers() -> Signers {
@@ -5,15 +5,47 @@ use crate::account::Account;
 
 use iota::{ReferenceUnlock, UnlockBlock};
 
-use std::{collections::HashMap, path::PathBuf};
+use once_cell::sync::OnceCell;
+use tokio::sync::RwLock;
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+type SnapshotPaths = Arc<RwLock<HashMap<PathBuf, PathBuf>>>;
+static SNAPSHOT_PATHS: OnceCell<SnapshotPaths> = OnceCell::new();
 
 #[derive(Default)]
 pub struct StrongholdSigner;
 
+/// Sets the Stronghold snapshot holding the seed of the accounts stored on the storage with the given path,
+/// e.g. when the manager uses a custom storage filename.
+pub(crate) async fn set_snapshot_path(storage_path: &PathBuf, snapshot_path: PathBuf) {
+    SNAPSHOT_PATHS
+        .get_or_init(Default::default)
+        .write()
+        .await
+        .insert(storage_path.clone(), snapshot_path);
+}
+
+/// Uses the Stronghold snapshot of the `manager_storage_path` storage for the accounts stored on `storage_path`.
+pub(crate) async fn link_snapshot_path(storage_path: &PathBuf, manager_storage_path: &PathBuf) {
+    let mut snapshot_paths = SNAPSHOT_PATHS.get_or_init(Default::default).write().await;
+    if let Some(snapshot_path) = snapshot_paths.get(manager_storage_path).cloned() {
+        snapshot_paths.insert(storage_path.clone(), snapshot_path);
+    }
+}
+
 pub(crate) async fn stronghold_path(storage_path: &PathBuf) -> crate::Result<PathBuf> {
     let storage_id = crate::storage::get(&storage_path).await?.lock().await.id();
+    let snapshot_path = SNAPSHOT_PATHS
+        .get_or_init(Default::default)
+        .read()
+        .await
+        .get(storage_path)
+        .cloned();
     let path = if storage_id == crate::storage::stronghold::STORAGE_ID {
         storage_path.clone()
+    } else if let Some(snapshot_path) = snapshot_path {
+        snapshot_path
     } else if storage_path.is_dir() {
         storage_path.join(crate::account_manager::STRONGHOLD_FILENAME)
     } else if let Some(parent) = storage_path.parent() {
@@ -27,10 +59,28 @@ pub(crate) async fn stronghold_path(storage_path: &PathBuf) -> crate::Result<Pat
 #[async_trait::async_trait]
 impl super::Signer for StrongholdSigner {
     async fn store_mnemonic(&mut self, storage_path: &PathBuf, mnemonic: String) -> crate::Result<()> {
-        crate::stronghold::store_mnemonic(&stronghold_path(storage_path).await?, mnemonic).await?;
+        crate::stronghold::store_mnemonic(&stronghold_path(storage_path).await?, mnemonic, None).await?;
         Ok(())
     }
 
+    async fn store_mnemonic_with_passphrase(
+        &mut self,
+        storage_path: &PathBuf,
+        mnemonic: String,
+        passphrase: &str,
+    ) -> crate::Result<()> {
+        crate::stronghold::store_mnemonic(&stronghold_path(storage_path).await?, mnemonic, Some(passphrase)).await?;
+        Ok(())
+    }
+
+    async fn check_mnemonic(&mut self, storage_path: &PathBuf, _account_index: usize) -> crate::Result<()> {
+        if crate::stronghold::is_mnemonic_stored(&stronghold_path(storage_path).await?).await? {
+            Ok(())
+        } else {
+            Err(crate::Error::MnemonicNotSet)
+        }
+    }
+
     async fn generate_address(
         &mut self,
         account: &Account,
@@ -40,6 +90,7 @@ impl super::Signer for StrongholdSigner {
     ) -> crate::Result<iota::Address> {
         let address = crate::stronghold::generate_address(
             &stronghold_path(account.storage_path()).await?,
+            *account.coin_type(),
             *account.index(),
             address_index,
             internal,
@@ -70,6 +121,7 @@ impl super::Signer for StrongholdSigner {
                 let signature = crate::stronghold::sign_transaction(
                     &stronghold_path(account.storage_path()).await?,
                     &essence.hash(),
+                    *account.coin_type(),
                     *account.index(),
                     recorder.address_index,
                     recorder.address_internal,
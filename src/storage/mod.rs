@@ -9,7 +9,10 @@ pub mod stronghold;
 
 use crate::{
     account::Account,
-    event::{BalanceEvent, TransactionConfirmationChangeEvent, TransactionEvent, TransactionReattachmentEvent},
+    event::{
+        BalanceEvent, StoredEvent, TimestampedEvent, TransactionConfirmationChangeEvent, TransactionEvent,
+        TransactionReattachmentEvent,
+    },
 };
 
 use chrono::Utc;
@@ -27,6 +30,14 @@ use std::{
 };
 
 const ACCOUNT_INDEXATION_KEY: &str = "iota-wallet-account-indexation";
+const ACCOUNT_STORAGE_PATHS_KEY: &str = "iota-wallet-account-storage-paths";
+const EVENT_INDEXATION_KEYS: [&str; 5] = [
+    "iota-wallet-balance-change-events",
+    "iota-wallet-tx-confirmation-events",
+    "iota-wallet-new-tx-events",
+    "iota-wallet-tx-reattachment-events",
+    "iota-wallet-tx-broadcast-events",
+];
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct AccountIndexation {
@@ -81,6 +92,10 @@ impl Storage {
     async fn remove(&mut self, key: &str) -> crate::Result<()> {
         self.inner.remove(key).await
     }
+
+    async fn compact(&mut self) -> crate::Result<()> {
+        self.inner.compact().await
+    }
 }
 
 pub(crate) struct StorageManager {
@@ -107,18 +122,57 @@ impl StorageManager {
         self.storage.get(key).await
     }
 
+    pub fn encryption_key(&self) -> Option<[u8; 32]> {
+        self.storage.encryption_key
+    }
+
+    /// Gets the paths of the additional storage files holding accounts created with a custom storage path.
+    pub async fn get_account_storage_paths(&self) -> crate::Result<Vec<PathBuf>> {
+        load_optional_data(&self.storage, ACCOUNT_STORAGE_PATHS_KEY).await
+    }
+
+    pub async fn add_account_storage_path(&mut self, storage_path: &PathBuf) -> crate::Result<()> {
+        let mut storage_paths = self.get_account_storage_paths().await?;
+        if !storage_paths.contains(storage_path) {
+            storage_paths.push(storage_path.clone());
+            self.storage.set(ACCOUNT_STORAGE_PATHS_KEY, &storage_paths).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn remove_account_storage_path(&mut self, storage_path: &PathBuf) -> crate::Result<()> {
+        let mut storage_paths = self.get_account_storage_paths().await?;
+        if let Some(index) = storage_paths.iter().position(|path| path == storage_path) {
+            storage_paths.remove(index);
+            self.storage.set(ACCOUNT_STORAGE_PATHS_KEY, &storage_paths).await?;
+        }
+        Ok(())
+    }
+
     pub async fn get_accounts(&mut self) -> crate::Result<Vec<Account>> {
+        let mut accounts = Vec::new();
+        for key in self.get_account_keys().await? {
+            accounts.push(self.get(&key).await?);
+        }
+        parse_accounts(&self.storage.storage_path, &accounts, &self.storage.encryption_key)
+    }
+
+    /// Gets the storage keys of the stored accounts.
+    pub async fn get_account_keys(&mut self) -> crate::Result<Vec<String>> {
         if self.account_indexation.is_empty() {
             if let Ok(record) = self.storage.get(ACCOUNT_INDEXATION_KEY).await {
                 self.account_indexation = serde_json::from_str(&record)?;
             }
         }
+        Ok(self.account_indexation.iter().map(|index| index.key.clone()).collect())
+    }
 
-        let mut accounts = Vec::new();
-        for account_index in self.account_indexation.clone() {
-            accounts.push(self.get(&account_index.key).await?);
-        }
-        parse_accounts(&self.storage.storage_path, &accounts, &self.storage.encryption_key)
+    /// Gets the account stored with the given key.
+    pub async fn get_account(&self, key: &str) -> crate::Result<Account> {
+        let account = self.get(key).await?;
+        parse_accounts(&self.storage.storage_path, &[account], &self.storage.encryption_key)?
+            .pop()
+            .ok_or(crate::Error::RecordNotFound)
     }
 
     pub async fn save_account(&mut self, key: &str, account: &Account) -> crate::Result<()> {
@@ -133,6 +187,48 @@ impl StorageManager {
         Ok(())
     }
 
+    pub async fn compact(&mut self) -> crate::Result<()> {
+        self.storage.compact().await
+    }
+
+    /// Re-encrypts the storage records with the given key, except the accounts which are re-encrypted when saved:
+    /// the account indexation, the account storage paths and the events.
+    /// The records are read with the current key, so it must be called before the key is replaced.
+    pub async fn reencrypt_records(&mut self, encryption_key: [u8; 32]) -> crate::Result<()> {
+        let mut records = Vec::new();
+        // loads the account indexation
+        self.get_account_keys().await?;
+        if !self.account_indexation.is_empty() {
+            records.push((
+                ACCOUNT_INDEXATION_KEY.to_string(),
+                serde_json::to_value(&self.account_indexation)?,
+            ));
+        }
+        let account_storage_paths = self.get_account_storage_paths().await?;
+        if !account_storage_paths.is_empty() {
+            records.push((
+                ACCOUNT_STORAGE_PATHS_KEY.to_string(),
+                serde_json::to_value(&account_storage_paths)?,
+            ));
+        }
+        for index_key in EVENT_INDEXATION_KEYS.iter() {
+            let indexation: Vec<EventIndexation> = load_optional_data(&self.storage, index_key).await?;
+            if indexation.is_empty() {
+                continue;
+            }
+            for index in &indexation {
+                let record: serde_json::Value = serde_json::from_str(&self.storage.get(&index.key).await?)?;
+                records.push((index.key.clone(), record));
+            }
+            records.push((index_key.to_string(), serde_json::to_value(&indexation)?));
+        }
+        self.storage.encryption_key = Some(encryption_key);
+        for (key, record) in records {
+            self.storage.set(&key, record).await?;
+        }
+        Ok(())
+    }
+
     pub async fn remove_account(&mut self, key: &str) -> crate::Result<()> {
         let index = AccountIndexation { key: key.to_string() };
         if let Some(index) = self.account_indexation.iter().position(|i| i == &index) {
@@ -279,9 +375,107 @@ event_manager_impl!(
     get_broadcast_event_count
 );
 
+// appends the events saved since `from_timestamp` on the given indexation, wrapped on the given `StoredEvent` variant
+macro_rules! collect_stored_events {
+    ($manager:ident, $events:ident, $from_timestamp:ident, $index_vec:ident, $index_key: expr, $variant:ident) => {
+        if $manager.$index_vec.is_none() {
+            $manager.$index_vec = Some(load_optional_data(&$manager.storage, $index_key).await?);
+        }
+        let indexes: Vec<EventIndexation> = $manager
+            .$index_vec
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter(|i| i.timestamp >= $from_timestamp)
+            .cloned()
+            .collect();
+        for index in indexes {
+            let event_json = $manager.get(&index.key).await?;
+            $events.push(TimestampedEvent {
+                timestamp: index.timestamp,
+                event: StoredEvent::$variant(serde_json::from_str(&event_json)?),
+            });
+        }
+    };
+}
+
+impl StorageManager {
+    /// Gets the events of every type saved since the given timestamp, ordered by the time they were saved at.
+    pub async fn get_events_since(&mut self, from_timestamp: Timestamp) -> crate::Result<Vec<TimestampedEvent>> {
+        let mut events = Vec::new();
+        collect_stored_events!(
+            self,
+            events,
+            from_timestamp,
+            balance_change_indexation,
+            "iota-wallet-balance-change-events",
+            BalanceChange
+        );
+        collect_stored_events!(
+            self,
+            events,
+            from_timestamp,
+            transaction_confirmation_indexation,
+            "iota-wallet-tx-confirmation-events",
+            TransactionConfirmationChange
+        );
+        collect_stored_events!(
+            self,
+            events,
+            from_timestamp,
+            new_transaction_indexation,
+            "iota-wallet-new-tx-events",
+            NewTransaction
+        );
+        collect_stored_events!(
+            self,
+            events,
+            from_timestamp,
+            reattachment_indexation,
+            "iota-wallet-tx-reattachment-events",
+            Reattachment
+        );
+        collect_stored_events!(
+            self,
+            events,
+            from_timestamp,
+            broadcast_indexation,
+            "iota-wallet-tx-broadcast-events",
+            Broadcast
+        );
+        // the sort is stable, so events saved on the same second keep their per-type order
+        events.sort_by_key(|event| event.timestamp);
+        Ok(events)
+    }
+
+    /// Gets the balance change events saved up to the given timestamp, along with the timestamp they were saved at,
+    /// in the order they were saved.
+    pub async fn get_balance_change_events_until(
+        &mut self,
+        to_timestamp: Timestamp,
+    ) -> crate::Result<Vec<(Timestamp, BalanceEvent)>> {
+        let indexation = match &self.balance_change_indexation {
+            Some(indexation) => indexation,
+            None => {
+                self.balance_change_indexation =
+                    Some(load_optional_data(&self.storage, "iota-wallet-balance-change-events").await?);
+                self.balance_change_indexation.as_ref().unwrap()
+            }
+        };
+        let mut events = Vec::new();
+        for index in indexation.iter().filter(|i| i.timestamp <= to_timestamp) {
+            let event_json = self.get(&index.key).await?;
+            events.push((index.timestamp, serde_json::from_str(&event_json)?));
+        }
+        Ok(events)
+    }
+}
+
 pub(crate) type StorageHandle = Arc<Mutex<StorageManager>>;
 type Storages = Arc<RwLock<HashMap<PathBuf, StorageHandle>>>;
 static INSTANCES: OnceCell<Storages> = OnceCell::new();
+type EventStoragePaths = Arc<RwLock<HashMap<PathBuf, PathBuf>>>;
+static EVENT_STORAGE_PATHS: OnceCell<EventStoragePaths> = OnceCell::new();
 
 /// Sets the storage adapter.
 pub(crate) async fn set<P: AsRef<Path>>(
@@ -333,6 +527,74 @@ pub(crate) async fn set_encryption_key(storage_path: &PathBuf, encryption_key: [
     }
 }
 
+/// Sets a SQLite storage adapter for an account storage file if it isn't set yet,
+/// and updates its encryption key.
+pub(crate) async fn init_account_storage(
+    storage_path: &PathBuf,
+    encryption_key: Option<[u8; 32]>,
+) -> crate::Result<()> {
+    if get(storage_path).await.is_err() {
+        if let Some(parent) = storage_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let storage = sqlite::SqliteStorageAdapter::new(storage_path)?;
+        set(storage_path, encryption_key, Box::new(storage)).await;
+    } else if let Some(encryption_key) = encryption_key {
+        set_encryption_key(storage_path, encryption_key).await?;
+    }
+    Ok(())
+}
+
+/// Sets the storage adapter that persists the events of the storage with the given path.
+/// If no adapter is given, a SQLite database is created at `event_storage_path`.
+pub(crate) async fn init_event_storage(
+    storage_path: &PathBuf,
+    event_storage_path: &PathBuf,
+    storage: Option<Box<dyn StorageAdapter + Send + Sync + 'static>>,
+    encryption_key: Option<[u8; 32]>,
+) -> crate::Result<()> {
+    let storage = match storage {
+        Some(storage) => storage,
+        None => {
+            if let Some(parent) = event_storage_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Box::new(sqlite::SqliteStorageAdapter::new(event_storage_path)?)
+        }
+    };
+    set(event_storage_path, encryption_key, storage).await;
+    link_event_storage(storage_path, event_storage_path).await;
+    Ok(())
+}
+
+/// Persists the events of the storage with the given path on the storage with the `event_storage_path` path.
+pub(crate) async fn link_event_storage(storage_path: &PathBuf, event_storage_path: &PathBuf) {
+    EVENT_STORAGE_PATHS
+        .get_or_init(Default::default)
+        .write()
+        .await
+        .insert(storage_path.clone(), event_storage_path.clone());
+}
+
+/// Gets the path of the storage that persists the events of the storage with the given path, if it's a separate one.
+pub(crate) async fn get_event_storage_path(storage_path: &PathBuf) -> Option<PathBuf> {
+    EVENT_STORAGE_PATHS
+        .get_or_init(Default::default)
+        .read()
+        .await
+        .get(storage_path)
+        .cloned()
+}
+
+/// Gets the storage adapter that persists the events of the storage with the given path:
+/// the separate event storage if one was set, otherwise the storage itself.
+pub(crate) async fn get_event_storage(storage_path: &PathBuf) -> crate::Result<StorageHandle> {
+    match get_event_storage_path(storage_path).await {
+        Some(event_storage_path) => get(&event_storage_path).await,
+        None => get(storage_path).await,
+    }
+}
+
 /// gets the storage adapter
 pub(crate) async fn get(storage_path: &PathBuf) -> crate::Result<StorageHandle> {
     let instances = INSTANCES.get_or_init(Default::default).read().await;
@@ -356,6 +618,10 @@ pub trait StorageAdapter {
     async fn set(&mut self, key: &str, record: String) -> crate::Result<()>;
     /// Removes a record from the storage.
     async fn remove(&mut self, key: &str) -> crate::Result<()>;
+    /// Compacts the storage, reclaiming the space left by updated and removed records.
+    async fn compact(&mut self) -> crate::Result<()> {
+        Ok(())
+    }
 }
 
 fn encrypt_record<O: Write>(record: &[u8], encryption_key: &[u8; 32], output: &mut O) -> crate::Result<()> {
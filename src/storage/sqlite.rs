@@ -88,4 +88,10 @@ impl StorageAdapter for SqliteStorageAdapter {
             .map_err(|_| crate::Error::Storage("failed to delete data".into()))?;
         Ok(())
     }
+
+    async fn compact(&mut self) -> crate::Result<()> {
+        let connection = self.connection.lock().await;
+        connection.execute("VACUUM", NO_PARAMS).map_err(storage_err)?;
+        Ok(())
+    }
 }
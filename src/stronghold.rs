@@ -44,6 +44,8 @@ static PRIVATE_DATA_CLIENT_PATH: &[u8] = b"iota_seed";
 const DEFAULT_PASSWORD_CLEAR_INTERVAL: Duration = Duration::from_secs(0);
 #[cfg(not(test))]
 const DEFAULT_PASSWORD_CLEAR_INTERVAL: Duration = Duration::from_secs(8 * 60);
+/// The maximum time to wait for the actor runtime pending tasks.
+const ACTOR_RUNTIME_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
 const SECRET_VAULT_PATH: &str = "iota-wallet-secret";
 const SEED_RECORD_PATH: &str = "iota-wallet-seed";
 const DERIVE_OUTPUT_RECORD_PATH: &str = "iota-wallet-derived";
@@ -293,6 +295,8 @@ pub enum Error {
     PasswordNotSet,
     #[error("invalid address or account index {0}")]
     TryFromIntError(#[from] TryFromIntError),
+    #[error("the stronghold actor runtime is busy")]
+    Busy,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -328,6 +332,15 @@ pub fn actor_runtime() -> &'static Arc<Mutex<ActorRuntime>> {
     &SYSTEM
 }
 
+/// Waits for the actor runtime to finish its pending tasks.
+/// Fails with `Error::Busy` if the runtime isn't released within `ACTOR_RUNTIME_WAIT_TIMEOUT`.
+pub async fn wait_for_pending_tasks() -> Result<()> {
+    match tokio::time::timeout(ACTOR_RUNTIME_WAIT_TIMEOUT, actor_runtime().lock()).await {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::Busy),
+    }
+}
+
 // check if the snapshot path is different than the current loaded one
 // if it is, write the current snapshot and load the new one
 async fn check_snapshot(
@@ -514,7 +527,7 @@ pub async fn change_password(snapshot_path: &PathBuf, current_password: Vec<u8>,
     Ok(())
 }
 
-pub async fn store_mnemonic(snapshot_path: &PathBuf, mnemonic: String) -> Result<()> {
+pub async fn store_mnemonic(snapshot_path: &PathBuf, mnemonic: String, passphrase: Option<&str>) -> Result<()> {
     let mut runtime = actor_runtime().lock().await;
     check_snapshot(&mut runtime, snapshot_path, None).await?;
     load_private_data_actor(&mut runtime, snapshot_path, None).await?;
@@ -523,7 +536,8 @@ pub async fn store_mnemonic(snapshot_path: &PathBuf, mnemonic: String) -> Result
         .stronghold
         .runtime_exec(Procedure::BIP39Recover {
             mnemonic,
-            passphrase: None,
+            // the procedure takes ownership of its copy of the passphrase
+            passphrase: passphrase.map(|passphrase| passphrase.to_string()),
             output: Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH),
             hint: RecordHint::new("wallet.rs-seed").unwrap(),
         })
@@ -537,6 +551,17 @@ pub async fn store_mnemonic(snapshot_path: &PathBuf, mnemonic: String) -> Result
     }
 }
 
+/// Checks whether a mnemonic was stored on the snapshot.
+pub async fn is_mnemonic_stored(snapshot_path: &PathBuf) -> Result<bool> {
+    let mut runtime = actor_runtime().lock().await;
+    check_snapshot(&mut runtime, snapshot_path, None).await?;
+    load_private_data_actor(&mut runtime, snapshot_path, None).await?;
+
+    // the derivation only succeeds if the seed record exists
+    let chain = Chain::from_u32_hardened(vec![44, crate::address::IOTA_COIN_TYPE]);
+    Ok(derive(&mut runtime, chain).await.is_ok())
+}
+
 async fn derive(runtime: &mut ActorRuntime, chain: Chain) -> Result<Location> {
     let derive_output = Location::generic(SECRET_VAULT_PATH, DERIVE_OUTPUT_RECORD_PATH);
     let res = runtime
@@ -572,6 +597,7 @@ async fn get_public_key(runtime: &mut ActorRuntime, derived_location: Location)
 
 pub async fn generate_address(
     snapshot_path: &PathBuf,
+    coin_type: u32,
     account_index: usize,
     address_index: usize,
     internal: bool,
@@ -582,7 +608,7 @@ pub async fn generate_address(
 
     let chain = Chain::from_u32_hardened(vec![
         44,
-        4218,
+        coin_type,
         account_index.try_into()?,
         internal as u32,
         address_index.try_into()?,
@@ -602,6 +628,7 @@ pub async fn generate_address(
 pub async fn sign_transaction(
     snapshot_path: &PathBuf,
     message: &[u8],
+    coin_type: u32,
     account_index: usize,
     address_index: usize,
     internal: bool,
@@ -612,7 +639,7 @@ pub async fn sign_transaction(
 
     let chain = Chain::from_u32_hardened(vec![
         44,
-        4218,
+        coin_type,
         account_index.try_into()?,
         internal as u32,
         address_index.try_into()?,